@@ -1,22 +1,30 @@
 //! Asynchronous CNAPI client implementation.
 
-use crate::models::{Server, ServerListParams, UpdateServerRequest};
+use crate::models::{
+    AllocationRequest, AllocationResult, BootParams, CapacitySummary, CommandExecuteRequest,
+    CommandExecuteResponse, Server, ServerJobResponse, ServerListParams, ServerRebootOptions,
+    UpdateServerRequest,
+};
 use crate::Result;
 use async_trait::async_trait;
-use reqwest::{Client, ClientBuilder, Method, StatusCode};
+use reqwest::header::HeaderName;
+use reqwest::{Client, Method, StatusCode};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
-use tokio::time::sleep;
-use tracing::{debug, info};
+use tracing::debug;
 use triton_core::client::{
-    ClientConfig, RetryPolicy, CNAPI_DEFAULT_TIMEOUT, DEFAULT_POOL_IDLE_TIMEOUT,
-    DEFAULT_POOL_MAX_IDLE_PER_HOST,
+    ClientConfig, ClientObserver, RequestObserver, RequestOptions, RetryPolicy, ServiceClient,
+    ServiceClientBuilder, CNAPI_DEFAULT_TIMEOUT,
 };
+use triton_core::path::encode_path_segment;
+use triton_core::preflight::{PreflightCheck, PreflightResult};
+use triton_core::query::QueryPair;
+use triton_core::selection::EndpointSelector;
 use triton_core::services::{DiscoveryStatus, ServiceDiscovery};
-use triton_core::types::TritonService;
-use triton_core::uuid::ServerUuid;
+use triton_core::types::{EndpointList, TritonService};
+use triton_core::uuid::{JobUuid, ServerUuid};
 use triton_core::Error;
 use url::Url;
 
@@ -25,11 +33,7 @@ const USER_AGENT: &str = concat!("triton-cnapi/", env!("CARGO_PKG_VERSION"));
 /// Builder for [`CnapiClient`].
 #[derive(Debug, Clone)]
 pub struct CnapiClientBuilder {
-    base_url: Url,
-    http_config: ClientConfig,
-    retry_policy: RetryPolicy,
-    basic_auth: Option<(String, String)>,
-    token: Option<String>,
+    inner: ServiceClientBuilder,
 }
 
 impl CnapiClientBuilder {
@@ -37,39 +41,27 @@ impl CnapiClientBuilder {
     ///
     /// The URL should include the protocol and hostname (e.g. `https://cnapi.example.com`).
     pub fn new(base_url: impl AsRef<str>) -> Result<Self> {
-        let url = Url::parse(base_url.as_ref()).map_err(|err| {
-            Error::ConfigError(format!(
-                "Invalid CNAPI base URL `{}`: {err}",
-                base_url.as_ref()
-            ))
-        })?;
-
-        let client_config = ClientConfig::new()
-            .with_timeout(Duration::from_secs(CNAPI_DEFAULT_TIMEOUT))
-            .with_pool_idle_timeout(Duration::from_secs(DEFAULT_POOL_IDLE_TIMEOUT))
-            .with_pool_max_idle(DEFAULT_POOL_MAX_IDLE_PER_HOST);
-
-        Ok(Self {
-            base_url: url,
-            retry_policy: client_config.retry_policy,
-            http_config: client_config,
-            basic_auth: None,
-            token: None,
-        })
+        let builder = ServiceClientBuilder::new(
+            TritonService::Cnapi,
+            base_url,
+            Duration::from_secs(CNAPI_DEFAULT_TIMEOUT),
+        )?
+        .with_user_agent(USER_AGENT);
+
+        Ok(Self { inner: builder })
     }
 
     /// Override the retry policy.
     #[must_use]
     pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
-        self.retry_policy = retry_policy;
+        self.inner = self.inner.with_retry_policy(retry_policy);
         self
     }
 
     /// Override the HTTP client configuration.
     #[must_use]
     pub fn with_http_config(mut self, config: ClientConfig) -> Self {
-        self.http_config = config.clone();
-        self.retry_policy = config.retry_policy;
+        self.inner = self.inner.with_http_config(config);
         self
     }
 
@@ -80,52 +72,84 @@ impl CnapiClientBuilder {
         username: impl Into<String>,
         password: impl Into<String>,
     ) -> Self {
-        self.basic_auth = Some((username.into(), password.into()));
+        self.inner = self.inner.with_basic_auth(username, password);
         self
     }
 
     /// Configure token based authentication (sent as `X-Auth-Token`).
     #[must_use]
     pub fn with_token(mut self, token: impl Into<String>) -> Self {
-        self.token = Some(token.into());
+        self.inner = self.inner.with_token(token);
         self
     }
 
-    /// Build the CNAPI client.
-    pub fn build(self) -> Result<CnapiClient> {
-        let mut builder = ClientBuilder::new()
-            .timeout(self.http_config.timeout)
-            .user_agent(USER_AGENT)
-            .pool_idle_timeout(self.http_config.pool_idle_timeout)
-            .pool_max_idle_per_host(self.http_config.pool_max_idle_per_host)
-            .connect_timeout(Duration::from_secs(10));
-
-        if !self.http_config.enable_compression {
-            builder = builder.no_gzip();
-        }
+    /// Override per-request options such as the JSON content type.
+    #[must_use]
+    pub fn with_request_options(mut self, options: RequestOptions) -> Self {
+        self.inner = self.inner.with_request_options(options);
+        self
+    }
+
+    /// Notify `observer` after every call, retries included.
+    #[must_use]
+    pub fn with_observer(mut self, observer: Arc<dyn RequestObserver>) -> Self {
+        self.inner = self.inner.with_observer(observer);
+        self
+    }
 
-        let http = builder.build().map_err(|err| {
-            Error::ConfigError(format!("Failed to build CNAPI HTTP client: {err}"))
-        })?;
+    /// Invoke `observer` around every attempt, retries included.
+    #[must_use]
+    pub fn with_client_observer(mut self, observer: Arc<dyn ClientObserver>) -> Self {
+        self.inner = self.inner.with_client_observer(observer);
+        self
+    }
 
-        Ok(CnapiClient {
-            http,
-            base_url: self.base_url,
-            retry_policy: self.retry_policy,
-            basic_auth: self.basic_auth,
-            token: self.token,
-        })
+    /// Send requests through a shared `reqwest::Client` instead of building a new one.
+    ///
+    /// The injected client's timeout, TLS and pooling settings take precedence; see
+    /// [`ServiceClientBuilder::with_http_client`].
+    #[must_use]
+    pub fn with_http_client(mut self, client: Client) -> Self {
+        self.inner = self.inner.with_http_client(client);
+        self
+    }
+
+    /// Send each call's request id in `name` instead of `x-request-id`.
+    #[must_use]
+    pub fn with_request_id_header(mut self, name: HeaderName) -> Self {
+        self.inner = self.inner.with_request_id_header(name);
+        self
+    }
+
+    /// Spread requests over `endpoints`, skipping stale and quarantined ones.
+    ///
+    /// See [`ServiceClientBuilder::with_endpoints`].
+    #[must_use]
+    pub fn with_endpoints(mut self, endpoints: EndpointList, selector: EndpointSelector) -> Self {
+        self.inner = self.inner.with_endpoints(endpoints, selector);
+        self
+    }
+
+    /// Send every request to the endpoint at `url` until it fails or is released.
+    ///
+    /// See [`ServiceClientBuilder::pin_endpoint`].
+    #[must_use]
+    pub fn pin_endpoint(mut self, url: impl Into<String>) -> Self {
+        self.inner = self.inner.pin_endpoint(url);
+        self
+    }
+
+    /// Build the CNAPI client.
+    pub fn build(self) -> Result<CnapiClient> {
+        let inner = self.inner.build()?;
+        Ok(CnapiClient { inner })
     }
 }
 
 /// Asynchronous CNAPI client.
 #[derive(Clone)]
 pub struct CnapiClient {
-    http: Client,
-    base_url: Url,
-    retry_policy: RetryPolicy,
-    basic_auth: Option<(String, String)>,
-    token: Option<String>,
+    inner: ServiceClient,
 }
 
 impl CnapiClient {
@@ -137,7 +161,23 @@ impl CnapiClient {
     /// Access the underlying base URL.
     #[must_use]
     pub fn base_url(&self) -> &Url {
-        &self.base_url
+        self.inner.base_url()
+    }
+
+    /// Identifier of the endpoint requests are pinned to, if any.
+    #[must_use]
+    pub fn pinned_endpoint(&self) -> Option<String> {
+        self.inner.pinned_endpoint()
+    }
+
+    /// Drop the endpoint pin so the next request selects afresh.
+    pub fn release_pinned_endpoint(&self) {
+        self.inner.release_pinned_endpoint();
+    }
+
+    /// Replace the endpoints requests are spread over, e.g. after rediscovery.
+    pub fn update_endpoints(&self, endpoints: EndpointList) {
+        self.inner.update_endpoints(endpoints);
     }
 
     /// List compute nodes using the provided filter parameters.
@@ -146,9 +186,28 @@ impl CnapiClient {
         self.get_json("servers", &query).await
     }
 
+    /// Sum unreserved capacity across the servers matching `params`.
+    ///
+    /// Stale, reserved, and not-yet-setup servers are counted in
+    /// [`CapacitySummary::total_servers`] but excluded from the totals.
+    pub async fn datacenter_capacity(&self, params: &ServerListParams) -> Result<CapacitySummary> {
+        let servers = self.list_servers(params).await?;
+        Ok(CapacitySummary::from_servers(&servers, chrono::Utc::now()))
+    }
+
+    /// Ask DAPI, through `POST /allocate`, which server should host a VM.
+    ///
+    /// CNAPI answers `409` when no server fits; [`Error::is_conflict`] holds
+    /// for that error and its message carries the rejection steps. The
+    /// request is not retried, so one call never makes two allocations.
+    pub async fn allocate(&self, request: &AllocationRequest) -> Result<AllocationResult> {
+        self.send_json_once(Method::POST, "allocate", Some(request))
+            .await
+    }
+
     /// Fetch a single server by UUID.
     pub async fn get_server(&self, uuid: ServerUuid) -> Result<Server> {
-        let path = format!("servers/{uuid}");
+        let path = format!("servers/{}", encode_path_segment(uuid)?);
         self.get_json(&path, &[]).await
     }
 
@@ -158,24 +217,97 @@ impl CnapiClient {
         uuid: ServerUuid,
         request: &UpdateServerRequest,
     ) -> Result<Server> {
-        let path = format!("servers/{uuid}");
+        let path = format!("servers/{}", encode_path_segment(uuid)?);
         self.send_json::<_, Server>(Method::PUT, &path, Some(request), &[])
             .await
     }
 
-    fn build_url(&self, path: &str) -> Result<Url> {
-        let normalized = if path.starts_with('/') {
-            &path[1..]
-        } else {
-            path
-        };
+    /// Run a script on a compute node and return its output.
+    ///
+    /// A non-zero exit status is reported in the response, not as an error.
+    /// Scripts can run long: set [`CommandExecuteRequest::timeout`] to give
+    /// this call more time than the client's default timeout.
+    ///
+    /// The request is sent once; a timeout or server error is returned rather
+    /// than retried, as the script may already have run.
+    pub async fn execute_command(
+        &self,
+        uuid: ServerUuid,
+        request: CommandExecuteRequest,
+    ) -> Result<CommandExecuteResponse> {
+        let path = format!("servers/{}/execute", encode_path_segment(uuid)?);
+        let mut inner = self.inner.with_retry_policy(RetryPolicy::no_retry());
+        if let Some(timeout) = request.timeout {
+            inner = inner.with_timeout(timeout);
+        }
+        send_json_via(&inner, Method::POST, &path, Some(&request), &[]).await
+    }
+
+    /// Fetch a server's boot parameters from `GET /boot/:uuid`.
+    pub async fn get_boot_params(&self, uuid: ServerUuid) -> Result<BootParams> {
+        let path = format!("boot/{}", encode_path_segment(uuid)?);
+        self.get_json(&path, &[]).await
+    }
+
+    /// Merge `request` into a server's boot parameters with `PUT /boot/:uuid`.
+    ///
+    /// Only the keys present in `request` change; the new values take effect
+    /// on the next boot.
+    pub async fn update_boot_params(&self, uuid: ServerUuid, request: &BootParams) -> Result<()> {
+        let path = format!("boot/{}", encode_path_segment(uuid)?);
+        self.send_json::<_, serde_json::Value>(Method::PUT, &path, Some(request), &[])
+            .await
+            .map(drop)
+    }
 
-        self.base_url
-            .join(normalized)
-            .map_err(|err| Error::InvalidEndpoint(format!("Invalid CNAPI path `{path}`: {err}")))
+    /// Reboot a compute node with `POST /servers/:uuid/reboot`.
+    ///
+    /// Returns the UUID of the reboot job, or `None` when `options.nojob`
+    /// asked CNAPI to reboot the server directly without a job. The request is
+    /// not retried, so a failure never reboots the server twice.
+    pub async fn reboot_server(
+        &self,
+        uuid: ServerUuid,
+        options: &ServerRebootOptions,
+    ) -> Result<Option<JobUuid>> {
+        let path = format!("servers/{}/reboot", encode_path_segment(uuid)?);
+        let response: Option<ServerJobResponse> = self
+            .send_json_once(Method::POST, &path, Some(options))
+            .await?;
+        Ok(response.map(|response| response.job_uuid))
+    }
+
+    /// Factory-reset a compute node with `POST /servers/:uuid/factory-reset`.
+    ///
+    /// This wipes the server's zpool and returns it to the unsetup state.
+    /// Returns the UUID of the reset job. Like
+    /// [`reboot_server`](Self::reboot_server), the request is not retried.
+    pub async fn factory_reset_server(&self, uuid: ServerUuid) -> Result<JobUuid> {
+        let path = format!("servers/{}/factory-reset", encode_path_segment(uuid)?);
+        self.send_json_once::<(), ServerJobResponse>(Method::POST, &path, None)
+            .await
+            .map(|response| response.job_uuid)
+    }
+
+    /// Fetch any CNAPI path as raw JSON; see [`ServiceClient::get_raw`].
+    pub async fn get_raw(&self, path: &str, params: &[QueryPair]) -> Result<serde_json::Value> {
+        self.inner.get_raw(path, params, map_status_to_error).await
+    }
+
+    /// Send an arbitrary request to CNAPI and return the raw JSON response;
+    /// see [`ServiceClient::send_raw`].
+    pub async fn send_raw(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        self.inner
+            .send_raw(method, path, body, &[], map_status_to_error)
+            .await
     }
 
-    async fn get_json<T>(&self, path: &str, params: &[(&'static str, String)]) -> Result<T>
+    async fn get_json<T>(&self, path: &str, params: &[QueryPair]) -> Result<T>
     where
         T: DeserializeOwned,
     {
@@ -188,99 +320,84 @@ impl CnapiClient {
         method: Method,
         path: &str,
         body: Option<&B>,
-        params: &[(&'static str, String)],
+        params: &[QueryPair],
     ) -> Result<R>
     where
         B: Serialize + ?Sized,
         R: DeserializeOwned,
     {
-        #[allow(unused_assignments)]
-        let mut last_error: Option<Error> = None;
-        let mut attempt = 0;
-
-        loop {
-            let url = self.build_url(path)?;
-            let mut request = self.http.request(method.clone(), url).query(params);
+        send_json_via(&self.inner, method, path, body, params).await
+    }
 
-            if let Some((user, pass)) = &self.basic_auth {
-                request = request.basic_auth(user, Some(pass));
-            }
-            if let Some(token) = &self.token {
-                request = request.header("X-Auth-Token", token);
-            }
-            request = request.header("Accept", "application/json");
+    /// Send a non-idempotent request without retrying it.
+    async fn send_json_once<B, R>(&self, method: Method, path: &str, body: Option<&B>) -> Result<R>
+    where
+        B: Serialize + ?Sized,
+        R: DeserializeOwned,
+    {
+        let inner = self.inner.with_retry_policy(RetryPolicy::no_retry());
+        send_json_via(&inner, method, path, body, &[]).await
+    }
+}
 
-            if let Some(payload) = body {
-                request = request.json(payload);
-            }
+async fn send_json_via<B, R>(
+    inner: &ServiceClient,
+    method: Method,
+    path: &str,
+    body: Option<&B>,
+    params: &[QueryPair],
+) -> Result<R>
+where
+    B: Serialize + ?Sized,
+    R: DeserializeOwned,
+{
+    inner
+        .execute_json_with_retry(
+            method,
+            path,
+            params,
+            |mut request| {
+                request = request.header("Accept", "application/json");
+                if let Some(payload) = body {
+                    request = inner.request_options().json_body(request, payload);
+                }
+                request
+            },
+            map_status_to_error,
+        )
+        .await
+}
 
-            info!(path, attempt, "CNAPI request");
-
-            match request.send().await {
-                Ok(response) => {
-                    let status = response.status();
-                    if status.is_success() {
-                        return response.json::<R>().await.map_err(|err| {
-                            Error::SapiParseError(format!(
-                                "Failed to parse CNAPI response for `{path}`: {err}"
-                            ))
-                        });
-                    }
+#[async_trait]
+impl PreflightCheck for CnapiClient {
+    fn service(&self) -> TritonService {
+        TritonService::Cnapi
+    }
 
-                    let text = response
-                        .text()
-                        .await
-                        .unwrap_or_else(|_| "Unknown error".to_string());
-
-                    let error = match status {
-                        StatusCode::NOT_FOUND => return Err(Error::NotFound(text)),
-                        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
-                            Error::InvalidRequest(format!("CNAPI authentication failed: {text}"))
-                        }
-                        StatusCode::TOO_MANY_REQUESTS
-                        | StatusCode::BAD_GATEWAY
-                        | StatusCode::SERVICE_UNAVAILABLE
-                        | StatusCode::GATEWAY_TIMEOUT => Error::ServiceUnavailable(format!(
-                            "CNAPI temporarily unavailable: {text}"
-                        )),
-                        status if status.is_server_error() => Error::ServiceUnavailable(format!(
-                            "CNAPI server error {status}: {text}"
-                        )),
-                        _ => Error::HttpError(format!("CNAPI error {status}: {text}")),
-                    };
-                    last_error = Some(error);
-                }
-                Err(err) => {
-                    let error = Error::from(err);
-                    if matches!(
-                        error,
-                        Error::Timeout(_) | Error::ServiceUnavailable(_) | Error::HttpError(_)
-                    ) {
-                        last_error = Some(error);
-                    } else {
-                        return Err(error);
-                    }
-                }
-            }
+    async fn preflight(&self) -> PreflightResult {
+        self.inner
+            .preflight("servers", &[("limit".into(), "1".to_string())])
+            .await
+    }
+}
 
-            attempt += 1;
-            if attempt > self.retry_policy.max_retries {
-                break;
-            }
-            let delay = self.retry_policy.delay_for_attempt(attempt);
-            if delay > Duration::from_millis(0) {
-                debug!("Retrying CNAPI request after {:?}", delay);
-                sleep(delay).await;
-            }
+fn map_status_to_error(status: StatusCode, text: String) -> Error {
+    match status {
+        StatusCode::NOT_FOUND => Error::NotFound(text),
+        StatusCode::PAYLOAD_TOO_LARGE => Error::PayloadTooLarge(text),
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+            Error::InvalidRequest(format!("CNAPI authentication failed: {text}"))
         }
-
-        if let Some(error) = last_error {
-            Err(error)
-        } else {
-            Err(Error::ServiceUnavailable(
-                "CNAPI request failed after retries".to_string(),
-            ))
+        StatusCode::TOO_MANY_REQUESTS
+        | StatusCode::BAD_GATEWAY
+        | StatusCode::SERVICE_UNAVAILABLE
+        | StatusCode::GATEWAY_TIMEOUT => {
+            Error::ServiceUnavailable(format!("CNAPI temporarily unavailable: {text}"))
         }
+        status if status.is_server_error() => {
+            Error::ServiceUnavailable(format!("CNAPI server error {status}: {text}"))
+        }
+        _ => Error::http(status, format!("CNAPI error {status}: {text}")),
     }
 }
 
@@ -377,7 +494,7 @@ pub type ServerQuery = ServerListParams;
 mod tests {
     use super::*;
     use serde_json::json;
-    use wiremock::matchers::{method, path, query_param};
+    use wiremock::matchers::{body_json, header, method, path, query_param};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     fn test_client(server: &MockServer) -> CnapiClient {
@@ -412,6 +529,108 @@ mod tests {
         assert_eq!(servers[0].hostname.as_deref(), Some("cn01"));
     }
 
+    #[tokio::test]
+    async fn injected_http_client_is_used() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/servers"))
+            .and(header("user-agent", "shared-agent/1.0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let http = Client::builder()
+            .user_agent("shared-agent/1.0")
+            .build()
+            .unwrap();
+        let client = CnapiClientBuilder::new(server.uri())
+            .unwrap()
+            .with_http_client(http)
+            .build()
+            .unwrap();
+
+        let servers = client
+            .list_servers(&ServerListParams::default())
+            .await
+            .unwrap();
+        assert!(servers.is_empty());
+    }
+
+    #[tokio::test]
+    async fn request_id_is_stable_across_retries() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/servers"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/servers"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+            .mount(&server)
+            .await;
+
+        let client = CnapiClientBuilder::new(server.uri())
+            .unwrap()
+            .with_retry_policy(RetryPolicy {
+                max_retries: 1,
+                ..RetryPolicy::default()
+            })
+            .with_request_id_header(HeaderName::from_static("request-id"))
+            .build()
+            .unwrap();
+        client
+            .list_servers(&ServerListParams::default())
+            .await
+            .unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].headers.contains_key("request-id"));
+        assert_eq!(
+            requests[0].headers["request-id"],
+            requests[1].headers["request-id"]
+        );
+    }
+
+    #[tokio::test]
+    async fn datacenter_capacity_sums_live_servers() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/servers"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                {
+                    "uuid": ServerUuid::new_v4(),
+                    "status": "running",
+                    "unreserved_cpu": 16,
+                    "unreserved_ram": 32768,
+                    "unreserved_disk": 500
+                },
+                {
+                    "uuid": ServerUuid::new_v4(),
+                    "status": "unknown",
+                    "unreserved_cpu": 16,
+                    "unreserved_ram": 32768,
+                    "unreserved_disk": 500
+                }
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let summary = client
+            .datacenter_capacity(&ServerListParams::default())
+            .await
+            .unwrap();
+        assert_eq!(summary.total_servers, 2);
+        assert_eq!(summary.live_servers, 1);
+        assert_eq!(summary.total_unreserved_cpu, 16);
+        assert_eq!(summary.total_unreserved_ram_mib, 32768);
+    }
+
     #[tokio::test]
     async fn get_server_not_found() {
         let server = MockServer::start().await;
@@ -456,6 +675,312 @@ mod tests {
         assert_eq!(server.reserved, Some(true));
     }
 
+    #[tokio::test]
+    async fn execute_command_posts_script() {
+        let server = MockServer::start().await;
+        let uuid = ServerUuid::new_v4();
+
+        Mock::given(method("POST"))
+            .and(path(format!("/servers/{uuid}/execute").as_str()))
+            .and(body_json(json!({
+                "script": "#!/bin/bash\nzpool status \"$1\"",
+                "args": ["zones"],
+                "env": { "LC_ALL": "C" }
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "stdout": "pool: zones\nstate: ONLINE\n",
+                "stderr": "",
+                "exitCode": 0
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let request = CommandExecuteRequest::new("#!/bin/bash\nzpool status \"$1\"")
+            .with_args(["zones"])
+            .with_env("LC_ALL", "C")
+            .with_timeout(Duration::from_secs(300));
+
+        let output = client.execute_command(uuid, request).await.unwrap();
+        assert!(output.success());
+        assert!(output.stdout.contains("state: ONLINE"));
+        assert!(output.stderr.is_empty());
+    }
+
+    #[tokio::test]
+    async fn execute_command_timeout_overrides_default() {
+        let server = MockServer::start().await;
+        let uuid = ServerUuid::new_v4();
+
+        Mock::given(method("POST"))
+            .and(path(format!("/servers/{uuid}/execute").as_str()))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_delay(Duration::from_millis(500))
+                    .set_body_json(json!({ "stdout": "", "stderr": "", "exitCode": 1 })),
+            )
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+
+        let request = CommandExecuteRequest::new("sleep 1").with_timeout(Duration::from_millis(50));
+        let err = client.execute_command(uuid, request).await.unwrap_err();
+        assert!(matches!(err, Error::Timeout { .. }));
+
+        let output = client
+            .execute_command(uuid, CommandExecuteRequest::new("false"))
+            .await
+            .unwrap();
+        assert_eq!(output.exit_status, 1);
+        assert!(!output.success());
+    }
+
+    #[tokio::test]
+    async fn execute_command_is_not_retried_on_server_error() {
+        let server = MockServer::start().await;
+        let uuid = ServerUuid::new_v4();
+
+        Mock::given(method("POST"))
+            .and(path(format!("/servers/{uuid}/execute").as_str()))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let err = test_client(&server)
+            .execute_command(uuid, CommandExecuteRequest::new("hostname"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::ServiceUnavailable(_)));
+    }
+
+    #[tokio::test]
+    async fn allocate_returns_chosen_server_and_steps() {
+        let server = MockServer::start().await;
+        let chosen = ServerUuid::new_v4();
+        let full = ServerUuid::new_v4();
+        let reason = "VM's calculated 2048 RAM is less than server's spare 512";
+
+        Mock::given(method("POST"))
+            .and(path("/allocate"))
+            .and(body_json(json!({
+                "vm": { "ram": 2048, "owner_uuid": "930896af-bf8c-48d4-885c-6573a94b1853" },
+                "image": { "uuid": "2b683a82-a066-11e3-97ab-2faa44701c5a" },
+                "nic_tags": ["external"],
+                "servers": [chosen, full]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "server": { "uuid": chosen, "hostname": "cn02" },
+                "steps": [
+                    { "step": "Received by DAPI", "remaining": [chosen, full] },
+                    {
+                        "step": "Servers with enough unreserved RAM",
+                        "remaining": [chosen],
+                        "reasons": { full.to_string(): reason }
+                    }
+                ]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let request = AllocationRequest::new(
+            json!({ "ram": 2048, "owner_uuid": "930896af-bf8c-48d4-885c-6573a94b1853" }),
+            json!({ "uuid": "2b683a82-a066-11e3-97ab-2faa44701c5a" }),
+        )
+        .with_nic_tags(["external"])
+        .with_servers([chosen, full]);
+
+        let result = client.allocate(&request).await.unwrap();
+        assert_eq!(result.server_uuid(), chosen);
+        assert_eq!(result.server.hostname.as_deref(), Some("cn02"));
+        assert_eq!(result.steps.len(), 2);
+        assert_eq!(result.steps[1].remaining, vec![chosen]);
+        assert_eq!(result.steps[1].reasons[&full.to_string()], reason);
+    }
+
+    #[tokio::test]
+    async fn allocate_is_not_retried() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/allocate"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let request = AllocationRequest::new(json!({ "ram": 2048 }), json!({}));
+        let err = test_client(&server).allocate(&request).await.unwrap_err();
+        assert!(matches!(err, Error::ServiceUnavailable(_)));
+    }
+
+    #[tokio::test]
+    async fn get_boot_params_round_trips_unmodelled_keys() {
+        let server = MockServer::start().await;
+        let uuid = ServerUuid::new_v4();
+        let payload = json!({
+            "platform": "20240101T000000Z",
+            "kernel_args": {
+                "rabbitmq": "guest:guest:10.0.0.10:5672",
+                "smt_enabled": true
+            },
+            "kernel_flags": { "-k": true },
+            "boot_modules": [],
+            "default_console": "serial",
+            "serial": "ttyb"
+        });
+
+        Mock::given(method("GET"))
+            .and(path(format!("/boot/{uuid}").as_str()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(payload.clone()))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let params = client.get_boot_params(uuid).await.unwrap();
+        assert_eq!(params.platform.as_deref(), Some("20240101T000000Z"));
+        assert_eq!(params.kernel_args["smt_enabled"], json!(true));
+        assert_eq!(params.kernel_flags["-k"], json!(true));
+        assert_eq!(params.extra["serial"], json!("ttyb"));
+        assert_eq!(serde_json::to_value(&params).unwrap(), payload);
+    }
+
+    #[tokio::test]
+    async fn update_boot_params_sends_only_set_fields() {
+        let server = MockServer::start().await;
+        let uuid = ServerUuid::new_v4();
+
+        Mock::given(method("PUT"))
+            .and(path(format!("/boot/{uuid}").as_str()))
+            .and(body_json(json!({
+                "platform": "latest",
+                "kernel_args": { "smt_enabled": false }
+            })))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let mut request = BootParams {
+            platform: Some("latest".to_string()),
+            ..BootParams::default()
+        };
+        request
+            .kernel_args
+            .insert("smt_enabled".to_string(), json!(false));
+        client.update_boot_params(uuid, &request).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn reboot_server_posts_options() {
+        let server = MockServer::start().await;
+        let uuid = ServerUuid::new_v4();
+        let job = JobUuid::new_v4();
+
+        Mock::given(method("POST"))
+            .and(path(format!("/servers/{uuid}/reboot").as_str()))
+            .and(body_json(json!({ "drain": true })))
+            .respond_with(ResponseTemplate::new(202).set_body_json(json!({ "job_uuid": job })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let options = ServerRebootOptions {
+            drain: Some(true),
+            ..ServerRebootOptions::default()
+        };
+        let job_uuid = client.reboot_server(uuid, &options).await.unwrap();
+        assert_eq!(job_uuid, Some(job));
+    }
+
+    #[tokio::test]
+    async fn reboot_server_without_job() {
+        let server = MockServer::start().await;
+        let uuid = ServerUuid::new_v4();
+
+        Mock::given(method("POST"))
+            .and(path(format!("/servers/{uuid}/reboot").as_str()))
+            .and(body_json(json!({ "nojob": true })))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let options = ServerRebootOptions {
+            nojob: Some(true),
+            ..ServerRebootOptions::default()
+        };
+        assert_eq!(client.reboot_server(uuid, &options).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn factory_reset_server_returns_job() {
+        let server = MockServer::start().await;
+        let uuid = ServerUuid::new_v4();
+        let job = JobUuid::new_v4();
+
+        Mock::given(method("POST"))
+            .and(path(format!("/servers/{uuid}/factory-reset").as_str()))
+            .respond_with(ResponseTemplate::new(202).set_body_json(json!({ "job_uuid": job })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        assert_eq!(client.factory_reset_server(uuid).await.unwrap(), job);
+    }
+
+    #[tokio::test]
+    async fn reboot_and_factory_reset_are_not_retried() {
+        let server = MockServer::start().await;
+        let uuid = ServerUuid::new_v4();
+
+        for action in ["reboot", "factory-reset"] {
+            Mock::given(method("POST"))
+                .and(path(format!("/servers/{uuid}/{action}").as_str()))
+                .respond_with(ResponseTemplate::new(503))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let client = test_client(&server);
+        let err = client
+            .reboot_server(uuid, &ServerRebootOptions::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::ServiceUnavailable(_)));
+        let err = client.factory_reset_server(uuid).await.unwrap_err();
+        assert!(matches!(err, Error::ServiceUnavailable(_)));
+    }
+
+    #[tokio::test]
+    async fn factory_reset_unknown_server_not_found() {
+        let server = MockServer::start().await;
+        let uuid = ServerUuid::new_v4();
+
+        Mock::given(method("POST"))
+            .and(path(format!("/servers/{uuid}/factory-reset").as_str()))
+            .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+                "code": "ResourceNotFound",
+                "message": "server not found"
+            })))
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let err = client.factory_reset_server(uuid).await.unwrap_err();
+        assert!(matches!(err, Error::NotFound(_)));
+    }
+
     #[tokio::test]
     async fn discovery_delegates_to_sapi() {
         struct MockDiscovery;
@@ -482,4 +1007,32 @@ mod tests {
         let endpoints = discovery.discover_service("cnapi").await.unwrap();
         assert_eq!(endpoints, vec!["http://cnapi.local:80"]);
     }
+
+    #[tokio::test]
+    async fn observer_sees_every_call() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/servers"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+            .mount(&server)
+            .await;
+
+        let metrics = Arc::new(triton_core::metrics::MetricsObserver::new());
+        let client = CnapiClientBuilder::new(server.uri())
+            .unwrap()
+            .with_observer(metrics.clone())
+            .build()
+            .unwrap();
+        client
+            .list_servers(&ServerListParams::default())
+            .await
+            .unwrap();
+        let missing = ServerUuid::new_v4();
+        client.get_server(missing).await.unwrap_err();
+
+        let snapshot = metrics.snapshot();
+        let cnapi = snapshot.service(TritonService::Cnapi).unwrap();
+        assert_eq!(cnapi.requests, 2);
+        assert_eq!(cnapi.errors_by_code.get("NOT_FOUND"), Some(&1));
+    }
 }
@@ -9,7 +9,12 @@ pub mod client;
 pub mod models;
 
 pub use client::{CnapiClient, CnapiClientBuilder, ServerQuery};
-pub use models::{Server, ServerCapacity, ServerListParams, ServerNic, UpdateServerRequest};
+pub use models::{
+    AllocationRequest, AllocationResult, AllocationStep, BootParams, CapacitySummary,
+    CapacityTotals, CommandExecuteRequest, CommandExecuteResponse, Server, ServerCapacity,
+    ServerJobResponse, ServerListParams, ServerNic, ServerRebootOptions, ServerStatus,
+    ServerVmSummary, UpdateServerRequest,
+};
 
 /// Convenient result alias matching the shared Triton error type.
 pub type Result<T> = triton_core::Result<T>;
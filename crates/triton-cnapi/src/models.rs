@@ -1,9 +1,11 @@
 //! CNAPI data models shared by clients and (eventual) servers.
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use triton_core::uuid::{ServerUuid, VmUuid};
+use triton_core::query::{append_extra, QueryPair};
+use triton_core::ratio::Ratio;
+use triton_core::uuid::{JobUuid, OwnerUuid, ServerUuid, VmUuid};
 
 /// Query parameters supported by CNAPI's `/servers` endpoint.
 #[derive(Debug, Default, Clone)]
@@ -32,22 +34,26 @@ pub struct ServerListParams {
     pub limit: Option<u32>,
     /// Offset for pagination.
     pub offset: Option<u32>,
+    /// Additional query parameters appended verbatim after the typed ones.
+    ///
+    /// Keys already set by a typed field are skipped.
+    pub extra_params: Vec<(String, String)>,
 }
 
 impl ServerListParams {
     /// Convert the parameter struct into URL query pairs.
     #[must_use]
-    pub fn to_pairs(&self) -> Vec<(&'static str, String)> {
+    pub fn to_pairs(&self) -> Vec<QueryPair> {
         let mut pairs = Vec::new();
 
         if let Some(datacenter) = &self.datacenter {
-            pairs.push(("datacenter", datacenter.clone()));
+            pairs.push(("datacenter".into(), datacenter.clone()));
         }
         if let Some(hostname) = &self.hostname {
-            pairs.push(("hostname", hostname.clone()));
+            pairs.push(("hostname".into(), hostname.clone()));
         }
         if let Some(uuid) = &self.uuid {
-            pairs.push(("uuid", uuid.to_string()));
+            pairs.push(("uuid".into(), uuid.to_string()));
         }
         if let Some(uuids) = &self.uuids {
             let combined = uuids
@@ -55,32 +61,33 @@ impl ServerListParams {
                 .map(ServerUuid::to_string)
                 .collect::<Vec<_>>()
                 .join(",");
-            pairs.push(("uuids", combined));
+            pairs.push(("uuids".into(), combined));
         }
         if let Some(setup) = self.setup {
-            pairs.push(("setup", setup.to_string()));
+            pairs.push(("setup".into(), setup.to_string()));
         }
         if let Some(reserved) = self.reserved {
-            pairs.push(("reserved", reserved.to_string()));
+            pairs.push(("reserved".into(), reserved.to_string()));
         }
         if let Some(headnode) = self.headnode {
-            pairs.push(("headnode", headnode.to_string()));
+            pairs.push(("headnode".into(), headnode.to_string()));
         }
         if let Some(reservoir) = self.reservoir {
-            pairs.push(("reservoir", reservoir.to_string()));
+            pairs.push(("reservoir".into(), reservoir.to_string()));
         }
         if let Some(extras) = &self.extras {
-            pairs.push(("extras", extras.clone()));
+            pairs.push(("extras".into(), extras.clone()));
         }
         if let Some(fields) = &self.fields {
-            pairs.push(("fields", fields.clone()));
+            pairs.push(("fields".into(), fields.clone()));
         }
         if let Some(limit) = self.limit {
-            pairs.push(("limit", limit.to_string()));
+            pairs.push(("limit".into(), limit.to_string()));
         }
         if let Some(offset) = self.offset {
-            pairs.push(("offset", offset.to_string()));
+            pairs.push(("offset".into(), offset.to_string()));
         }
+        append_extra(&mut pairs, &self.extra_params);
 
         pairs
     }
@@ -97,9 +104,9 @@ pub struct Server {
     /// Current datacenter label.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub datacenter: Option<String>,
-    /// Current operational status (running, unknown, rebooting).
+    /// Current operational status.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub status: Option<String>,
+    pub status: Option<ServerStatus>,
     /// Whether the server has completed setup.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub setup: Option<bool>,
@@ -226,9 +233,152 @@ pub struct Server {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub serial: Option<String>,
 
-    /// Optional VM metadata keyed by VM UUID.
+    /// Summaries of the VMs on this server, keyed by VM UUID.
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub vms: Option<HashMap<VmUuid, serde_json::Value>>,
+    pub vms: Option<HashMap<VmUuid, ServerVmSummary>>,
+}
+
+impl Server {
+    /// How long a server may go without a heartbeat before it is treated as stale.
+    pub const HEARTBEAT_TIMEOUT: Duration = Duration::seconds(60);
+
+    /// Returns true if the server's reported capacity cannot be trusted.
+    ///
+    /// A server is stale when CNAPI reports it as `unknown` (or a status this
+    /// client does not recognise), or when its last
+    /// heartbeat is older than [`Self::HEARTBEAT_TIMEOUT`]. A missing heartbeat
+    /// alone is not stale, since list responses often omit it.
+    #[must_use]
+    pub fn is_stale(&self, now: DateTime<Utc>) -> bool {
+        matches!(
+            self.status,
+            Some(ServerStatus::Unknown | ServerStatus::Other(_))
+        ) || self
+            .last_heartbeat
+            .is_some_and(|heartbeat| now - heartbeat > Self::HEARTBEAT_TIMEOUT)
+    }
+
+    /// Returns true if a VM needing `ram_mib` of RAM and `disk_gib` of disk can
+    /// be placed here.
+    ///
+    /// Only fresh, `running`, set-up, unreserved servers are considered, and
+    /// missing capacity figures count as no capacity.
+    #[must_use]
+    pub fn can_fit(&self, ram_mib: u64, disk_gib: u64, now: DateTime<Utc>) -> bool {
+        let fits = |unreserved: Option<i64>, needed: u64| {
+            unreserved.is_some_and(|unreserved| {
+                u64::try_from(unreserved).is_ok_and(|unreserved| unreserved >= needed)
+            })
+        };
+
+        matches!(self.status, Some(ServerStatus::Running))
+            && !self.is_stale(now)
+            && self.setup != Some(false)
+            && self.reserved != Some(true)
+            && fits(self.unreserved_ram, ram_mib)
+            && fits(self.unreserved_disk, disk_gib)
+    }
+
+    /// Number of VMs on this server whose state is `running`.
+    #[must_use]
+    pub fn running_vm_count(&self) -> usize {
+        self.vms.as_ref().map_or(0, |vms| {
+            vms.values()
+                .filter(|vm| vm.state.as_deref() == Some("running"))
+                .count()
+        })
+    }
+}
+
+/// Operational status reported by CNAPI for a server.
+///
+/// The value CNAPI sent is kept for `rebooting`/`maintenance` and for
+/// unrecognised statuses, so every status serializes back unchanged.
+/// Unrecognised values are treated like [`ServerStatus::Unknown`] by
+/// [`Server::is_stale`] so they are never mistaken for a live server.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum ServerStatus {
+    /// Heartbeating normally.
+    Running,
+    /// Heartbeats have been missed; capacity fields may be stale or null.
+    Unknown,
+    /// Rebooting or in maintenance; holds `rebooting` or `maintenance`.
+    RebootingOrMaintenance(String),
+    /// Value not known to this client, preserved verbatim.
+    Other(String),
+}
+
+impl ServerStatus {
+    /// Return the CNAPI string for this status.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Running => "running",
+            Self::Unknown => "unknown",
+            Self::RebootingOrMaintenance(value) | Self::Other(value) => value,
+        }
+    }
+}
+
+impl From<String> for ServerStatus {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "running" => Self::Running,
+            "unknown" => Self::Unknown,
+            "rebooting" | "maintenance" => Self::RebootingOrMaintenance(value),
+            _ => Self::Other(value),
+        }
+    }
+}
+
+impl From<ServerStatus> for String {
+    fn from(status: ServerStatus) -> Self {
+        match status {
+            ServerStatus::RebootingOrMaintenance(value) | ServerStatus::Other(value) => value,
+            known => known.as_str().to_string(),
+        }
+    }
+}
+
+/// Partial VM record embedded in a server's `vms` map.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ServerVmSummary {
+    /// VM UUID.
+    pub uuid: VmUuid,
+    /// Owning account.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner_uuid: Option<OwnerUuid>,
+    /// Zone brand (e.g. `joyent`, `bhyve`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub brand: Option<String>,
+    /// VM state as reported by vmadm.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+    /// Underlying zone state.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zone_state: Option<String>,
+    /// Memory cap in MiB.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_physical_memory: Option<u64>,
+    /// Memory in MiB as reported by older agents under the `ram` key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ram: Option<u64>,
+    /// Disk quota in GiB.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quota: Option<u64>,
+    /// Fields not modelled above.
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl ServerVmSummary {
+    /// Memory in MiB, preferring `max_physical_memory` over the older `ram`
+    /// key.
+    #[must_use]
+    pub fn memory_mib(&self) -> Option<u64> {
+        self.max_physical_memory.or(self.ram)
+    }
 }
 
 /// Compute node capacity summary.
@@ -255,15 +405,97 @@ impl From<&Server> for ServerCapacity {
     }
 }
 
+/// Unreserved capacity summed over a group of servers.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CapacityTotals {
+    /// Number of servers contributing to the totals.
+    pub servers: usize,
+    /// Unreserved CPU (cores).
+    pub unreserved_cpu: i64,
+    /// Unreserved RAM in MiB.
+    pub unreserved_ram_mib: i64,
+    /// Unreserved disk in GiB.
+    pub unreserved_disk_gib: i64,
+}
+
+impl CapacityTotals {
+    fn add(&mut self, server: &Server) {
+        let capacity = ServerCapacity::from(server);
+        self.servers += 1;
+        self.unreserved_cpu += capacity.unreserved_cpu;
+        self.unreserved_ram_mib += capacity.unreserved_ram;
+        self.unreserved_disk_gib += capacity.unreserved_disk;
+    }
+}
+
+/// Datacenter-wide unreserved capacity, as used for capacity planning.
+///
+/// Only set-up, unreserved servers that are not [stale](Server::is_stale)
+/// contribute to the totals; missing capacity figures count as zero.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CapacitySummary {
+    /// Every server considered, including skipped ones.
+    pub total_servers: usize,
+    /// Servers that contributed to the totals.
+    pub live_servers: usize,
+    /// Unreserved CPU (cores) across live servers.
+    pub total_unreserved_cpu: i64,
+    /// Unreserved RAM in MiB across live servers.
+    pub total_unreserved_ram_mib: i64,
+    /// Unreserved disk in GiB across live servers.
+    pub total_unreserved_disk_gib: i64,
+    /// Totals for the live servers carrying each enabled trait.
+    pub by_trait: HashMap<String, CapacityTotals>,
+}
+
+impl CapacitySummary {
+    /// Summarise `servers`, judging heartbeat freshness against `now`.
+    #[must_use]
+    pub fn from_servers<'a>(
+        servers: impl IntoIterator<Item = &'a Server>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let mut summary = Self::default();
+        let mut totals = CapacityTotals::default();
+        for server in servers {
+            summary.total_servers += 1;
+            if server.is_stale(now) || server.setup == Some(false) || server.reserved == Some(true)
+            {
+                continue;
+            }
+            totals.add(server);
+            let enabled = server.traits.iter().flatten().filter(|(_, value)| {
+                !matches!(
+                    value,
+                    serde_json::Value::Null | serde_json::Value::Bool(false)
+                )
+            });
+            for (name, _) in enabled {
+                summary
+                    .by_trait
+                    .entry(name.clone())
+                    .or_default()
+                    .add(server);
+            }
+        }
+
+        summary.live_servers = totals.servers;
+        summary.total_unreserved_cpu = totals.unreserved_cpu;
+        summary.total_unreserved_ram_mib = totals.unreserved_ram_mib;
+        summary.total_unreserved_disk_gib = totals.unreserved_disk_gib;
+        summary
+    }
+}
+
 /// Update server request payload for CNAPI.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct UpdateServerRequest {
     /// Update the reserved flag.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub reserved: Option<bool>,
-    /// Update the reservation ratio.
+    /// Update the reservation ratio (0.0 to 1.0).
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub reservation_ratio: Option<f64>,
+    pub reservation_ratio: Option<Ratio>,
     /// Update the global overprovision ratio.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub overprovision_ratio: Option<f64>,
@@ -275,6 +507,213 @@ pub struct UpdateServerRequest {
     pub traits: Option<HashMap<String, bool>>,
 }
 
+/// Script to run on a compute node through `POST /servers/:uuid/execute`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct CommandExecuteRequest {
+    /// Script body, run by the CN agent.
+    pub script: String,
+    /// Arguments passed to the script.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// Extra environment variables for the script.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+    /// HTTP timeout for this call, replacing the client default.
+    ///
+    /// Not sent to CNAPI; raise it for scripts that outlast the default.
+    #[serde(skip)]
+    pub timeout: Option<std::time::Duration>,
+}
+
+impl CommandExecuteRequest {
+    /// Create a request that runs `script` with no arguments.
+    #[must_use]
+    pub fn new(script: impl Into<String>) -> Self {
+        Self {
+            script: script.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Set the script arguments.
+    #[must_use]
+    pub fn with_args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Add an environment variable.
+    #[must_use]
+    pub fn with_env(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.insert(name.into(), value.into());
+        self
+    }
+
+    /// Override the HTTP timeout for this call.
+    #[must_use]
+    pub fn with_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+/// Output of a script run with
+/// [`CnapiClient::execute_command`](crate::CnapiClient::execute_command).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CommandExecuteResponse {
+    /// Captured standard output.
+    #[serde(default)]
+    pub stdout: String,
+    /// Captured standard error.
+    #[serde(default)]
+    pub stderr: String,
+    /// Exit status of the script.
+    #[serde(rename = "exitCode", alias = "exit_status", alias = "exit_code")]
+    pub exit_status: i32,
+}
+
+impl CommandExecuteResponse {
+    /// Returns true when the script exited with status 0.
+    #[must_use]
+    pub fn success(&self) -> bool {
+        self.exit_status == 0
+    }
+}
+
+/// Boot-time configuration of a compute node, as served by CNAPI's
+/// `/boot/:server_uuid` resource.
+///
+/// Used both to read the current parameters and to update them; fields left
+/// empty are not sent, so an update touches only what it sets. Keys CNAPI
+/// adds beyond these (`boot_modules`, `default_console`, ...) are kept in
+/// [`extra`](Self::extra) and written back unchanged.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct BootParams {
+    /// Platform image to boot, e.g. `20240101T000000Z`; `latest` follows the newest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    /// Kernel arguments (`key=value` on the boot line).
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub kernel_args: serde_json::Map<String, serde_json::Value>,
+    /// Kernel flags (e.g. `-k` for the debugger).
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub kernel_flags: serde_json::Map<String, serde_json::Value>,
+    /// Fields not modelled above.
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// Placement query for CNAPI's `POST /allocate` (DAPI) endpoint.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct AllocationRequest {
+    /// VM to place: `ram`, `quota`, `cpu_cap`, `owner_uuid`, `brand`, and so on.
+    pub vm: serde_json::Value,
+    /// Image the VM will be created from (at least `uuid` and `requirements`).
+    pub image: serde_json::Value,
+    /// Package the VM is sized by, when it has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package: Option<serde_json::Value>,
+    /// NIC tags the chosen server must provide.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub nic_tags: Vec<String>,
+    /// Restrict placement to these servers; empty means every server.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub servers: Vec<ServerUuid>,
+}
+
+impl AllocationRequest {
+    /// Create a request for placing `vm` built from `image`.
+    #[must_use]
+    pub fn new(vm: serde_json::Value, image: serde_json::Value) -> Self {
+        Self {
+            vm,
+            image,
+            ..Self::default()
+        }
+    }
+
+    /// Set the package the VM is sized by.
+    #[must_use]
+    pub fn with_package(mut self, package: serde_json::Value) -> Self {
+        self.package = Some(package);
+        self
+    }
+
+    /// Require the chosen server to provide these NIC tags.
+    #[must_use]
+    pub fn with_nic_tags<I, S>(mut self, nic_tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.nic_tags = nic_tags.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Only consider these servers.
+    #[must_use]
+    pub fn with_servers(mut self, servers: impl IntoIterator<Item = ServerUuid>) -> Self {
+        self.servers = servers.into_iter().collect();
+        self
+    }
+}
+
+/// One filtering step of a DAPI allocation.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AllocationStep {
+    /// Description of the filter or ranking applied.
+    pub step: String,
+    /// Servers still in the running after this step.
+    #[serde(default)]
+    pub remaining: Vec<ServerUuid>,
+    /// Why servers were dropped at this step, keyed by server UUID.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub reasons: HashMap<String, String>,
+}
+
+/// Outcome of [`CnapiClient::allocate`](crate::CnapiClient::allocate).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AllocationResult {
+    /// Server chosen to host the VM.
+    pub server: Server,
+    /// Steps DAPI took to reach the choice, in order.
+    #[serde(default)]
+    pub steps: Vec<AllocationStep>,
+}
+
+impl AllocationResult {
+    /// UUID of the chosen server.
+    #[must_use]
+    pub fn server_uuid(&self) -> ServerUuid {
+        self.server.uuid
+    }
+}
+
+/// Options for [`CnapiClient::reboot_server`](crate::CnapiClient::reboot_server).
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ServerRebootOptions {
+    /// Wait for in-flight tasks on the server to finish before rebooting.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drain: Option<bool>,
+    /// Reboot directly instead of through a workflow job.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nojob: Option<bool>,
+    /// Free-form label for who requested the reboot, recorded on the job.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
+}
+
+/// Job descriptor returned by CNAPI server actions.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ServerJobResponse {
+    /// Workflow job running the action.
+    pub job_uuid: JobUuid,
+}
+
 /// Representation of a server NIC.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ServerNic {
@@ -318,6 +757,119 @@ mod tests {
         assert!(pairs.iter().any(|(k, v)| *k == "limit" && v == "10"));
     }
 
+    #[test]
+    fn server_list_params_appends_extra_params() {
+        let params = ServerListParams {
+            limit: Some(10),
+            extra_params: vec![
+                ("limit".into(), "5000".into()),
+                ("beta_filter".into(), "on".into()),
+            ],
+            ..ServerListParams::default()
+        };
+
+        let pairs = params.to_pairs();
+        assert_eq!(
+            pairs,
+            vec![
+                ("limit".into(), "10".to_string()),
+                ("beta_filter".into(), "on".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn running_vm_count_counts_running_vms() {
+        let running = VmUuid::new_v4();
+        let stopped = VmUuid::new_v4();
+        let server: Server = serde_json::from_value(json!({
+            "uuid": ServerUuid::new_v4(),
+            "vms": {
+                running.to_string(): {
+                    "uuid": running,
+                    "state": "running",
+                    "max_physical_memory": 1024,
+                    "cpu_cap": 100
+                },
+                stopped.to_string(): { "uuid": stopped, "state": "stopped" }
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(server.running_vm_count(), 1);
+        let vm = &server.vms.as_ref().unwrap()[&running];
+        assert_eq!(vm.max_physical_memory, Some(1024));
+        assert_eq!(vm.memory_mib(), Some(1024));
+        assert_eq!(vm.extra.get("cpu_cap"), Some(&json!(100)));
+    }
+
+    #[test]
+    fn update_server_request_validates_reservation_ratio() {
+        let request: UpdateServerRequest =
+            serde_json::from_value(json!({ "reservation_ratio": 0.25 })).unwrap();
+        assert_eq!(request.reservation_ratio, Some(Ratio::new(0.25).unwrap()));
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            json!({ "reservation_ratio": 0.25 })
+        );
+
+        assert!(
+            serde_json::from_value::<UpdateServerRequest>(json!({ "reservation_ratio": 1.5 }))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn server_status_round_trips_every_value() {
+        for (raw, status) in [
+            ("running", ServerStatus::Running),
+            ("unknown", ServerStatus::Unknown),
+            (
+                "rebooting",
+                ServerStatus::RebootingOrMaintenance("rebooting".into()),
+            ),
+            (
+                "maintenance",
+                ServerStatus::RebootingOrMaintenance("maintenance".into()),
+            ),
+            ("exploded", ServerStatus::Other("exploded".into())),
+        ] {
+            let parsed = serde_json::from_value::<ServerStatus>(json!(raw)).unwrap();
+            assert_eq!(parsed, status);
+            assert_eq!(serde_json::to_value(&parsed).unwrap(), json!(raw));
+        }
+    }
+
+    #[test]
+    fn unrecognised_status_is_stale() {
+        let server: Server = serde_json::from_value(json!({
+            "uuid": ServerUuid::new_v4(),
+            "status": "exploded"
+        }))
+        .unwrap();
+        assert!(server.is_stale(Utc::now()));
+    }
+
+    #[test]
+    fn can_fit_refuses_servers_with_old_heartbeats() {
+        let now = Utc::now();
+        let mut server: Server = serde_json::from_value(json!({
+            "uuid": ServerUuid::new_v4(),
+            "status": "running",
+            "setup": true,
+            "unreserved_ram": 4096,
+            "unreserved_disk": 100
+        }))
+        .unwrap();
+        server.last_heartbeat = Some(now - Duration::seconds(5));
+        assert!(server.can_fit(2048, 50, now));
+        assert!(!server.can_fit(8192, 50, now));
+
+        server.last_heartbeat = Some(now - Duration::minutes(10));
+        assert!(server.is_stale(now));
+        assert!(!server.can_fit(2048, 50, now));
+    }
+
     #[test]
     fn server_capacity_from_server() {
         let mut server: Server = serde_json::from_value(json!({
@@ -334,4 +886,43 @@ mod tests {
         assert_eq!(capacity.unreserved_ram, 16384);
         assert_eq!(capacity.unreserved_disk, 1024);
     }
+
+    #[test]
+    fn capacity_summary_skips_stale_reserved_and_unset_servers() {
+        let now = Utc::now();
+        let server = |extra: serde_json::Value| -> Server {
+            let mut value = json!({
+                "uuid": ServerUuid::new_v4(),
+                "status": "running",
+                "setup": true,
+                "reserved": false,
+                "unreserved_cpu": 4,
+                "unreserved_ram": 8192,
+                "unreserved_disk": 100,
+                "traits": { "ssd": true, "gpu": false }
+            });
+            value
+                .as_object_mut()
+                .unwrap()
+                .extend(extra.as_object().unwrap().clone());
+            serde_json::from_value(value).unwrap()
+        };
+        let servers = [
+            server(json!({})),
+            server(json!({ "traits": {} })),
+            server(json!({ "status": "unknown" })),
+            server(json!({ "reserved": true })),
+            server(json!({ "setup": false })),
+        ];
+
+        let summary = CapacitySummary::from_servers(&servers, now);
+        assert_eq!(summary.total_servers, 5);
+        assert_eq!(summary.live_servers, 2);
+        assert_eq!(summary.total_unreserved_cpu, 8);
+        assert_eq!(summary.total_unreserved_ram_mib, 16384);
+        assert_eq!(summary.total_unreserved_disk_gib, 200);
+        assert_eq!(summary.by_trait.len(), 1);
+        assert_eq!(summary.by_trait["ssd"].servers, 1);
+        assert_eq!(summary.by_trait["ssd"].unreserved_ram_mib, 8192);
+    }
 }
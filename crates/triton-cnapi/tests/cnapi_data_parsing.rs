@@ -5,7 +5,7 @@
 
 use std::fs;
 use std::path::PathBuf;
-use triton_cnapi::models::Server;
+use triton_cnapi::models::{CapacitySummary, Server, ServerStatus};
 
 /// Get the path to the test fixtures directory.
 fn fixtures_dir() -> PathBuf {
@@ -121,7 +121,7 @@ fn test_server_detail_complete_fields() {
     // Validate basic server info
     assert_eq!(server.hostname.as_deref(), Some("b16"));
     assert_eq!(server.datacenter.as_deref(), Some("dc-test-1"));
-    assert_eq!(server.status.as_deref(), Some("running"));
+    assert_eq!(server.status, Some(ServerStatus::Running));
     assert_eq!(server.setup, Some(true));
     assert_eq!(server.setting_up, Some(false));
     assert_eq!(server.headnode, Some(false));
@@ -129,14 +129,8 @@ fn test_server_detail_complete_fields() {
     assert_eq!(server.reservoir, Some(false));
 
     // Validate platform info
-    assert_eq!(
-        server.boot_platform.as_deref(),
-        Some("20241212T000748Z")
-    );
-    assert_eq!(
-        server.current_platform.as_deref(),
-        Some("20241112T202951Z")
-    );
+    assert_eq!(server.boot_platform.as_deref(), Some("20241212T000748Z"));
+    assert_eq!(server.current_platform.as_deref(), Some("20241112T202951Z"));
 
     // Validate timestamps
     assert!(server.created.is_some(), "Should have created timestamp");
@@ -244,10 +238,7 @@ fn test_server_sysinfo_parsing() {
             sysinfo.get("Manufacturer").and_then(|v| v.as_str()),
             Some("Generic Manufacturer")
         );
-        assert_eq!(
-            sysinfo.get("Setup").and_then(|v| v.as_str()),
-            Some("true")
-        );
+        assert_eq!(sysinfo.get("Setup").and_then(|v| v.as_str()), Some("true"));
 
         // Validate CPU info
         assert_eq!(
@@ -257,10 +248,7 @@ fn test_server_sysinfo_parsing() {
                 .map(|s| s.contains("Intel")),
             Some(true)
         );
-        assert_eq!(
-            sysinfo.get("CPU Count").and_then(|v| v.as_u64()),
-            Some(40)
-        );
+        assert_eq!(sysinfo.get("CPU Count").and_then(|v| v.as_u64()), Some(40));
         assert_eq!(
             sysinfo.get("CPU Core Count").and_then(|v| v.as_u64()),
             Some(20)
@@ -273,10 +261,7 @@ fn test_server_sysinfo_parsing() {
         );
 
         // Validate storage info
-        assert_eq!(
-            sysinfo.get("Zpool").and_then(|v| v.as_str()),
-            Some("zones")
-        );
+        assert_eq!(sysinfo.get("Zpool").and_then(|v| v.as_str()), Some("zones"));
         assert_eq!(
             sysinfo.get("Zpool Encrypted").and_then(|v| v.as_bool()),
             Some(false)
@@ -319,50 +304,35 @@ fn test_server_vms_parsing() {
         // Should have 7 VMs based on the test data
         assert_eq!(vms.len(), 7, "Should have 7 VMs");
 
-        // Check that VM entries have expected fields
-        for (vm_uuid, vm_data) in vms {
-            assert!(!vm_uuid.to_string().is_empty(), "VM should have UUID");
-
-            // Validate VM data has essential fields
-            assert!(
-                vm_data.get("uuid").is_some(),
-                "VM should have uuid field"
-            );
-            assert!(
-                vm_data.get("owner_uuid").is_some(),
-                "VM should have owner_uuid field"
-            );
-            assert!(
-                vm_data.get("state").is_some(),
-                "VM should have state field"
-            );
+        // Check that VM entries decode into typed summaries
+        for (vm_uuid, vm) in vms {
+            assert_eq!(&vm.uuid, vm_uuid, "Map key should match VM uuid");
+            assert!(vm.owner_uuid.is_some(), "VM should have owner_uuid");
+            assert_eq!(vm.state.as_deref(), Some("running"));
+            assert_eq!(vm.zone_state.as_deref(), Some("running"));
+            assert!(vm.brand.is_some(), "VM should have brand");
             assert!(
-                vm_data.get("zone_state").is_some(),
-                "VM should have zone_state field"
+                vm.max_physical_memory.is_some(),
+                "VM should have max_physical_memory"
             );
+            assert!(vm.quota.is_some(), "VM should have quota");
             assert!(
-                vm_data.get("brand").is_some(),
-                "VM should have brand field"
+                vm.extra.contains_key("cpu_cap"),
+                "Unknown keys should be kept"
             );
         }
 
         // Test specific VM brands are present
-        let has_bhyve = vms.values().any(|vm| {
-            vm.get("brand")
-                .and_then(|v| v.as_str())
-                .map(|b| b == "bhyve")
-                .unwrap_or(false)
-        });
-        let has_joyent = vms.values().any(|vm| {
-            vm.get("brand")
-                .and_then(|v| v.as_str())
-                .map(|b| b.contains("joyent"))
-                .unwrap_or(false)
-        });
+        let has_bhyve = vms.values().any(|vm| vm.brand.as_deref() == Some("bhyve"));
+        let has_joyent = vms
+            .values()
+            .any(|vm| vm.brand.as_deref().is_some_and(|b| b.contains("joyent")));
 
         assert!(has_bhyve, "Should have bhyve brand VMs");
         assert!(has_joyent, "Should have joyent brand VMs");
     }
+
+    assert_eq!(server.running_vm_count(), 7);
 }
 
 #[test]
@@ -421,10 +391,7 @@ fn test_server_traits_parsing() {
 
     assert!(has_m5d_trait, "Should have servers with m5d trait");
     assert!(has_manta_trait, "Should have servers with manta trait");
-    assert!(
-        has_storage_trait,
-        "Should have servers with storage trait"
-    );
+    assert!(has_storage_trait, "Should have servers with storage trait");
     assert!(
         has_headnode_trait,
         "Should have servers with headnode trait"
@@ -456,9 +423,7 @@ fn test_server_boot_params_parsing() {
 
         // Validate specific values
         assert_eq!(
-            boot_params
-                .get("rabbitmq")
-                .and_then(|v| v.as_str()),
+            boot_params.get("rabbitmq").and_then(|v| v.as_str()),
             Some("guest:guest:rabbitmq.dc-test-1.example.local:5672")
         );
         assert_eq!(
@@ -475,15 +440,103 @@ fn test_server_status_variations() {
 
     let has_running = servers
         .iter()
-        .any(|s| s.status.as_deref() == Some("running"));
+        .any(|s| s.status == Some(ServerStatus::Running));
     let has_unknown = servers
         .iter()
-        .any(|s| s.status.as_deref() == Some("unknown"));
+        .any(|s| s.status == Some(ServerStatus::Unknown));
 
     assert!(has_running, "Should have servers with running status");
     assert!(has_unknown, "Should have servers with unknown status");
 }
 
+#[test]
+fn test_unknown_servers_are_stale_and_never_fit() {
+    let json_data = load_server_list_fixture();
+    let servers: Vec<Server> = serde_json::from_str(&json_data).unwrap();
+    let now = chrono::Utc::now();
+
+    // The fixture omits capacity, so give every server room to spare; only
+    // status and freshness should decide placement.
+    let with_capacity = |server: &Server| {
+        let mut server = server.clone();
+        server.unreserved_ram = Some(65536);
+        server.unreserved_disk = Some(1024);
+        server
+    };
+
+    let unknown: Vec<Server> = servers
+        .iter()
+        .filter(|s| s.status == Some(ServerStatus::Unknown))
+        .map(with_capacity)
+        .collect();
+    assert_eq!(unknown.len(), 2);
+    for server in &unknown {
+        assert!(server.is_stale(now), "{} should be stale", server.uuid);
+        assert!(
+            !server.can_fit(1024, 10, now),
+            "{} should not fit",
+            server.uuid
+        );
+    }
+
+    let live = servers
+        .iter()
+        .find(|s| s.status == Some(ServerStatus::Running) && s.reserved == Some(false))
+        .map(with_capacity)
+        .expect("Should have an unreserved running server");
+    assert!(!live.is_stale(now));
+    assert!(live.can_fit(1024, 10, now));
+}
+
+#[test]
+fn test_datacenter_capacity_excludes_stale_servers() {
+    let json_data = load_server_list_fixture();
+    let servers: Vec<Server> = serde_json::from_str(&json_data).unwrap();
+    let now = chrono::Utc::now();
+
+    // The fixture omits capacity, so derive it from each server's RAM.
+    let servers: Vec<Server> = servers
+        .into_iter()
+        .map(|mut server| {
+            let ram = i64::try_from(server.ram.unwrap_or_default()).unwrap();
+            server.unreserved_cpu = Some(8);
+            server.unreserved_ram = Some(ram / 2);
+            server.unreserved_disk = Some(100);
+            server
+        })
+        .collect();
+
+    let live: Vec<&Server> = servers
+        .iter()
+        .filter(|s| s.status == Some(ServerStatus::Running))
+        .filter(|s| s.setup != Some(false) && s.reserved != Some(true))
+        .collect();
+    let summary = CapacitySummary::from_servers(&servers, now);
+
+    assert_eq!(summary.total_servers, servers.len());
+    assert_eq!(summary.live_servers, live.len());
+    assert_eq!(summary.total_unreserved_cpu, 8 * live.len() as i64);
+    assert_eq!(
+        summary.total_unreserved_ram_mib,
+        live.iter()
+            .map(|s| s.unreserved_ram.unwrap_or_default())
+            .sum::<i64>()
+    );
+    assert_eq!(summary.total_unreserved_disk_gib, 100 * live.len() as i64);
+
+    // Both `unknown` servers are unreserved and set up, so only staleness excludes them.
+    let unknown = servers
+        .iter()
+        .filter(|s| s.status == Some(ServerStatus::Unknown))
+        .count();
+    assert_eq!(unknown, 2);
+    let m5d = live
+        .iter()
+        .filter(|s| s.traits.as_ref().is_some_and(|t| t.contains_key("m5d")))
+        .count();
+    assert_eq!(summary.by_trait["m5d"].servers, m5d);
+}
+
 #[test]
 fn test_server_headnode_flag() {
     let json_data = load_server_list_fixture();
@@ -519,10 +572,7 @@ fn test_server_setup_states() {
     // Find a server that is currently being set up
     let has_setting_up = servers.iter().any(|s| s.setting_up == Some(true));
 
-    assert!(
-        has_setting_up,
-        "Should have servers currently being setup"
-    );
+    assert!(has_setting_up, "Should have servers currently being setup");
 
     // All servers in this dataset should have setup = true
     assert!(
@@ -537,9 +587,12 @@ fn test_server_rack_identifier() {
     let servers: Vec<Server> = serde_json::from_str(&json_data).unwrap();
 
     // Find servers with rack identifiers
-    let has_rack_id = servers
-        .iter()
-        .any(|s| s.rack_identifier.as_ref().map(|r| !r.is_empty()).unwrap_or(false));
+    let has_rack_id = servers.iter().any(|s| {
+        s.rack_identifier
+            .as_ref()
+            .map(|r| !r.is_empty())
+            .unwrap_or(false)
+    });
 
     // At least one server has a rack identifier in the test data
     assert!(has_rack_id, "Should have servers with rack identifiers");
@@ -566,11 +619,11 @@ fn test_server_roundtrip_serialization() {
     let original_server: Server = serde_json::from_str(&json_data).unwrap();
 
     // Serialize and deserialize to ensure roundtrip works
-    let serialized = serde_json::to_string(&original_server)
-        .expect("Should be able to serialize server");
+    let serialized =
+        serde_json::to_string(&original_server).expect("Should be able to serialize server");
 
-    let deserialized: Server = serde_json::from_str(&serialized)
-        .expect("Should be able to deserialize serialized server");
+    let deserialized: Server =
+        serde_json::from_str(&serialized).expect("Should be able to deserialize serialized server");
 
     // Key fields should match
     assert_eq!(original_server.uuid, deserialized.uuid);
@@ -608,7 +661,10 @@ fn test_timestamp_parsing() {
 
     // Validate timestamps are parsed correctly
     assert!(server.created.is_some(), "Should have created timestamp");
-    assert!(server.last_boot.is_some(), "Should have last_boot timestamp");
+    assert!(
+        server.last_boot.is_some(),
+        "Should have last_boot timestamp"
+    );
     assert!(
         server.last_heartbeat.is_some(),
         "Should have last_heartbeat timestamp"
@@ -626,10 +682,7 @@ fn test_server_with_different_ram_sizes() {
     let servers: Vec<Server> = serde_json::from_str(&json_data).unwrap();
 
     // Collect different RAM sizes
-    let mut ram_sizes: Vec<u64> = servers
-        .iter()
-        .filter_map(|s| s.ram)
-        .collect();
+    let mut ram_sizes: Vec<u64> = servers.iter().filter_map(|s| s.ram).collect();
     ram_sizes.sort();
     ram_sizes.dedup();
 
@@ -649,3 +702,31 @@ fn test_server_with_different_ram_sizes() {
         "Should have lower-memory servers"
     );
 }
+
+#[test]
+fn test_server_vms_with_both_ram_keys() {
+    let fixture_path = fixtures_dir().join("server_vm_ram_keys.json");
+    let json_data = fs::read_to_string(&fixture_path).unwrap();
+    let server: Server = serde_json::from_str(&json_data)
+        .expect("VM entries carrying both ram and max_physical_memory should parse");
+
+    let vms = server.vms.as_ref().expect("Should have VMs");
+    assert_eq!(vms.len(), 2);
+    assert_eq!(server.running_vm_count(), 1);
+
+    let both = vms
+        .values()
+        .find(|vm| vm.brand.as_deref() == Some("joyent"))
+        .unwrap();
+    assert_eq!(both.max_physical_memory, Some(2048));
+    assert_eq!(both.ram, Some(1024));
+    assert_eq!(both.memory_mib(), Some(2048));
+
+    let legacy = vms
+        .values()
+        .find(|vm| vm.brand.as_deref() == Some("bhyve"))
+        .unwrap();
+    assert_eq!(legacy.max_physical_memory, None);
+    assert_eq!(legacy.memory_mib(), Some(4096));
+    assert!(legacy.extra.is_empty());
+}
@@ -0,0 +1,514 @@
+//! Capacity-bounded caches for long-lived clients.
+//!
+//! Per-resource caches keyed by UUID grow with the number of resources a
+//! process touches. [`LruCache`] caps that growth by evicting the entry that
+//! was used least recently. [`ResponseCache`] builds on it to serve repeated
+//! GET requests from memory for a fixed time-to-live.
+
+use crate::clock::{Clock, SystemClock};
+use reqwest::header::{
+    HeaderMap, HeaderName, HeaderValue, CONTENT_LENGTH, CONTENT_TYPE, IF_MODIFIED_SINCE,
+    IF_NONE_MATCH,
+};
+use reqwest::{Method, Request, Response, StatusCode, Url};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Default number of entries kept by client caches.
+pub const DEFAULT_CACHE_CAPACITY: usize = 1024;
+
+/// Default size limit, in bytes, of a response body kept by [`ResponseCache`].
+pub const DEFAULT_MAX_CACHED_BODY: usize = 1024 * 1024;
+
+/// Request headers that identify the caller; their values are part of the cache key.
+const IDENTITY_HEADERS: [&str; 3] = ["authorization", "x-auth-token", "x-api-key"];
+
+/// Least-recently-used cache holding at most `capacity` entries.
+#[derive(Debug, Clone)]
+pub struct LruCache<K, V> {
+    capacity: usize,
+    tick: u64,
+    entries: HashMap<K, (V, u64)>,
+    order: BTreeMap<u64, K>,
+}
+
+impl<K, V> LruCache<K, V>
+where
+    K: Clone + Eq + Hash,
+{
+    /// Create a cache holding at most `capacity` entries (minimum one).
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            tick: 0,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+        }
+    }
+
+    /// Maximum number of entries.
+    #[must_use]
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of cached entries.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true when nothing is cached.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Look up `key`, marking it as most recently used.
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let tick = self.next_tick();
+        let (_, used) = self.entries.get_mut(key)?;
+        self.order.remove(used);
+        *used = tick;
+        self.order.insert(tick, key.clone());
+        self.entries.get(key).map(|(value, _)| value)
+    }
+
+    /// Insert `value` under `key`, evicting the least recently used entry when
+    /// full. Returns the value previously stored under `key`.
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        let tick = self.next_tick();
+        if let Some((previous, used)) = self.entries.remove(&key) {
+            self.order.remove(&used);
+            self.insert(key, value, tick);
+            return Some(previous);
+        }
+
+        if self.entries.len() >= self.capacity {
+            if let Some((_, oldest)) = self.order.pop_first() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.insert(key, value, tick);
+        None
+    }
+
+    /// Remove `key`, returning its value.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let (value, used) = self.entries.remove(key)?;
+        self.order.remove(&used);
+        Some(value)
+    }
+
+    /// Remove every entry for which `keep` returns false.
+    pub fn retain(&mut self, mut keep: impl FnMut(&K, &V) -> bool) {
+        let order = &mut self.order;
+        self.entries.retain(|key, (value, used)| {
+            let kept = keep(key, value);
+            if !kept {
+                order.remove(used);
+            }
+            kept
+        });
+    }
+
+    /// Remove every entry.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+
+    fn insert(&mut self, key: K, value: V, tick: u64) {
+        self.order.insert(tick, key.clone());
+        self.entries.insert(key, (value, tick));
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+}
+
+impl<K, V> Default for LruCache<K, V>
+where
+    K: Clone + Eq + Hash,
+{
+    fn default() -> Self {
+        Self::new(DEFAULT_CACHE_CAPACITY)
+    }
+}
+
+/// In-process cache of successful GET responses, shared by service clients.
+///
+/// Only JSON bodies up to [`max_body_size`](Self::max_body_size) are kept, so
+/// file downloads still stream. Entries are keyed by full URL (path and query)
+/// and the caller's credentials, so clients sharing a cache never see each
+/// other's responses, and expire after the configured time-to-live.
+/// Conditional GETs bypass the cache. A request with any other method drops
+/// the entries for its path and the collections above it, so
+/// `POST /vms/:uuid` evicts both `/vms/:uuid` and `/vms`.
+///
+/// Attach one to a client builder with
+/// [`with_response_cache`](crate::client::ServiceClientBuilder::with_response_cache).
+pub struct ResponseCache {
+    ttl: Duration,
+    max_body_size: usize,
+    clock: Arc<dyn Clock>,
+    entries: Mutex<LruCache<CacheKey, CachedResponse>>,
+}
+
+/// Identifies a cacheable request: its URL and a digest of the caller's credentials.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct CacheKey {
+    url: Url,
+    identity: u64,
+}
+
+impl CacheKey {
+    /// The key for `request`, or `None` unless it is an unconditional GET.
+    pub(crate) fn for_request(request: &Request) -> Option<Self> {
+        let headers = request.headers();
+        if request.method() != Method::GET
+            || headers.contains_key(IF_NONE_MATCH)
+            || headers.contains_key(IF_MODIFIED_SINCE)
+        {
+            return None;
+        }
+
+        let mut hasher = DefaultHasher::new();
+        for name in IDENTITY_HEADERS {
+            headers
+                .get(HeaderName::from_static(name))
+                .map(HeaderValue::as_bytes)
+                .hash(&mut hasher);
+        }
+        Some(Self {
+            url: request.url().clone(),
+            identity: hasher.finish(),
+        })
+    }
+
+    /// The URL this key caches.
+    pub(crate) const fn url(&self) -> &Url {
+        &self.url
+    }
+}
+
+#[derive(Clone)]
+struct CachedResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Vec<u8>,
+    stored_at: Instant,
+}
+
+impl ResponseCache {
+    /// Create a cache whose entries live for `ttl`, holding at most
+    /// [`DEFAULT_CACHE_CAPACITY`] responses.
+    #[must_use]
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            max_body_size: DEFAULT_MAX_CACHED_BODY,
+            clock: Arc::new(SystemClock),
+            entries: Mutex::new(LruCache::new(DEFAULT_CACHE_CAPACITY)),
+        }
+    }
+
+    /// Keep only bodies of at most `bytes` bytes.
+    #[must_use]
+    pub const fn with_max_body_size(mut self, bytes: usize) -> Self {
+        self.max_body_size = bytes;
+        self
+    }
+
+    /// Hold at most `capacity` responses (minimum one).
+    #[must_use]
+    pub fn with_capacity(self, capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+            ..self
+        }
+    }
+
+    /// Measure entry age with `clock` instead of the system clock.
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Time-to-live of each entry.
+    #[must_use]
+    pub const fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Largest body, in bytes, that is cached.
+    #[must_use]
+    pub const fn max_body_size(&self) -> usize {
+        self.max_body_size
+    }
+
+    /// Number of cached responses, expired ones included until next looked up.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns true when nothing is cached.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Drop every cached response.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Returns true if a successful response with `headers` may be stored:
+    /// its body is JSON and not declared larger than the size limit.
+    pub(crate) fn accepts(&self, headers: &HeaderMap) -> bool {
+        let json = headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(';').next())
+            .is_some_and(|mime| {
+                let mime = mime.trim();
+                mime.eq_ignore_ascii_case("application/json") || mime.ends_with("+json")
+            });
+        let length = headers
+            .get(CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<usize>().ok());
+        json && length.map_or(true, |length| length <= self.max_body_size)
+    }
+
+    /// A fresh response stored under `key`, evicting it once expired.
+    pub(crate) fn get(&self, key: &CacheKey) -> Option<Response> {
+        let now = self.clock.now();
+        let mut entries = self.lock();
+        let cached = entries.get(key)?.clone();
+        if now.saturating_duration_since(cached.stored_at) >= self.ttl {
+            entries.remove(key);
+            return None;
+        }
+
+        let mut response = http::Response::new(cached.body);
+        *response.status_mut() = cached.status;
+        *response.headers_mut() = cached.headers;
+        Some(Response::from(response))
+    }
+
+    /// Store a successful response body under `key`, unless it is over the size limit.
+    pub(crate) fn put(&self, key: CacheKey, status: StatusCode, headers: HeaderMap, body: Vec<u8>) {
+        if body.len() > self.max_body_size {
+            return;
+        }
+        let stored_at = self.clock.now();
+        self.lock().put(
+            key,
+            CachedResponse {
+                status,
+                headers,
+                body,
+                stored_at,
+            },
+        );
+    }
+
+    /// Drop the entries for `url`'s path and every collection above it, for
+    /// any query and any caller.
+    pub(crate) fn invalidate(&self, url: &Url) {
+        let changed = url.path().trim_end_matches('/');
+        self.lock().retain(|key, _| {
+            let cached = key.url.path().trim_end_matches('/');
+            let affected = key.url.origin() == url.origin()
+                && (changed == cached || changed.starts_with(&format!("{cached}/")));
+            !affected
+        });
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LruCache<CacheKey, CachedResponse>> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl fmt::Debug for ResponseCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseCache")
+            .field("ttl", &self.ttl)
+            .field("max_body_size", &self.max_body_size)
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::AUTHORIZATION;
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut cache = LruCache::new(2);
+        cache.put("a", 1);
+        cache.put("b", 2);
+        assert_eq!(cache.get(&"a"), Some(&1));
+
+        cache.put("c", 3);
+        assert_eq!(cache.get(&"b"), None);
+        assert_eq!(cache.get(&"a"), Some(&1));
+        assert_eq!(cache.get(&"c"), Some(&3));
+    }
+
+    #[test]
+    fn enforces_capacity() {
+        let mut cache = LruCache::new(3);
+        for i in 0..10 {
+            cache.put(i, i * 10);
+            assert!(cache.len() <= 3);
+        }
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache.get(&6), None);
+        assert_eq!(cache.get(&9), Some(&90));
+    }
+
+    #[test]
+    fn replacing_refreshes_without_evicting() {
+        let mut cache = LruCache::new(2);
+        cache.put("a", 1);
+        cache.put("b", 2);
+        assert_eq!(cache.put("a", 10), Some(1));
+        assert_eq!(cache.len(), 2);
+
+        cache.put("c", 3);
+        assert_eq!(cache.get(&"b"), None);
+        assert_eq!(cache.get(&"a"), Some(&10));
+    }
+
+    #[test]
+    fn zero_capacity_holds_one_entry() {
+        let mut cache = LruCache::new(0);
+        cache.put("a", 1);
+        cache.put("b", 2);
+        assert_eq!(cache.capacity(), 1);
+        assert_eq!(cache.remove(&"b"), Some(2));
+        assert!(cache.is_empty());
+    }
+
+    fn url(path: &str) -> Url {
+        Url::parse("http://vmapi.example.com")
+            .unwrap()
+            .join(path)
+            .unwrap()
+    }
+
+    fn request(method: Method, path: &str, headers: &[(HeaderName, &str)]) -> Request {
+        let mut request = Request::new(method, url(path));
+        for (name, value) in headers {
+            request
+                .headers_mut()
+                .insert(name.clone(), value.parse().unwrap());
+        }
+        request
+    }
+
+    fn key(path: &str) -> CacheKey {
+        CacheKey::for_request(&request(Method::GET, path, &[])).unwrap()
+    }
+
+    fn json_headers(length: usize) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            CONTENT_TYPE,
+            "application/json; charset=utf-8".parse().unwrap(),
+        );
+        headers.insert(CONTENT_LENGTH, length.into());
+        headers
+    }
+
+    #[test]
+    fn response_cache_keys_only_unconditional_gets() {
+        assert_eq!(key("/vms?state=running").url(), &url("/vms?state=running"));
+        assert!(CacheKey::for_request(&request(Method::POST, "/vms", &[])).is_none());
+        assert!(CacheKey::for_request(&request(Method::DELETE, "/vms", &[])).is_none());
+        let conditional = request(Method::GET, "/vms", &[(IF_NONE_MATCH, "\"abc\"")]);
+        assert!(CacheKey::for_request(&conditional).is_none());
+    }
+
+    #[test]
+    fn response_cache_key_includes_credentials() {
+        let alice = request(
+            Method::GET,
+            "/vms",
+            &[(AUTHORIZATION, "Basic YWxpY2U6eA==")],
+        );
+        let bob = request(Method::GET, "/vms", &[(AUTHORIZATION, "Basic Ym9iOng=")]);
+        let alice = CacheKey::for_request(&alice).unwrap();
+        let bob = CacheKey::for_request(&bob).unwrap();
+        assert_ne!(alice, bob);
+        assert_ne!(alice, key("/vms"));
+    }
+
+    #[test]
+    fn response_cache_accepts_only_bounded_json() {
+        let cache = ResponseCache::new(Duration::from_secs(5)).with_max_body_size(10);
+        assert!(cache.accepts(&json_headers(10)));
+        assert!(!cache.accepts(&json_headers(11)));
+
+        let mut headers = json_headers(4);
+        headers.insert(CONTENT_TYPE, "application/octet-stream".parse().unwrap());
+        assert!(!cache.accepts(&headers));
+
+        cache.put(
+            key("/big"),
+            StatusCode::OK,
+            HeaderMap::new(),
+            vec![b' '; 11],
+        );
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn response_cache_invalidates_path_and_parents() {
+        let cache = ResponseCache::new(Duration::from_secs(5));
+        for path in ["/vms", "/vms?state=running", "/vms/a", "/vms/b", "/images"] {
+            cache.put(key(path), StatusCode::OK, HeaderMap::new(), b"[]".to_vec());
+        }
+
+        cache.invalidate(&url("/vms/a?action=stop"));
+        assert!(cache.get(&key("/vms")).is_none());
+        assert!(cache.get(&key("/vms?state=running")).is_none());
+        assert!(cache.get(&key("/vms/a")).is_none());
+        assert!(cache.get(&key("/vms/b")).is_some());
+        assert!(cache.get(&key("/images")).is_some());
+    }
+
+    #[test]
+    fn response_cache_expires_after_ttl() {
+        let clock = crate::clock::ManualClock::new();
+        let cache = ResponseCache::new(Duration::from_secs(5)).with_clock(Arc::new(clock.clone()));
+        let key = key("/vms");
+        cache.put(
+            key.clone(),
+            StatusCode::OK,
+            HeaderMap::new(),
+            b"[]".to_vec(),
+        );
+
+        clock.advance(Duration::from_secs(4));
+        let response = cache.get(&key).unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        clock.advance(Duration::from_secs(1));
+        assert!(cache.get(&key).is_none());
+        assert!(cache.is_empty());
+    }
+}
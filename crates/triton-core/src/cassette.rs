@@ -0,0 +1,365 @@
+//! Record and replay HTTP interactions (requires the `record-replay` feature).
+//!
+//! A [`Cassette`] attached via
+//! [`ServiceClientBuilder::with_cassette`](crate::client::ServiceClientBuilder::with_cassette)
+//! either records every response the service returns to a JSON file, or
+//! serves responses from a previously recorded file without touching the
+//! network. Replayed requests are matched on method, path, and query string;
+//! a request with no remaining recorded match fails with
+//! [`Error::InvalidRequest`].
+//!
+//! Credentials never reach the file: request headers are not recorded,
+//! sensitive response headers are replaced with `[REDACTED]`, and so are JSON
+//! body fields and query parameters whose names look like secrets (`password`,
+//! `token`, `root_pw`, ...).
+
+use crate::Error;
+use reqwest::{Client, RequestBuilder, Response};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Placeholder written in place of scrubbed values.
+pub const REDACTED: &str = "[REDACTED]";
+
+const SENSITIVE_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "x-auth-token",
+    "cookie",
+    "set-cookie",
+];
+
+const SENSITIVE_KEY_FRAGMENTS: &[&str] = &[
+    "password",
+    "passphrase",
+    "secret",
+    "token",
+    "private_key",
+    "credential",
+];
+
+// Recorded bodies are already decoded, so framing headers would be wrong on replay.
+const FRAMING_HEADERS: &[&str] = &["content-length", "content-encoding", "transfer-encoding"];
+
+/// Whether a [`Cassette`] records live traffic or replays a recording.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Send requests to the service and save each response.
+    Record,
+    /// Serve responses from the cassette file; nothing is sent.
+    Replay,
+}
+
+/// A single recorded request/response pair.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Interaction {
+    /// HTTP method, e.g. `GET`.
+    pub method: String,
+    /// URL path, e.g. `/vms`.
+    pub path: String,
+    /// Scrubbed query string without the leading `?`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// Response status code.
+    pub status: u16,
+    /// Scrubbed response headers in the order they were received.
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    /// Scrubbed response body (lossily decoded as UTF-8).
+    #[serde(default)]
+    pub body: String,
+}
+
+impl Interaction {
+    fn matches(&self, method: &str, path: &str, query: Option<&str>) -> bool {
+        self.method == method && self.path == path && self.query.as_deref() == query
+    }
+
+    fn to_response(&self) -> crate::Result<Response> {
+        build_response(self.status, &self.headers, self.body.clone().into_bytes())
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+/// Recorded HTTP interactions backed by a JSON file.
+///
+/// Cloning a cassette shares its interactions, so every client built from
+/// the same builder records to (or replays from) one file.
+#[derive(Clone)]
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    interactions: Arc<Mutex<Vec<Interaction>>>,
+}
+
+impl fmt::Debug for Cassette {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cassette")
+            .field("path", &self.path)
+            .field("mode", &self.mode)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Cassette {
+    /// Start a new recording that is written to `path` after every interaction.
+    #[must_use]
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            mode: CassetteMode::Record,
+            interactions: Arc::default(),
+        }
+    }
+
+    /// Load a recording from `path` for replay.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ConfigError`] if the file cannot be read or parsed.
+    pub fn replay(path: impl Into<PathBuf>) -> crate::Result<Self> {
+        let path = path.into();
+        let contents = std::fs::read_to_string(&path).map_err(|err| {
+            Error::ConfigError(format!("Failed to read cassette {}: {err}", path.display()))
+        })?;
+        let file: CassetteFile = serde_json::from_str(&contents).map_err(|err| {
+            Error::ConfigError(format!(
+                "Failed to parse cassette {}: {err}",
+                path.display()
+            ))
+        })?;
+
+        Ok(Self {
+            path,
+            mode: CassetteMode::Replay,
+            interactions: Arc::new(Mutex::new(file.interactions)),
+        })
+    }
+
+    /// Returns the cassette file location.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns whether this cassette records or replays.
+    #[must_use]
+    pub const fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    /// Interactions recorded so far, or still waiting to be replayed.
+    #[must_use]
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.lock().clone()
+    }
+
+    pub(crate) async fn dispatch(
+        &self,
+        http: &Client,
+        request: RequestBuilder,
+    ) -> crate::Result<Response> {
+        let request = request.build()?;
+        let method = request.method().to_string();
+        let path = request.url().path().to_string();
+        let query = request.url().query().map(scrub_query);
+
+        match self.mode {
+            CassetteMode::Replay => {
+                let mut interactions = self.lock();
+                let index = interactions
+                    .iter()
+                    .position(|interaction| interaction.matches(&method, &path, query.as_deref()))
+                    .ok_or_else(|| {
+                        Error::InvalidRequest(format!(
+                            "No recorded interaction for {method} {path}{} in cassette {}",
+                            query.as_ref().map(|q| format!("?{q}")).unwrap_or_default(),
+                            self.path.display()
+                        ))
+                    })?;
+                interactions.remove(index).to_response()
+            }
+            CassetteMode::Record => {
+                let response = http.execute(request).await?;
+                let status = response.status().as_u16();
+                let headers: Vec<(String, String)> = response
+                    .headers()
+                    .iter()
+                    .filter(|(name, _)| !FRAMING_HEADERS.contains(&name.as_str()))
+                    .map(|(name, value)| {
+                        (
+                            name.as_str().to_string(),
+                            String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                        )
+                    })
+                    .collect();
+                let body = response.bytes().await?.to_vec();
+
+                self.save(Interaction {
+                    method,
+                    path,
+                    query,
+                    status,
+                    headers: scrub_headers(&headers),
+                    body: scrub_body(&String::from_utf8_lossy(&body)),
+                })?;
+                build_response(status, &headers, body)
+            }
+        }
+    }
+
+    fn save(&self, interaction: Interaction) -> crate::Result<()> {
+        let mut interactions = self.lock();
+        interactions.push(interaction);
+        let file = CassetteFile {
+            interactions: interactions.clone(),
+        };
+        let json = serde_json::to_string_pretty(&file)
+            .map_err(|err| Error::InternalError(format!("Failed to encode cassette: {err}")))?;
+        std::fs::write(&self.path, json).map_err(|err| {
+            Error::InternalError(format!(
+                "Failed to write cassette {}: {err}",
+                self.path.display()
+            ))
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Interaction>> {
+        self.interactions
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+fn build_response(
+    status: u16,
+    headers: &[(String, String)],
+    body: Vec<u8>,
+) -> crate::Result<Response> {
+    let mut builder = http::Response::builder().status(status);
+    for (name, value) in headers {
+        builder = builder.header(name.as_str(), value.as_str());
+    }
+    builder
+        .body(body)
+        .map(Response::from)
+        .map_err(|err| Error::InternalError(format!("Invalid recorded response: {err}")))
+}
+
+fn is_sensitive_key(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    key == "pw"
+        || key.ends_with("_pw")
+        || SENSITIVE_KEY_FRAGMENTS
+            .iter()
+            .any(|fragment| key.contains(fragment))
+}
+
+fn scrub_headers(headers: &[(String, String)]) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if SENSITIVE_HEADERS.contains(&name.to_ascii_lowercase().as_str()) {
+                REDACTED.to_string()
+            } else {
+                value.clone()
+            };
+            (name.clone(), value)
+        })
+        .collect()
+}
+
+fn scrub_query(query: &str) -> String {
+    query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((key, _)) if is_sensitive_key(key) => format!("{key}={REDACTED}"),
+            _ => pair.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn scrub_body(body: &str) -> String {
+    match serde_json::from_str::<Value>(body) {
+        Ok(mut value) => {
+            scrub_value(&mut value);
+            value.to_string()
+        }
+        Err(_) => body.to_string(),
+    }
+}
+
+fn scrub_value(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if is_sensitive_key(key) {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    scrub_value(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(scrub_value),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn scrub_body_redacts_nested_secrets() {
+        let body = json!([{
+            "uuid": "abc",
+            "customer_metadata": { "root_pw": "hunter2", "user-script": "echo hi" },
+            "auth": { "api_token": "t0k3n", "password": "pw" }
+        }])
+        .to_string();
+
+        let scrubbed: Value = serde_json::from_str(&scrub_body(&body)).unwrap();
+        assert_eq!(
+            scrubbed,
+            json!([{
+                "uuid": "abc",
+                "customer_metadata": { "root_pw": REDACTED, "user-script": "echo hi" },
+                "auth": { "api_token": REDACTED, "password": REDACTED }
+            }])
+        );
+    }
+
+    #[test]
+    fn scrub_headers_and_query_redact_credentials() {
+        let headers = vec![
+            ("Set-Cookie".to_string(), "session=1".to_string()),
+            ("etag".to_string(), "\"v1\"".to_string()),
+        ];
+        assert_eq!(
+            scrub_headers(&headers),
+            vec![
+                ("Set-Cookie".to_string(), REDACTED.to_string()),
+                ("etag".to_string(), "\"v1\"".to_string()),
+            ]
+        );
+        assert_eq!(
+            scrub_query("state=running&auth_token=abc"),
+            format!("state=running&auth_token={REDACTED}")
+        );
+    }
+
+    #[test]
+    fn replay_rejects_missing_cassette() {
+        let err = Cassette::replay("/nonexistent/cassette.json").unwrap_err();
+        assert!(matches!(err, Error::ConfigError(_)));
+    }
+}
@@ -3,13 +3,32 @@
 //! This module provides HTTP client configuration and retry policies
 //! for reliable communication with Triton DataCenter services.
 
-use crate::error::Error;
-use crate::types::TritonService;
-use reqwest::{Client, ClientBuilder, Method, RequestBuilder, Response, StatusCode};
-use std::time::Duration;
+use crate::cache::{CacheKey, ResponseCache};
+#[cfg(feature = "record-replay")]
+use crate::cassette::Cassette;
+use crate::config::TritonClientConfig;
+use crate::context::{RequestContext, REQUEST_ID_TAG};
+use crate::error::{Error, TimeoutKind};
+use crate::otel::RequestSpan;
+use crate::preflight::PreflightResult;
+use crate::query::QueryPair;
+use crate::selection::{EndpointSelector, StickySession};
+use crate::types::{EndpointList, ServiceEndpoint, TritonService};
+use reqwest::header::{HeaderMap, HeaderName, CONTENT_TYPE, RETRY_AFTER};
+use reqwest::{
+    Client, ClientBuilder, Identity, Method, NoProxy, Proxy, RequestBuilder, Response, StatusCode,
+};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt;
+use std::future::Future;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant, SystemTime};
 use tokio::time::sleep;
-use tracing::debug;
-use url::Url;
+use tracing::{debug, info_span, warn, Instrument};
+use url::{Host, Url};
 
 // Service-specific timeout configurations (in seconds)
 
@@ -51,6 +70,12 @@ pub const DEFAULT_POOL_IDLE_TIMEOUT: u64 = 90;
 /// Default maximum idle connections per host
 pub const DEFAULT_POOL_MAX_IDLE_PER_HOST: usize = 10;
 
+/// Default connection establishment timeout (in seconds)
+pub const DEFAULT_CONNECT_TIMEOUT: u64 = 10;
+
+/// Header carrying the per-call request id, echoed into Triton service logs
+pub const DEFAULT_REQUEST_ID_HEADER: &str = "x-request-id";
+
 // Retry settings
 
 /// Default maximum number of retry attempts
@@ -62,6 +87,19 @@ pub const DEFAULT_RETRY_DELAY_MS: u64 = 500;
 /// Default maximum retry delay in milliseconds (for exponential backoff)
 pub const DEFAULT_RETRY_MAX_DELAY_MS: u64 = 5000;
 
+/// Randomization applied to retry delays.
+///
+/// Without jitter every client that failed at the same moment also retries at
+/// the same moment; spreading the delays avoids a thundering herd.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JitterKind {
+    /// Pick uniformly from `[0, computed_delay]`.
+    Full,
+    /// Pick uniformly from `[initial_delay, previous_delay * 3]`, capped at
+    /// `max_delay`, so each delay grows from the one actually slept.
+    Decorrelated,
+}
+
 /// Retry policy with exponential backoff.
 ///
 /// Configures how HTTP requests should be retried on failure, using exponential
@@ -79,6 +117,9 @@ pub struct RetryPolicy {
 
     /// Backoff multiplier (typically 2.0 for exponential backoff)
     pub backoff_multiplier: u32,
+
+    /// Optional randomization of the computed delay
+    pub jitter: Option<JitterKind>,
 }
 
 impl RetryPolicy {
@@ -90,6 +131,7 @@ impl RetryPolicy {
             initial_delay: Duration::from_millis(DEFAULT_RETRY_DELAY_MS),
             max_delay: Duration::from_millis(DEFAULT_RETRY_MAX_DELAY_MS),
             backoff_multiplier: 2,
+            jitter: None,
         }
     }
 
@@ -101,6 +143,7 @@ impl RetryPolicy {
             initial_delay: Duration::from_millis(0),
             max_delay: Duration::from_millis(0),
             backoff_multiplier: 1,
+            jitter: None,
         }
     }
 
@@ -132,22 +175,66 @@ impl RetryPolicy {
         self
     }
 
+    /// Randomize delays with the given jitter strategy.
+    #[must_use]
+    pub const fn with_jitter(mut self, jitter: JitterKind) -> Self {
+        self.jitter = Some(jitter);
+        self
+    }
+
     /// Calculate delay for a given attempt number.
     ///
-    /// Uses exponential backoff: delay = min(initial_delay * multiplier^attempt, max_delay)
+    /// Uses exponential backoff: delay = `min(initial_delay * multiplier^attempt, max_delay)`,
+    /// then applies [`RetryPolicy::jitter`]. Decorrelated jitter assumes the
+    /// previous attempt slept its un-jittered delay; retry loops should use
+    /// [`RetryPolicy::next_delay`] to feed back the delay they actually slept.
     #[must_use]
     pub fn delay_for_attempt(&self, attempt: u32) -> Duration {
         if attempt == 0 {
             return Duration::from_secs(0);
         }
 
+        let delay = self.backoff_delay(attempt);
+        match self.jitter {
+            None => delay,
+            Some(JitterKind::Full) => random_between(Duration::ZERO, delay),
+            Some(JitterKind::Decorrelated) => {
+                self.decorrelated_delay(self.backoff_delay(attempt - 1))
+            }
+        }
+    }
+
+    /// Calculate the delay before retry `attempt`, given the delay slept before the previous one.
+    ///
+    /// Identical to [`RetryPolicy::delay_for_attempt`] except that
+    /// decorrelated jitter grows from `previous` rather than from the
+    /// un-jittered schedule. Pass [`Duration::ZERO`] before the first retry.
+    #[must_use]
+    pub fn next_delay(&self, attempt: u32, previous: Duration) -> Duration {
+        match self.jitter {
+            Some(JitterKind::Decorrelated) if attempt > 0 => self.decorrelated_delay(previous),
+            _ => self.delay_for_attempt(attempt),
+        }
+    }
+
+    fn backoff_delay(&self, attempt: u32) -> Duration {
+        if attempt == 0 {
+            return Duration::from_secs(0);
+        }
+
         let multiplier = self.backoff_multiplier.saturating_pow(attempt - 1);
-        let delay_ms = self.initial_delay.as_millis() as u64 * u64::from(multiplier);
-        let delay = Duration::from_millis(delay_ms);
+        let initial_ms = u64::try_from(self.initial_delay.as_millis()).unwrap_or(u64::MAX);
+        let delay = Duration::from_millis(initial_ms.saturating_mul(u64::from(multiplier)));
 
         std::cmp::min(delay, self.max_delay)
     }
 
+    fn decorrelated_delay(&self, previous: Duration) -> Duration {
+        let floor = std::cmp::min(self.initial_delay, self.max_delay);
+        let ceiling = std::cmp::min(previous.max(floor).saturating_mul(3), self.max_delay);
+        random_between(floor, ceiling)
+    }
+
     /// Check if retries are enabled.
     #[must_use]
     pub const fn has_retries(&self) -> bool {
@@ -161,14 +248,33 @@ impl Default for RetryPolicy {
     }
 }
 
+/// Uniformly random duration in `[low, high]` (nanosecond resolution).
+fn random_between(low: Duration, high: Duration) -> Duration {
+    if high <= low {
+        return low;
+    }
+    let span = u64::try_from(high.saturating_sub(low).as_nanos()).unwrap_or(u64::MAX);
+    low + Duration::from_nanos(fastrand::u64(0..=span))
+}
+
 /// HTTP client configuration.
 ///
 /// Configures HTTP client behavior including timeouts, retries, and connection pooling.
 #[derive(Debug, Clone)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct ClientConfig {
     /// Request timeout
     pub timeout: Duration,
 
+    /// Connection establishment timeout
+    pub connect_timeout: Duration,
+
+    /// Overall deadline for a call, including retries and backoff
+    pub deadline: Option<Duration>,
+
+    /// Calls slower than this, retries included, are logged as warnings
+    pub slow_request_threshold: Option<Duration>,
+
     /// Retry policy
     pub retry_policy: RetryPolicy,
 
@@ -183,6 +289,114 @@ pub struct ClientConfig {
 
     /// Enable response compression
     pub enable_compression: bool,
+
+    /// Also accept brotli-encoded responses (needs `enable_compression`)
+    pub enable_brotli: bool,
+
+    /// Also accept zstd-encoded responses (needs `enable_compression`)
+    pub enable_zstd: bool,
+
+    /// Client certificate presented for mutual TLS
+    pub client_identity: Option<ClientIdentity>,
+
+    /// Proxy that outbound requests are sent through
+    pub proxy: Option<ProxyConfig>,
+}
+
+/// PEM files holding the certificate and private key for mutual TLS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientIdentity {
+    /// Client certificate chain
+    pub cert_pem: PathBuf,
+    /// Private key for the certificate (PKCS#8, PKCS#1, or SEC1)
+    pub key_pem: PathBuf,
+}
+
+impl ClientIdentity {
+    /// Read both files into a [`reqwest::Identity`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ConfigError`] if a file cannot be read or the PEM data
+    /// does not hold a usable certificate and key.
+    pub fn load(&self) -> crate::Result<Identity> {
+        let read = |path: &PathBuf, what: &str| {
+            std::fs::read(path).map_err(|err| {
+                Error::ConfigError(format!(
+                    "Failed to read client {what} {}: {err}",
+                    path.display()
+                ))
+            })
+        };
+        let mut pem = read(&self.cert_pem, "certificate")?;
+        pem.push(b'\n');
+        pem.extend(read(&self.key_pem, "key")?);
+
+        Identity::from_pem(&pem).map_err(|err| {
+            Error::ConfigError(format!(
+                "Invalid client identity {} / {}: {err}",
+                self.cert_pem.display(),
+                self.key_pem.display()
+            ))
+        })
+    }
+}
+
+/// Outbound HTTP/HTTPS proxy for every request a client makes.
+///
+/// Setting a proxy replaces any proxy picked up from the environment
+/// (`HTTP_PROXY`, `HTTPS_PROXY`), so the proxy in use does not depend on how
+/// the process was started.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProxyConfig {
+    /// Proxy URL, e.g. `http://proxy.example.com:3128`; used for HTTP and HTTPS
+    pub url: String,
+    /// Hosts reached without the proxy, in `NO_PROXY` syntax: domains (a
+    /// leading `.` matches subdomains), IP addresses, or CIDR blocks
+    pub no_proxy: Vec<String>,
+    /// Username and password sent to the proxy with basic authentication
+    pub auth: Option<(String, String)>,
+}
+
+impl ProxyConfig {
+    /// Send every request through the proxy at `url`.
+    #[must_use]
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            no_proxy: Vec::new(),
+            auth: None,
+        }
+    }
+
+    /// Reach `host` directly instead of through the proxy.
+    #[must_use]
+    pub fn with_no_proxy(mut self, host: impl Into<String>) -> Self {
+        self.no_proxy.push(host.into());
+        self
+    }
+
+    /// Authenticate to the proxy with `username` and `password`.
+    #[must_use]
+    pub fn with_auth(mut self, username: impl Into<String>, password: impl Into<String>) -> Self {
+        self.auth = Some((username.into(), password.into()));
+        self
+    }
+
+    /// Build the [`reqwest::Proxy`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ConfigError`] if the proxy URL is invalid.
+    pub fn build(&self) -> crate::Result<Proxy> {
+        let mut proxy = Proxy::all(&self.url).map_err(|err| {
+            Error::ConfigError(format!("Invalid proxy URL `{}`: {err}", self.url))
+        })?;
+        if let Some((username, password)) = &self.auth {
+            proxy = proxy.basic_auth(username, password);
+        }
+        Ok(proxy.no_proxy(NoProxy::from_string(&self.no_proxy.join(","))))
+    }
 }
 
 impl ClientConfig {
@@ -191,11 +405,18 @@ impl ClientConfig {
     pub const fn new() -> Self {
         Self {
             timeout: Duration::from_secs(30),
+            connect_timeout: Duration::from_secs(DEFAULT_CONNECT_TIMEOUT),
+            deadline: None,
+            slow_request_threshold: None,
             retry_policy: RetryPolicy::new(),
             pool_idle_timeout: Duration::from_secs(DEFAULT_POOL_IDLE_TIMEOUT),
             pool_max_idle_per_host: DEFAULT_POOL_MAX_IDLE_PER_HOST,
             enable_logging: true,
             enable_compression: true,
+            enable_brotli: false,
+            enable_zstd: false,
+            client_identity: None,
+            proxy: None,
         }
     }
 
@@ -206,6 +427,27 @@ impl ClientConfig {
         self
     }
 
+    /// Set connection establishment timeout.
+    #[must_use]
+    pub const fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// Set an overall deadline covering every attempt of a call.
+    #[must_use]
+    pub const fn with_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Warn about calls that take longer than `threshold`, retries included.
+    #[must_use]
+    pub const fn with_slow_request_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_request_threshold = Some(threshold);
+        self
+    }
+
     /// Set retry policy.
     #[must_use]
     pub const fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
@@ -247,6 +489,73 @@ impl ClientConfig {
         self.enable_compression = enabled;
         self
     }
+
+    /// Advertise and decode brotli response bodies as well as gzip.
+    #[must_use]
+    pub const fn with_brotli(mut self, enabled: bool) -> Self {
+        self.enable_brotli = enabled;
+        self
+    }
+
+    /// Advertise and decode zstd response bodies as well as gzip.
+    #[must_use]
+    pub const fn with_zstd(mut self, enabled: bool) -> Self {
+        self.enable_zstd = enabled;
+        self
+    }
+
+    /// Present the certificate in `cert_pem`, with the key in `key_pem`, for mutual TLS.
+    ///
+    /// The files are read when the client is built. PEM identities are only
+    /// understood by rustls, so setting one switches the client to the rustls
+    /// TLS backend even when native TLS is also compiled in.
+    #[must_use]
+    pub fn with_client_identity(mut self, cert_pem: PathBuf, key_pem: PathBuf) -> Self {
+        self.client_identity = Some(ClientIdentity { cert_pem, key_pem });
+        self
+    }
+
+    /// Send every request through `proxy`.
+    #[must_use]
+    pub fn with_proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Set the response encodings `builder` advertises in `Accept-Encoding` and decodes.
+    ///
+    /// Gzip follows `enable_compression`; brotli and zstd also need their own
+    /// flags. Disabling compression turns all three off.
+    pub fn apply_compression(&self, builder: ClientBuilder) -> ClientBuilder {
+        builder
+            .gzip(self.enable_compression)
+            .brotli(self.enable_compression && self.enable_brotli)
+            .zstd(self.enable_compression && self.enable_zstd)
+    }
+
+    /// Add the configured proxy, if any, to `builder`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ConfigError`] if the proxy URL is invalid.
+    pub fn apply_proxy(&self, builder: ClientBuilder) -> crate::Result<ClientBuilder> {
+        match &self.proxy {
+            Some(proxy) => Ok(builder.proxy(proxy.build()?)),
+            None => Ok(builder),
+        }
+    }
+
+    /// Add the configured client identity, if any, to `builder`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ConfigError`] if the identity cannot be loaded.
+    pub fn apply_client_identity(&self, builder: ClientBuilder) -> crate::Result<ClientBuilder> {
+        match &self.client_identity {
+            Some(identity) => Ok(builder.use_rustls_tls().identity(identity.load()?)),
+            None => Ok(builder),
+        }
+    }
 }
 
 impl Default for ClientConfig {
@@ -255,6 +564,86 @@ impl Default for ClientConfig {
     }
 }
 
+/// Per-client options applied to outgoing requests.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequestOptions {
+    /// Media type sent with JSON bodies instead of plain `application/json`.
+    pub content_type: Option<String>,
+    /// Retry 2xx responses whose body is empty or whitespace when JSON was expected.
+    ///
+    /// Off by default so that genuine server bugs are not masked. A 204 is
+    /// never retried.
+    pub retry_empty_success: bool,
+    /// Timeout for each attempt, replacing the `ClientConfig::timeout` baked
+    /// into the HTTP client.
+    pub timeout: Option<Duration>,
+}
+
+impl RequestOptions {
+    /// Create options with default behaviour.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Override the `Content-Type` sent with JSON bodies.
+    #[must_use]
+    pub fn with_content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.content_type = Some(content_type.into());
+        self
+    }
+
+    /// Treat empty or whitespace 2xx bodies as retryable when JSON is expected.
+    #[must_use]
+    pub const fn with_retry_on_empty_body(mut self, enabled: bool) -> Self {
+        self.retry_empty_success = enabled;
+        self
+    }
+
+    /// Give each attempt `timeout` instead of the client-wide timeout.
+    #[must_use]
+    pub const fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Apply the per-request timeout, if any, to `request`.
+    pub fn apply_timeout(&self, request: RequestBuilder) -> RequestBuilder {
+        match self.timeout {
+            Some(timeout) => request.timeout(timeout),
+            None => request,
+        }
+    }
+
+    /// Returns true when a successful `status` with `body` should be retried.
+    ///
+    /// Only applies when the expected type cannot be built from an empty body,
+    /// which callers check before consulting this.
+    #[must_use]
+    pub fn should_retry_empty(&self, status: StatusCode, body: &[u8]) -> bool {
+        self.retry_empty_success
+            && status.is_success()
+            && status != StatusCode::NO_CONTENT
+            && body.iter().all(u8::is_ascii_whitespace)
+    }
+
+    /// Attach `body` as serialized JSON, honouring any content-type override.
+    pub fn json_body<B>(&self, request: RequestBuilder, body: &B) -> RequestBuilder
+    where
+        B: Serialize + ?Sized,
+    {
+        let Some(content_type) = &self.content_type else {
+            return request.json(body);
+        };
+
+        match serde_json::to_vec(body) {
+            Ok(bytes) => request.header(CONTENT_TYPE, content_type).body(bytes),
+            // Let reqwest report the serialization failure when the request is sent.
+            Err(_) => request.json(body),
+        }
+    }
+}
+
 /// Builder for [`ServiceClient`].
 #[derive(Debug, Clone)]
 pub struct ServiceClientBuilder {
@@ -265,6 +654,16 @@ pub struct ServiceClientBuilder {
     basic_auth: Option<(String, String)>,
     token: Option<String>,
     user_agent: String,
+    request_options: RequestOptions,
+    observer: Option<Arc<dyn RequestObserver>>,
+    client_observer: Option<Arc<dyn ClientObserver>>,
+    response_cache: Option<Arc<ResponseCache>>,
+    http_client: Option<Client>,
+    request_id_header: HeaderName,
+    endpoints: Option<(EndpointList, EndpointSelector)>,
+    pinned_endpoint: Option<String>,
+    #[cfg(feature = "record-replay")]
+    cassette: Option<Cassette>,
 }
 
 impl ServiceClientBuilder {
@@ -297,9 +696,43 @@ impl ServiceClientBuilder {
             basic_auth: None,
             token: None,
             user_agent,
+            request_options: RequestOptions::default(),
+            observer: None,
+            client_observer: None,
+            response_cache: None,
+            http_client: None,
+            request_id_header: HeaderName::from_static(DEFAULT_REQUEST_ID_HEADER),
+            endpoints: None,
+            pinned_endpoint: None,
+            #[cfg(feature = "record-replay")]
+            cassette: None,
         })
     }
 
+    /// Create a builder for `service` from a [`TritonClientConfig`].
+    ///
+    /// Uses the static endpoint configured for `service`, the timeout from
+    /// [`TritonClientConfig::timeout_for`], and the configured retry count.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no endpoint is configured for `service` or its URL is invalid.
+    pub fn from_config(service: TritonService, config: &TritonClientConfig) -> crate::Result<Self> {
+        let endpoint = config
+            .service_discovery
+            .services
+            .get(service)
+            .ok_or_else(|| {
+                Error::ConfigError(format!("No {} endpoint configured", service.name()))
+            })?;
+        let retry = RetryPolicy::new().with_max_retries(config.max_retries);
+
+        Ok(
+            Self::new(service, &endpoint.url, config.timeout_for(service))?
+                .with_retry_policy(retry),
+        )
+    }
+
     /// Override the retry policy.
     #[must_use]
     pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
@@ -341,36 +774,176 @@ impl ServiceClientBuilder {
         self
     }
 
-    /// Build the service client.
+    /// Override the per-request options (e.g. JSON content type).
+    #[must_use]
+    pub fn with_request_options(mut self, options: RequestOptions) -> Self {
+        self.request_options = options;
+        self
+    }
+
+    /// Notify `observer` after every call, retries included.
+    #[must_use]
+    pub fn with_observer(mut self, observer: Arc<dyn RequestObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Invoke `observer` around every attempt, retries included.
+    #[must_use]
+    pub fn with_client_observer(mut self, observer: Arc<dyn ClientObserver>) -> Self {
+        self.client_observer = Some(observer);
+        self
+    }
+
+    /// Record responses to, or replay them from, `cassette`.
+    #[cfg(feature = "record-replay")]
+    #[must_use]
+    pub fn with_cassette(mut self, cassette: Cassette) -> Self {
+        self.cassette = Some(cassette);
+        self
+    }
+
+    /// Serve repeated successful GETs from `cache` until its TTL expires.
     ///
-    /// # Errors
+    /// The cache may be shared by several clients; keys include the full URL
+    /// and the credentials sent, so clients with different identities do not
+    /// share entries. See [`ResponseCache`] for what is cached.
+    #[must_use]
+    pub fn with_response_cache(mut self, cache: Arc<ResponseCache>) -> Self {
+        self.response_cache = Some(cache);
+        self
+    }
+
+    /// Send requests through `client` instead of building a new one.
     ///
-    /// Returns an error if the HTTP client cannot be constructed.
-    pub fn build(self) -> crate::Result<ServiceClient> {
-        let mut builder = ClientBuilder::new()
-            .timeout(self.http_config.timeout)
-            .user_agent(&self.user_agent)
-            .pool_idle_timeout(self.http_config.pool_idle_timeout)
-            .pool_max_idle_per_host(self.http_config.pool_max_idle_per_host)
-            .connect_timeout(Duration::from_secs(10));
+    /// Clones of a `reqwest::Client` share one connection pool, so several service clients
+    /// can be given the same client. Its own timeout, TLS, pooling, compression and user agent
+    /// settings take precedence; those in [`ClientConfig`] and [`Self::with_user_agent`] are
+    /// ignored, while the retry policy, deadline and authentication still apply.
+    #[must_use]
+    pub fn with_http_client(mut self, client: Client) -> Self {
+        self.http_client = Some(client);
+        self
+    }
 
-        if !self.http_config.enable_compression {
-            builder = builder.no_gzip();
-        }
+    /// Send each call's request id in `name` instead of [`DEFAULT_REQUEST_ID_HEADER`].
+    #[must_use]
+    pub fn with_request_id_header(mut self, name: HeaderName) -> Self {
+        self.request_id_header = name;
+        self
+    }
 
-        let http = builder
-            .build()
-            .map_err(|err| Error::ConfigError(format!("Failed to build HTTP client: {err}")))?;
+    /// Send each attempt to an endpoint from `endpoints` picked by `selector`.
+    ///
+    /// Only the host and port of the base URL change; its scheme and path are
+    /// kept. A 5xx response or transport failure counts against the endpoint
+    /// in `selector`, any other response clears its record. Replace the list
+    /// after rediscovery with [`ServiceClient::update_endpoints`].
+    #[must_use]
+    pub fn with_endpoints(mut self, endpoints: EndpointList, selector: EndpointSelector) -> Self {
+        self.endpoints = Some((endpoints, selector));
+        self
+    }
+
+    /// Keep sending to the endpoint at `url` until it fails or the pin is
+    /// released with [`ServiceClient::release_pinned_endpoint`].
+    ///
+    /// For stateful workflows that must reach the same backend on every call.
+    /// `url`'s host and port must match the address of one of the endpoints
+    /// given to [`with_endpoints`](Self::with_endpoints). After a failure the
+    /// client pins whichever endpoint it selects next.
+    #[must_use]
+    pub fn pin_endpoint(mut self, url: impl Into<String>) -> Self {
+        self.pinned_endpoint = Some(url.into());
+        self
+    }
+
+    /// Build the service client.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP client cannot be constructed, or if a
+    /// pinned endpoint is not one of the configured endpoints.
+    pub fn build(mut self) -> crate::Result<ServiceClient> {
+        let routing = self.build_routing()?;
+        let http = match self.http_client.take() {
+            Some(http) => http,
+            None => self.build_http_client()?,
+        };
 
         Ok(ServiceClient {
             http,
             base_url: self.base_url,
             retry_policy: self.retry_policy,
+            deadline: self.http_config.deadline,
+            slow_request_threshold: self.http_config.slow_request_threshold,
             basic_auth: self.basic_auth,
             token: self.token,
             service: self.service,
+            request_options: self.request_options,
+            context: RequestContext::default(),
+            observer: self.observer,
+            client_observer: self.client_observer,
+            response_cache: self.response_cache,
+            request_id_header: self.request_id_header,
+            routing,
+            #[cfg(feature = "record-replay")]
+            cassette: self.cassette,
         })
     }
+
+    fn build_routing(&mut self) -> crate::Result<Option<Routing>> {
+        let service = self.service;
+        let Some((endpoints, selector)) = self.endpoints.take() else {
+            return match &self.pinned_endpoint {
+                Some(url) => Err(Error::ConfigError(format!(
+                    "Cannot pin {service} endpoint `{url}` without configured endpoints"
+                ))),
+                None => Ok(None),
+            };
+        };
+
+        let session = match &self.pinned_endpoint {
+            Some(url) => {
+                let id = endpoints
+                    .endpoints
+                    .iter()
+                    .find(|endpoint| endpoint_matches_url(endpoint, url))
+                    .map(|endpoint| endpoint.id.clone())
+                    .ok_or_else(|| {
+                        Error::ConfigError(format!(
+                            "`{url}` is not one of the configured {service} endpoints"
+                        ))
+                    })?;
+                let session = StickySession::new(selector.clone());
+                session.pin(id);
+                Some(session)
+            }
+            None => None,
+        };
+
+        Ok(Some(Routing {
+            endpoints: Arc::new(RwLock::new(endpoints)),
+            selector,
+            session,
+        }))
+    }
+
+    fn build_http_client(&self) -> crate::Result<Client> {
+        let builder = ClientBuilder::new()
+            .timeout(self.http_config.timeout)
+            .user_agent(&self.user_agent)
+            .pool_idle_timeout(self.http_config.pool_idle_timeout)
+            .pool_max_idle_per_host(self.http_config.pool_max_idle_per_host)
+            .connect_timeout(self.http_config.connect_timeout);
+        let builder = self.http_config.apply_compression(builder);
+        let builder = self.http_config.apply_client_identity(builder)?;
+        let builder = self.http_config.apply_proxy(builder)?;
+
+        builder
+            .build()
+            .map_err(|err| Error::ConfigError(format!("Failed to build HTTP client: {err}")))
+    }
 }
 
 /// Shared HTTP client wrapper used by Triton service clients.
@@ -379,9 +952,135 @@ pub struct ServiceClient {
     http: Client,
     base_url: Url,
     retry_policy: RetryPolicy,
+    deadline: Option<Duration>,
+    slow_request_threshold: Option<Duration>,
     basic_auth: Option<(String, String)>,
     token: Option<String>,
     service: TritonService,
+    request_options: RequestOptions,
+    context: RequestContext,
+    observer: Option<Arc<dyn RequestObserver>>,
+    client_observer: Option<Arc<dyn ClientObserver>>,
+    response_cache: Option<Arc<ResponseCache>>,
+    request_id_header: HeaderName,
+    routing: Option<Routing>,
+    #[cfg(feature = "record-replay")]
+    cassette: Option<Cassette>,
+}
+
+/// Per-attempt endpoint selection set up by [`ServiceClientBuilder::with_endpoints`].
+///
+/// Shared by every handle cloned from one client.
+#[derive(Debug, Clone)]
+struct Routing {
+    endpoints: Arc<RwLock<EndpointList>>,
+    selector: EndpointSelector,
+    session: Option<StickySession>,
+}
+
+impl Routing {
+    /// Pick an endpoint, returning its identifier and address.
+    fn select(&self) -> Option<(String, std::net::SocketAddr)> {
+        let endpoints = self.endpoints.read().ok()?;
+        let endpoint = match &self.session {
+            Some(session) => session.select(&endpoints),
+            None => self.selector.select(&endpoints),
+        }?;
+        Some((endpoint.id.clone(), endpoint.address))
+    }
+
+    fn record(&self, id: &str, failed: bool) {
+        match (&self.session, failed) {
+            (Some(session), true) => session.record_failure(id),
+            (Some(session), false) => session.record_success(id),
+            (None, true) => self.selector.record_failure(id),
+            (None, false) => self.selector.record_success(id),
+        }
+    }
+}
+
+/// Whether `url`'s host and port are `endpoint`'s address.
+fn endpoint_matches_url(endpoint: &ServiceEndpoint, url: &str) -> bool {
+    let Ok(url) = Url::parse(url) else {
+        return false;
+    };
+    let ip = match url.host() {
+        Some(Host::Ipv4(ip)) => IpAddr::V4(ip),
+        Some(Host::Ipv6(ip)) => IpAddr::V6(ip),
+        Some(Host::Domain(host)) => match host.parse() {
+            Ok(ip) => ip,
+            Err(_) => return false,
+        },
+        None => return false,
+    };
+    endpoint.address.ip() == ip && url.port_or_known_default() == Some(endpoint.address.port())
+}
+
+/// Summary of a finished call, passed to a [`RequestObserver`].
+#[derive(Debug)]
+pub struct RequestEvent<'a> {
+    /// Service that was called
+    pub service: TritonService,
+    /// HTTP method
+    pub method: &'a Method,
+    /// Request path relative to the service base URL
+    pub path: &'a str,
+    /// Number of attempts made
+    pub attempts: u32,
+    /// Time spent on the call, retries included
+    pub elapsed: Duration,
+    /// The error the call failed with, if any
+    pub error: Option<&'a Error>,
+    /// Tags attached to the call
+    pub context: &'a RequestContext,
+    /// Request id sent with every attempt of the call
+    pub request_id: &'a str,
+}
+
+/// Callback notified after every [`ServiceClient`] call, e.g. to record metrics.
+pub trait RequestObserver: Send + Sync {
+    /// Called once per call after the final attempt.
+    fn on_request(&self, event: &RequestEvent<'_>);
+}
+
+impl fmt::Debug for dyn RequestObserver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RequestObserver")
+    }
+}
+
+/// Hooks invoked around every attempt of a [`ServiceClient`] call, retries included.
+///
+/// Unlike [`RequestObserver`], which sees one summary per call, this sees each
+/// attempt as it happens. Every method defaults to a no-op, so implementors
+/// override only the hooks they need.
+pub trait ClientObserver: Send + Sync {
+    /// Called before attempt `attempt` (starting at 0) is sent.
+    fn on_request(&self, method: &Method, path: &str, attempt: u32) {
+        let _ = (method, path, attempt);
+    }
+
+    /// Called when a response arrives, whatever its status.
+    fn on_response(&self, status: StatusCode, elapsed: Duration) {
+        let _ = (status, elapsed);
+    }
+
+    /// Called when an attempt fails, whether or not it will be retried.
+    fn on_error(&self, error: &Error) {
+        let _ = error;
+    }
+}
+
+/// [`ClientObserver`] that ignores every hook.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopClientObserver;
+
+impl ClientObserver for NoopClientObserver {}
+
+impl fmt::Debug for dyn ClientObserver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ClientObserver")
+    }
 }
 
 impl ServiceClient {
@@ -397,6 +1096,101 @@ impl ServiceClient {
         &self.base_url
     }
 
+    /// Returns the options applied to outgoing requests.
+    #[must_use]
+    pub const fn request_options(&self) -> &RequestOptions {
+        &self.request_options
+    }
+
+    /// Returns a handle that tags every call with `context`.
+    ///
+    /// The handle shares the connection pool. Its tags are merged over any
+    /// context attached to the task with [`RequestContext::scope`].
+    #[must_use]
+    pub fn with_context(&self, context: RequestContext) -> Self {
+        Self {
+            context,
+            ..self.clone()
+        }
+    }
+
+    /// Returns a handle that sends its calls with `options` instead.
+    ///
+    /// The handle shares the connection pool, so a single slow operation can
+    /// be given its own timeout without rebuilding the client.
+    #[must_use]
+    pub fn with_request_options(&self, options: RequestOptions) -> Self {
+        Self {
+            request_options: options,
+            ..self.clone()
+        }
+    }
+
+    /// Returns a handle whose attempts each time out after `timeout`.
+    ///
+    /// Shorthand for [`with_request_options`](Self::with_request_options) with
+    /// only the timeout changed.
+    #[must_use]
+    pub fn with_timeout(&self, timeout: Duration) -> Self {
+        self.with_request_options(self.request_options.clone().with_timeout(timeout))
+    }
+
+    /// Returns a handle that retries its calls as `retry` allows instead.
+    ///
+    /// Pass [`RetryPolicy::no_retry`] for non-idempotent requests, which must
+    /// not be repeated after a timeout or server error.
+    #[must_use]
+    pub fn with_retry_policy(&self, retry: RetryPolicy) -> Self {
+        Self {
+            retry_policy: retry,
+            ..self.clone()
+        }
+    }
+
+    /// Identifier of the endpoint a [`pin_endpoint`](ServiceClientBuilder::pin_endpoint)
+    /// client is currently pinned to.
+    #[must_use]
+    pub fn pinned_endpoint(&self) -> Option<String> {
+        self.routing
+            .as_ref()
+            .and_then(|routing| routing.session.as_ref())
+            .and_then(StickySession::pinned)
+    }
+
+    /// Drop the pin so the next call selects, and pins, an endpoint afresh.
+    pub fn release_pinned_endpoint(&self) {
+        if let Some(session) = self
+            .routing
+            .as_ref()
+            .and_then(|routing| routing.session.as_ref())
+        {
+            session.release();
+        }
+    }
+
+    /// Replace the endpoints set with [`ServiceClientBuilder::with_endpoints`],
+    /// e.g. after rediscovery.
+    ///
+    /// Every handle cloned from this client sees the new list. Does nothing
+    /// for a client without configured endpoints.
+    pub fn update_endpoints(&self, endpoints: EndpointList) {
+        if let Some(mut current) = self
+            .routing
+            .as_ref()
+            .and_then(|routing| routing.endpoints.write().ok())
+        {
+            *current = endpoints;
+        }
+    }
+
+    /// The context for a call: the task's context overlaid with this handle's.
+    fn effective_context(&self) -> RequestContext {
+        RequestContext::current().map_or_else(
+            || self.context.clone(),
+            |current| current.merged(&self.context),
+        )
+    }
+
     /// Access the underlying reqwest client.
     #[must_use]
     pub fn http_client(&self) -> &Client {
@@ -404,13 +1198,49 @@ impl ServiceClient {
     }
 
     /// Construct a request builder for the given method/path with optional query parameters.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidEndpoint`] if `path` cannot be joined onto the base URL.
     pub fn request(
         &self,
         method: Method,
         path: &str,
-        params: &[(&'static str, String)],
+        params: &[QueryPair],
     ) -> crate::Result<RequestBuilder> {
-        let url = self.build_url(path)?;
+        let url = join_path(&self.base_url, path)?;
+        Ok(self.request_to(url, method, params))
+    }
+
+    /// Build a request for an attempt, choosing its endpoint when the client
+    /// has several. Returns the chosen endpoint's identifier alongside.
+    fn routed_request(
+        &self,
+        method: Method,
+        path: &str,
+        params: &[QueryPair],
+    ) -> crate::Result<(RequestBuilder, Option<String>)> {
+        let Some(routing) = &self.routing else {
+            return Ok((self.request(method, path, params)?, None));
+        };
+        let (id, address) = routing.select().ok_or_else(|| {
+            Error::ServiceUnavailable(format!("No {} endpoint is available", self.service))
+        })?;
+
+        let mut base = self.base_url.clone();
+        base.set_ip_host(address.ip())
+            .and_then(|()| base.set_port(Some(address.port())))
+            .map_err(|()| {
+                Error::InvalidEndpoint(format!(
+                    "Cannot send to endpoint {id} at {address} through `{}`",
+                    self.base_url
+                ))
+            })?;
+        let url = join_path(&base, path)?;
+        Ok((self.request_to(url, method, params), Some(id)))
+    }
+
+    fn request_to(&self, url: Url, method: Method, params: &[QueryPair]) -> RequestBuilder {
         let mut request = self.http.request(method, url).query(params);
 
         if let Some((user, pass)) = &self.basic_auth {
@@ -420,15 +1250,25 @@ impl ServiceClient {
             request = request.header("X-Auth-Token", token);
         }
 
-        Ok(request)
+        self.request_options.apply_timeout(request)
     }
 
     /// Execute a request with retry semantics.
+    ///
+    /// A `304 Not Modified` is returned like a success so callers that send
+    /// conditional requests can serve their cached copy. When a deadline is
+    /// configured, running past it fails with a [`TimeoutKind::Deadline`]
+    /// timeout.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error built by `map_error` for a non-success response, or a
+    /// transport/timeout error once retries are exhausted.
     pub async fn execute_with_retry<F, G>(
         &self,
         method: Method,
         path: &str,
-        params: &[(&'static str, String)],
+        params: &[QueryPair],
         mut configure: F,
         mut map_error: G,
     ) -> crate::Result<Response>
@@ -436,86 +1276,643 @@ impl ServiceClient {
         F: FnMut(RequestBuilder) -> RequestBuilder,
         G: FnMut(StatusCode, String) -> Error,
     {
-        let mut attempt = 0;
-        #[allow(unused_assignments)]
-        let mut last_error: Option<Error> = None;
+        let context = self.effective_context();
+        let request_id = context.request_id();
+        let span = self.span(&method, path, &context, &request_id);
+        let otel = RequestSpan::start(self.service, &method, path);
+        let mut configure = |request: RequestBuilder| {
+            configure(request.header(&self.request_id_header, &request_id))
+        };
+        let started = Instant::now();
+        let mut attempts = 0;
+        let mut delay = Duration::ZERO;
+        let result = self
+            .within_deadline(async {
+                loop {
+                    let sent = self
+                        .send_once(
+                            &method,
+                            path,
+                            params,
+                            &mut configure,
+                            &mut map_error,
+                            &otel,
+                            attempts,
+                        )
+                        .await;
+                    attempts += 1;
+                    let (error, retry_after) = match sent? {
+                        Attempt::Success(response) => return Ok(response),
+                        Attempt::Retryable { error, retry_after } => (error, retry_after),
+                    };
+
+                    if !self.backoff(attempts, &mut delay, retry_after).await {
+                        return Err(error);
+                    }
+                }
+            })
+            .instrument(span.clone())
+            .await
+            .map_err(|err| {
+                err.with_context(&context.clone().with_tag(REQUEST_ID_TAG, &request_id))
+            });
+        otel.finish(result.as_ref().map(Response::status), attempts);
+        span.in_scope(|| {
+            self.finish(
+                &method,
+                path,
+                started,
+                attempts,
+                &context,
+                &request_id,
+                &result,
+            );
+        });
+        result
+    }
 
-        loop {
-            let builder = self.request(method.clone(), path, params)?;
-            let request = configure(builder);
+    /// Execute a request with retry semantics and decode the JSON response.
+    ///
+    /// An empty success body decodes as JSON `null`, so unit and optional
+    /// responses still succeed. For other types the empty body is retried when
+    /// [`RequestOptions::retry_empty_success`] is enabled. Reading the body
+    /// counts against the configured deadline.
+    ///
+    /// # Errors
+    ///
+    /// Fails as [`execute_with_retry`](Self::execute_with_retry) does, or with
+    /// [`Error::SapiParseError`] if the body is not valid JSON for `R`.
+    pub async fn execute_json_with_retry<R, F, G>(
+        &self,
+        method: Method,
+        path: &str,
+        params: &[QueryPair],
+        configure: F,
+        map_error: G,
+    ) -> crate::Result<R>
+    where
+        R: DeserializeOwned,
+        F: FnMut(RequestBuilder) -> RequestBuilder,
+        G: FnMut(StatusCode, String) -> Error,
+    {
+        self.execute_json_response_with_retry(method, path, params, configure, map_error)
+            .await
+            .map(|response| response.body)
+    }
 
-            debug!(
-                service = self.service.name(),
-                path, attempt, "Service request"
-            );
+    /// Fetch any path as raw JSON.
+    ///
+    /// Escape hatch for endpoints and fields a service's typed models do not
+    /// cover yet. Authentication, retries, and `map_error` still apply.
+    ///
+    /// # Errors
+    ///
+    /// Fails as [`execute_json_with_retry`](Self::execute_json_with_retry) does.
+    pub async fn get_raw<G>(
+        &self,
+        path: &str,
+        params: &[QueryPair],
+        map_error: G,
+    ) -> crate::Result<serde_json::Value>
+    where
+        G: FnMut(StatusCode, String) -> Error,
+    {
+        self.send_raw(Method::GET, path, None, params, map_error)
+            .await
+    }
 
-            match request.send().await {
-                Ok(response) => {
-                    let status = response.status();
-                    if status.is_success() {
-                        return Ok(response);
-                    }
+    /// Send an arbitrary request and return the raw JSON response.
+    ///
+    /// `body` is encoded as the [`RequestOptions`] configure; otherwise this
+    /// behaves as [`get_raw`](Self::get_raw).
+    ///
+    /// # Errors
+    ///
+    /// Fails as [`execute_json_with_retry`](Self::execute_json_with_retry) does.
+    pub async fn send_raw<G>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&serde_json::Value>,
+        params: &[QueryPair],
+        map_error: G,
+    ) -> crate::Result<serde_json::Value>
+    where
+        G: FnMut(StatusCode, String) -> Error,
+    {
+        self.execute_json_with_retry(
+            method,
+            path,
+            params,
+            |mut request| {
+                request = request.header("Accept", "application/json");
+                if let Some(payload) = body {
+                    request = self.request_options.json_body(request, payload);
+                }
+                request
+            },
+            map_error,
+        )
+        .await
+    }
 
-                    let text = response
-                        .text()
-                        .await
-                        .unwrap_or_else(|_| "Unknown error".to_string());
-                    let error = map_error(status, text);
-                    if should_retry(status) {
-                        last_error = Some(error);
-                    } else {
+    /// Like [`execute_json_with_retry`](Self::execute_json_with_retry), but
+    /// also returns the status and headers of the final response.
+    ///
+    /// # Errors
+    ///
+    /// Fails as [`execute_json_with_retry`](Self::execute_json_with_retry) does.
+    pub async fn execute_json_response_with_retry<R, F, G>(
+        &self,
+        method: Method,
+        path: &str,
+        params: &[QueryPair],
+        mut configure: F,
+        mut map_error: G,
+    ) -> crate::Result<ServiceResponse<R>>
+    where
+        R: DeserializeOwned,
+        F: FnMut(RequestBuilder) -> RequestBuilder,
+        G: FnMut(StatusCode, String) -> Error,
+    {
+        let service = self.service;
+        let context = self.effective_context();
+        let request_id = context.request_id();
+        let span = self.span(&method, path, &context, &request_id);
+        let otel = RequestSpan::start(self.service, &method, path);
+        let mut configure = |request: RequestBuilder| {
+            configure(request.header(&self.request_id_header, &request_id))
+        };
+        let started = Instant::now();
+        let mut attempts = 0;
+        let mut delay = Duration::ZERO;
+        let result = self
+            .within_deadline(async {
+                loop {
+                    let sent = self
+                        .send_once(
+                            &method,
+                            path,
+                            params,
+                            &mut configure,
+                            &mut map_error,
+                            &otel,
+                            attempts,
+                        )
+                        .await;
+                    attempts += 1;
+                    let (error, retry_after) = match sent? {
+                        Attempt::Success(response) => {
+                            let status = response.status();
+                            let headers = response.headers().clone();
+                            let read_started = Instant::now();
+                            let body = response.bytes().await.map_err(|err| {
+                                Error::from(err).with_elapsed(read_started.elapsed())
+                            })?;
+                            if !body.iter().all(u8::is_ascii_whitespace) {
+                                let body = serde_json::from_slice(&body).map_err(|err| {
+                                    Error::SapiParseError(format!(
+                                        "Failed to parse {service} response for `{path}`: {err}"
+                                    ))
+                                })?;
+                                return Ok(ServiceResponse {
+                                    body,
+                                    status,
+                                    headers,
+                                });
+                            }
+
+                            match serde_json::from_value(serde_json::Value::Null) {
+                                Ok(body) => {
+                                    return Ok(ServiceResponse {
+                                        body,
+                                        status,
+                                        headers,
+                                    })
+                                }
+                                Err(_)
+                                    if self.request_options.should_retry_empty(status, &body) =>
+                                {
+                                    let error = Error::ServiceUnavailable(format!(
+                                        "{service} returned an empty {status} body for `{path}`"
+                                    ));
+                                    (error, None)
+                                }
+                                Err(err) => {
+                                    return Err(Error::SapiParseError(format!(
+                                    "Failed to parse empty {service} response for `{path}`: {err}"
+                                )))
+                                }
+                            }
+                        }
+                        Attempt::Retryable { error, retry_after } => (error, retry_after),
+                    };
+
+                    if !self.backoff(attempts, &mut delay, retry_after).await {
                         return Err(error);
                     }
                 }
-                Err(err) => {
-                    let error = Error::from(err);
-                    if matches!(
+            })
+            .instrument(span.clone())
+            .await
+            .map_err(|err| {
+                err.with_context(&context.clone().with_tag(REQUEST_ID_TAG, &request_id))
+            });
+        otel.finish(result.as_ref().map(|response| response.status), attempts);
+        span.in_scope(|| {
+            self.finish(
+                &method,
+                path,
+                started,
+                attempts,
+                &context,
+                &request_id,
+                &result,
+            );
+        });
+        result
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn send_once<F, G>(
+        &self,
+        method: &Method,
+        path: &str,
+        params: &[QueryPair],
+        configure: &mut F,
+        map_error: &mut G,
+        otel: &RequestSpan,
+        attempt: u32,
+    ) -> crate::Result<Attempt>
+    where
+        F: FnMut(RequestBuilder) -> RequestBuilder,
+        G: FnMut(StatusCode, String) -> Error,
+    {
+        let (builder, endpoint) = match self.routed_request(method.clone(), path, params) {
+            Ok(routed) => routed,
+            Err(error @ Error::ServiceUnavailable(_)) => {
+                self.notify_error(&error);
+                return Ok(Attempt::Retryable {
+                    error,
+                    retry_after: None,
+                });
+            }
+            Err(error) => return Err(error),
+        };
+        let request = otel.inject(configure(builder));
+
+        debug!(
+            service = self.service.name(),
+            path, attempt, "Service request"
+        );
+
+        if let Some(observer) = &self.client_observer {
+            observer.on_request(method, path, attempt);
+        }
+        let started = Instant::now();
+        let dispatched = self.dispatch(request).await;
+        if let (Some(routing), Some(id)) = (&self.routing, &endpoint) {
+            let failed = dispatched
+                .as_ref()
+                .map_or(true, |response| response.status().is_server_error());
+            routing.record(id, failed);
+        }
+        match dispatched {
+            Ok(response) => {
+                let status = response.status();
+                if let Some(observer) = &self.client_observer {
+                    observer.on_response(status, started.elapsed());
+                }
+                if status.is_success() || status == StatusCode::NOT_MODIFIED {
+                    return Ok(Attempt::Success(response));
+                }
+
+                let retry_after = retry_after(response.headers());
+                let text = response
+                    .text()
+                    .await
+                    .unwrap_or_else(|_| "Unknown error".to_string());
+                let error = map_error(status, text.clone()).with_api_body(status, &text);
+                self.notify_error(&error);
+                if should_retry(status) {
+                    Ok(Attempt::Retryable { error, retry_after })
+                } else {
+                    Err(error)
+                }
+            }
+            Err(error) => {
+                let error = error.with_elapsed(started.elapsed());
+                self.notify_error(&error);
+                if matches!(
+                    error,
+                    Error::Timeout { .. } | Error::ServiceUnavailable(_) | Error::HttpError { .. }
+                ) {
+                    Ok(Attempt::Retryable {
                         error,
-                        Error::Timeout(_) | Error::ServiceUnavailable(_) | Error::HttpError(_)
-                    ) {
-                        last_error = Some(error);
-                    } else {
-                        return Err(error);
-                    }
+                        retry_after: None,
+                    })
+                } else {
+                    Err(error)
                 }
             }
+        }
+    }
 
-            attempt += 1;
-            if attempt > self.retry_policy.max_retries {
-                break;
-            }
-            let delay = self.retry_policy.delay_for_attempt(attempt);
-            if delay > Duration::from_millis(0) {
-                debug!(
-                    service = self.service.name(),
-                    ?delay,
-                    "Retrying service request"
-                );
-                sleep(delay).await;
+    /// Send a request, answering GETs from the response cache when one is
+    /// attached, storing successful JSON GET responses in it, and evicting
+    /// the entries a mutating request may have changed.
+    async fn dispatch(&self, request: RequestBuilder) -> crate::Result<Response> {
+        let Some(cache) = self.response_cache.as_deref() else {
+            return self.send(request).await;
+        };
+        let Some(built) = request.try_clone().and_then(|request| request.build().ok()) else {
+            return self.send(request).await;
+        };
+        let Some(key) = CacheKey::for_request(&built) else {
+            let result = self.send(request).await;
+            if !built.method().is_safe() {
+                cache.invalidate(built.url());
             }
+            return result;
+        };
+        if let Some(response) = cache.get(&key) {
+            debug!(service = self.service.name(), url = %key.url(), "Serving cached response");
+            return Ok(response);
         }
 
-        Err(last_error.unwrap_or_else(|| {
-            Error::ServiceUnavailable(format!(
-                "{} request failed after retries",
-                self.service.name()
-            ))
-        }))
+        let response = self.send(request).await?;
+        if !response.status().is_success() || !cache.accepts(response.headers()) {
+            return Ok(response);
+        }
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response.bytes().await?.to_vec();
+        cache.put(key, status, headers.clone(), body.clone());
+
+        let mut response = http::Response::new(body);
+        *response.status_mut() = status;
+        *response.headers_mut() = headers;
+        Ok(Response::from(response))
+    }
+
+    /// Send a request, going through the cassette when one is attached.
+    async fn send(&self, request: RequestBuilder) -> crate::Result<Response> {
+        #[cfg(feature = "record-replay")]
+        if let Some(cassette) = &self.cassette {
+            return cassette.dispatch(&self.http, request).await;
+        }
+        request.send().await.map_err(Error::from)
+    }
+
+    fn notify_error(&self, error: &Error) {
+        if let Some(observer) = &self.client_observer {
+            observer.on_error(error);
+        }
+    }
+
+    /// The span that every attempt of a call runs in.
+    fn span(
+        &self,
+        method: &Method,
+        path: &str,
+        context: &RequestContext,
+        request_id: &str,
+    ) -> tracing::Span {
+        info_span!(
+            "triton_request",
+            service = self.service.name(),
+            %method,
+            path,
+            context = %context,
+            request_id
+        )
+    }
+
+    /// Report a finished call to the observer and warn if it was slow.
+    #[allow(clippy::too_many_arguments)]
+    fn finish<T>(
+        &self,
+        method: &Method,
+        path: &str,
+        started: Instant,
+        attempts: u32,
+        context: &RequestContext,
+        request_id: &str,
+        result: &crate::Result<T>,
+    ) {
+        let elapsed = started.elapsed();
+        self.warn_if_slow(path, elapsed, attempts);
+        if let Some(observer) = &self.observer {
+            observer.on_request(&RequestEvent {
+                service: self.service,
+                method,
+                path,
+                attempts,
+                elapsed,
+                error: result.as_ref().err(),
+                context,
+                request_id,
+            });
+        }
     }
 
-    fn build_url(&self, path: &str) -> crate::Result<Url> {
-        let normalized = if path.starts_with('/') {
-            &path[1..]
-        } else {
-            path
+    /// Warn when a call, retries included, took longer than the configured threshold.
+    fn warn_if_slow(&self, path: &str, elapsed: Duration, attempts: u32) {
+        let Some(threshold) = self.slow_request_threshold else {
+            return;
         };
-        self.base_url
-            .join(normalized)
-            .map_err(|err| Error::InvalidEndpoint(format!("Invalid path `{path}`: {err}")))
+        if elapsed > threshold {
+            warn!(
+                service = self.service.name(),
+                path,
+                ?elapsed,
+                attempts,
+                "Slow service request"
+            );
+        }
+    }
+
+    /// Run `call` under the configured deadline, if any.
+    async fn within_deadline<T>(
+        &self,
+        call: impl Future<Output = crate::Result<T>>,
+    ) -> crate::Result<T> {
+        let Some(deadline) = self.deadline else {
+            return call.await;
+        };
+        let started = Instant::now();
+        match tokio::time::timeout(deadline, call).await {
+            Ok(result) => result,
+            Err(_) => Err(Error::timeout(TimeoutKind::Deadline, started.elapsed())),
+        }
     }
+
+    /// Sleep before retry `attempt`, returning false once retries are exhausted.
+    ///
+    /// `previous` holds the delay slept before the last retry and is updated.
+    /// A server-supplied `Retry-After` raises the delay but never shortens it.
+    async fn backoff(
+        &self,
+        attempt: u32,
+        previous: &mut Duration,
+        retry_after: Option<Duration>,
+    ) -> bool {
+        if attempt > self.retry_policy.max_retries {
+            return false;
+        }
+        let delay = self
+            .retry_policy
+            .next_delay(attempt, *previous)
+            .max(retry_after.unwrap_or_default());
+        *previous = delay;
+        if delay > Duration::from_millis(0) {
+            debug!(
+                service = self.service.name(),
+                ?delay,
+                "Retrying service request"
+            );
+            sleep(delay).await;
+        }
+        true
+    }
+
+    /// Ping the service and attempt one authenticated read of `read_path`.
+    ///
+    /// Each probe is sent once without retries. A failed or 5xx ping, or a
+    /// read that gets no response, marks the service unreachable; a 401/403
+    /// on the read marks it unauthorized.
+    pub async fn preflight(&self, read_path: &str, read_params: &[QueryPair]) -> PreflightResult {
+        self.preflight_with(read_path, read_params, |request| request)
+            .await
+    }
+
+    /// Like [`preflight`](Self::preflight), with `configure` applied to the
+    /// authenticated read, e.g. to add service-specific auth headers.
+    pub async fn preflight_with<F>(
+        &self,
+        read_path: &str,
+        read_params: &[QueryPair],
+        configure: F,
+    ) -> PreflightResult
+    where
+        F: FnOnce(RequestBuilder) -> RequestBuilder,
+    {
+        let service = self.service;
+        let started = Instant::now();
+        let ping = match self.request(Method::GET, "ping", &[]) {
+            Ok(request) => request.send().await,
+            Err(err) => return PreflightResult::unreachable(err.to_string()),
+        };
+        let latency = started.elapsed();
+
+        match ping {
+            Ok(response) if response.status().is_server_error() => {
+                return PreflightResult::unreachable(format!(
+                    "{service} ping returned {}",
+                    response.status()
+                ));
+            }
+            Ok(_) => {}
+            Err(err) => return PreflightResult::unreachable(Error::from(err).to_string()),
+        }
+
+        let read = match self.request(Method::GET, read_path, read_params) {
+            Ok(request) => configure(request).send().await,
+            Err(err) => return PreflightResult::unreachable(err.to_string()),
+        };
+
+        match read {
+            Ok(response) => {
+                let status = response.status();
+                if status.is_success() {
+                    PreflightResult::ready(latency)
+                } else if matches!(status, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) {
+                    PreflightResult::unauthorized(
+                        latency,
+                        format!("{service} rejected credentials with {status}"),
+                    )
+                } else {
+                    PreflightResult {
+                        error: Some(format!("{service} read `{read_path}` returned {status}")),
+                        ..PreflightResult::ready(latency)
+                    }
+                }
+            }
+            Err(err) => PreflightResult::unreachable(Error::from(err).to_string()),
+        }
+    }
+}
+
+/// Join `path` onto `base`, ignoring a leading slash.
+fn join_path(base: &Url, path: &str) -> crate::Result<Url> {
+    let normalized = path.strip_prefix('/').unwrap_or(path);
+    base.join(normalized)
+        .map_err(|err| Error::InvalidEndpoint(format!("Invalid path `{path}`: {err}")))
+}
+
+/// A decoded response body together with the status and headers it came with.
+///
+/// Lets callers read metadata such as `ETag` or `x-resource-count` that the
+/// typed methods otherwise discard.
+#[derive(Debug, Clone)]
+pub struct ServiceResponse<T> {
+    /// Decoded body.
+    pub body: T,
+    /// HTTP status of the final attempt.
+    pub status: StatusCode,
+    /// Headers of the final attempt.
+    pub headers: HeaderMap,
+}
+
+impl<T> ServiceResponse<T> {
+    /// Returns a header value if it is present and valid UTF-8.
+    #[must_use]
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|value| value.to_str().ok())
+    }
+
+    /// Transform the body, keeping status and headers.
+    #[must_use]
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> ServiceResponse<U> {
+        ServiceResponse {
+            body: f(self.body),
+            status: self.status,
+            headers: self.headers,
+        }
+    }
+}
+
+/// Outcome of a single request attempt that did not fail permanently.
+enum Attempt {
+    Success(Response),
+    Retryable {
+        error: Error,
+        retry_after: Option<Duration>,
+    },
+}
+
+/// Parse a `Retry-After` header as delta-seconds or an HTTP-date.
+///
+/// Dates in the past yield [`Duration::ZERO`]; missing or malformed headers
+/// yield `None`.
+#[must_use]
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = httpdate::parse_http_date(value).ok()?;
+    Some(
+        at.duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
 }
 
 fn should_retry(status: StatusCode) -> bool {
+    // 507 reports exhausted capacity (e.g. a full NAPI subnet); retrying will not free it.
+    if status == StatusCode::INSUFFICIENT_STORAGE {
+        return false;
+    }
     matches!(
         status,
         StatusCode::TOO_MANY_REQUESTS
@@ -590,16 +1987,73 @@ mod tests {
         assert_eq!(policy.delay_for_attempt(1), Duration::from_millis(500));
 
         // Attempt 2: initial_delay * 2^1 = 1000ms
-        assert_eq!(policy.delay_for_attempt(2), Duration::from_millis(1000));
+        assert_eq!(policy.delay_for_attempt(2), Duration::from_secs(1));
 
         // Attempt 3: initial_delay * 2^2 = 2000ms
-        assert_eq!(policy.delay_for_attempt(3), Duration::from_millis(2000));
+        assert_eq!(policy.delay_for_attempt(3), Duration::from_secs(2));
 
         // Attempt 4: initial_delay * 2^3 = 4000ms
-        assert_eq!(policy.delay_for_attempt(4), Duration::from_millis(4000));
+        assert_eq!(policy.delay_for_attempt(4), Duration::from_secs(4));
 
         // Attempt 5: would be 8000ms but capped at max_delay (5000ms)
-        assert_eq!(policy.delay_for_attempt(5), Duration::from_millis(5000));
+        assert_eq!(policy.delay_for_attempt(5), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_retry_policy_full_jitter_stays_within_bounds() {
+        let policy = RetryPolicy::new().with_jitter(JitterKind::Full);
+        assert_eq!(policy.jitter, Some(JitterKind::Full));
+        assert_eq!(policy.delay_for_attempt(0), Duration::ZERO);
+
+        for attempt in 1..=6 {
+            let ceiling = RetryPolicy::new().delay_for_attempt(attempt);
+            for _ in 0..200 {
+                assert!(policy.delay_for_attempt(attempt) <= ceiling);
+            }
+        }
+    }
+
+    #[test]
+    fn test_retry_policy_decorrelated_jitter_tracks_previous_delay() {
+        let policy = RetryPolicy::new()
+            .with_initial_delay(Duration::from_millis(100))
+            .with_max_delay(Duration::from_secs(2))
+            .with_jitter(JitterKind::Decorrelated);
+
+        let mut previous = Duration::ZERO;
+        for attempt in 1..=200 {
+            let delay = policy.next_delay(attempt, previous);
+            let ceiling = std::cmp::min(
+                previous.max(Duration::from_millis(100)) * 3,
+                Duration::from_secs(2),
+            );
+            assert!(delay >= Duration::from_millis(100), "{delay:?} below floor");
+            assert!(delay <= ceiling, "{delay:?} above {ceiling:?}");
+            previous = delay;
+        }
+    }
+
+    #[test]
+    fn test_retry_after_parses_seconds_and_http_dates() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, "10".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(10)));
+
+        let future = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(30));
+        headers.insert(RETRY_AFTER, future.parse().unwrap());
+        let delay = retry_after(&headers).unwrap();
+        assert!(delay > Duration::from_secs(25) && delay <= Duration::from_secs(30));
+
+        headers.insert(
+            RETRY_AFTER,
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+
+        headers.insert(RETRY_AFTER, "soon".parse().unwrap());
+        assert_eq!(retry_after(&headers), None);
     }
 
     #[test]
@@ -669,8 +2123,20 @@ mod tests {
         assert_eq!(policy.delay_for_attempt(6), Duration::from_millis(3200));
 
         // Should cap at max_delay (5000ms)
-        assert_eq!(policy.delay_for_attempt(7), Duration::from_millis(5000));
-        assert_eq!(policy.delay_for_attempt(10), Duration::from_millis(5000));
+        assert_eq!(policy.delay_for_attempt(7), Duration::from_secs(5));
+        assert_eq!(policy.delay_for_attempt(10), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_retry_policy_backoff_saturates_at_max_delay() {
+        let policy = RetryPolicy::new()
+            .with_initial_delay(Duration::from_secs(u64::MAX))
+            .with_backoff_multiplier(u32::MAX)
+            .with_max_delay(Duration::from_secs(30));
+
+        assert_eq!(policy.delay_for_attempt(1), Duration::from_secs(30));
+        assert_eq!(policy.delay_for_attempt(2), Duration::from_secs(30));
+        assert_eq!(policy.delay_for_attempt(u32::MAX), Duration::from_secs(30));
     }
 
     #[test]
@@ -685,4 +2151,42 @@ mod tests {
         assert_eq!(DEFAULT_RETRY_DELAY_MS, 500);
         assert_eq!(DEFAULT_RETRY_MAX_DELAY_MS, 5000);
     }
+
+    #[test]
+    fn test_request_options_content_type_override() {
+        let client = Client::new();
+        let body = serde_json::json!({ "name": "web01" });
+
+        let default = RequestOptions::new()
+            .json_body(client.post("http://localhost/vms"), &body)
+            .build()
+            .unwrap();
+        assert_eq!(default.headers()[CONTENT_TYPE], "application/json");
+
+        let options = RequestOptions::new().with_content_type("application/json; charset=utf-8");
+        let request = options
+            .json_body(client.post("http://localhost/vms"), &body)
+            .build()
+            .unwrap();
+        assert_eq!(
+            request.headers()[CONTENT_TYPE],
+            "application/json; charset=utf-8"
+        );
+        assert_eq!(request.headers().get_all(CONTENT_TYPE).iter().count(), 1);
+        let sent: serde_json::Value =
+            serde_json::from_slice(request.body().unwrap().as_bytes().unwrap()).unwrap();
+        assert_eq!(sent, body);
+    }
+
+    #[test]
+    fn test_request_options_empty_body_retry() {
+        assert!(!RequestOptions::new().should_retry_empty(StatusCode::OK, b""));
+
+        let options = RequestOptions::new().with_retry_on_empty_body(true);
+        assert!(options.should_retry_empty(StatusCode::OK, b""));
+        assert!(options.should_retry_empty(StatusCode::CREATED, b" \r\n"));
+        assert!(!options.should_retry_empty(StatusCode::NO_CONTENT, b""));
+        assert!(!options.should_retry_empty(StatusCode::OK, b"[]"));
+        assert!(!options.should_retry_empty(StatusCode::BAD_GATEWAY, b""));
+    }
 }
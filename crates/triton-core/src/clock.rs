@@ -0,0 +1,82 @@
+//! Injectable time source.
+//!
+//! Components that make time-based decisions (quarantines, cache expiry) take
+//! a [`Clock`] so tests can advance time deterministically with [`ManualClock`].
+
+use std::fmt;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// Source of monotonic time.
+pub trait Clock: Send + Sync {
+    /// Current instant.
+    fn now(&self) -> Instant;
+}
+
+/// Clock backed by [`Instant::now`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Clock that only moves when advanced explicitly.
+#[derive(Clone)]
+pub struct ManualClock {
+    now: Arc<RwLock<Instant>>,
+}
+
+impl ManualClock {
+    /// Create a clock frozen at the current instant.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            now: Arc::new(RwLock::new(Instant::now())),
+        }
+    }
+
+    /// Move the clock forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        if let Ok(mut now) = self.now.write() {
+            *now += duration;
+        }
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for ManualClock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ManualClock")
+            .field("now", &self.now())
+            .finish()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.now.read().map_or_else(|_| Instant::now(), |now| *now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manual_clock_advances_only_when_told() {
+        let clock = ManualClock::new();
+        let start = clock.now();
+        assert_eq!(clock.now(), start);
+
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(clock.now(), start + Duration::from_secs(5));
+    }
+}
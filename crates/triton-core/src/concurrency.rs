@@ -0,0 +1,57 @@
+//! Bounded-parallelism helpers for batch operations.
+//!
+//! Client crates use these helpers to fan out many independent requests
+//! (NIC cleanup, VM actions, discovery) without flooding a service.
+
+use futures::stream::{self, StreamExt};
+use std::future::Future;
+
+/// Run `f` over every item with at most `limit` futures in flight.
+///
+/// Results are returned in the same order as the input items, regardless of
+/// completion order. A `limit` of zero is treated as one.
+pub async fn run_bounded<I, F, Fut, T>(items: I, limit: usize, f: F) -> Vec<T>
+where
+    I: IntoIterator,
+    F: FnMut(I::Item) -> Fut,
+    Fut: Future<Output = T>,
+{
+    stream::iter(items)
+        .map(f)
+        .buffered(limit.max(1))
+        .collect()
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::run_bounded;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn preserves_input_order() {
+        let results = run_bounded(vec![30_u64, 10, 20], 3, |delay| async move {
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+            delay
+        })
+        .await;
+        assert_eq!(results, vec![30, 10, 20]);
+    }
+
+    #[tokio::test]
+    async fn respects_limit() {
+        let in_flight = &AtomicUsize::new(0);
+        let peak = &AtomicUsize::new(0);
+
+        run_bounded(0..8, 2, |_| async move {
+            let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(current, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            in_flight.fetch_sub(1, Ordering::SeqCst);
+        })
+        .await;
+
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+    }
+}
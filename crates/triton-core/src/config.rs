@@ -3,8 +3,14 @@
 //! This module provides configuration types for connecting to Triton DataCenter services,
 //! including service discovery, endpoint configuration, and validation.
 
+use crate::client::{
+    AMON_DEFAULT_TIMEOUT, CNAPI_DEFAULT_TIMEOUT, FWAPI_DEFAULT_TIMEOUT, IMGAPI_DEFAULT_TIMEOUT,
+    NAPI_DEFAULT_TIMEOUT, PAPI_DEFAULT_TIMEOUT, SAPI_DEFAULT_TIMEOUT, UFDS_DEFAULT_TIMEOUT,
+    VMAPI_DEFAULT_TIMEOUT, WORKFLOW_DEFAULT_TIMEOUT,
+};
+use crate::types::TritonService;
 use crate::Error;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::time::Duration;
 use url::Url;
 use validator::Validate;
@@ -45,6 +51,10 @@ pub struct TritonClientConfig {
     #[validate(nested)]
     #[serde(default)]
     pub service_discovery: ServiceDiscoveryConfig,
+
+    /// Default request timeout for each service's client
+    #[serde(default)]
+    pub service_timeouts: ServiceTimeouts,
 }
 
 const fn default_tls_verify() -> bool {
@@ -64,7 +74,7 @@ impl TritonClientConfig {
     ///
     /// # Arguments
     ///
-    /// * `sapi_url` - The base URL for SAPI (e.g., "https://sapi.example.com")
+    /// * `sapi_url` - The base URL for SAPI (e.g., `"https://sapi.example.com"`)
     ///
     /// # Errors
     ///
@@ -78,11 +88,12 @@ impl TritonClientConfig {
             request_timeout_secs: default_request_timeout_secs(),
             max_retries: default_max_retries(),
             service_discovery: ServiceDiscoveryConfig::default(),
+            service_timeouts: ServiceTimeouts::default(),
         };
 
         config
             .validate()
-            .map_err(|e| Error::ConfigError(format!("Invalid configuration: {}", e)))?;
+            .map_err(|e| Error::ConfigError(format!("Invalid configuration: {e}")))?;
 
         Ok(config)
     }
@@ -129,20 +140,39 @@ impl TritonClientConfig {
         self
     }
 
+    /// Set the per-service default timeouts.
+    #[must_use]
+    pub const fn with_service_timeouts(mut self, timeouts: ServiceTimeouts) -> Self {
+        self.service_timeouts = timeouts;
+        self
+    }
+
     /// Get the request timeout as a Duration.
     #[must_use]
     pub const fn timeout(&self) -> Duration {
         Duration::from_secs(self.request_timeout_secs)
     }
 
+    /// Get the request timeout for `service`'s client.
+    ///
+    /// A static endpoint's `timeout_override_secs` wins; otherwise the value
+    /// from [`Self::service_timeouts`] is used.
+    #[must_use]
+    pub fn timeout_for(&self, service: TritonService) -> Duration {
+        self.service_discovery
+            .services
+            .get(service)
+            .and_then(ServiceEndpointConfig::timeout)
+            .unwrap_or_else(|| self.service_timeouts.get(service))
+    }
+
     /// Parse and validate the SAPI URL.
     ///
     /// # Errors
     ///
     /// Returns an error if the URL cannot be parsed.
     pub fn parse_sapi_url(&self) -> Result<Url, Error> {
-        Url::parse(&self.sapi_url)
-            .map_err(|e| Error::ConfigError(format!("Invalid SAPI URL: {}", e)))
+        Url::parse(&self.sapi_url).map_err(|e| Error::ConfigError(format!("Invalid SAPI URL: {e}")))
     }
 }
 
@@ -156,8 +186,189 @@ impl Default for TritonClientConfig {
             request_timeout_secs: default_request_timeout_secs(),
             max_retries: default_max_retries(),
             service_discovery: ServiceDiscoveryConfig::default(),
+            service_timeouts: ServiceTimeouts::default(),
+        }
+    }
+}
+
+/// Default request timeout for each Triton service.
+///
+/// Starts from the per-service constants in [`crate::client`] and can be
+/// adjusted in code, loaded from a config file (values in seconds), or
+/// overridden from the environment with [`Self::from_env`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServiceTimeouts {
+    /// VMAPI timeout
+    #[serde(with = "duration_secs")]
+    pub vmapi: Duration,
+    /// CNAPI timeout
+    #[serde(with = "duration_secs")]
+    pub cnapi: Duration,
+    /// NAPI timeout
+    #[serde(with = "duration_secs")]
+    pub napi: Duration,
+    /// IMGAPI timeout
+    #[serde(with = "duration_secs")]
+    pub imgapi: Duration,
+    /// PAPI timeout
+    #[serde(with = "duration_secs")]
+    pub papi: Duration,
+    /// FWAPI timeout
+    #[serde(with = "duration_secs")]
+    pub fwapi: Duration,
+    /// SAPI timeout
+    #[serde(with = "duration_secs")]
+    pub sapi: Duration,
+    /// UFDS timeout
+    #[serde(with = "duration_secs")]
+    pub ufds: Duration,
+    /// Amon timeout
+    #[serde(with = "duration_secs")]
+    pub amon: Duration,
+    /// Workflow timeout
+    #[serde(with = "duration_secs")]
+    pub workflow: Duration,
+}
+
+impl ServiceTimeouts {
+    /// Environment variable holding a factor applied to every timeout.
+    pub const SCALE_ENV: &'static str = "TRITON_TIMEOUT_SCALE";
+
+    /// Create the timeouts from the per-service default constants.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            vmapi: Duration::from_secs(VMAPI_DEFAULT_TIMEOUT),
+            cnapi: Duration::from_secs(CNAPI_DEFAULT_TIMEOUT),
+            napi: Duration::from_secs(NAPI_DEFAULT_TIMEOUT),
+            imgapi: Duration::from_secs(IMGAPI_DEFAULT_TIMEOUT),
+            papi: Duration::from_secs(PAPI_DEFAULT_TIMEOUT),
+            fwapi: Duration::from_secs(FWAPI_DEFAULT_TIMEOUT),
+            sapi: Duration::from_secs(SAPI_DEFAULT_TIMEOUT),
+            ufds: Duration::from_secs(UFDS_DEFAULT_TIMEOUT),
+            amon: Duration::from_secs(AMON_DEFAULT_TIMEOUT),
+            workflow: Duration::from_secs(WORKFLOW_DEFAULT_TIMEOUT),
+        }
+    }
+
+    /// Load the defaults, then apply overrides from the environment.
+    ///
+    /// `TRITON_TIMEOUT_SCALE` multiplies every timeout (e.g. `2.5`), and
+    /// `TRITON_<SERVICE>_TIMEOUT_SECS` (e.g. `TRITON_VMAPI_TIMEOUT_SECS`)
+    /// then sets a single service outright.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ConfigError`] if a variable is set but not a positive number.
+    pub fn from_env() -> Result<Self, Error> {
+        Self::new().with_overrides(|name| std::env::var(name).ok())
+    }
+
+    /// Get the timeout for `service`.
+    #[must_use]
+    pub const fn get(&self, service: TritonService) -> Duration {
+        match service {
+            TritonService::Vmapi => self.vmapi,
+            TritonService::Cnapi => self.cnapi,
+            TritonService::Napi => self.napi,
+            TritonService::Imgapi => self.imgapi,
+            TritonService::Papi => self.papi,
+            TritonService::Fwapi => self.fwapi,
+            TritonService::Sapi => self.sapi,
+            TritonService::Ufds => self.ufds,
+            TritonService::Amon => self.amon,
+            TritonService::Workflow => self.workflow,
+        }
+    }
+
+    /// Set the timeout for `service`.
+    #[must_use]
+    pub fn with_timeout(mut self, service: TritonService, timeout: Duration) -> Self {
+        *self.slot(service) = timeout;
+        self
+    }
+
+    /// Multiply every timeout by `factor`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ConfigError`] if `factor` is negative or not finite, or
+    /// a scaled timeout does not fit in a [`Duration`].
+    pub fn scaled(mut self, factor: f64) -> Result<Self, Error> {
+        for service in TritonService::all() {
+            let slot = self.slot(*service);
+            *slot = Duration::try_from_secs_f64(slot.as_secs_f64() * factor).map_err(|err| {
+                Error::ConfigError(format!(
+                    "Cannot scale the {} timeout by {factor}: {err}",
+                    service.name()
+                ))
+            })?;
+        }
+        Ok(self)
+    }
+
+    fn slot(&mut self, service: TritonService) -> &mut Duration {
+        match service {
+            TritonService::Vmapi => &mut self.vmapi,
+            TritonService::Cnapi => &mut self.cnapi,
+            TritonService::Napi => &mut self.napi,
+            TritonService::Imgapi => &mut self.imgapi,
+            TritonService::Papi => &mut self.papi,
+            TritonService::Fwapi => &mut self.fwapi,
+            TritonService::Sapi => &mut self.sapi,
+            TritonService::Ufds => &mut self.ufds,
+            TritonService::Amon => &mut self.amon,
+            TritonService::Workflow => &mut self.workflow,
         }
     }
+
+    fn with_overrides(mut self, lookup: impl Fn(&str) -> Option<String>) -> Result<Self, Error> {
+        let positive = |name: &str, raw: String| -> Result<f64, Error> {
+            raw.trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|value| value.is_finite() && *value > 0.0)
+                .ok_or_else(|| {
+                    Error::ConfigError(format!("{name} must be a positive number, got `{raw}`"))
+                })
+        };
+
+        if let Some(raw) = lookup(Self::SCALE_ENV) {
+            self = self.scaled(positive(Self::SCALE_ENV, raw)?)?;
+        }
+        for service in TritonService::all() {
+            let name = format!(
+                "TRITON_{}_TIMEOUT_SECS",
+                service.name().to_ascii_uppercase()
+            );
+            if let Some(raw) = lookup(&name) {
+                let secs = positive(&name, raw)?;
+                *self.slot(*service) = Duration::try_from_secs_f64(secs)
+                    .map_err(|err| Error::ConfigError(format!("{name} is out of range: {err}")))?;
+            }
+        }
+        Ok(self)
+    }
+}
+
+impl Default for ServiceTimeouts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+mod duration_secs {
+    use super::{Deserialize, Deserializer, Duration, Serializer};
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(duration.as_secs_f64())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        let secs = f64::deserialize(deserializer)?;
+        Duration::try_from_secs_f64(secs).map_err(serde::de::Error::custom)
+    }
 }
 
 /// Configuration for service discovery.
@@ -268,31 +479,49 @@ impl Default for ServiceDiscoveryConfig {
 /// Static service endpoint configurations.
 ///
 /// Provides fallback endpoints when service discovery is unavailable or disabled.
+/// Each service may list several endpoints; in configuration files a single
+/// endpoint may be written as an object instead of a one-element list.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ServiceEndpoints {
-    /// VMAPI endpoint
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub vmapi: Option<ServiceEndpointConfig>,
+    /// VMAPI endpoints
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "one_or_many")]
+    pub vmapi: Vec<ServiceEndpointConfig>,
 
-    /// CNAPI endpoint
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub cnapi: Option<ServiceEndpointConfig>,
+    /// CNAPI endpoints
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "one_or_many")]
+    pub cnapi: Vec<ServiceEndpointConfig>,
 
-    /// NAPI endpoint
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub napi: Option<ServiceEndpointConfig>,
+    /// NAPI endpoints
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "one_or_many")]
+    pub napi: Vec<ServiceEndpointConfig>,
 
-    /// IMGAPI endpoint
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub imgapi: Option<ServiceEndpointConfig>,
+    /// IMGAPI endpoints
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "one_or_many")]
+    pub imgapi: Vec<ServiceEndpointConfig>,
 
-    /// PAPI endpoint
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub papi: Option<ServiceEndpointConfig>,
+    /// PAPI endpoints
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "one_or_many")]
+    pub papi: Vec<ServiceEndpointConfig>,
 
-    /// FWAPI endpoint
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub fwapi: Option<ServiceEndpointConfig>,
+    /// FWAPI endpoints
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "one_or_many")]
+    pub fwapi: Vec<ServiceEndpointConfig>,
+
+    /// SAPI endpoints
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "one_or_many")]
+    pub sapi: Vec<ServiceEndpointConfig>,
+
+    /// UFDS endpoints
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "one_or_many")]
+    pub ufds: Vec<ServiceEndpointConfig>,
+
+    /// Amon endpoints
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "one_or_many")]
+    pub amon: Vec<ServiceEndpointConfig>,
+
+    /// Workflow endpoints
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "one_or_many")]
+    pub workflow: Vec<ServiceEndpointConfig>,
 }
 
 impl ServiceEndpoints {
@@ -300,56 +529,169 @@ impl ServiceEndpoints {
     #[must_use]
     pub const fn new() -> Self {
         Self {
-            vmapi: None,
-            cnapi: None,
-            napi: None,
-            imgapi: None,
-            papi: None,
-            fwapi: None,
+            vmapi: Vec::new(),
+            cnapi: Vec::new(),
+            napi: Vec::new(),
+            imgapi: Vec::new(),
+            papi: Vec::new(),
+            fwapi: Vec::new(),
+            sapi: Vec::new(),
+            ufds: Vec::new(),
+            amon: Vec::new(),
+            workflow: Vec::new(),
+        }
+    }
+
+    /// Get the first static endpoint configured for `service`, if any.
+    #[must_use]
+    pub fn get(&self, service: TritonService) -> Option<&ServiceEndpointConfig> {
+        self.endpoints(service).first()
+    }
+
+    /// Get every static endpoint configured for `service`.
+    #[must_use]
+    pub fn endpoints(&self, service: TritonService) -> &[ServiceEndpointConfig] {
+        match service {
+            TritonService::Vmapi => &self.vmapi,
+            TritonService::Cnapi => &self.cnapi,
+            TritonService::Napi => &self.napi,
+            TritonService::Imgapi => &self.imgapi,
+            TritonService::Papi => &self.papi,
+            TritonService::Fwapi => &self.fwapi,
+            TritonService::Sapi => &self.sapi,
+            TritonService::Ufds => &self.ufds,
+            TritonService::Amon => &self.amon,
+            TritonService::Workflow => &self.workflow,
+        }
+    }
+
+    /// Replace the endpoints configured for `service`.
+    #[must_use]
+    pub fn with_endpoints(
+        mut self,
+        service: TritonService,
+        endpoints: impl IntoIterator<Item = ServiceEndpointConfig>,
+    ) -> Self {
+        *self.slot(service) = endpoints.into_iter().collect();
+        self
+    }
+
+    fn slot(&mut self, service: TritonService) -> &mut Vec<ServiceEndpointConfig> {
+        match service {
+            TritonService::Vmapi => &mut self.vmapi,
+            TritonService::Cnapi => &mut self.cnapi,
+            TritonService::Napi => &mut self.napi,
+            TritonService::Imgapi => &mut self.imgapi,
+            TritonService::Papi => &mut self.papi,
+            TritonService::Fwapi => &mut self.fwapi,
+            TritonService::Sapi => &mut self.sapi,
+            TritonService::Ufds => &mut self.ufds,
+            TritonService::Amon => &mut self.amon,
+            TritonService::Workflow => &mut self.workflow,
         }
     }
 
     /// Set VMAPI endpoint.
     #[must_use]
     pub fn with_vmapi(mut self, endpoint: ServiceEndpointConfig) -> Self {
-        self.vmapi = Some(endpoint);
+        self.vmapi = vec![endpoint];
         self
     }
 
     /// Set CNAPI endpoint.
     #[must_use]
     pub fn with_cnapi(mut self, endpoint: ServiceEndpointConfig) -> Self {
-        self.cnapi = Some(endpoint);
+        self.cnapi = vec![endpoint];
         self
     }
 
     /// Set NAPI endpoint.
     #[must_use]
     pub fn with_napi(mut self, endpoint: ServiceEndpointConfig) -> Self {
-        self.napi = Some(endpoint);
+        self.napi = vec![endpoint];
         self
     }
 
     /// Set IMGAPI endpoint.
     #[must_use]
     pub fn with_imgapi(mut self, endpoint: ServiceEndpointConfig) -> Self {
-        self.imgapi = Some(endpoint);
+        self.imgapi = vec![endpoint];
         self
     }
 
     /// Set PAPI endpoint.
     #[must_use]
     pub fn with_papi(mut self, endpoint: ServiceEndpointConfig) -> Self {
-        self.papi = Some(endpoint);
+        self.papi = vec![endpoint];
         self
     }
 
     /// Set FWAPI endpoint.
     #[must_use]
     pub fn with_fwapi(mut self, endpoint: ServiceEndpointConfig) -> Self {
-        self.fwapi = Some(endpoint);
+        self.fwapi = vec![endpoint];
+        self
+    }
+
+    /// Set SAPI endpoint.
+    #[must_use]
+    pub fn with_sapi(mut self, endpoint: ServiceEndpointConfig) -> Self {
+        self.sapi = vec![endpoint];
+        self
+    }
+
+    /// Set UFDS endpoint.
+    #[must_use]
+    pub fn with_ufds(mut self, endpoint: ServiceEndpointConfig) -> Self {
+        self.ufds = vec![endpoint];
         self
     }
+
+    /// Set Amon endpoint.
+    #[must_use]
+    pub fn with_amon(mut self, endpoint: ServiceEndpointConfig) -> Self {
+        self.amon = vec![endpoint];
+        self
+    }
+
+    /// Set Workflow endpoint.
+    #[must_use]
+    pub fn with_workflow(mut self, endpoint: ServiceEndpointConfig) -> Self {
+        self.workflow = vec![endpoint];
+        self
+    }
+}
+
+/// Serializes one endpoint as an object and several as a list.
+mod one_or_many {
+    use super::{Deserialize, Deserializer, Serialize, Serializer, ServiceEndpointConfig};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(ServiceEndpointConfig),
+        Many(Vec<ServiceEndpointConfig>),
+    }
+
+    pub fn serialize<S: Serializer>(
+        endpoints: &[ServiceEndpointConfig],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match endpoints {
+            [endpoint] => endpoint.serialize(serializer),
+            endpoints => endpoints.serialize(serializer),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<ServiceEndpointConfig>, D::Error> {
+        Ok(match Option::<OneOrMany>::deserialize(deserializer)? {
+            None => Vec::new(),
+            Some(OneOrMany::One(endpoint)) => vec![endpoint],
+            Some(OneOrMany::Many(endpoints)) => endpoints,
+        })
+    }
 }
 
 /// Configuration for a single service endpoint.
@@ -383,7 +725,7 @@ impl ServiceEndpointConfig {
 
         config
             .validate()
-            .map_err(|e| Error::ConfigError(format!("Invalid endpoint configuration: {}", e)))?;
+            .map_err(|e| Error::ConfigError(format!("Invalid endpoint configuration: {e}")))?;
 
         Ok(config)
     }
@@ -407,7 +749,7 @@ impl ServiceEndpointConfig {
     ///
     /// Returns an error if the URL cannot be parsed.
     pub fn parse_url(&self) -> Result<Url, Error> {
-        Url::parse(&self.url).map_err(|e| Error::ConfigError(format!("Invalid service URL: {}", e)))
+        Url::parse(&self.url).map_err(|e| Error::ConfigError(format!("Invalid service URL: {e}")))
     }
 }
 
@@ -510,9 +852,9 @@ mod tests {
     #[test]
     fn test_service_endpoints_new() {
         let endpoints = ServiceEndpoints::new();
-        assert!(endpoints.vmapi.is_none());
-        assert!(endpoints.cnapi.is_none());
-        assert!(endpoints.napi.is_none());
+        assert!(endpoints.vmapi.is_empty());
+        assert!(endpoints.cnapi.is_empty());
+        assert!(endpoints.napi.is_empty());
     }
 
     #[test]
@@ -522,9 +864,9 @@ mod tests {
 
         let endpoints = ServiceEndpoints::new().with_vmapi(vmapi).with_cnapi(cnapi);
 
-        assert!(endpoints.vmapi.is_some());
-        assert!(endpoints.cnapi.is_some());
-        assert!(endpoints.napi.is_none());
+        assert_eq!(endpoints.vmapi.len(), 1);
+        assert_eq!(endpoints.cnapi.len(), 1);
+        assert!(endpoints.napi.is_empty());
     }
 
     #[test]
@@ -601,10 +943,69 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_service_endpoints_serialization_round_trip() {
+        let endpoints = ServiceEndpoints::new()
+            .with_vmapi(ServiceEndpointConfig::new("http://vmapi:80").unwrap())
+            .with_sapi(ServiceEndpointConfig::new("http://sapi:80").unwrap())
+            .with_ufds(ServiceEndpointConfig::new("ldaps://ufds:636").unwrap())
+            .with_amon(ServiceEndpointConfig::new("http://amon:80").unwrap())
+            .with_workflow(ServiceEndpointConfig::new("http://workflow:80").unwrap());
+
+        let json = serde_json::to_value(&endpoints).unwrap();
+        assert_eq!(json["ufds"]["url"], "ldaps://ufds:636");
+        assert!(json.get("cnapi").is_none());
+
+        let deserialized: ServiceEndpoints = serde_json::from_value(json).unwrap();
+        for service in TritonService::all() {
+            assert_eq!(
+                deserialized.get(*service).map(|e| &e.url),
+                endpoints.get(*service).map(|e| &e.url),
+            );
+        }
+
+        // Configs written before these services existed still load.
+        let old: ServiceEndpoints =
+            serde_json::from_str(r#"{"vmapi":{"url":"http://vmapi:80"}}"#).unwrap();
+        assert!(old.ufds.is_empty() && old.workflow.is_empty());
+    }
+
+    #[test]
+    fn test_service_endpoints_accept_one_or_many() {
+        let json = r#"{
+            "vmapi": {"url": "http://vmapi:80"},
+            "cnapi": [{"url": "http://cnapi0:80"}, {"url": "http://cnapi1:80"}]
+        }"#;
+        let endpoints: ServiceEndpoints = serde_json::from_str(json).unwrap();
+
+        let urls = |service| {
+            endpoints
+                .endpoints(service)
+                .iter()
+                .map(|e| e.url.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(urls(TritonService::Vmapi), ["http://vmapi:80"]);
+        assert_eq!(
+            urls(TritonService::Cnapi),
+            ["http://cnapi0:80", "http://cnapi1:80"]
+        );
+        assert_eq!(
+            endpoints.get(TritonService::Cnapi).unwrap().url,
+            "http://cnapi0:80"
+        );
+
+        let value = serde_json::to_value(&endpoints).unwrap();
+        assert!(value["vmapi"].is_object());
+        assert_eq!(value["cnapi"].as_array().unwrap().len(), 2);
+    }
+
     #[test]
     fn test_config_validation_timeout_range() {
-        let mut config = TritonClientConfig::default();
-        config.request_timeout_secs = 0;
+        let mut config = TritonClientConfig {
+            request_timeout_secs: 0,
+            ..TritonClientConfig::default()
+        };
         assert!(config.validate().is_err());
 
         config.request_timeout_secs = 301;
@@ -616,8 +1017,10 @@ mod tests {
 
     #[test]
     fn test_config_validation_retries_range() {
-        let mut config = TritonClientConfig::default();
-        config.max_retries = 11;
+        let mut config = TritonClientConfig {
+            max_retries: 11,
+            ..TritonClientConfig::default()
+        };
         assert!(config.validate().is_err());
 
         config.max_retries = 3;
@@ -626,8 +1029,10 @@ mod tests {
 
     #[test]
     fn test_discovery_config_validation_cache_ttl_range() {
-        let mut config = ServiceDiscoveryConfig::default();
-        config.cache_ttl_secs = 0;
+        let mut config = ServiceDiscoveryConfig {
+            cache_ttl_secs: 0,
+            ..ServiceDiscoveryConfig::default()
+        };
         assert!(config.validate().is_err());
 
         config.cache_ttl_secs = 3601;
@@ -639,8 +1044,10 @@ mod tests {
 
     #[test]
     fn test_discovery_config_validation_timeout_range() {
-        let mut config = ServiceDiscoveryConfig::default();
-        config.timeout_secs = 0;
+        let mut config = ServiceDiscoveryConfig {
+            timeout_secs: 0,
+            ..ServiceDiscoveryConfig::default()
+        };
         assert!(config.validate().is_err());
 
         config.timeout_secs = 61;
@@ -649,4 +1056,81 @@ mod tests {
         config.timeout_secs = 5;
         assert!(config.validate().is_ok());
     }
+
+    #[test]
+    fn test_service_timeouts_env_overrides() {
+        let vars = [
+            ("TRITON_TIMEOUT_SCALE", "2"),
+            ("TRITON_VMAPI_TIMEOUT_SECS", "90"),
+        ];
+        let timeouts = ServiceTimeouts::new()
+            .with_overrides(|name| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| (*value).to_string())
+            })
+            .unwrap();
+
+        assert_eq!(timeouts.vmapi, Duration::from_secs(90));
+        assert_eq!(
+            timeouts.cnapi,
+            Duration::from_secs(CNAPI_DEFAULT_TIMEOUT * 2)
+        );
+        assert_eq!(timeouts.ufds, Duration::from_secs(UFDS_DEFAULT_TIMEOUT * 2));
+
+        let err = ServiceTimeouts::new()
+            .with_overrides(|name| (name == "TRITON_TIMEOUT_SCALE").then(|| "-1".to_string()))
+            .unwrap_err();
+        assert!(matches!(err, Error::ConfigError(_)));
+
+        for (var, value) in [
+            ("TRITON_TIMEOUT_SCALE", "1e30"),
+            ("TRITON_CNAPI_TIMEOUT_SECS", "1e300"),
+        ] {
+            let err = ServiceTimeouts::new()
+                .with_overrides(|name| (name == var).then(|| value.to_string()))
+                .unwrap_err();
+            assert!(matches!(err, Error::ConfigError(_)), "{var}={value}");
+        }
+    }
+
+    #[test]
+    fn test_timeout_for_prefers_endpoint_override() {
+        let mut discovery = ServiceDiscoveryConfig::new();
+        discovery.services = ServiceEndpoints::new().with_cnapi(
+            ServiceEndpointConfig::new("http://cnapi:80")
+                .unwrap()
+                .with_timeout(5),
+        );
+        let config = TritonClientConfig::default()
+            .with_service_discovery(discovery)
+            .with_service_timeouts(
+                ServiceTimeouts::new().with_timeout(TritonService::Vmapi, Duration::from_secs(120)),
+            );
+
+        assert_eq!(
+            config.timeout_for(TritonService::Vmapi),
+            Duration::from_secs(120)
+        );
+        assert_eq!(
+            config.timeout_for(TritonService::Cnapi),
+            Duration::from_secs(5)
+        );
+        assert_eq!(
+            config.timeout_for(TritonService::Napi),
+            Duration::from_secs(NAPI_DEFAULT_TIMEOUT)
+        );
+    }
+
+    #[test]
+    fn test_service_timeouts_serialize_as_seconds() {
+        let timeouts =
+            ServiceTimeouts::new().with_timeout(TritonService::Vmapi, Duration::from_millis(1500));
+        let json = serde_json::to_value(timeouts).unwrap();
+        assert_eq!(json["vmapi"], serde_json::json!(1.5));
+
+        let parsed: ServiceTimeouts = serde_json::from_str(r#"{"imgapi": 300}"#).unwrap();
+        assert_eq!(parsed.imgapi, Duration::from_secs(300));
+        assert_eq!(parsed.vmapi, Duration::from_secs(VMAPI_DEFAULT_TIMEOUT));
+    }
 }
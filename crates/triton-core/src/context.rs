@@ -0,0 +1,149 @@
+//! Request-level context tags for observability.
+//!
+//! A [`RequestContext`] carries free-form tags such as `operation_id` or
+//! `tenant` that tie together every service call made for one logical
+//! operation. Attach it to a client handle with
+//! [`ServiceClient::with_context`](crate::client::ServiceClient::with_context),
+//! or to everything a task does with [`RequestContext::scope`]. The tags are
+//! recorded on the `triton_request` tracing span, passed to any
+//! [`RequestObserver`](crate::client::RequestObserver), and appended to error
+//! messages.
+//!
+//! Every call also gets a request id, sent as a header and appended to error
+//! messages under the [`REQUEST_ID_TAG`] tag. Set that tag to supply your own
+//! id; otherwise a random UUID is generated per call.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+
+/// Tag that, when set, supplies the request id sent with each call.
+pub const REQUEST_ID_TAG: &str = "request_id";
+
+tokio::task_local! {
+    static CURRENT: RequestContext;
+}
+
+/// Tags shared by every request made for one logical operation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequestContext {
+    /// Tag names and values, e.g. `operation_id` → `provision-env-x`.
+    pub tags: HashMap<String, String>,
+}
+
+impl RequestContext {
+    /// Create an empty context.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add or replace a tag.
+    #[must_use]
+    pub fn with_tag(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.insert(name.into(), value.into());
+        self
+    }
+
+    /// Returns the value of the tag `name`, if set.
+    #[must_use]
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.get(name).map(String::as_str)
+    }
+
+    /// The request id for a call made with this context: the
+    /// [`REQUEST_ID_TAG`] tag if set, or else a new random UUID.
+    #[must_use]
+    pub fn request_id(&self) -> String {
+        self.tag(REQUEST_ID_TAG)
+            .map_or_else(|| ::uuid::Uuid::new_v4().to_string(), str::to_string)
+    }
+
+    /// Returns true when no tags are set.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+    }
+
+    /// Combine with `other`, whose tags win on conflicts.
+    #[must_use]
+    pub fn merged(&self, other: &Self) -> Self {
+        let mut tags = self.tags.clone();
+        tags.extend(other.tags.iter().map(|(k, v)| (k.clone(), v.clone())));
+        Self { tags }
+    }
+
+    /// The context attached to the current task by [`scope`](Self::scope), if any.
+    #[must_use]
+    pub fn current() -> Option<Self> {
+        CURRENT.try_with(Clone::clone).ok()
+    }
+
+    /// Run `future` with this context attached to every request it makes.
+    ///
+    /// Scopes nest: tags from an enclosing scope are kept unless this context
+    /// overrides them.
+    pub fn scope<F: Future>(self, future: F) -> impl Future<Output = F::Output> {
+        let context = match Self::current() {
+            Some(outer) => outer.merged(&self),
+            None => self,
+        };
+        CURRENT.scope(context, future)
+    }
+}
+
+/// Formats the tags as `name=value` pairs sorted by name.
+impl fmt::Display for RequestContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut tags: Vec<_> = self.tags.iter().collect();
+        tags.sort();
+        for (index, (name, value)) in tags.into_iter().enumerate() {
+            if index > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{name}={value}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_sorts_tags() {
+        let context = RequestContext::new()
+            .with_tag("tenant", "acme")
+            .with_tag("operation_id", "op-1");
+        assert_eq!(context.to_string(), "operation_id=op-1 tenant=acme");
+        assert_eq!(RequestContext::new().to_string(), "");
+    }
+
+    #[test]
+    fn request_id_prefers_the_tag() {
+        let tagged = RequestContext::new().with_tag(REQUEST_ID_TAG, "req-1");
+        assert_eq!(tagged.request_id(), "req-1");
+
+        let generated = RequestContext::new().request_id();
+        assert!(::uuid::Uuid::parse_str(&generated).is_ok());
+        assert_ne!(generated, RequestContext::new().request_id());
+    }
+
+    #[tokio::test]
+    async fn scopes_nest_and_override() {
+        assert_eq!(RequestContext::current(), None);
+
+        let outer = RequestContext::new()
+            .with_tag("operation_id", "op-1")
+            .with_tag("tenant", "acme");
+        let inner = RequestContext::new().with_tag("tenant", "globex");
+
+        let seen = outer
+            .scope(async { inner.scope(async { RequestContext::current() }).await })
+            .await
+            .unwrap();
+        assert_eq!(seen.tag("operation_id"), Some("op-1"));
+        assert_eq!(seen.tag("tenant"), Some("globex"));
+    }
+}
@@ -0,0 +1,103 @@
+//! Snapshot diffing for polled resource lists.
+//!
+//! Watchers and reconcilers compare successive listings of a resource; this
+//! module classifies each item as added, removed, or modified by key.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Kind of change detected between two snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeKind {
+    /// Present only in the current snapshot.
+    Added,
+    /// Present only in the previous snapshot.
+    Removed,
+    /// Present in both snapshots with different contents.
+    Modified,
+}
+
+/// A single change between two snapshots.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change<T> {
+    /// What happened to the item.
+    pub kind: ChangeKind,
+    /// The current item, or the previous item when removed.
+    pub item: T,
+}
+
+/// Compare two snapshots keyed by `key`.
+///
+/// Added and modified items are reported in `current` order, followed by
+/// removed items in `previous` order, so the output is deterministic.
+#[must_use]
+pub fn diff_keyed<T, K, F>(previous: &[T], current: &[T], key: F) -> Vec<Change<T>>
+where
+    T: Clone + PartialEq,
+    K: Eq + Hash,
+    F: Fn(&T) -> K,
+{
+    let before: HashMap<K, &T> = previous.iter().map(|item| (key(item), item)).collect();
+    let after: HashMap<K, &T> = current.iter().map(|item| (key(item), item)).collect();
+
+    let mut changes = Vec::new();
+    for item in current {
+        match before.get(&key(item)) {
+            None => changes.push(Change {
+                kind: ChangeKind::Added,
+                item: item.clone(),
+            }),
+            Some(old) if *old != item => changes.push(Change {
+                kind: ChangeKind::Modified,
+                item: item.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    for item in previous {
+        if !after.contains_key(&key(item)) {
+            changes.push(Change {
+                kind: ChangeKind::Removed,
+                item: item.clone(),
+            });
+        }
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_added_removed_and_modified() {
+        let previous = vec![(1, "a"), (2, "b"), (3, "c")];
+        let current = vec![(2, "b"), (3, "C"), (4, "d")];
+
+        let changes = diff_keyed(&previous, &current, |(id, _)| *id);
+        assert_eq!(
+            changes,
+            vec![
+                Change {
+                    kind: ChangeKind::Modified,
+                    item: (3, "C"),
+                },
+                Change {
+                    kind: ChangeKind::Added,
+                    item: (4, "d"),
+                },
+                Change {
+                    kind: ChangeKind::Removed,
+                    item: (1, "a"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn identical_snapshots_have_no_changes() {
+        let snapshot = vec![(1, "a"), (2, "b")];
+        assert!(diff_keyed(&snapshot, &snapshot, |(id, _)| *id).is_empty());
+    }
+}
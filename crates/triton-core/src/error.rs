@@ -3,7 +3,11 @@
 //! This module provides a comprehensive error type hierarchy for Triton DataCenter operations,
 //! including HTTP status code mapping and structured error responses.
 
-use serde::Serialize;
+use crate::context::{RequestContext, REQUEST_ID_TAG};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Write as _};
+use std::time::Duration;
 use thiserror::Error;
 
 /// Main error type for Triton operations.
@@ -38,12 +42,26 @@ pub enum Error {
     ConfigError(String),
 
     /// HTTP request failed
-    #[error("HTTP request failed: {0}")]
-    HttpError(String),
+    #[error("HTTP request failed: {message}")]
+    HttpError {
+        /// Response status, or `None` if no response was received
+        status: Option<StatusCode>,
+        /// Error message
+        message: String,
+        /// Id of the request that failed, when known
+        request_id: Option<String>,
+    },
 
     /// Operation timed out
-    #[error("Timeout waiting for service: {0}")]
-    Timeout(String),
+    #[error("{kind} timed out after {elapsed:?}{}", request_id_suffix(request_id.as_deref()))]
+    Timeout {
+        /// Which limit was hit
+        kind: TimeoutKind,
+        /// Time spent before giving up (zero when unknown)
+        elapsed: Duration,
+        /// Id of the request that timed out, when known
+        request_id: Option<String>,
+    },
 
     /// Resource not found
     #[error("Not found: {0}")]
@@ -57,6 +75,19 @@ pub enum Error {
     #[error("Bad request: {0}")]
     BadRequest(String),
 
+    /// Service rejected the request with a structured error body
+    #[error("{code} ({status}): {message}")]
+    Api {
+        /// HTTP status code
+        status: StatusCode,
+        /// Triton error code, e.g. `ResourceNotFound`
+        code: String,
+        /// Error message, including any per-field errors
+        message: String,
+        /// Id of the request that failed, when known
+        request_id: Option<String>,
+    },
+
     /// Validation error
     #[error("Validation error: {0}")]
     ValidationError(String),
@@ -65,6 +96,10 @@ pub enum Error {
     #[error("Conflict: {0}")]
     Conflict(String),
 
+    /// Request body exceeded the server's or a proxy's size limit (HTTP 413)
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+
     /// External service error
     #[error("External service error: {service}: {message}")]
     ExternalServiceError {
@@ -91,6 +126,36 @@ pub enum Error {
     NotImplemented(String),
 }
 
+/// Formats a request id for a message that has no text to tag.
+fn request_id_suffix(request_id: Option<&str>) -> String {
+    request_id.map_or_else(String::new, |id| format!(" [request_id={id}]"))
+}
+
+/// Which limit a [`Error::Timeout`] ran into.
+///
+/// A connect timeout usually means the endpoint is down and another one should
+/// be tried; a read timeout means it accepted the request but is slow; a
+/// deadline timeout means the caller's overall budget, retries included, ran out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimeoutKind {
+    /// Establishing the connection took too long
+    Connect,
+    /// The server did not finish responding in time
+    Read,
+    /// The overall operation deadline expired
+    Deadline,
+}
+
+impl fmt::Display for TimeoutKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Connect => "Connection",
+            Self::Read => "Response",
+            Self::Deadline => "Request deadline",
+        })
+    }
+}
+
 /// Specialized result type for Triton operations.
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -116,6 +181,67 @@ pub struct ErrorDetail {
     pub details: Option<serde_json::Value>,
 }
 
+/// Structured error body returned by Triton services.
+///
+/// Services built on restify answer failures with
+/// `{"code": "ResourceNotFound", "message": "..."}`, optionally listing
+/// per-field problems under `errors`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TritonApiError {
+    /// Error code for programmatic handling
+    pub code: String,
+    /// Human-readable error message
+    #[serde(default)]
+    pub message: String,
+    /// Per-field validation errors
+    #[serde(default)]
+    pub errors: Vec<FieldError>,
+}
+
+/// A single field problem inside a [`TritonApiError`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldError {
+    /// Offending field name
+    #[serde(default)]
+    pub field: String,
+    /// Error code for the field, e.g. `Invalid` or `Missing`
+    #[serde(default)]
+    pub code: String,
+    /// Human-readable description
+    #[serde(default)]
+    pub message: String,
+}
+
+impl TritonApiError {
+    /// Parse a response body, returning `None` unless it is a JSON error with a `code`.
+    #[must_use]
+    pub fn parse(body: &str) -> Option<Self> {
+        serde_json::from_str::<Self>(body)
+            .ok()
+            .filter(|error| !error.code.is_empty())
+    }
+
+    /// Convert into [`Error::Api`], folding field errors into the message.
+    #[must_use]
+    pub fn into_error(self, status: StatusCode) -> Error {
+        let mut message = self.message;
+        for field in &self.errors {
+            let detail = if field.message.is_empty() {
+                &field.code
+            } else {
+                &field.message
+            };
+            let _ = write!(message, "; {}: {detail}", field.field);
+        }
+        Error::Api {
+            status,
+            code: self.code,
+            message,
+            request_id: None,
+        }
+    }
+}
+
 impl Error {
     /// Returns the error code for this error type.
     #[must_use]
@@ -128,13 +254,15 @@ impl Error {
             Self::InvalidNetwork(_) => "INVALID_NETWORK",
             Self::InvalidVmState(_) => "INVALID_VM_STATE",
             Self::ConfigError(_) => "CONFIG_ERROR",
-            Self::HttpError(_) => "HTTP_ERROR",
-            Self::Timeout(_) => "TIMEOUT",
+            Self::HttpError { .. } => "HTTP_ERROR",
+            Self::Timeout { .. } => "TIMEOUT",
             Self::NotFound(_) => "NOT_FOUND",
             Self::InvalidRequest(_) => "INVALID_REQUEST",
             Self::BadRequest(_) => "BAD_REQUEST",
+            Self::Api { .. } => "API_ERROR",
             Self::ValidationError(_) => "VALIDATION_ERROR",
             Self::Conflict(_) => "CONFLICT",
+            Self::PayloadTooLarge(_) => "PAYLOAD_TOO_LARGE",
             Self::ExternalServiceError { .. } => "EXTERNAL_SERVICE_ERROR",
             Self::InternalError(_) => "INTERNAL_ERROR",
             Self::CacheError(_) => "CACHE_ERROR",
@@ -143,10 +271,11 @@ impl Error {
         }
     }
 
-    /// Converts the error into an `ErrorResponse`.
+    /// Converts the error into an `ErrorResponse`, keeping its request id.
     #[must_use]
     pub fn into_error_response(self) -> ErrorResponse {
-        self.into_error_response_with_id(None)
+        let request_id = self.request_id().map(str::to_string);
+        self.into_error_response_with_id(request_id)
     }
 
     /// Converts the error into an `ErrorResponse` with a request ID.
@@ -162,6 +291,233 @@ impl Error {
         }
     }
 
+    /// Build an [`Error::HttpError`] for a response that came back with `status`.
+    #[must_use]
+    pub fn http(status: StatusCode, message: impl Into<String>) -> Self {
+        Self::HttpError {
+            status: Some(status),
+            message: message.into(),
+            request_id: None,
+        }
+    }
+
+    /// Build a timeout error.
+    #[must_use]
+    pub const fn timeout(kind: TimeoutKind, elapsed: Duration) -> Self {
+        Self::Timeout {
+            kind,
+            elapsed,
+            request_id: None,
+        }
+    }
+
+    /// Record how long the failed operation ran, if this is a timeout.
+    #[must_use]
+    pub fn with_elapsed(self, elapsed: Duration) -> Self {
+        match self {
+            Self::Timeout {
+                kind, request_id, ..
+            } => Self::Timeout {
+                kind,
+                elapsed,
+                request_id,
+            },
+            other => other,
+        }
+    }
+
+    /// Returns the timeout kind when this is an [`Error::Timeout`].
+    #[must_use]
+    pub const fn timeout_kind(&self) -> Option<TimeoutKind> {
+        match self {
+            Self::Timeout { kind, .. } => Some(*kind),
+            _ => None,
+        }
+    }
+
+    /// Upgrade a generic [`Error::HttpError`] to [`Error::Api`] when `body`
+    /// is a structured Triton error.
+    ///
+    /// Typed variants chosen by a service's error mapper, such as
+    /// [`Error::NotFound`] or [`Error::Conflict`], and bodies that are not a
+    /// [`TritonApiError`], are returned unchanged.
+    #[must_use]
+    pub fn with_api_body(self, status: StatusCode, body: &str) -> Self {
+        match self {
+            Self::HttpError { .. } => match TritonApiError::parse(body) {
+                Some(api) => api.into_error(status),
+                None => self,
+            },
+            other => other,
+        }
+    }
+
+    /// Append the tags of `context` to the error message.
+    ///
+    /// A [`REQUEST_ID_TAG`] tag is also stored in the `request_id` field of
+    /// the variants that have one. Timeouts carry no message, so they only
+    /// keep the request id. Everything is unchanged when the context is empty.
+    #[must_use]
+    pub fn with_context(self, context: &RequestContext) -> Self {
+        if context.is_empty() {
+            return self;
+        }
+        let tag = |message: String| format!("{message} [{context}]");
+        let tagged = match self {
+            Self::ServiceUnavailable(m) => Self::ServiceUnavailable(tag(m)),
+            Self::DiscoveryFailed(m) => Self::DiscoveryFailed(tag(m)),
+            Self::SapiParseError(m) => Self::SapiParseError(tag(m)),
+            Self::InvalidUuid(m) => Self::InvalidUuid(tag(m)),
+            Self::InvalidNetwork(m) => Self::InvalidNetwork(tag(m)),
+            Self::InvalidVmState(m) => Self::InvalidVmState(tag(m)),
+            Self::ConfigError(m) => Self::ConfigError(tag(m)),
+            Self::HttpError {
+                status,
+                message,
+                request_id,
+            } => Self::HttpError {
+                status,
+                message: tag(message),
+                request_id,
+            },
+            Self::NotFound(m) => Self::NotFound(tag(m)),
+            Self::InvalidRequest(m) => Self::InvalidRequest(tag(m)),
+            Self::BadRequest(m) => Self::BadRequest(tag(m)),
+            Self::Api {
+                status,
+                code,
+                message,
+                request_id,
+            } => Self::Api {
+                status,
+                code,
+                message: tag(message),
+                request_id,
+            },
+            Self::ValidationError(m) => Self::ValidationError(tag(m)),
+            Self::Conflict(m) => Self::Conflict(tag(m)),
+            Self::PayloadTooLarge(m) => Self::PayloadTooLarge(tag(m)),
+            Self::ExternalServiceError { service, message } => Self::ExternalServiceError {
+                service,
+                message: tag(message),
+            },
+            Self::InternalError(m) => Self::InternalError(tag(m)),
+            Self::CacheError(m) => Self::CacheError(tag(m)),
+            Self::InvalidEndpoint(m) => Self::InvalidEndpoint(tag(m)),
+            Self::NotImplemented(m) => Self::NotImplemented(tag(m)),
+            timeout @ Self::Timeout { .. } => timeout,
+        };
+        match context.tag(REQUEST_ID_TAG) {
+            Some(id) => tagged.set_request_id(id),
+            None => tagged,
+        }
+    }
+
+    /// Record `request_id` on the error: in the `request_id` field of
+    /// [`Error::HttpError`], [`Error::Api`] and [`Error::Timeout`], and as a
+    /// `request_id` tag in the message of every variant that has one.
+    ///
+    /// Used by clients that do not carry a [`RequestContext`].
+    #[must_use]
+    pub fn with_request_id(self, request_id: &str) -> Self {
+        self.with_context(&RequestContext::new().with_tag(REQUEST_ID_TAG, request_id))
+    }
+
+    fn set_request_id(self, id: &str) -> Self {
+        match self {
+            Self::HttpError {
+                status, message, ..
+            } => Self::HttpError {
+                status,
+                message,
+                request_id: Some(id.to_string()),
+            },
+            Self::Api {
+                status,
+                code,
+                message,
+                ..
+            } => Self::Api {
+                status,
+                code,
+                message,
+                request_id: Some(id.to_string()),
+            },
+            Self::Timeout { kind, elapsed, .. } => Self::Timeout {
+                kind,
+                elapsed,
+                request_id: Some(id.to_string()),
+            },
+            other => other,
+        }
+    }
+
+    /// Returns the id of the request that failed, when recorded.
+    ///
+    /// Only [`Error::HttpError`], [`Error::Api`] and [`Error::Timeout`] store
+    /// it; other variants carry it as a tag in their message.
+    #[must_use]
+    pub fn request_id(&self) -> Option<&str> {
+        match self {
+            Self::HttpError { request_id, .. }
+            | Self::Api { request_id, .. }
+            | Self::Timeout { request_id, .. } => request_id.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Returns the Triton error code when this is an [`Error::Api`].
+    #[must_use]
+    pub fn api_code(&self) -> Option<&str> {
+        match self {
+            Self::Api { code, .. } => Some(code),
+            _ => None,
+        }
+    }
+
+    /// Returns the HTTP status of the response that caused this error, when known.
+    ///
+    /// Only [`Error::HttpError`] and [`Error::Api`] carry a status; variants
+    /// such as [`Error::NotFound`] may also be raised without a response.
+    #[must_use]
+    pub fn status_code(&self) -> Option<StatusCode> {
+        match self {
+            Self::HttpError { status, .. } => *status,
+            Self::Api { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// Returns true for [`Error::NotFound`] and for errors from a `404` response.
+    ///
+    /// Covers services whose error mapper leaves a `404` as a generic error.
+    #[must_use]
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, Self::NotFound(_)) || self.status_code() == Some(StatusCode::NOT_FOUND)
+    }
+
+    /// Returns true for [`Error::Conflict`] and for errors from a `409` response.
+    #[must_use]
+    pub fn is_conflict(&self) -> bool {
+        matches!(self, Self::Conflict(_)) || self.status_code() == Some(StatusCode::CONFLICT)
+    }
+
+    /// Returns true if repeating the request may succeed.
+    ///
+    /// Timeouts, [`Error::ServiceUnavailable`], and HTTP errors with a 5xx
+    /// status are retryable; everything else needs the request or the
+    /// environment to change first.
+    #[must_use]
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Timeout { .. } | Self::ServiceUnavailable(_) => true,
+            Self::HttpError { .. } | Self::Api { .. } => self
+                .status_code()
+                .is_some_and(|status| status.is_server_error()),
+            _ => false,
+        }
+    }
+
     /// Returns true if this error should be logged as a serious error.
     #[must_use]
     pub const fn should_log(&self) -> bool {
@@ -175,12 +531,23 @@ impl Error {
 // Conversions from external error types
 impl From<reqwest::Error> for Error {
     fn from(err: reqwest::Error) -> Self {
+        // reqwest does not report elapsed time; callers fill it in with
+        // `with_elapsed` when they track it.
         if err.is_timeout() {
-            Self::Timeout(err.to_string())
+            let kind = if err.is_connect() {
+                TimeoutKind::Connect
+            } else {
+                TimeoutKind::Read
+            };
+            Self::timeout(kind, Duration::ZERO)
         } else if err.is_connect() {
             Self::ServiceUnavailable(err.to_string())
         } else {
-            Self::HttpError(err.to_string())
+            Self::HttpError {
+                status: err.status(),
+                message: err.to_string(),
+                request_id: None,
+            }
         }
     }
 }
@@ -244,10 +611,13 @@ mod tests {
             "CONFIG_ERROR"
         );
         assert_eq!(
-            Error::HttpError("test".to_string()).error_code(),
+            Error::http(StatusCode::IM_A_TEAPOT, "test").error_code(),
             "HTTP_ERROR"
         );
-        assert_eq!(Error::Timeout("test".to_string()).error_code(), "TIMEOUT");
+        assert_eq!(
+            Error::timeout(TimeoutKind::Read, Duration::ZERO).error_code(),
+            "TIMEOUT"
+        );
         assert_eq!(
             Error::NotFound("test".to_string()).error_code(),
             "NOT_FOUND"
@@ -265,6 +635,10 @@ mod tests {
             "VALIDATION_ERROR"
         );
         assert_eq!(Error::Conflict("test".to_string()).error_code(), "CONFLICT");
+        assert_eq!(
+            Error::PayloadTooLarge("test".to_string()).error_code(),
+            "PAYLOAD_TOO_LARGE"
+        );
         assert_eq!(
             Error::ExternalServiceError {
                 service: "test".to_string(),
@@ -304,6 +678,56 @@ mod tests {
             err.to_string(),
             "External service error: cnapi: connection failed"
         );
+
+        let err = Error::timeout(TimeoutKind::Connect, Duration::from_millis(1500));
+        assert_eq!(err.to_string(), "Connection timed out after 1.5s");
+        let err = Error::timeout(TimeoutKind::Deadline, Duration::from_secs(2));
+        assert_eq!(err.to_string(), "Request deadline timed out after 2s");
+    }
+
+    #[test]
+    fn test_with_elapsed_only_touches_timeouts() {
+        let err =
+            Error::timeout(TimeoutKind::Read, Duration::ZERO).with_elapsed(Duration::from_secs(3));
+        assert_eq!(
+            err,
+            Error::Timeout {
+                kind: TimeoutKind::Read,
+                elapsed: Duration::from_secs(3),
+                request_id: None,
+            }
+        );
+        assert_eq!(err.timeout_kind(), Some(TimeoutKind::Read));
+
+        let err = Error::NotFound("vm".to_string()).with_elapsed(Duration::from_secs(3));
+        assert_eq!(err, Error::NotFound("vm".to_string()));
+        assert_eq!(err.timeout_kind(), None);
+    }
+
+    #[test]
+    fn test_with_request_id_is_stored_and_tagged() {
+        let err =
+            Error::timeout(TimeoutKind::Read, Duration::from_secs(1)).with_request_id("req-1");
+        assert_eq!(err.request_id(), Some("req-1"));
+        assert_eq!(
+            err.to_string(),
+            "Response timed out after 1s [request_id=req-1]"
+        );
+
+        let err = Error::http(StatusCode::IM_A_TEAPOT, "teapot").with_request_id("req-2");
+        assert_eq!(err.request_id(), Some("req-2"));
+        assert_eq!(
+            err.to_string(),
+            "HTTP request failed: teapot [request_id=req-2]"
+        );
+        assert_eq!(
+            err.into_error_response().request_id.as_deref(),
+            Some("req-2")
+        );
+
+        let err = Error::NotFound("vm".to_string()).with_request_id("req-3");
+        assert_eq!(err.request_id(), None);
+        assert_eq!(err.to_string(), "Not found: vm [request_id=req-3]");
     }
 
     #[test]
@@ -406,4 +830,114 @@ mod tests {
         assert_eq!(err1, err2);
         assert_ne!(err1, err3);
     }
+
+    #[test]
+    fn test_triton_api_error_parse() {
+        assert!(TritonApiError::parse("not json").is_none());
+        assert!(TritonApiError::parse(r#"{"message":"no code"}"#).is_none());
+
+        let api = TritonApiError::parse(
+            r#"{"code":"InvalidParameters","message":"Invalid","errors":[{"field":"ram","code":"Missing"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            api.into_error(StatusCode::CONFLICT),
+            Error::Api {
+                status: StatusCode::CONFLICT,
+                code: "InvalidParameters".to_string(),
+                message: "Invalid; ram: Missing".to_string(),
+                request_id: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_with_api_body_keeps_specific_variants() {
+        let body = r#"{"code":"ResourceNotFound","message":"gone"}"#;
+        let err = Error::NotFound(body.to_string()).with_api_body(StatusCode::NOT_FOUND, body);
+        assert_eq!(err, Error::NotFound(body.to_string()));
+        assert!(err.is_not_found());
+        assert!(!err.is_conflict());
+
+        let err = Error::http(StatusCode::GONE, body).with_api_body(StatusCode::GONE, body);
+        assert_eq!(err.api_code(), Some("ResourceNotFound"));
+        assert_eq!(err.status_code(), Some(StatusCode::GONE));
+        assert_eq!(err.error_code(), "API_ERROR");
+
+        let err = Error::http(StatusCode::GONE, "gone").with_api_body(StatusCode::GONE, "gone");
+        assert_eq!(err.api_code(), None);
+        assert!(Error::http(StatusCode::CONFLICT, "taken").is_conflict());
+    }
+
+    #[test]
+    fn test_status_code() {
+        let err = Error::http(StatusCode::CONFLICT, "taken");
+        assert_eq!(err.status_code(), Some(StatusCode::CONFLICT));
+
+        let err = Error::HttpError {
+            status: None,
+            message: "body read failed".to_string(),
+            request_id: None,
+        };
+        assert_eq!(err.status_code(), None);
+
+        let err = Error::Api {
+            status: StatusCode::UNPROCESSABLE_ENTITY,
+            code: "InvalidParameters".to_string(),
+            message: "bad".to_string(),
+            request_id: None,
+        };
+        assert_eq!(err.status_code(), Some(StatusCode::UNPROCESSABLE_ENTITY));
+
+        assert_eq!(Error::NotFound("vm".to_string()).status_code(), None);
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        let api = |status: u16| Error::Api {
+            status: StatusCode::from_u16(status).unwrap(),
+            code: "InternalError".to_string(),
+            message: "oops".to_string(),
+            request_id: None,
+        };
+        let retryable = [
+            Error::timeout(TimeoutKind::Connect, Duration::ZERO),
+            Error::timeout(TimeoutKind::Read, Duration::ZERO),
+            Error::timeout(TimeoutKind::Deadline, Duration::ZERO),
+            Error::ServiceUnavailable("down".to_string()),
+            Error::http(StatusCode::INTERNAL_SERVER_ERROR, "boom"),
+            Error::http(StatusCode::BAD_GATEWAY, "proxy"),
+            api(500),
+        ];
+        for err in &retryable {
+            assert!(err.is_retryable(), "{err:?} should be retryable");
+        }
+
+        let permanent = [
+            Error::http(StatusCode::IM_A_TEAPOT, "teapot"),
+            Error::HttpError {
+                status: None,
+                message: "redirect loop".to_string(),
+                request_id: None,
+            },
+            api(409),
+            Error::NotFound("vm".to_string()),
+            Error::BadRequest("bad".to_string()),
+            Error::InvalidRequest("bad".to_string()),
+            Error::ValidationError("bad".to_string()),
+            Error::Conflict("taken".to_string()),
+            Error::PayloadTooLarge("big".to_string()),
+            Error::ConfigError("missing".to_string()),
+            Error::DiscoveryFailed("none".to_string()),
+            Error::InvalidUuid("x".to_string()),
+            Error::InternalError("bug".to_string()),
+            Error::ExternalServiceError {
+                service: "vmapi".to_string(),
+                message: "job failed".to_string(),
+            },
+        ];
+        for err in &permanent {
+            assert!(!err.is_retryable(), "{err:?} should not be retryable");
+        }
+    }
 }
@@ -0,0 +1,167 @@
+//! Active endpoint health checks.
+//!
+//! [`ServiceEndpoint::is_healthy`] only looks at when an endpoint was last
+//! seen. [`health_check`] probes the endpoint as described by its
+//! [`HealthCheckConfig`], and [`EndpointList::refresh_health`] marks every
+//! endpoint that passes as seen, so time-based selection follows real health.
+
+use crate::types::{EndpointList, HealthCheckConfig, ServiceEndpoint, TransportType};
+use futures::future::join_all;
+use reqwest::Client;
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+
+/// Probe `endpoint` once.
+///
+/// HTTP and HTTPS endpoints get a `GET` to the configured `path` (`/` when
+/// unset) and are healthy on a 2xx response. Other transports are healthy when
+/// a TCP connection can be opened. The probe is bounded by the configured
+/// `timeout`; endpoints without a [`HealthCheckConfig`] use its defaults.
+pub async fn health_check(endpoint: &ServiceEndpoint, client: &Client) -> bool {
+    let config = endpoint.health_check.clone().unwrap_or_default();
+
+    match endpoint.transport {
+        TransportType::Http | TransportType::Https => {
+            let url = health_url(endpoint, &config);
+            client
+                .get(url)
+                .timeout(config.timeout)
+                .send()
+                .await
+                .is_ok_and(|response| response.status().is_success())
+        }
+        TransportType::Tcp | TransportType::Grpc => {
+            matches!(
+                timeout(config.timeout, TcpStream::connect(endpoint.address)).await,
+                Ok(Ok(_))
+            )
+        }
+    }
+}
+
+fn health_url(endpoint: &ServiceEndpoint, config: &HealthCheckConfig) -> String {
+    let scheme = if endpoint.transport == TransportType::Https {
+        "https"
+    } else {
+        "http"
+    };
+    let path = config.path.as_deref().unwrap_or("/");
+    let separator = if path.starts_with('/') { "" } else { "/" };
+    format!("{scheme}://{}{separator}{path}", endpoint.address)
+}
+
+impl EndpointList {
+    /// Run [`health_check`] against every endpoint with checks enabled and
+    /// mark those that pass as seen.
+    ///
+    /// Checks run concurrently. Endpoints without an enabled
+    /// [`HealthCheckConfig`] are left untouched. Returns how many passed;
+    /// call this on the config's `interval` to keep health current.
+    pub async fn refresh_health(&mut self, client: &Client) -> usize {
+        let checked: Vec<&mut ServiceEndpoint> = self
+            .endpoints
+            .iter_mut()
+            .filter(|ep| ep.health_check.as_ref().is_some_and(|hc| hc.enabled))
+            .collect();
+        let results = join_all(checked.iter().map(|ep| health_check(ep, client))).await;
+
+        let mut passed = 0;
+        for (endpoint, healthy) in checked.into_iter().zip(results) {
+            if healthy {
+                endpoint.touch();
+                passed += 1;
+            }
+        }
+        passed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn http_endpoint(server: &MockServer) -> ServiceEndpoint {
+        let mut endpoint =
+            ServiceEndpoint::new("ep-1".to_string(), "vmapi".to_string(), *server.address());
+        endpoint.transport = TransportType::Http;
+        endpoint.health_check = Some(HealthCheckConfig {
+            enabled: true,
+            timeout: Duration::from_secs(1),
+            path: Some("ping".to_string()),
+            ..HealthCheckConfig::default()
+        });
+        endpoint
+    }
+
+    #[tokio::test]
+    async fn health_flips_with_status() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/ping"))
+            .respond_with(ResponseTemplate::new(200))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/ping"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+
+        let client = Client::new();
+        let endpoint = http_endpoint(&server);
+        assert!(health_check(&endpoint, &client).await);
+        assert!(!health_check(&endpoint, &client).await);
+    }
+
+    #[tokio::test]
+    async fn refresh_health_touches_passing_endpoints() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/ping"))
+            .respond_with(ResponseTemplate::new(200))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/ping"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+
+        let stale = Duration::from_secs(60);
+        let mut endpoint = http_endpoint(&server);
+        endpoint.last_seen = Instant::now()
+            .checked_sub(Duration::from_secs(120))
+            .unwrap();
+        let mut unchecked = endpoint.clone();
+        unchecked.health_check = None;
+        let mut list = EndpointList::from_endpoints(vec![endpoint, unchecked]);
+        let client = Client::new();
+
+        assert_eq!(list.refresh_health(&client).await, 1);
+        assert!(list.endpoints[0].is_healthy(stale));
+        assert!(!list.endpoints[1].is_healthy(stale));
+
+        list.endpoints[0].last_seen = Instant::now()
+            .checked_sub(Duration::from_secs(120))
+            .unwrap();
+        assert_eq!(list.refresh_health(&client).await, 0);
+        assert!(!list.endpoints[0].is_healthy(stale));
+    }
+
+    #[tokio::test]
+    async fn tcp_endpoints_need_a_listener() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let endpoint = ServiceEndpoint::new("ep-1".to_string(), "ufds".to_string(), addr);
+        let client = Client::new();
+
+        assert!(health_check(&endpoint, &client).await);
+        drop(listener);
+        assert!(!health_check(&endpoint, &client).await);
+    }
+}
@@ -11,21 +11,50 @@
 //! - [`uuid`] - Strongly-typed UUID wrappers for Triton resources
 //! - [`types`] - Core Triton domain types (VMs, networks, packages, etc.)
 //! - [`config`] - Configuration structures for Triton clients
+//! - [`ratio`] - Range-checked ratio newtype for requests
+//! - [`cache`] - Capacity-bounded LRU cache for per-resource caching
 //! - [`client`] - HTTP client utilities and retry logic
+//! - [`context`] - Request-level context tags for observability
+//! - `cassette` - Record/replay of HTTP interactions (`record-replay` feature)
+//! - [`clock`] - Injectable time source for time-based decisions
+//! - [`concurrency`] - Bounded-parallelism helpers for batch operations
+//! - [`diff`] - Keyed snapshot diffing for watchers and reconcilers
+//! - [`health`] - Active endpoint health checks
 //! - [`services`] - Service discovery and integration patterns
+//! - [`selection`] - Endpoint selection with per-endpoint quarantine
+//! - [`shutdown`] - Cooperative shutdown for background tasks
+//! - [`preflight`] - Fail-fast connectivity and credential checks
+//! - [`path`] - Percent-encoding for dynamic URL path segments
+//! - [`wait`] - Polling until a resource reaches a desired state
 
 #![deny(missing_docs)]
 #![deny(clippy::all)]
 #![warn(clippy::pedantic)]
 #![allow(clippy::module_name_repetitions)]
 
+pub mod cache;
+#[cfg(feature = "record-replay")]
+pub mod cassette;
 pub mod client;
+pub mod clock;
+pub mod concurrency;
 pub mod config;
+pub mod context;
+pub mod diff;
 pub mod error;
+pub mod health;
+pub mod metrics;
+mod otel;
+pub mod path;
+pub mod preflight;
 pub mod query;
+pub mod ratio;
+pub mod selection;
 pub mod services;
+pub mod shutdown;
 pub mod types;
 pub mod uuid;
+pub mod wait;
 
 // Re-export commonly used types
-pub use error::{Error, Result};
+pub use error::{Error, FieldError, Result, TimeoutKind, TritonApiError};
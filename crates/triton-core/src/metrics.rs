@@ -0,0 +1,264 @@
+//! Lock-free request metrics for service clients.
+//!
+//! [`MetricsObserver`] is a [`RequestObserver`] that counts calls and errors
+//! and records a latency histogram per service using atomics only. Attach it
+//! to any number of clients with
+//! [`ServiceClientBuilder::with_observer`](crate::client::ServiceClientBuilder::with_observer)
+//! and read a [`MetricsSnapshot`] to export the totals, for example into an
+//! existing Prometheus registry.
+
+use crate::client::{RequestEvent, RequestObserver};
+use crate::types::TritonService;
+use crate::Error;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Upper bounds of the latency histogram buckets, matching the Prometheus
+/// client defaults. Slower calls land in the implicit `+Inf` bucket.
+pub const LATENCY_BUCKETS: [Duration; 11] = [
+    Duration::from_millis(5),
+    Duration::from_millis(10),
+    Duration::from_millis(25),
+    Duration::from_millis(50),
+    Duration::from_millis(100),
+    Duration::from_millis(250),
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+    Duration::from_millis(2500),
+    Duration::from_secs(5),
+    Duration::from_secs(10),
+];
+
+/// Error codes (see [`Error::error_code`]) counted individually; anything
+/// else is counted as `OTHER`.
+const ERROR_CODES: [&str; 21] = [
+    "SERVICE_UNAVAILABLE",
+    "DISCOVERY_FAILED",
+    "SAPI_PARSE_ERROR",
+    "INVALID_UUID",
+    "INVALID_NETWORK",
+    "INVALID_VM_STATE",
+    "CONFIG_ERROR",
+    "HTTP_ERROR",
+    "TIMEOUT",
+    "NOT_FOUND",
+    "INVALID_REQUEST",
+    "BAD_REQUEST",
+    "API_ERROR",
+    "VALIDATION_ERROR",
+    "CONFLICT",
+    "PAYLOAD_TOO_LARGE",
+    "EXTERNAL_SERVICE_ERROR",
+    "INTERNAL_ERROR",
+    "CACHE_ERROR",
+    "INVALID_ENDPOINT",
+    "NOT_IMPLEMENTED",
+];
+
+/// Counters for one service.
+#[derive(Debug, Default)]
+struct ServiceCounters {
+    requests: AtomicU64,
+    errors: [AtomicU64; ERROR_CODES.len() + 1],
+    buckets: [AtomicU64; LATENCY_BUCKETS.len() + 1],
+    latency_micros: AtomicU64,
+}
+
+impl ServiceCounters {
+    fn record(&self, elapsed: Duration, error: Option<&Error>) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| elapsed <= *bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        let micros = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+        self.latency_micros.fetch_add(micros, Ordering::Relaxed);
+
+        if let Some(error) = error {
+            let code = error.error_code();
+            let index = ERROR_CODES
+                .iter()
+                .position(|known| *known == code)
+                .unwrap_or(ERROR_CODES.len());
+            self.errors[index].fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn snapshot(&self, service: TritonService) -> ServiceMetrics {
+        let errors_by_code: BTreeMap<&'static str, u64> = ERROR_CODES
+            .iter()
+            .copied()
+            .chain(std::iter::once("OTHER"))
+            .zip(&self.errors)
+            .map(|(code, count)| (code, count.load(Ordering::Relaxed)))
+            .filter(|(_, count)| *count > 0)
+            .collect();
+
+        let mut cumulative = 0;
+        let buckets = LATENCY_BUCKETS
+            .iter()
+            .zip(&self.buckets)
+            .map(|(bound, count)| {
+                cumulative += count.load(Ordering::Relaxed);
+                (*bound, cumulative)
+            })
+            .collect();
+        let overflow = self.buckets[LATENCY_BUCKETS.len()].load(Ordering::Relaxed);
+
+        ServiceMetrics {
+            service,
+            requests: self.requests.load(Ordering::Relaxed),
+            errors: errors_by_code.values().sum(),
+            errors_by_code,
+            latency: LatencyHistogram {
+                buckets,
+                count: cumulative + overflow,
+                sum: Duration::from_micros(self.latency_micros.load(Ordering::Relaxed)),
+            },
+        }
+    }
+}
+
+/// Observer that aggregates per-service request metrics.
+///
+/// Each finished call, retries included, counts as one request; a call that
+/// ends in an error also counts under its [`Error::error_code`].
+#[derive(Debug)]
+pub struct MetricsObserver {
+    services: Vec<ServiceCounters>,
+}
+
+impl MetricsObserver {
+    /// Create an observer with every counter at zero.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            services: TritonService::all()
+                .iter()
+                .map(|_| ServiceCounters::default())
+                .collect(),
+        }
+    }
+
+    /// Copy the current counters of every service that has seen a request.
+    #[must_use]
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let services = TritonService::all()
+            .iter()
+            .zip(&self.services)
+            .filter(|(_, counters)| counters.requests.load(Ordering::Relaxed) > 0)
+            .map(|(service, counters)| counters.snapshot(*service))
+            .collect();
+        MetricsSnapshot { services }
+    }
+
+    fn counters(&self, service: TritonService) -> Option<&ServiceCounters> {
+        let index = TritonService::all().iter().position(|s| *s == service)?;
+        self.services.get(index)
+    }
+}
+
+impl Default for MetricsObserver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RequestObserver for MetricsObserver {
+    fn on_request(&self, event: &RequestEvent<'_>) {
+        if let Some(counters) = self.counters(event.service) {
+            counters.record(event.elapsed, event.error);
+        }
+    }
+}
+
+/// Point-in-time copy of the metrics gathered by a [`MetricsObserver`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MetricsSnapshot {
+    /// Metrics for each service that has seen at least one request
+    pub services: Vec<ServiceMetrics>,
+}
+
+impl MetricsSnapshot {
+    /// Metrics for `service`, if it has seen a request.
+    #[must_use]
+    pub fn service(&self, service: TritonService) -> Option<&ServiceMetrics> {
+        self.services
+            .iter()
+            .find(|metrics| metrics.service == service)
+    }
+
+    /// Requests across every service.
+    #[must_use]
+    pub fn total_requests(&self) -> u64 {
+        self.services.iter().map(|metrics| metrics.requests).sum()
+    }
+
+    /// Errors across every service.
+    #[must_use]
+    pub fn total_errors(&self) -> u64 {
+        self.services.iter().map(|metrics| metrics.errors).sum()
+    }
+}
+
+/// Request metrics for one service.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceMetrics {
+    /// Service the calls went to
+    pub service: TritonService,
+    /// Finished calls
+    pub requests: u64,
+    /// Calls that ended in an error
+    pub errors: u64,
+    /// Failed calls by [`Error::error_code`]; codes without failures are omitted
+    pub errors_by_code: BTreeMap<&'static str, u64>,
+    /// Call latency, retries included
+    pub latency: LatencyHistogram,
+}
+
+/// Cumulative latency histogram in the Prometheus layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    /// `(upper bound, calls at or below it)` for each of [`LATENCY_BUCKETS`]
+    pub buckets: Vec<(Duration, u64)>,
+    /// Every recorded call, i.e. the `+Inf` bucket
+    pub count: u64,
+    /// Total time across every recorded call
+    pub sum: Duration,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_buckets_are_cumulative() {
+        let counters = ServiceCounters::default();
+        counters.record(Duration::from_millis(3), None);
+        counters.record(Duration::from_millis(40), None);
+        counters.record(Duration::from_secs(30), None);
+
+        let latency = counters.snapshot(TritonService::Vmapi).latency;
+        assert_eq!(latency.buckets[0], (Duration::from_millis(5), 1));
+        assert_eq!(latency.buckets[3], (Duration::from_millis(50), 2));
+        assert_eq!(latency.buckets.last(), Some(&(Duration::from_secs(10), 2)));
+        assert_eq!(latency.count, 3);
+        assert_eq!(latency.sum, Duration::from_millis(30_043));
+    }
+
+    #[test]
+    fn errors_are_counted_by_code() {
+        let counters = ServiceCounters::default();
+        counters.record(Duration::ZERO, Some(&Error::NotFound("vm".into())));
+        counters.record(Duration::ZERO, Some(&Error::NotFound("vm".into())));
+        counters.record(Duration::ZERO, Some(&Error::NotImplemented("x".into())));
+
+        let metrics = counters.snapshot(TritonService::Papi);
+        assert_eq!(metrics.errors, 3);
+        assert_eq!(metrics.errors_by_code.get("NOT_FOUND"), Some(&2));
+        assert_eq!(metrics.errors_by_code.get("NOT_IMPLEMENTED"), Some(&1));
+        assert!(!metrics.errors_by_code.contains_key("OTHER"));
+    }
+}
@@ -0,0 +1,104 @@
+//! OpenTelemetry span and `traceparent` propagation for service calls.
+//!
+//! With the `otel` feature every [`ServiceClient`](crate::client::ServiceClient)
+//! call opens a client span named `triton.{service}.{method}` under the current
+//! OpenTelemetry context and sends its W3C `traceparent`/`tracestate` headers.
+//! Spans go to the globally installed tracer provider. Without the feature the
+//! same calls compile to no-ops.
+
+use crate::error::Error;
+use crate::types::TritonService;
+use reqwest::{Method, RequestBuilder, StatusCode};
+
+#[cfg(feature = "otel")]
+use opentelemetry::trace::{SpanKind, Status, TraceContextExt, Tracer};
+#[cfg(feature = "otel")]
+use opentelemetry::{global, Context, KeyValue};
+
+/// Span covering one call, retries included.
+pub(crate) struct RequestSpan {
+    #[cfg(feature = "otel")]
+    cx: Context,
+}
+
+#[cfg(feature = "otel")]
+impl RequestSpan {
+    /// Open a client span for `method path` as a child of the current context.
+    pub(crate) fn start(service: TritonService, method: &Method, path: &str) -> Self {
+        let tracer = global::tracer("triton-core");
+        let span = tracer
+            .span_builder(format!("triton.{}.{method}", service.name()))
+            .with_kind(SpanKind::Client)
+            .with_attributes(vec![
+                KeyValue::new("triton.service", service.name()),
+                KeyValue::new("http.request.method", method.to_string()),
+                KeyValue::new("url.path", path.to_string()),
+            ])
+            .start(&tracer);
+        Self {
+            cx: Context::current_with_span(span),
+        }
+    }
+
+    /// Add the span's `traceparent` (and any `tracestate`) headers to `request`.
+    pub(crate) fn inject(&self, request: RequestBuilder) -> RequestBuilder {
+        let span = self.cx.span();
+        let span_context = span.span_context();
+        if !span_context.is_valid() {
+            return request;
+        }
+
+        let traceparent = format!(
+            "00-{}-{}-{:02x}",
+            span_context.trace_id(),
+            span_context.span_id(),
+            span_context.trace_flags().to_u8()
+        );
+        let request = request.header("traceparent", traceparent);
+        let tracestate = span_context.trace_state().header();
+        if tracestate.is_empty() {
+            request
+        } else {
+            request.header("tracestate", tracestate)
+        }
+    }
+
+    /// Record the outcome of the call and end the span.
+    pub(crate) fn finish(self, outcome: Result<StatusCode, &Error>, attempts: u32) {
+        let span = self.cx.span();
+        let status = match outcome {
+            Ok(status) => Some(status),
+            Err(error) => error.status_code(),
+        };
+        if let Some(status) = status {
+            span.set_attribute(KeyValue::new(
+                "http.response.status_code",
+                i64::from(status.as_u16()),
+            ));
+        }
+        span.set_attribute(KeyValue::new("triton.attempts", i64::from(attempts)));
+        match outcome {
+            Ok(_) => span.set_status(Status::Ok),
+            Err(error) => {
+                span.set_attribute(KeyValue::new("error.type", error.error_code()));
+                span.set_status(Status::error(error.to_string()));
+            }
+        }
+        span.end();
+    }
+}
+
+#[cfg(not(feature = "otel"))]
+impl RequestSpan {
+    pub(crate) fn start(_service: TritonService, _method: &Method, _path: &str) -> Self {
+        Self {}
+    }
+
+    #[allow(clippy::unused_self)]
+    pub(crate) fn inject(&self, request: RequestBuilder) -> RequestBuilder {
+        request
+    }
+
+    #[allow(clippy::needless_pass_by_value, clippy::unused_self)]
+    pub(crate) fn finish(self, _outcome: Result<StatusCode, &Error>, _attempts: u32) {}
+}
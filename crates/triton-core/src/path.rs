@@ -0,0 +1,77 @@
+//! Helpers for building request paths from untrusted values.
+//!
+//! Every dynamic path segment (UUIDs, MAC addresses, snapshot names, and so
+//! on) goes through [`encode_path_segment`] so a value can never add extra
+//! segments or climb out of its parent resource.
+
+use crate::error::Error;
+use std::fmt::{Display, Write};
+
+/// Percent-encode a value for use as a single URL path segment.
+///
+/// RFC 3986 unreserved characters plus `:` and `@` are kept as-is; every
+/// other byte of the UTF-8 encoding becomes `%XX`, so `/`, `?`, `#`, and
+/// spaces cannot change the shape of the URL.
+///
+/// # Errors
+///
+/// Returns [`Error::InvalidRequest`] for an empty segment or one that is
+/// `.` or `..`. URL parsers resolve those as dot segments even when encoded,
+/// so they cannot be sent safely.
+pub fn encode_path_segment(segment: impl Display) -> crate::Result<String> {
+    let raw = segment.to_string();
+    if raw.is_empty() || raw == "." || raw == ".." {
+        return Err(Error::InvalidRequest(format!(
+            "Invalid path segment `{raw}`"
+        )));
+    }
+
+    let mut encoded = String::with_capacity(raw.len());
+    for byte in raw.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~' | b':' | b'@') {
+            encoded.push(char::from(byte));
+        } else {
+            let _ = write!(encoded, "%{byte:02X}");
+        }
+    }
+    Ok(encoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leaves_safe_values_untouched() {
+        assert_eq!(
+            encode_path_segment("0c9a8d4e-1f3b-4d0e-9a36-2b9b6f0d1e22").unwrap(),
+            "0c9a8d4e-1f3b-4d0e-9a36-2b9b6f0d1e22"
+        );
+        assert_eq!(
+            encode_path_segment("90:b8:d0:1a:2b:3c").unwrap(),
+            "90:b8:d0:1a:2b:3c"
+        );
+        assert_eq!(encode_path_segment("app.role").unwrap(), "app.role");
+    }
+
+    #[test]
+    fn encodes_separators_and_spaces() {
+        assert_eq!(
+            encode_path_segment("nightly backup/2024").unwrap(),
+            "nightly%20backup%2F2024"
+        );
+        assert_eq!(encode_path_segment("a?b#c%d").unwrap(), "a%3Fb%23c%25d");
+        assert_eq!(encode_path_segment("../etc").unwrap(), "..%2Fetc");
+        assert_eq!(encode_path_segment("café").unwrap(), "caf%C3%A9");
+    }
+
+    #[test]
+    fn rejects_dot_and_empty_segments() {
+        for segment in ["", ".", ".."] {
+            assert!(matches!(
+                encode_path_segment(segment),
+                Err(Error::InvalidRequest(_))
+            ));
+        }
+    }
+}
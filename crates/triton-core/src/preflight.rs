@@ -0,0 +1,186 @@
+//! Pre-flight connectivity and authentication checks.
+//!
+//! Long-running orchestration should fail fast when a service is down or the
+//! configured credentials are rejected. [`TritonClients`] collects the clients
+//! an application has built and probes each of them, gathering every outcome
+//! into a [`PreflightReport`] instead of stopping at the first failure.
+
+use crate::concurrency::run_bounded;
+use crate::types::TritonService;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Outcome of probing a single service.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreflightResult {
+    /// Whether the service answered its health check.
+    pub reachable: bool,
+    /// Whether an authenticated read was accepted.
+    pub authorized: bool,
+    /// Round-trip time of the health check, when it completed.
+    pub latency: Option<Duration>,
+    /// Description of the failure, if any.
+    pub error: Option<String>,
+}
+
+impl PreflightResult {
+    /// Result for a service that answered and accepted our credentials.
+    #[must_use]
+    pub const fn ready(latency: Duration) -> Self {
+        Self {
+            reachable: true,
+            authorized: true,
+            latency: Some(latency),
+            error: None,
+        }
+    }
+
+    /// Result for a service that could not be reached.
+    #[must_use]
+    pub fn unreachable(error: impl Into<String>) -> Self {
+        Self {
+            reachable: false,
+            authorized: false,
+            latency: None,
+            error: Some(error.into()),
+        }
+    }
+
+    /// Result for a service that answered but rejected our credentials.
+    #[must_use]
+    pub fn unauthorized(latency: Duration, error: impl Into<String>) -> Self {
+        Self {
+            reachable: true,
+            authorized: false,
+            latency: Some(latency),
+            error: Some(error.into()),
+        }
+    }
+
+    /// Returns true when the service is reachable and authorized.
+    #[must_use]
+    pub const fn is_ok(&self) -> bool {
+        self.reachable && self.authorized
+    }
+}
+
+/// Aggregated pre-flight results keyed by service.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PreflightReport {
+    /// Result for every probed service.
+    pub per_service: HashMap<TritonService, PreflightResult>,
+}
+
+impl PreflightReport {
+    /// Returns true when every probed service is reachable and authorized.
+    #[must_use]
+    pub fn is_ready(&self) -> bool {
+        self.per_service.values().all(PreflightResult::is_ok)
+    }
+
+    /// Iterate over the services that failed their checks.
+    pub fn failures(&self) -> impl Iterator<Item = (&TritonService, &PreflightResult)> {
+        self.per_service
+            .iter()
+            .filter(|(_, result)| !result.is_ok())
+    }
+}
+
+/// Implemented by clients that can probe their service's health and credentials.
+#[async_trait]
+pub trait PreflightCheck: Send + Sync {
+    /// Service the client talks to.
+    fn service(&self) -> TritonService;
+
+    /// Ping the service and attempt one authenticated read.
+    async fn preflight(&self) -> PreflightResult;
+}
+
+/// Collection of built service clients that can be checked together.
+#[derive(Clone, Default)]
+pub struct TritonClients {
+    clients: Vec<Arc<dyn PreflightCheck>>,
+}
+
+impl TritonClients {
+    /// Create an empty collection.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a client to the collection.
+    #[must_use]
+    pub fn with_client(mut self, client: impl PreflightCheck + 'static) -> Self {
+        self.clients.push(Arc::new(client));
+        self
+    }
+
+    /// Add a shared client to the collection.
+    pub fn register(&mut self, client: Arc<dyn PreflightCheck>) {
+        self.clients.push(client);
+    }
+
+    /// Number of registered clients.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.clients.len()
+    }
+
+    /// Returns true when no clients are registered.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.clients.is_empty()
+    }
+
+    /// Probe every registered client concurrently and collect the results.
+    pub async fn preflight(&self) -> PreflightReport {
+        let results = run_bounded(&self.clients, self.clients.len(), |client| async move {
+            (client.service(), client.preflight().await)
+        })
+        .await;
+
+        PreflightReport {
+            per_service: results.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed(TritonService, PreflightResult);
+
+    #[async_trait]
+    impl PreflightCheck for Fixed {
+        fn service(&self) -> TritonService {
+            self.0
+        }
+
+        async fn preflight(&self) -> PreflightResult {
+            self.1.clone()
+        }
+    }
+
+    #[tokio::test]
+    async fn report_gathers_every_result() {
+        let clients = TritonClients::new()
+            .with_client(Fixed(
+                TritonService::Vmapi,
+                PreflightResult::ready(Duration::from_millis(3)),
+            ))
+            .with_client(Fixed(
+                TritonService::Papi,
+                PreflightResult::unreachable("connection refused"),
+            ));
+
+        let report = clients.preflight().await;
+        assert_eq!(report.per_service.len(), 2);
+        assert!(!report.is_ready());
+        let failed: Vec<_> = report.failures().map(|(service, _)| *service).collect();
+        assert_eq!(failed, vec![TritonService::Papi]);
+    }
+}
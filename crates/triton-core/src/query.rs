@@ -3,12 +3,17 @@
 //! This module provides a lightweight helper for constructing URL query pairs
 //! from optional values, reducing boilerplate in client crates.
 
+use std::borrow::Cow;
 use std::fmt::Display;
 
+/// A single query parameter. Keys are usually static but may be built at
+/// runtime, e.g. VMAPI's `tag.<key>` filters.
+pub type QueryPair = (Cow<'static, str>, String);
+
 /// Builder for assembling query parameter pairs.
 #[derive(Debug, Default, Clone)]
 pub struct QueryParams {
-    pairs: Vec<(&'static str, String)>,
+    pairs: Vec<QueryPair>,
 }
 
 impl QueryParams {
@@ -19,36 +24,81 @@ impl QueryParams {
     }
 
     /// Append a key/value pair when the value is present.
-    pub fn push_opt<T>(&mut self, key: &'static str, value: Option<T>)
+    pub fn push_opt<T>(&mut self, key: impl Into<Cow<'static, str>>, value: Option<T>)
     where
         T: ToString,
     {
         if let Some(value) = value {
-            self.pairs.push((key, value.to_string()));
+            self.pairs.push((key.into(), value.to_string()));
         }
     }
 
     /// Append using a mapping function when the value is present.
-    pub fn push_opt_with<T, F>(&mut self, key: &'static str, value: Option<T>, mut map: F)
-    where
+    pub fn push_opt_with<T, F>(
+        &mut self,
+        key: impl Into<Cow<'static, str>>,
+        value: Option<T>,
+        mut map: F,
+    ) where
         F: FnMut(T) -> String,
     {
         if let Some(value) = value {
-            self.pairs.push((key, map(value)));
+            self.pairs.push((key.into(), map(value)));
         }
     }
 
     /// Append a required key/value pair.
-    pub fn push<T>(&mut self, key: &'static str, value: T)
+    pub fn push<T>(&mut self, key: impl Into<Cow<'static, str>>, value: T)
     where
         T: Display,
     {
-        self.pairs.push((key, value.to_string()));
+        self.pairs.push((key.into(), value.to_string()));
+    }
+
+    /// Append one pair per value, repeating the key.
+    ///
+    /// For filters such as NAPI's `owner_uuids[]=a&owner_uuids[]=b`; pass the
+    /// key exactly as the API expects it, brackets included. Nothing is
+    /// appended when `values` is empty.
+    pub fn push_all<I>(&mut self, key: impl Into<Cow<'static, str>>, values: I)
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        let key = key.into();
+        self.pairs.extend(
+            values
+                .into_iter()
+                .map(|value| (key.clone(), value.to_string())),
+        );
+    }
+
+    /// Append a single pair whose value is the JSON array of `values` as
+    /// strings, e.g. `uuids=["a","b"]`, for APIs that parse the parameter as
+    /// JSON. Nothing is appended when `values` is empty.
+    pub fn push_json_array<I>(&mut self, key: impl Into<Cow<'static, str>>, values: I)
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        let values: Vec<serde_json::Value> = values
+            .into_iter()
+            .map(|value| serde_json::Value::String(value.to_string()))
+            .collect();
+        if !values.is_empty() {
+            self.pairs
+                .push((key.into(), serde_json::Value::Array(values).to_string()));
+        }
+    }
+
+    /// Append caller-supplied pairs after the typed ones; see [`append_extra`].
+    pub fn push_extra(&mut self, extra: &[(String, String)]) {
+        append_extra(&mut self.pairs, extra);
     }
 
     /// Return the collected key/value pairs.
     #[must_use]
-    pub fn into_pairs(self) -> Vec<(&'static str, String)> {
+    pub fn into_pairs(self) -> Vec<QueryPair> {
         self.pairs
     }
 
@@ -59,6 +109,22 @@ impl QueryParams {
     }
 }
 
+/// Append `extra` pairs verbatim, skipping keys already present in `pairs`.
+///
+/// Used for the `extra_params` escape hatch on list parameter structs: typed
+/// fields always win, so an extra pair can add a filter but never override
+/// or duplicate one the caller also set through a typed field. Repeated keys
+/// within `extra` itself are kept.
+pub fn append_extra(pairs: &mut Vec<QueryPair>, extra: &[(String, String)]) {
+    let typed: Vec<Cow<'static, str>> = pairs.iter().map(|(key, _)| key.clone()).collect();
+    pairs.extend(
+        extra
+            .iter()
+            .filter(|(key, _)| !typed.iter().any(|typed| typed == key))
+            .map(|(key, value)| (Cow::Owned(key.clone()), value.clone())),
+    );
+}
+
 #[cfg(test)]
 mod tests {
     use super::QueryParams;
@@ -70,10 +136,71 @@ mod tests {
         assert!(params.is_empty());
     }
 
+    #[test]
+    fn accepts_owned_keys() {
+        let mut params = QueryParams::new();
+        params.push("limit", 5);
+        params.push(format!("tag.{}", "env"), "prod");
+        assert_eq!(
+            params.into_pairs(),
+            vec![
+                ("limit".into(), "5".to_string()),
+                ("tag.env".into(), "prod".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn push_extra_appends_without_overriding_typed_keys() {
+        let mut params = QueryParams::new();
+        params.push("limit", 5);
+        params.push_extra(&[
+            ("limit".to_string(), "500".to_string()),
+            ("beta".to_string(), "1".to_string()),
+            ("beta".to_string(), "2".to_string()),
+        ]);
+        assert_eq!(
+            params.into_pairs(),
+            vec![
+                ("limit".into(), "5".to_string()),
+                ("beta".into(), "1".to_string()),
+                ("beta".into(), "2".to_string())
+            ]
+        );
+    }
+
     #[test]
     fn push_opt_with_applies_mapper() {
         let mut params = QueryParams::new();
         params.push_opt_with("limit", Some(5u32), |v| format!("{v:02}"));
-        assert_eq!(params.into_pairs(), vec![("limit", "05".to_string())]);
+        assert_eq!(
+            params.into_pairs(),
+            vec![("limit".into(), "05".to_string())]
+        );
+    }
+
+    #[test]
+    fn push_all_repeats_the_key() {
+        let mut params = QueryParams::new();
+        params.push_all("owner_uuids[]", ["a", "b"]);
+        params.push_all("tag", Vec::<String>::new());
+        assert_eq!(
+            params.into_pairs(),
+            vec![
+                ("owner_uuids[]".into(), "a".to_string()),
+                ("owner_uuids[]".into(), "b".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn push_json_array_emits_one_pair() {
+        let mut params = QueryParams::new();
+        params.push_json_array("uuids", ["a", "b\"c"]);
+        params.push_json_array("tags", Vec::<String>::new());
+        assert_eq!(
+            params.into_pairs(),
+            vec![("uuids".into(), r#"["a","b\"c"]"#.to_string())]
+        );
     }
 }
@@ -0,0 +1,109 @@
+//! Range-checked numeric newtypes for request payloads.
+//!
+//! Request fields such as CNAPI's `reservation_ratio` have a valid range that
+//! the services only enforce late, with an opaque conflict. These wrappers
+//! validate on construction and on deserialization, and serialize as the
+//! bare number. Read models keep plain floats so unexpected server values
+//! still parse. Percentages such as `cpu_cap`, where 100 means one full CPU,
+//! can exceed 100 and stay plain integers.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::error::{Error, Result};
+
+/// Macro to generate a float newtype restricted to an inclusive range.
+macro_rules! bounded_float {
+    ($name:ident, $min:expr, $max:expr, $doc:expr) => {
+        #[doc = $doc]
+        #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+        #[serde(try_from = "f64", into = "f64")]
+        pub struct $name(f64);
+
+        impl $name {
+            /// Smallest accepted value.
+            pub const MIN: f64 = $min;
+
+            /// Largest accepted value.
+            pub const MAX: f64 = $max;
+
+            /// Creates a value, checking that it is finite and within range.
+            ///
+            /// # Errors
+            ///
+            /// Returns [`Error::ValidationError`] if the value is NaN, infinite,
+            /// or outside the accepted range.
+            pub fn new(value: f64) -> Result<Self> {
+                if value.is_finite() && (Self::MIN..=Self::MAX).contains(&value) {
+                    Ok(Self(value))
+                } else {
+                    Err(Error::ValidationError(format!(
+                        "{} must be between {} and {}, got {value}",
+                        stringify!($name),
+                        Self::MIN,
+                        Self::MAX
+                    )))
+                }
+            }
+
+            /// Returns the inner value.
+            #[must_use]
+            pub const fn get(self) -> f64 {
+                self.0
+            }
+        }
+
+        impl TryFrom<f64> for $name {
+            type Error = Error;
+
+            fn try_from(value: f64) -> Result<Self> {
+                Self::new(value)
+            }
+        }
+
+        impl From<$name> for f64 {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+    };
+}
+
+bounded_float!(Ratio, 0.0, 1.0, "A fraction between 0.0 and 1.0 inclusive.");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn accepts_values_in_range() {
+        assert_eq!(Ratio::new(0.0).unwrap().get(), 0.0);
+        assert_eq!(Ratio::new(0.15).unwrap().get(), 0.15);
+        assert_eq!(Ratio::new(1.0).unwrap().get(), 1.0);
+    }
+
+    #[test]
+    fn rejects_out_of_range_and_non_finite() {
+        for value in [-0.1, 1.5, f64::NAN, f64::INFINITY] {
+            assert!(matches!(Ratio::new(value), Err(Error::ValidationError(_))));
+        }
+    }
+
+    #[test]
+    fn serde_uses_the_bare_number_and_validates() {
+        let ratio = Ratio::new(0.25).unwrap();
+        assert_eq!(
+            serde_json::to_value(ratio).unwrap(),
+            serde_json::json!(0.25)
+        );
+        assert_eq!(serde_json::from_str::<Ratio>("0.25").unwrap(), ratio);
+        assert!(serde_json::from_str::<Ratio>("1.5").is_err());
+    }
+}
@@ -0,0 +1,542 @@
+//! Endpoint selection with per-endpoint quarantine.
+//!
+//! [`EndpointSelector`] picks the endpoint for each request: it skips
+//! endpoints that have gone stale, then takes turns over the rest in
+//! proportion to their [`weight`](ServiceEndpoint::weight) (or evenly, with
+//! [`SelectionStrategy::RoundRobin`]).
+//!
+//! The client-wide retry policy treats a service as a whole. When one of
+//! several discovered instances keeps failing while its peers are fine, the
+//! selector takes it out of rotation for a cooldown and then lets it back in
+//! on probation: the next request either clears its record or puts it
+//! straight back into quarantine.
+//!
+//! Multi-phase workflows that must keep talking to one backend use a
+//! [`StickySession`], which pins the first selected endpoint until it is
+//! released or fails.
+//!
+//! [`ServiceClientBuilder::with_endpoints`](crate::client::ServiceClientBuilder::with_endpoints)
+//! routes a client's requests through a selector, and
+//! [`ServiceClientBuilder::pin_endpoint`](crate::client::ServiceClientBuilder::pin_endpoint)
+//! through a sticky session.
+
+use crate::clock::{Clock, SystemClock};
+use crate::types::{EndpointList, ServiceEndpoint};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// Default number of consecutive failures before an endpoint is quarantined.
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 3;
+
+/// Default quarantine duration in seconds.
+pub const DEFAULT_QUARANTINE_SECS: u64 = 30;
+
+/// Thresholds controlling when endpoints are quarantined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuarantinePolicy {
+    /// Consecutive failures that trigger a quarantine.
+    pub failure_threshold: u32,
+    /// How long a quarantined endpoint is excluded from selection.
+    pub cooldown: Duration,
+}
+
+impl QuarantinePolicy {
+    /// Create a policy with default values.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            failure_threshold: DEFAULT_FAILURE_THRESHOLD,
+            cooldown: Duration::from_secs(DEFAULT_QUARANTINE_SECS),
+        }
+    }
+
+    /// Set the consecutive failure threshold (minimum one).
+    #[must_use]
+    pub const fn with_failure_threshold(mut self, threshold: u32) -> Self {
+        self.failure_threshold = if threshold == 0 { 1 } else { threshold };
+        self
+    }
+
+    /// Set the quarantine cooldown.
+    #[must_use]
+    pub const fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+}
+
+impl Default for QuarantinePolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Lock-free round-robin cursor.
+///
+/// Cloning copies the current position; the clone then advances on its own.
+#[derive(Debug, Default)]
+pub struct RoundRobin {
+    cursor: AtomicUsize,
+}
+
+impl RoundRobin {
+    /// Create a cursor starting at the first item.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            cursor: AtomicUsize::new(0),
+        }
+    }
+
+    /// Advance and return the next index into a list of `len` items.
+    ///
+    /// Returns `None` when `len` is zero.
+    pub fn next_index(&self, len: usize) -> Option<usize> {
+        if len == 0 {
+            return None;
+        }
+        Some(self.cursor.fetch_add(1, Ordering::Relaxed) % len)
+    }
+
+    /// Advance and return the next index into `weights`.
+    ///
+    /// Over any run of `weights.iter().sum()` calls, item `i` is returned
+    /// exactly `weights[i]` times, so zero-weight items are never picked.
+    /// Returns `None` when every weight is zero.
+    pub fn next_weighted_index(&self, weights: &[u32]) -> Option<usize> {
+        let total: u64 = weights.iter().copied().map(u64::from).sum();
+        if total == 0 {
+            return None;
+        }
+        let cursor = self.cursor.fetch_add(1, Ordering::Relaxed) as u64;
+        let mut point = cursor % total;
+        weights.iter().position(|&weight| {
+            let weight = u64::from(weight);
+            if point < weight {
+                return true;
+            }
+            point -= weight;
+            false
+        })
+    }
+}
+
+impl Clone for RoundRobin {
+    fn clone(&self) -> Self {
+        Self {
+            cursor: AtomicUsize::new(self.cursor.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// How [`EndpointSelector`] spreads requests over eligible endpoints.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SelectionStrategy {
+    /// Take turns, one request per endpoint.
+    RoundRobin,
+    /// Take turns in proportion to each endpoint's weight; weight zero is
+    /// never picked.
+    #[default]
+    Weighted,
+}
+
+/// Advance `cursor` and pick one of `candidates` according to `strategy`.
+pub(crate) fn pick<'a>(
+    cursor: &RoundRobin,
+    candidates: &[&'a ServiceEndpoint],
+    strategy: SelectionStrategy,
+) -> Option<&'a ServiceEndpoint> {
+    let index = match strategy {
+        SelectionStrategy::RoundRobin => cursor.next_index(candidates.len()),
+        SelectionStrategy::Weighted => {
+            let weights: Vec<u32> = candidates.iter().map(|ep| ep.weight()).collect();
+            cursor.next_weighted_index(&weights)
+        }
+    }?;
+    Some(candidates[index])
+}
+
+/// Observable quarantine state of a single endpoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuarantinedEndpoint {
+    /// Endpoint identifier.
+    pub id: String,
+    /// Consecutive failures recorded for the endpoint.
+    pub consecutive_failures: u32,
+    /// Time left before the endpoint is probed again.
+    pub remaining: Duration,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct EndpointHealth {
+    consecutive_failures: u32,
+    quarantined_until: Option<Instant>,
+}
+
+/// Endpoint selector that skips stale and quarantined endpoints.
+///
+/// Clones share their cursor and quarantine state.
+#[derive(Clone)]
+pub struct EndpointSelector {
+    policy: QuarantinePolicy,
+    strategy: SelectionStrategy,
+    clock: Arc<dyn Clock>,
+    health: Arc<RwLock<HashMap<String, EndpointHealth>>>,
+    cursor: Arc<RoundRobin>,
+}
+
+impl EndpointSelector {
+    /// Create a selector using the system clock.
+    #[must_use]
+    pub fn new(policy: QuarantinePolicy) -> Self {
+        Self::with_clock(policy, Arc::new(SystemClock))
+    }
+
+    /// Create a selector using a custom clock.
+    #[must_use]
+    pub fn with_clock(policy: QuarantinePolicy, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            policy,
+            strategy: SelectionStrategy::default(),
+            clock,
+            health: Arc::new(RwLock::new(HashMap::new())),
+            cursor: Arc::new(RoundRobin::new()),
+        }
+    }
+
+    /// Spread requests as `strategy` says instead of by weight.
+    #[must_use]
+    pub fn with_strategy(mut self, strategy: SelectionStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Returns the active policy.
+    #[must_use]
+    pub const fn policy(&self) -> &QuarantinePolicy {
+        &self.policy
+    }
+
+    /// Returns the active strategy.
+    #[must_use]
+    pub const fn strategy(&self) -> SelectionStrategy {
+        self.strategy
+    }
+
+    /// Pick the next endpoint that is neither stale nor quarantined.
+    ///
+    /// Staleness uses the list's [`stale_threshold`](EndpointList::stale_threshold).
+    /// Returns `None` when no endpoint is eligible.
+    #[must_use]
+    pub fn select<'a>(&self, endpoints: &'a EndpointList) -> Option<&'a ServiceEndpoint> {
+        let eligible: Vec<&ServiceEndpoint> = endpoints
+            .get_healthy(endpoints.stale_threshold)
+            .into_iter()
+            .filter(|endpoint| self.is_available(&endpoint.id))
+            .collect();
+        pick(&self.cursor, &eligible, self.strategy)
+    }
+
+    /// Returns true unless the endpoint is currently quarantined.
+    #[must_use]
+    pub fn is_available(&self, id: &str) -> bool {
+        let now = self.clock.now();
+        let until = self
+            .health
+            .read()
+            .ok()
+            .and_then(|health| health.get(id).and_then(|state| state.quarantined_until));
+        !matches!(until, Some(until) if now < until)
+    }
+
+    /// Record a successful request, clearing any failure history.
+    pub fn record_success(&self, id: &str) {
+        if let Ok(mut health) = self.health.write() {
+            health.remove(id);
+        }
+    }
+
+    /// Record a failed request, quarantining the endpoint at the threshold.
+    ///
+    /// An endpoint on probation after its cooldown is quarantined again on
+    /// its first failure.
+    pub fn record_failure(&self, id: &str) {
+        let now = self.clock.now();
+        if let Ok(mut health) = self.health.write() {
+            let state = health.entry(id.to_string()).or_default();
+            state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+            if state.consecutive_failures >= self.policy.failure_threshold {
+                state.quarantined_until = Some(now + self.policy.cooldown);
+            }
+        }
+    }
+
+    /// Endpoints currently excluded from selection, sorted by identifier.
+    #[must_use]
+    pub fn quarantined(&self) -> Vec<QuarantinedEndpoint> {
+        let now = self.clock.now();
+        let Ok(health) = self.health.read() else {
+            return Vec::new();
+        };
+
+        let mut quarantined: Vec<QuarantinedEndpoint> = health
+            .iter()
+            .filter_map(|(id, state)| {
+                let until = state.quarantined_until.filter(|until| *until > now)?;
+                Some(QuarantinedEndpoint {
+                    id: id.clone(),
+                    consecutive_failures: state.consecutive_failures,
+                    remaining: until - now,
+                })
+            })
+            .collect();
+        quarantined.sort_by(|a, b| a.id.cmp(&b.id));
+        quarantined
+    }
+}
+
+impl Default for EndpointSelector {
+    fn default() -> Self {
+        Self::new(QuarantinePolicy::default())
+    }
+}
+
+impl std::fmt::Debug for EndpointSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EndpointSelector")
+            .field("policy", &self.policy)
+            .field("strategy", &self.strategy)
+            .field("quarantined", &self.quarantined())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Selection scope that keeps returning the same endpoint.
+///
+/// The first [`select`](Self::select) picks an endpoint through the wrapped
+/// [`EndpointSelector`] and pins it. Later calls return the pinned endpoint
+/// for as long as it is listed and not quarantined, until
+/// [`release`](Self::release) is called or a failure is recorded for it.
+#[derive(Debug, Clone)]
+pub struct StickySession {
+    selector: EndpointSelector,
+    pinned: Arc<RwLock<Option<String>>>,
+}
+
+impl StickySession {
+    /// Start an unpinned session over `selector`.
+    #[must_use]
+    pub fn new(selector: EndpointSelector) -> Self {
+        Self {
+            selector,
+            pinned: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Pin the session to the endpoint with identifier `id`.
+    pub(crate) fn pin(&self, id: impl Into<String>) {
+        self.set_pinned(Some(id.into()));
+    }
+
+    /// Identifier of the currently pinned endpoint, if any.
+    #[must_use]
+    pub fn pinned(&self) -> Option<String> {
+        self.pinned.read().ok().and_then(|pinned| pinned.clone())
+    }
+
+    /// Drop the pin so the next selection picks afresh.
+    pub fn release(&self) {
+        self.set_pinned(None);
+    }
+
+    /// Return the pinned endpoint, pinning a newly selected one if needed.
+    ///
+    /// A pinned endpoint that is no longer listed, is stale or is
+    /// quarantined is replaced. Returns `None` when no endpoint is available.
+    #[must_use]
+    pub fn select<'a>(&self, endpoints: &'a EndpointList) -> Option<&'a ServiceEndpoint> {
+        if let Some(id) = self.pinned() {
+            if self.selector.is_available(&id) {
+                let pinned = endpoints.endpoints.iter().find(|endpoint| {
+                    endpoint.id == id && endpoint.is_healthy(endpoints.stale_threshold)
+                });
+                if pinned.is_some() {
+                    return pinned;
+                }
+            }
+        }
+
+        let endpoint = self.selector.select(endpoints);
+        self.set_pinned(endpoint.map(|endpoint| endpoint.id.clone()));
+        endpoint
+    }
+
+    /// Record a successful request against `id`.
+    pub fn record_success(&self, id: &str) {
+        self.selector.record_success(id);
+    }
+
+    /// Record a failed request against `id`, releasing the pin if it matches.
+    pub fn record_failure(&self, id: &str) {
+        self.selector.record_failure(id);
+        if self.pinned().as_deref() == Some(id) {
+            self.release();
+        }
+    }
+
+    fn set_pinned(&self, id: Option<String>) {
+        if let Ok(mut pinned) = self.pinned.write() {
+            *pinned = id;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use std::collections::HashSet;
+    use std::net::SocketAddr;
+
+    fn endpoints() -> EndpointList {
+        let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+        EndpointList::from_endpoints(
+            ["a", "b", "c"]
+                .into_iter()
+                .map(|id| ServiceEndpoint::new(id.into(), "vmapi".into(), addr))
+                .collect(),
+        )
+    }
+
+    fn picks(selector: &EndpointSelector, list: &EndpointList, count: usize) -> HashSet<String> {
+        (0..count)
+            .filter_map(|_| selector.select(list))
+            .map(|endpoint| endpoint.id.clone())
+            .collect()
+    }
+
+    #[test]
+    fn failing_endpoint_is_excluded_until_cooldown_elapses() {
+        let clock = ManualClock::new();
+        let policy = QuarantinePolicy::new()
+            .with_failure_threshold(3)
+            .with_cooldown(Duration::from_secs(30));
+        let selector = EndpointSelector::with_clock(policy, Arc::new(clock.clone()));
+        let list = endpoints();
+
+        assert_eq!(picks(&selector, &list, 6).len(), 3);
+
+        for _ in 0..3 {
+            selector.record_failure("b");
+            selector.record_success("a");
+        }
+        let excluded = picks(&selector, &list, 12);
+        assert!(!excluded.contains("b"));
+        assert_eq!(excluded.len(), 2);
+
+        let quarantined = selector.quarantined();
+        assert_eq!(quarantined.len(), 1);
+        assert_eq!(quarantined[0].id, "b");
+        assert_eq!(quarantined[0].consecutive_failures, 3);
+        assert_eq!(quarantined[0].remaining, Duration::from_secs(30));
+
+        clock.advance(Duration::from_secs(29));
+        assert!(!selector.is_available("b"));
+
+        clock.advance(Duration::from_secs(1));
+        assert!(selector.quarantined().is_empty());
+        assert!(picks(&selector, &list, 6).contains("b"));
+    }
+
+    #[test]
+    fn probe_failure_requarantines_and_success_restores() {
+        let clock = ManualClock::new();
+        let policy = QuarantinePolicy::new()
+            .with_failure_threshold(2)
+            .with_cooldown(Duration::from_secs(10));
+        let selector = EndpointSelector::with_clock(policy, Arc::new(clock.clone()));
+
+        selector.record_failure("a");
+        assert!(selector.is_available("a"));
+        selector.record_failure("a");
+        assert!(!selector.is_available("a"));
+
+        clock.advance(Duration::from_secs(10));
+        assert!(selector.is_available("a"));
+        selector.record_failure("a");
+        assert!(!selector.is_available("a"));
+
+        clock.advance(Duration::from_secs(10));
+        selector.record_success("a");
+        selector.record_failure("a");
+        assert!(selector.is_available("a"));
+    }
+
+    #[test]
+    fn fully_quarantined_list_selects_nothing() {
+        let selector = EndpointSelector::new(QuarantinePolicy::new().with_failure_threshold(1));
+        let list = endpoints();
+        for id in ["a", "b", "c"] {
+            selector.record_failure(id);
+        }
+        assert!(selector.select(&list).is_none());
+    }
+
+    #[test]
+    fn selector_follows_weights_and_skips_stale_endpoints() {
+        let mut list = endpoints();
+        list.endpoints[0].details.weight = Some(3);
+        list.endpoints[1].details.weight = Some(1);
+        list.endpoints[2].last_seen = Instant::now()
+            .checked_sub(Duration::from_secs(120))
+            .unwrap();
+        let selector = EndpointSelector::default();
+
+        let mut counts = HashMap::new();
+        for _ in 0..40 {
+            let endpoint = selector.select(&list).unwrap();
+            *counts.entry(endpoint.id.clone()).or_insert(0) += 1;
+        }
+        assert_eq!(counts.get("a"), Some(&30));
+        assert_eq!(counts.get("b"), Some(&10));
+        assert_eq!(counts.get("c"), None);
+
+        let even = EndpointSelector::default().with_strategy(SelectionStrategy::RoundRobin);
+        assert_eq!(picks(&even, &list, 4).len(), 2);
+    }
+
+    #[test]
+    fn sticky_session_reuses_first_selection() {
+        let session = StickySession::new(EndpointSelector::default());
+        let list = endpoints();
+
+        let ids: Vec<String> = (0..3)
+            .filter_map(|_| session.select(&list))
+            .map(|endpoint| endpoint.id.clone())
+            .collect();
+        assert_eq!(ids.len(), 3);
+        assert!(ids.iter().all(|id| *id == ids[0]));
+        assert_eq!(session.pinned(), Some(ids[0].clone()));
+
+        session.release();
+        assert_eq!(session.pinned(), None);
+    }
+
+    #[test]
+    fn sticky_session_repins_after_failure() {
+        let selector = EndpointSelector::new(QuarantinePolicy::new().with_failure_threshold(1));
+        let session = StickySession::new(selector);
+        session.pin("b");
+        let list = endpoints();
+
+        assert_eq!(session.select(&list).unwrap().id, "b");
+        session.record_failure("b");
+        assert_eq!(session.pinned(), None);
+
+        let next = session.select(&list).unwrap().id.clone();
+        assert_ne!(next, "b");
+        assert_eq!(session.pinned(), Some(next));
+    }
+}
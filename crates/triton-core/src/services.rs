@@ -142,6 +142,7 @@ impl DiscoveryStatus {
 
     /// Calculate cache hit ratio (0.0 to 1.0).
     #[must_use]
+    #[allow(clippy::cast_precision_loss)]
     pub fn cache_hit_ratio(&self) -> f64 {
         let total = self.cache_hits + self.cache_misses;
         if total == 0 {
@@ -283,8 +284,7 @@ impl ServiceDiscovery for ServiceDiscoveryProxy {
     fn get_status(&self) -> DiscoveryStatus {
         self.status
             .read()
-            .map(|status| status.clone())
-            .unwrap_or_else(|_| DiscoveryStatus::new())
+            .map_or_else(|_| DiscoveryStatus::new(), |status| status.clone())
     }
 
     fn clear_cache(&self) {
@@ -388,6 +388,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::float_cmp)]
     fn test_discovery_status_cache_hit_ratio() {
         let status1 = DiscoveryStatus::new().with_cache_stats(10, 5);
         assert!((status1.cache_hit_ratio() - 0.666_666).abs() < 0.001);
@@ -0,0 +1,133 @@
+//! Cooperative shutdown for background tasks.
+//!
+//! Methods that spawn long-running loops return a [`Shutdown`] handle. The
+//! loop receives a [`ShutdownSignal`] and checks it between iterations;
+//! [`Shutdown::drain`] raises the signal and waits for the task to exit.
+
+use crate::error::Error;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+#[derive(Debug, Default)]
+struct SignalState {
+    raised: AtomicBool,
+    notify: Notify,
+}
+
+/// Listener side of a shutdown request, handed to background loops.
+#[derive(Debug, Clone, Default)]
+pub struct ShutdownSignal {
+    state: Arc<SignalState>,
+}
+
+impl ShutdownSignal {
+    /// Create a signal that has not been raised.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true once shutdown has been requested.
+    #[must_use]
+    pub fn is_raised(&self) -> bool {
+        self.state.raised.load(Ordering::Acquire)
+    }
+
+    /// Request shutdown and wake every waiting task.
+    pub fn raise(&self) {
+        self.state.raised.store(true, Ordering::Release);
+        self.state.notify.notify_waiters();
+    }
+
+    /// Wait until shutdown has been requested.
+    pub async fn raised(&self) {
+        let notified = self.state.notify.notified();
+        tokio::pin!(notified);
+        // Register before checking the flag so a concurrent `raise` is not missed.
+        notified.as_mut().enable();
+        if self.is_raised() {
+            return;
+        }
+        notified.await;
+    }
+}
+
+/// Handle to a spawned background task that can be stopped cleanly.
+#[derive(Debug)]
+pub struct Shutdown {
+    signal: ShutdownSignal,
+    handle: JoinHandle<()>,
+}
+
+impl Shutdown {
+    /// Spawn `task` with a fresh signal and return its handle.
+    pub fn spawn<F, Fut>(task: F) -> Self
+    where
+        F: FnOnce(ShutdownSignal) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let signal = ShutdownSignal::new();
+        let handle = tokio::spawn(task(signal.clone()));
+        Self { signal, handle }
+    }
+
+    /// Signal shared with the task.
+    #[must_use]
+    pub const fn signal(&self) -> &ShutdownSignal {
+        &self.signal
+    }
+
+    /// Returns true when the task has exited.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    /// Signal the task and wait for it to exit.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InternalError`] if the task panicked.
+    pub async fn drain(self) -> crate::Result<()> {
+        self.signal.raise();
+        self.handle
+            .await
+            .map_err(|err| Error::InternalError(format!("Background task failed: {err}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn drain_stops_a_looping_task() {
+        let shutdown = Shutdown::spawn(|signal| async move {
+            while !signal.is_raised() {
+                tokio::select! {
+                    () = signal.raised() => {}
+                    () = tokio::time::sleep(Duration::from_secs(60)) => {}
+                }
+            }
+        });
+
+        assert!(!shutdown.is_finished());
+        tokio::time::timeout(Duration::from_secs(1), shutdown.drain())
+            .await
+            .expect("task did not stop")
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn raised_returns_immediately_after_raise() {
+        let signal = ShutdownSignal::new();
+        signal.raise();
+        tokio::time::timeout(Duration::from_millis(100), signal.raised())
+            .await
+            .unwrap();
+    }
+}
@@ -10,6 +10,7 @@ use std::str::FromStr;
 use std::time::{Duration, Instant};
 
 use crate::error::{Error, Result};
+use crate::selection::{pick, RoundRobin, SelectionStrategy};
 
 /// Constants for Triton services
 pub const DISCOVERY_APP_NAME: &str = "sdc";
@@ -17,6 +18,8 @@ pub const DISCOVERY_APP_NAME: &str = "sdc";
 pub const DEFAULT_HTTP_PORT: u16 = 80;
 /// Default HTTPS port
 pub const DEFAULT_HTTPS_PORT: u16 = 443;
+/// Endpoints not seen for this long are skipped by [`EndpointList`] selection
+pub const DEFAULT_STALE_THRESHOLD: Duration = Duration::from_secs(60);
 /// Default LDAPS port
 pub const DEFAULT_LDAPS_PORT: u16 = 636;
 
@@ -160,7 +163,7 @@ impl Default for HealthCheckConfig {
 }
 
 /// Details about a specific endpoint.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct EndpointDetails {
     /// Load balancing weight
     pub weight: Option<u32>,
@@ -174,18 +177,6 @@ pub struct EndpointDetails {
     pub tags: Vec<String>,
 }
 
-impl Default for EndpointDetails {
-    fn default() -> Self {
-        Self {
-            weight: None,
-            priority: None,
-            region: None,
-            zone: None,
-            tags: Vec::new(),
-        }
-    }
-}
-
 /// Represents a single service endpoint.
 #[derive(Debug, Clone)]
 pub struct ServiceEndpoint {
@@ -201,6 +192,8 @@ pub struct ServiceEndpoint {
     pub metadata: HashMap<String, String>,
     /// Optional health check configuration
     pub health_check: Option<HealthCheckConfig>,
+    /// Load balancing and placement details
+    pub details: EndpointDetails,
     /// Last time this endpoint was seen/updated
     pub last_seen: Instant,
 }
@@ -216,6 +209,7 @@ impl ServiceEndpoint {
             transport: TransportType::Tcp,
             metadata: HashMap::new(),
             health_check: None,
+            details: EndpointDetails::default(),
             last_seen: Instant::now(),
         }
     }
@@ -230,28 +224,68 @@ impl ServiceEndpoint {
     pub fn touch(&mut self) {
         self.last_seen = Instant::now();
     }
+
+    /// Load balancing weight, defaulting to 1 when unset.
+    #[must_use]
+    pub fn weight(&self) -> u32 {
+        self.details.weight.unwrap_or(1)
+    }
 }
 
 /// Collection of endpoints for a service.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct EndpointList {
     /// List of endpoints
     pub endpoints: Vec<ServiceEndpoint>,
+    /// Endpoints not seen for this long are skipped by round-robin selection
+    pub stale_threshold: Duration,
+    cursor: RoundRobin,
 }
 
 impl EndpointList {
     /// Creates a new empty endpoint list.
     #[must_use]
     pub const fn new() -> Self {
-        Self {
-            endpoints: Vec::new(),
-        }
+        Self::from_endpoints(Vec::new())
     }
 
     /// Creates an endpoint list from a vector of endpoints.
     #[must_use]
     pub const fn from_endpoints(endpoints: Vec<ServiceEndpoint>) -> Self {
-        Self { endpoints }
+        Self {
+            endpoints,
+            stale_threshold: DEFAULT_STALE_THRESHOLD,
+            cursor: RoundRobin::new(),
+        }
+    }
+
+    /// Sets how long an endpoint may go unseen before selection skips it.
+    #[must_use]
+    pub const fn with_stale_threshold(mut self, threshold: Duration) -> Self {
+        self.stale_threshold = threshold;
+        self
+    }
+
+    /// Picks the next healthy endpoint in turn.
+    ///
+    /// Selects as an [`EndpointSelector`](crate::selection::EndpointSelector)
+    /// with [`SelectionStrategy::RoundRobin`] would, without quarantine.
+    /// Returns `None` when no endpoint has been seen within the stale threshold.
+    #[must_use]
+    pub fn next_round_robin(&self) -> Option<&ServiceEndpoint> {
+        let healthy = self.get_healthy(self.stale_threshold);
+        pick(&self.cursor, &healthy, SelectionStrategy::RoundRobin)
+    }
+
+    /// Picks the next healthy endpoint in proportion to its
+    /// [`weight`](ServiceEndpoint::weight).
+    ///
+    /// Endpoints with weight zero are never picked. Returns `None` when no
+    /// healthy endpoint has a positive weight.
+    #[must_use]
+    pub fn next_weighted(&self) -> Option<&ServiceEndpoint> {
+        let healthy = self.get_healthy(self.stale_threshold);
+        pick(&self.cursor, &healthy, SelectionStrategy::Weighted)
     }
 
     /// Returns the number of endpoints.
@@ -295,6 +329,12 @@ impl EndpointList {
     }
 }
 
+impl Default for EndpointList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -506,14 +546,9 @@ mod tests {
 
         let list = EndpointList::from_endpoints(vec![ep1, ep2]);
 
-        let http_endpoints = list.by_transport(TransportType::Http);
-        assert_eq!(http_endpoints.len(), 1);
-
-        let https_endpoints = list.by_transport(TransportType::Https);
-        assert_eq!(https_endpoints.len(), 1);
-
-        let grpc_endpoints = list.by_transport(TransportType::Grpc);
-        assert_eq!(grpc_endpoints.len(), 0);
+        assert_eq!(list.by_transport(TransportType::Http).len(), 1);
+        assert_eq!(list.by_transport(TransportType::Https).len(), 1);
+        assert!(list.by_transport(TransportType::Grpc).is_empty());
     }
 
     #[test]
@@ -521,4 +556,77 @@ mod tests {
         let list = EndpointList::default();
         assert!(list.is_empty());
     }
+
+    fn weighted_list(weights: &[Option<u32>]) -> EndpointList {
+        let endpoints = weights
+            .iter()
+            .enumerate()
+            .map(|(i, weight)| {
+                let addr = SocketAddr::from(([127, 0, 0, 1], 8080 + u16::try_from(i).unwrap()));
+                let mut ep = ServiceEndpoint::new(format!("ep-{i}"), "vmapi".to_string(), addr);
+                ep.details.weight = *weight;
+                ep
+            })
+            .collect();
+        EndpointList::from_endpoints(endpoints)
+    }
+
+    fn tally<'a>(
+        picks: impl Iterator<Item = Option<&'a ServiceEndpoint>>,
+    ) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+        for ep in picks {
+            *counts.entry(ep.unwrap().id.clone()).or_default() += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn test_endpoint_list_round_robin_is_even() {
+        let list = weighted_list(&[None, None, None]);
+
+        let order: Vec<&str> = (0..4)
+            .map(|_| list.next_round_robin().unwrap().id.as_str())
+            .collect();
+        assert_eq!(order, ["ep-0", "ep-1", "ep-2", "ep-0"]);
+
+        let counts = tally((0..300).map(|_| list.next_round_robin()));
+        assert!(counts.values().all(|&count| count == 100));
+    }
+
+    #[test]
+    fn test_endpoint_list_weighted_is_proportional() {
+        let list = weighted_list(&[Some(3), Some(1), Some(0), None]);
+
+        let counts = tally((0..500).map(|_| list.next_weighted()));
+        assert_eq!(counts["ep-0"], 300);
+        assert_eq!(counts["ep-1"], 100);
+        assert_eq!(counts["ep-3"], 100);
+        assert!(!counts.contains_key("ep-2"));
+
+        let equal = weighted_list(&[Some(2), Some(2)]);
+        let counts = tally((0..40).map(|_| equal.next_weighted()));
+        assert_eq!(counts["ep-0"], 20);
+        assert_eq!(counts["ep-1"], 20);
+    }
+
+    #[test]
+    fn test_endpoint_list_selection_skips_stale() {
+        let mut list = weighted_list(&[None, None]).with_stale_threshold(Duration::from_secs(60));
+        list.endpoints[0].last_seen = Instant::now()
+            .checked_sub(Duration::from_secs(120))
+            .unwrap();
+
+        for _ in 0..3 {
+            assert_eq!(list.next_round_robin().unwrap().id, "ep-1");
+            assert_eq!(list.next_weighted().unwrap().id, "ep-1");
+        }
+
+        list.endpoints[1].last_seen = Instant::now()
+            .checked_sub(Duration::from_secs(120))
+            .unwrap();
+        assert!(list.next_round_robin().is_none());
+        assert!(list.next_weighted().is_none());
+        assert!(EndpointList::new().next_round_robin().is_none());
+    }
 }
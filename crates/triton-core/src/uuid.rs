@@ -1,7 +1,8 @@
 //! Strongly-typed UUID wrappers for Triton resources.
 //!
 //! This module provides type-safe UUID wrappers for different Triton resources,
-//! preventing UUID mix-ups at compile time.
+//! preventing UUID mix-ups at compile time. Wrappers order and hash exactly
+//! like the UUIDs they hold, so they can key `HashMap`s and `BTreeSet`s.
 
 use serde::{Deserialize, Serialize};
 use std::fmt;
@@ -15,7 +16,9 @@ macro_rules! uuid_type {
     ($(#[$meta:meta])* $name:ident, $doc:expr) => {
         $(#[$meta])*
         #[doc = $doc]
-        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+        #[derive(
+            Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+        )]
         #[serde(transparent)]
         pub struct $name(Uuid);
 
@@ -54,6 +57,15 @@ macro_rules! uuid_type {
                     .map(Self)
                     .map_err(|_| Error::InvalidUuid(input.to_string()))
             }
+
+            /// Collects UUIDs into ascending order with duplicates removed.
+            #[must_use]
+            pub fn sorted(uuids: impl IntoIterator<Item = Self>) -> Vec<Self> {
+                let mut uuids: Vec<Self> = uuids.into_iter().collect();
+                uuids.sort_unstable();
+                uuids.dedup();
+                uuids
+            }
         }
 
         impl From<Uuid> for $name {
@@ -187,12 +199,12 @@ mod tests {
         let uuid = Uuid::parse_str(VALID_UUID).unwrap();
         let vm_uuid = VmUuid::new(uuid);
         let json = serde_json::to_string(&vm_uuid).unwrap();
-        assert_eq!(json, format!("\"{}\"", VALID_UUID));
+        assert_eq!(json, format!("\"{VALID_UUID}\""));
     }
 
     #[test]
     fn test_vm_uuid_deserialize() {
-        let json = format!("\"{}\"", VALID_UUID);
+        let json = format!("\"{VALID_UUID}\"");
         let vm_uuid: VmUuid = serde_json::from_str(&json).unwrap();
         assert_eq!(vm_uuid.to_string(), VALID_UUID);
     }
@@ -312,4 +324,33 @@ mod tests {
         // vm_uuid1 and vm_uuid3 are equal, so should only be counted once
         assert_eq!(set.len(), 2);
     }
+
+    #[test]
+    fn test_server_uuids_sort_deterministically() {
+        let low = ServerUuid::parse_str("00000000-0000-4000-8000-000000000001").unwrap();
+        let mid = ServerUuid::parse_str(VALID_UUID).unwrap();
+        let high = ServerUuid::parse_str("ffffffff-0000-4000-8000-000000000000").unwrap();
+
+        let mut uuids = vec![high, low, mid];
+        uuids.sort();
+        assert_eq!(uuids, vec![low, mid, high]);
+        assert!(low < mid && mid < high);
+        assert_eq!(low.cmp(&mid), low.as_uuid().cmp(mid.as_uuid()));
+
+        assert_eq!(
+            ServerUuid::sorted([mid, high, low, mid, high]),
+            vec![low, mid, high]
+        );
+    }
+
+    #[test]
+    fn test_server_uuids_dedupe_in_btree_set() {
+        use std::collections::BTreeSet;
+
+        let first = ServerUuid::parse_str(VALID_UUID).unwrap();
+        let second = ServerUuid::parse_str("00000000-0000-4000-8000-000000000001").unwrap();
+
+        let set: BTreeSet<ServerUuid> = [first, second, first, second].into_iter().collect();
+        assert_eq!(set.into_iter().collect::<Vec<_>>(), vec![second, first]);
+    }
 }
@@ -0,0 +1,127 @@
+//! Polling until a resource reaches a desired state.
+//!
+//! Client crates build their `wait_for_*` helpers (image states, NIC states,
+//! workflow jobs) on [`poll_until`], configured by a shared [`WaitOptions`].
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use crate::error::{Error, Result, TimeoutKind};
+
+/// Polling configuration for `wait_for_*` helpers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WaitOptions {
+    /// Delay between successive polls.
+    pub poll_interval: Duration,
+    /// Total time to wait before giving up.
+    pub timeout: Duration,
+}
+
+impl WaitOptions {
+    /// Default delay between polls.
+    pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+    /// Default overall wait limit.
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(600);
+
+    /// Override the delay between polls.
+    #[must_use]
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Override the overall wait limit.
+    #[must_use]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+impl Default for WaitOptions {
+    fn default() -> Self {
+        Self {
+            poll_interval: Self::DEFAULT_POLL_INTERVAL,
+            timeout: Self::DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+/// Call `poll` every `options.poll_interval` until it yields a value.
+///
+/// `poll` returns `Ok(Some(value))` when done, `Ok(None)` to poll again, or
+/// an error to stop immediately. When the next poll would start after
+/// `options.timeout`, the wait ends with a deadline [`Error::Timeout`].
+///
+/// # Errors
+///
+/// Returns the first error from `poll`, or a deadline timeout.
+pub async fn poll_until<T, F, Fut>(options: WaitOptions, mut poll: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Option<T>>>,
+{
+    let started = Instant::now();
+    loop {
+        if let Some(value) = poll().await? {
+            return Ok(value);
+        }
+
+        let elapsed = started.elapsed();
+        if elapsed + options.poll_interval > options.timeout {
+            return Err(Error::timeout(TimeoutKind::Deadline, elapsed));
+        }
+        tokio::time::sleep(options.poll_interval).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn fast() -> WaitOptions {
+        WaitOptions::default()
+            .with_poll_interval(Duration::from_millis(1))
+            .with_timeout(Duration::from_millis(50))
+    }
+
+    #[tokio::test]
+    async fn polls_until_ready() {
+        let calls = &AtomicUsize::new(0);
+        let value = poll_until(fast(), || async move {
+            let call = calls.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok((call == 3).then_some(call))
+        })
+        .await
+        .unwrap();
+        assert_eq!(value, 3);
+    }
+
+    #[tokio::test]
+    async fn stops_on_error() {
+        let calls = &AtomicUsize::new(0);
+        let err = poll_until::<(), _, _>(fast(), || async move {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(Error::NotFound("gone".into()))
+        })
+        .await
+        .unwrap_err();
+        assert!(err.is_not_found());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn times_out_with_deadline() {
+        let err = poll_until::<(), _, _>(fast(), || async { Ok(None) })
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Timeout {
+                kind: TimeoutKind::Deadline,
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,98 @@
+//! Integration tests for parsing structured Triton error bodies.
+
+use reqwest::{Method, StatusCode};
+use std::time::Duration;
+use triton_core::client::{RetryPolicy, ServiceClient, ServiceClientBuilder};
+use triton_core::context::{RequestContext, REQUEST_ID_TAG};
+use triton_core::types::TritonService;
+use triton_core::Error;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn client(base_url: &str) -> ServiceClient {
+    ServiceClientBuilder::new(TritonService::Vmapi, base_url, Duration::from_secs(2))
+        .unwrap()
+        .with_retry_policy(RetryPolicy::no_retry())
+        .build()
+        .unwrap()
+        .with_context(RequestContext::new().with_tag(REQUEST_ID_TAG, "req-1"))
+}
+
+async fn get(server: &MockServer, template: ResponseTemplate) -> Error {
+    Mock::given(method("GET"))
+        .and(path("/vms"))
+        .respond_with(template)
+        .mount(server)
+        .await;
+
+    client(&server.uri())
+        .execute_json_with_retry::<serde_json::Value, _, _>(
+            Method::GET,
+            "vms",
+            &[],
+            |r| r,
+            |status, text| match status {
+                StatusCode::NOT_FOUND => Error::NotFound(text),
+                StatusCode::BAD_REQUEST => Error::BadRequest(text),
+                _ => Error::http(status, format!("VMAPI error {status}: {text}")),
+            },
+        )
+        .await
+        .unwrap_err()
+}
+
+#[tokio::test]
+async fn structured_body_becomes_api_error() {
+    let server = MockServer::start().await;
+    let body = serde_json::json!({
+        "code": "ValidationFailed",
+        "message": "Invalid VM parameters",
+        "errors": [
+            { "field": "alias", "code": "Invalid", "message": "must be alphanumeric" }
+        ]
+    });
+
+    let err = get(&server, ResponseTemplate::new(422).set_body_json(body)).await;
+    assert_eq!(err.api_code(), Some("ValidationFailed"));
+    match err {
+        Error::Api {
+            status,
+            code,
+            message,
+            request_id,
+        } => {
+            assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+            assert_eq!(request_id.as_deref(), Some("req-1"));
+            assert_eq!(code, "ValidationFailed");
+            assert_eq!(
+                message,
+                "Invalid VM parameters; alias: must be alphanumeric [request_id=req-1]"
+            );
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn plain_text_body_falls_back_to_raw_text() {
+    let server = MockServer::start().await;
+
+    let err = get(&server, ResponseTemplate::new(400).set_body_string("oops")).await;
+    assert_eq!(
+        err,
+        Error::BadRequest("oops [request_id=req-1]".to_string())
+    );
+}
+
+#[tokio::test]
+async fn specific_variants_are_kept_for_structured_bodies() {
+    let server = MockServer::start().await;
+    let body = serde_json::json!({ "code": "ResourceNotFound", "message": "VM not found" });
+    let err = get(&server, ResponseTemplate::new(404).set_body_json(body)).await;
+    assert!(matches!(err, Error::NotFound(_)));
+
+    let server = MockServer::start().await;
+    let body = serde_json::json!({ "code": "InvalidParameters", "message": "bad owner_uuid" });
+    let err = get(&server, ResponseTemplate::new(400).set_body_json(body)).await;
+    assert!(matches!(err, Error::BadRequest(_)));
+}
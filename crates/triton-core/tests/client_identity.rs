@@ -0,0 +1,51 @@
+//! Integration tests for mutual TLS client identities.
+
+use std::path::PathBuf;
+use std::time::Duration;
+use triton_core::client::{ClientConfig, ServiceClientBuilder};
+use triton_core::types::TritonService;
+use triton_core::Error;
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+fn build(config: ClientConfig) -> triton_core::Result<()> {
+    ServiceClientBuilder::new(
+        TritonService::Vmapi,
+        "https://vmapi.example.com",
+        Duration::from_secs(2),
+    )?
+    .with_http_config(config)
+    .build()
+    .map(|_| ())
+}
+
+#[test]
+fn self_signed_identity_builds_a_client() {
+    let config = ClientConfig::new()
+        .with_client_identity(fixture("client-cert.pem"), fixture("client-key.pem"));
+
+    build(config).unwrap();
+}
+
+#[test]
+fn missing_identity_file_is_a_config_error() {
+    let config = ClientConfig::new()
+        .with_client_identity(fixture("client-cert.pem"), fixture("missing-key.pem"));
+
+    let err = build(config).unwrap_err();
+    assert!(matches!(err, Error::ConfigError(ref m) if m.contains("missing-key.pem")));
+}
+
+#[test]
+fn malformed_identity_is_a_config_error() {
+    // A certificate without its private key cannot be used as an identity.
+    let config = ClientConfig::new()
+        .with_client_identity(fixture("client-cert.pem"), fixture("client-cert.pem"));
+
+    let err = build(config).unwrap_err();
+    assert!(matches!(err, Error::ConfigError(ref m) if m.contains("Invalid client identity")));
+}
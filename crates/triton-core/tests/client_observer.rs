@@ -0,0 +1,130 @@
+//! Integration tests for per-attempt `ClientObserver` hooks.
+
+use reqwest::{Method, StatusCode};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use triton_core::client::{ClientObserver, RetryPolicy, ServiceClient, ServiceClientBuilder};
+use triton_core::types::TritonService;
+use triton_core::Error;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Observer that records every hook as a short line.
+#[derive(Default)]
+struct Recorder {
+    events: Mutex<Vec<String>>,
+}
+
+impl Recorder {
+    fn push(&self, event: String) {
+        self.events.lock().unwrap().push(event);
+    }
+
+    fn events(&self) -> Vec<String> {
+        self.events.lock().unwrap().clone()
+    }
+}
+
+impl ClientObserver for Recorder {
+    fn on_request(&self, method: &Method, path: &str, attempt: u32) {
+        self.push(format!("request {method} {path} #{attempt}"));
+    }
+
+    fn on_response(&self, status: StatusCode, _elapsed: Duration) {
+        self.push(format!("response {}", status.as_u16()));
+    }
+
+    fn on_error(&self, error: &Error) {
+        let kind = match error {
+            Error::ServiceUnavailable(_) => "unavailable",
+            Error::NotFound(_) => "not found",
+            _ => "other",
+        };
+        self.push(format!("error {kind}"));
+    }
+}
+
+fn client(base_url: &str, observer: Arc<Recorder>) -> ServiceClient {
+    ServiceClientBuilder::new(TritonService::Vmapi, base_url, Duration::from_secs(2))
+        .unwrap()
+        .with_retry_policy(
+            RetryPolicy::new()
+                .with_max_retries(2)
+                .with_initial_delay(Duration::from_millis(10)),
+        )
+        .with_client_observer(observer)
+        .build()
+        .unwrap()
+}
+
+async fn get(client: &ServiceClient, route: &str) -> Result<serde_json::Value, Error> {
+    client
+        .execute_json_with_retry(
+            Method::GET,
+            route,
+            &[],
+            |r| r,
+            |status, text| match status {
+                StatusCode::NOT_FOUND => Error::NotFound(text),
+                _ => Error::ServiceUnavailable(text),
+            },
+        )
+        .await
+}
+
+#[tokio::test]
+async fn hooks_fire_in_order_across_retries() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/vms"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/vms"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("[]"))
+        .mount(&server)
+        .await;
+    let recorder = Arc::new(Recorder::default());
+
+    get(&client(&server.uri(), recorder.clone()), "vms")
+        .await
+        .unwrap();
+
+    assert_eq!(
+        recorder.events(),
+        [
+            "request GET vms #0",
+            "response 503",
+            "error unavailable",
+            "request GET vms #1",
+            "response 200",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn final_error_is_reported_once() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/vms/missing"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+    let recorder = Arc::new(Recorder::default());
+
+    let err = get(&client(&server.uri(), recorder.clone()), "vms/missing")
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::NotFound(_)));
+    assert_eq!(
+        recorder.events(),
+        [
+            "request GET vms/missing #0",
+            "response 404",
+            "error not found"
+        ]
+    );
+}
@@ -0,0 +1,134 @@
+//! Integration tests for response decompression settings.
+
+use reqwest::Method;
+use std::io::Write;
+use std::time::Duration;
+use triton_core::client::{ClientConfig, RetryPolicy, ServiceClient, ServiceClientBuilder};
+use triton_core::types::TritonService;
+use triton_core::Error;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+const BODY: &[u8] = br#"[{"uuid":"a"}]"#;
+
+/// `BODY` as a zstd frame (`zstd --no-check`).
+const ZSTD_BODY: [u8; 23] = [
+    0x28, 0xb5, 0x2f, 0xfd, 0x00, 0x58, 0x71, 0x00, 0x00, 0x5b, 0x7b, 0x22, 0x75, 0x75, 0x69, 0x64,
+    0x22, 0x3a, 0x22, 0x61, 0x22, 0x7d, 0x5d,
+];
+
+fn brotli_body() -> Vec<u8> {
+    let mut encoded = Vec::new();
+    {
+        let mut writer = brotli::CompressorWriter::new(&mut encoded, 4096, 5, 22);
+        writer.write_all(BODY).unwrap();
+    }
+    encoded
+}
+
+fn client(base_url: &str, config: ClientConfig) -> ServiceClient {
+    ServiceClientBuilder::new(TritonService::Imgapi, base_url, Duration::from_secs(2))
+        .unwrap()
+        .with_http_config(config)
+        .with_retry_policy(RetryPolicy::no_retry())
+        .build()
+        .unwrap()
+}
+
+async fn get(client: &ServiceClient) -> Result<serde_json::Value, Error> {
+    client
+        .execute_json_with_retry(Method::GET, "images", &[], |r| r, Error::http)
+        .await
+}
+
+async fn mount_encoded(server: &MockServer, encoding: &str, body: Vec<u8>) {
+    Mock::given(method("GET"))
+        .and(path("/images"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-encoding", encoding)
+                .set_body_raw(body, "application/json"),
+        )
+        .mount(server)
+        .await;
+}
+
+fn accept_encoding(request: &Request) -> String {
+    request
+        .headers
+        .get("accept-encoding")
+        .map(|value| value.to_str().unwrap().to_string())
+        .unwrap_or_default()
+}
+
+#[test]
+fn builder_accepts_the_flags() {
+    let config = ClientConfig::new().with_brotli(true).with_zstd(true);
+    assert!(config.enable_brotli && config.enable_zstd);
+    assert!(ServiceClientBuilder::new(
+        TritonService::Imgapi,
+        "https://imgapi.example.com",
+        Duration::from_secs(2),
+    )
+    .unwrap()
+    .with_http_config(config)
+    .build()
+    .is_ok());
+}
+
+#[tokio::test]
+async fn brotli_body_is_decoded() {
+    let server = MockServer::start().await;
+    mount_encoded(&server, "br", brotli_body()).await;
+    let client = client(&server.uri(), ClientConfig::new().with_brotli(true));
+
+    let images = get(&client).await.unwrap();
+    assert_eq!(images, serde_json::json!([{ "uuid": "a" }]));
+
+    let sent = server.received_requests().await.unwrap();
+    assert!(accept_encoding(&sent[0]).contains("br"));
+}
+
+#[tokio::test]
+async fn zstd_body_is_decoded() {
+    let server = MockServer::start().await;
+    mount_encoded(&server, "zstd", ZSTD_BODY.to_vec()).await;
+    let client = client(&server.uri(), ClientConfig::new().with_zstd(true));
+
+    let images = get(&client).await.unwrap();
+    assert_eq!(images, serde_json::json!([{ "uuid": "a" }]));
+}
+
+#[tokio::test]
+async fn only_gzip_is_advertised_by_default() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/images"))
+        .and(header("accept-encoding", "gzip"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("[]"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    get(&client(&server.uri(), ClientConfig::new()))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn disabling_compression_turns_every_encoding_off() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/images"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("[]"))
+        .mount(&server)
+        .await;
+    let config = ClientConfig::new()
+        .with_compression(false)
+        .with_brotli(true)
+        .with_zstd(true);
+
+    get(&client(&server.uri(), config)).await.unwrap();
+    let sent = server.received_requests().await.unwrap();
+    assert_eq!(accept_encoding(&sent[0]), "");
+}
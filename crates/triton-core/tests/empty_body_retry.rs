@@ -0,0 +1,84 @@
+//! Integration tests for retrying empty 2xx bodies on JSON endpoints.
+
+use reqwest::Method;
+use std::time::Duration;
+use triton_core::client::{RequestOptions, RetryPolicy, ServiceClient, ServiceClientBuilder};
+use triton_core::types::TritonService;
+use triton_core::Error;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn client(base_url: &str, options: RequestOptions) -> ServiceClient {
+    ServiceClientBuilder::new(TritonService::Vmapi, base_url, Duration::from_secs(2))
+        .unwrap()
+        .with_retry_policy(
+            RetryPolicy::new()
+                .with_max_retries(2)
+                .with_initial_delay(Duration::from_millis(1)),
+        )
+        .with_request_options(options)
+        .build()
+        .unwrap()
+}
+
+async fn flaky_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/vms"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("  \n"))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/vms"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"["a","b"]"#))
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn empty_success_body_is_retried_when_enabled() {
+    let server = flaky_server().await;
+    let client = client(
+        &server.uri(),
+        RequestOptions::new().with_retry_on_empty_body(true),
+    );
+
+    let vms: Vec<String> = client
+        .execute_json_with_retry(Method::GET, "vms", &[], |r| r, Error::http)
+        .await
+        .unwrap();
+    assert_eq!(vms, vec!["a".to_string(), "b".to_string()]);
+}
+
+#[tokio::test]
+async fn empty_success_body_fails_by_default() {
+    let server = flaky_server().await;
+    let client = client(&server.uri(), RequestOptions::new());
+
+    let result: triton_core::Result<Vec<String>> = client
+        .execute_json_with_retry(Method::GET, "vms", &[], |r| r, Error::http)
+        .await;
+    assert!(matches!(result, Err(Error::SapiParseError(_))));
+}
+
+#[tokio::test]
+async fn empty_body_still_decodes_unit_responses() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/vms"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let client = client(
+        &server.uri(),
+        RequestOptions::new().with_retry_on_empty_body(true),
+    );
+
+    client
+        .execute_json_with_retry::<(), _, _>(Method::POST, "vms", &[], |r| r, Error::http)
+        .await
+        .unwrap();
+}
@@ -0,0 +1,129 @@
+//! Integration tests for per-attempt endpoint selection on `ServiceClient`.
+
+use reqwest::Method;
+use std::time::Duration;
+use triton_core::client::{RetryPolicy, ServiceClientBuilder};
+use triton_core::selection::{EndpointSelector, QuarantinePolicy};
+use triton_core::types::{EndpointList, ServiceEndpoint, TritonService};
+use triton_core::Error;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn backend(status: u16) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/ping"))
+        .respond_with(ResponseTemplate::new(status).set_body_json(serde_json::json!({})))
+        .mount(&server)
+        .await;
+    server
+}
+
+fn endpoints(servers: &[(&str, &MockServer)]) -> EndpointList {
+    EndpointList::from_endpoints(
+        servers
+            .iter()
+            .map(|(id, server)| {
+                ServiceEndpoint::new((*id).to_string(), "vmapi".into(), *server.address())
+            })
+            .collect(),
+    )
+}
+
+fn builder() -> ServiceClientBuilder {
+    ServiceClientBuilder::new(
+        TritonService::Vmapi,
+        "http://127.0.0.1:9/",
+        Duration::from_secs(2),
+    )
+    .unwrap()
+    .with_retry_policy(
+        RetryPolicy::new()
+            .with_max_retries(2)
+            .with_initial_delay(Duration::ZERO),
+    )
+}
+
+async fn hits(server: &MockServer) -> usize {
+    server.received_requests().await.unwrap_or_default().len()
+}
+
+#[tokio::test]
+async fn requests_rotate_over_configured_endpoints() {
+    let (a, b, c) = (backend(200).await, backend(200).await, backend(200).await);
+    let client = builder()
+        .with_endpoints(
+            endpoints(&[("a", &a), ("b", &b), ("c", &c)]),
+            EndpointSelector::default(),
+        )
+        .build()
+        .unwrap();
+
+    for _ in 0..6 {
+        client
+            .execute_with_retry(Method::GET, "ping", &[], |r| r, Error::http)
+            .await
+            .unwrap();
+    }
+    assert_eq!((hits(&a).await, hits(&b).await, hits(&c).await), (2, 2, 2));
+}
+
+#[tokio::test]
+async fn failing_endpoint_is_quarantined_and_retried_elsewhere() {
+    let (a, b) = (backend(200).await, backend(500).await);
+    let selector = EndpointSelector::new(QuarantinePolicy::new().with_failure_threshold(1));
+    let client = builder()
+        .with_endpoints(endpoints(&[("a", &a), ("b", &b)]), selector.clone())
+        .build()
+        .unwrap();
+
+    for _ in 0..4 {
+        client
+            .execute_with_retry(Method::GET, "ping", &[], |r| r, Error::http)
+            .await
+            .unwrap();
+    }
+    assert_eq!(hits(&b).await, 1);
+    assert_eq!(hits(&a).await, 4);
+    assert_eq!(selector.quarantined()[0].id, "b");
+}
+
+#[tokio::test]
+async fn pinned_endpoint_serves_every_call() {
+    let (a, b, c) = (backend(200).await, backend(200).await, backend(200).await);
+    let client = builder()
+        .with_endpoints(
+            endpoints(&[("a", &a), ("b", &b), ("c", &c)]),
+            EndpointSelector::default(),
+        )
+        .pin_endpoint(b.uri())
+        .build()
+        .unwrap();
+    assert_eq!(client.pinned_endpoint().as_deref(), Some("b"));
+
+    for _ in 0..3 {
+        client
+            .execute_with_retry(Method::GET, "ping", &[], |r| r, Error::http)
+            .await
+            .unwrap();
+    }
+    assert_eq!((hits(&a).await, hits(&b).await, hits(&c).await), (0, 3, 0));
+
+    client.release_pinned_endpoint();
+    assert_eq!(client.pinned_endpoint(), None);
+}
+
+#[tokio::test]
+async fn pinning_an_unknown_endpoint_fails_to_build() {
+    let a = backend(200).await;
+    let err = builder()
+        .with_endpoints(endpoints(&[("a", &a)]), EndpointSelector::default())
+        .pin_endpoint("http://192.0.2.1:80/")
+        .build()
+        .err()
+        .unwrap();
+    assert!(matches!(err, Error::ConfigError(_)));
+
+    let err = builder().pin_endpoint(a.uri()).build().err().unwrap();
+    assert!(matches!(err, Error::ConfigError(_)));
+}
@@ -0,0 +1,85 @@
+//! Integration tests for aggregating client metrics with `MetricsObserver`.
+
+use reqwest::{Method, StatusCode};
+use std::sync::Arc;
+use std::time::Duration;
+use triton_core::client::{RetryPolicy, ServiceClient, ServiceClientBuilder};
+use triton_core::metrics::MetricsObserver;
+use triton_core::types::TritonService;
+use triton_core::Error;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn client(service: TritonService, base_url: &str, metrics: Arc<MetricsObserver>) -> ServiceClient {
+    ServiceClientBuilder::new(service, base_url, Duration::from_secs(2))
+        .unwrap()
+        .with_retry_policy(RetryPolicy::no_retry())
+        .with_observer(metrics)
+        .build()
+        .unwrap()
+}
+
+async fn get(client: &ServiceClient, route: &str) -> Result<serde_json::Value, Error> {
+    client
+        .execute_json_with_retry(
+            Method::GET,
+            route,
+            &[],
+            |r| r,
+            |status, text| match status {
+                StatusCode::NOT_FOUND => Error::NotFound(text),
+                _ => Error::http(status, text),
+            },
+        )
+        .await
+}
+
+async fn server() -> MockServer {
+    let server = MockServer::start().await;
+    for (route, status) in [("/vms", 200), ("/packages", 200), ("/vms/missing", 404)] {
+        Mock::given(method("GET"))
+            .and(path(route))
+            .respond_with(ResponseTemplate::new(status).set_body_string("[]"))
+            .mount(&server)
+            .await;
+    }
+    Mock::given(method("GET"))
+        .and(path("/packages/broken"))
+        .respond_with(ResponseTemplate::new(500).set_body_string("boom"))
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn snapshot_totals_cover_every_call() {
+    let server = server().await;
+    let metrics = Arc::new(MetricsObserver::new());
+    let vmapi = client(TritonService::Vmapi, &server.uri(), metrics.clone());
+    let papi = client(TritonService::Papi, &server.uri(), metrics.clone());
+
+    get(&vmapi, "vms").await.unwrap();
+    get(&vmapi, "vms").await.unwrap();
+    get(&vmapi, "vms/missing").await.unwrap_err();
+    get(&papi, "packages").await.unwrap();
+    get(&papi, "packages/broken").await.unwrap_err();
+
+    let snapshot = metrics.snapshot();
+    assert_eq!(snapshot.total_requests(), 5);
+    assert_eq!(snapshot.total_errors(), 2);
+    assert!(snapshot.service(TritonService::Cnapi).is_none());
+
+    let vms = snapshot.service(TritonService::Vmapi).unwrap();
+    assert_eq!(vms.requests, 3);
+    assert_eq!(vms.errors, 1);
+    assert_eq!(vms.errors_by_code.get("NOT_FOUND"), Some(&1));
+    assert_eq!(vms.latency.count, 3);
+
+    let packages = snapshot.service(TritonService::Papi).unwrap();
+    assert_eq!(packages.requests, 2);
+    assert_eq!(packages.errors_by_code.get("HTTP_ERROR"), Some(&1));
+    assert_eq!(
+        packages.latency.buckets.last().map(|(_, count)| *count),
+        Some(2)
+    );
+}
@@ -0,0 +1,72 @@
+//! Integration tests for OpenTelemetry `traceparent` propagation.
+#![cfg(feature = "otel")]
+
+use opentelemetry::trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState};
+use opentelemetry::Context;
+use reqwest::Method;
+use std::time::Duration;
+use triton_core::client::{RetryPolicy, ServiceClient, ServiceClientBuilder};
+use triton_core::types::TritonService;
+use triton_core::Error;
+use wiremock::matchers::{header_exists, header_regex, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+
+fn client(base_url: &str) -> ServiceClient {
+    ServiceClientBuilder::new(TritonService::Vmapi, base_url, Duration::from_secs(2))
+        .unwrap()
+        .with_retry_policy(RetryPolicy::no_retry())
+        .build()
+        .unwrap()
+}
+
+async fn get(client: &ServiceClient) -> Result<serde_json::Value, Error> {
+    client
+        .execute_json_with_retry(Method::GET, "vms", &[], |r| r, Error::http)
+        .await
+}
+
+#[tokio::test]
+async fn traceparent_is_sent_within_an_active_context() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/vms"))
+        .and(header_regex(
+            "traceparent",
+            &format!("^00-{TRACE_ID}-[0-9a-f]{{16}}-01$"),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_string("[]"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let remote = SpanContext::new(
+        TraceId::from_hex(TRACE_ID).unwrap(),
+        SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+        TraceFlags::SAMPLED,
+        true,
+        TraceState::default(),
+    );
+    let _guard = Context::current().with_remote_span_context(remote).attach();
+
+    get(&client(&server.uri())).await.unwrap();
+}
+
+#[tokio::test]
+async fn no_traceparent_without_a_context() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(header_exists("traceparent"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(0)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/vms"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("[]"))
+        .mount(&server)
+        .await;
+
+    get(&client(&server.uri())).await.unwrap();
+}
@@ -0,0 +1,146 @@
+//! Integration tests for pre-flight checks across several services.
+
+use async_trait::async_trait;
+use std::time::Duration;
+use triton_core::client::{ServiceClient, ServiceClientBuilder};
+use triton_core::preflight::{PreflightCheck, PreflightResult, TritonClients};
+use triton_core::types::TritonService;
+use triton_napi::NapiClient;
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+struct Probe {
+    client: ServiceClient,
+    read_path: &'static str,
+}
+
+#[async_trait]
+impl PreflightCheck for Probe {
+    fn service(&self) -> TritonService {
+        self.client.service()
+    }
+
+    async fn preflight(&self) -> PreflightResult {
+        self.client.preflight(self.read_path, &[]).await
+    }
+}
+
+fn probe(service: TritonService, base_url: &str, read_path: &'static str) -> Probe {
+    probe_with_timeout(service, base_url, read_path, Duration::from_secs(2))
+}
+
+fn probe_with_timeout(
+    service: TritonService,
+    base_url: &str,
+    read_path: &'static str,
+    timeout: Duration,
+) -> Probe {
+    let client = ServiceClientBuilder::new(service, base_url, timeout)
+        .unwrap()
+        .build()
+        .unwrap();
+    Probe { client, read_path }
+}
+
+#[tokio::test]
+async fn preflight_distinguishes_unreachable_from_unauthorized() {
+    let healthy = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/ping"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&healthy)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/vms"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("[]"))
+        .mount(&healthy)
+        .await;
+
+    let locked = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/ping"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&locked)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/packages"))
+        .respond_with(ResponseTemplate::new(401).set_body_string("bad token"))
+        .mount(&locked)
+        .await;
+
+    // Nothing listens on port 1, so the connection is refused.
+    let clients = TritonClients::new()
+        .with_client(probe(TritonService::Vmapi, &healthy.uri(), "vms"))
+        .with_client(probe(TritonService::Papi, &locked.uri(), "packages"))
+        .with_client(probe(TritonService::Fwapi, "http://127.0.0.1:1", "rules"));
+
+    let report = clients.preflight().await;
+    assert_eq!(report.per_service.len(), 3);
+    assert!(!report.is_ready());
+
+    let vmapi = &report.per_service[&TritonService::Vmapi];
+    assert!(vmapi.is_ok());
+    assert!(vmapi.latency.is_some());
+
+    let papi = &report.per_service[&TritonService::Papi];
+    assert!(papi.reachable);
+    assert!(!papi.authorized);
+
+    let fwapi = &report.per_service[&TritonService::Fwapi];
+    assert!(!fwapi.reachable);
+    assert!(!fwapi.authorized);
+    assert!(fwapi.error.is_some());
+}
+
+#[tokio::test]
+async fn service_clients_probe_ping_and_a_cheap_read() {
+    let napi = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/ping"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&napi)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/networks"))
+        .and(query_param("limit", "1"))
+        .respond_with(ResponseTemplate::new(403).set_body_string("forbidden"))
+        .expect(1)
+        .mount(&napi)
+        .await;
+
+    let report = TritonClients::new()
+        .with_client(NapiClient::new(napi.uri()).unwrap())
+        .preflight()
+        .await;
+    let result = &report.per_service[&TritonService::Napi];
+    assert!(result.reachable);
+    assert!(!result.authorized);
+}
+
+#[tokio::test]
+async fn read_without_a_response_is_unreachable() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/ping"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/vms"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(500)))
+        .mount(&server)
+        .await;
+
+    let result = probe_with_timeout(
+        TritonService::Vmapi,
+        &server.uri(),
+        "vms",
+        Duration::from_millis(100),
+    )
+    .preflight()
+    .await;
+    assert!(!result.reachable);
+    assert!(!result.authorized);
+    assert!(result.error.is_some());
+}
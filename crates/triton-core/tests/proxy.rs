@@ -0,0 +1,77 @@
+//! Integration tests for routing service clients through an HTTP proxy.
+
+use reqwest::Method;
+use std::time::Duration;
+use triton_core::client::{
+    ClientConfig, ProxyConfig, RetryPolicy, ServiceClient, ServiceClientBuilder,
+};
+use triton_core::types::TritonService;
+use triton_core::Error;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn client(base_url: &str, proxy: ProxyConfig) -> triton_core::Result<ServiceClient> {
+    ServiceClientBuilder::new(TritonService::Vmapi, base_url, Duration::from_secs(2))?
+        .with_http_config(ClientConfig::new().with_proxy(proxy))
+        .with_retry_policy(RetryPolicy::no_retry())
+        .build()
+}
+
+async fn get(client: &ServiceClient) -> Result<serde_json::Value, Error> {
+    client
+        .execute_json_with_retry(Method::GET, "vms", &[], |r| r, Error::http)
+        .await
+}
+
+#[test]
+fn client_with_proxy_builds() {
+    let proxy = ProxyConfig::new("http://proxy.example.com:3128")
+        .with_no_proxy(".internal")
+        .with_no_proxy("10.0.0.0/8")
+        .with_auth("user", "secret");
+    assert!(client("https://vmapi.example.com", proxy).is_ok());
+}
+
+#[test]
+fn invalid_proxy_url_is_a_config_error() {
+    let result = client("https://vmapi.example.com", ProxyConfig::new("not a url"));
+    assert!(matches!(result, Err(Error::ConfigError(_))));
+}
+
+#[tokio::test]
+async fn requests_are_routed_through_the_proxy() {
+    let proxy = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/vms"))
+        .and(header("host", "vmapi.invalid"))
+        .and(header("proxy-authorization", "Basic dXNlcjpzZWNyZXQ="))
+        .respond_with(ResponseTemplate::new(200).set_body_string("[]"))
+        .expect(1)
+        .mount(&proxy)
+        .await;
+
+    let config = ProxyConfig::new(proxy.uri()).with_auth("user", "secret");
+    let client = client("http://vmapi.invalid", config).unwrap();
+    get(&client).await.unwrap();
+}
+
+#[tokio::test]
+async fn no_proxy_hosts_are_reached_directly() {
+    let proxy = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(502))
+        .expect(0)
+        .mount(&proxy)
+        .await;
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/vms"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("[]"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let config = ProxyConfig::new(proxy.uri()).with_no_proxy("127.0.0.1");
+    let client = client(&server.uri(), config).unwrap();
+    get(&client).await.unwrap();
+}
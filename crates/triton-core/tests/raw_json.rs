@@ -0,0 +1,84 @@
+//! Integration tests for the raw JSON escape hatch on `ServiceClient`.
+
+use reqwest::Method;
+use serde_json::json;
+use std::time::Duration;
+use triton_core::client::{ServiceClient, ServiceClientBuilder};
+use triton_core::types::TritonService;
+use triton_core::Error;
+use wiremock::matchers::{body_json, header, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn client(base_url: &str) -> ServiceClient {
+    ServiceClientBuilder::new(TritonService::Vmapi, base_url, Duration::from_secs(2))
+        .unwrap()
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn get_raw_returns_unmodelled_json_unmodified() {
+    let server = MockServer::start().await;
+    let payload = json!({
+        "unmodelled": { "nested": [1, "two", null, { "flag": true }] },
+        "count": 3
+    });
+
+    Mock::given(method("GET"))
+        .and(path("/custom/endpoint"))
+        .and(query_param("detail", "full"))
+        .and(header("accept", "application/json"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(payload.clone()))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let raw = client(&server.uri())
+        .get_raw(
+            "custom/endpoint",
+            &[("detail".into(), "full".to_string())],
+            Error::http,
+        )
+        .await
+        .unwrap();
+    assert_eq!(raw, payload);
+}
+
+#[tokio::test]
+async fn send_raw_posts_body_and_returns_json() {
+    let server = MockServer::start().await;
+    let body = json!({ "action": "custom", "extra": { "a": 1 } });
+
+    Mock::given(method("POST"))
+        .and(path("/custom/actions"))
+        .and(body_json(body.clone()))
+        .respond_with(ResponseTemplate::new(202).set_body_json(body.clone()))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let raw = client(&server.uri())
+        .send_raw(Method::POST, "custom/actions", Some(&body), &[], Error::http)
+        .await
+        .unwrap();
+    assert_eq!(raw, body);
+}
+
+#[tokio::test]
+async fn send_raw_maps_errors_with_the_callers_mapper() {
+    let server = MockServer::start().await;
+    Mock::given(method("DELETE"))
+        .and(path("/custom/missing"))
+        .respond_with(ResponseTemplate::new(404).set_body_string("gone"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let err = client(&server.uri())
+        .send_raw(Method::DELETE, "custom/missing", None, &[], |_, text| {
+            Error::NotFound(text)
+        })
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::NotFound(message) if message.starts_with("gone")));
+}
@@ -0,0 +1,190 @@
+//! Integration tests for request context tags in spans, observers, and errors.
+
+use reqwest::Method;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+use triton_core::client::{
+    RequestEvent, RequestObserver, RetryPolicy, ServiceClient, ServiceClientBuilder,
+};
+use triton_core::context::{RequestContext, REQUEST_ID_TAG};
+use triton_core::types::TritonService;
+use triton_core::Error;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Records the fields of every `triton_request` span as `name=value` strings.
+#[derive(Clone, Default)]
+struct SpanCapture {
+    spans: Arc<Mutex<Vec<Vec<String>>>>,
+}
+
+struct FieldCollector(Vec<String>);
+
+impl Visit for FieldCollector {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.push(format!("{}={value:?}", field.name()));
+    }
+}
+
+impl Subscriber for SpanCapture {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        if span.metadata().name() == "triton_request" {
+            let mut fields = FieldCollector(Vec::new());
+            span.record(&mut fields);
+            self.spans.lock().unwrap().push(fields.0);
+        }
+        Id::from_u64(1)
+    }
+
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, _event: &Event<'_>) {}
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+/// Observer that keeps the context and outcome of every call.
+#[derive(Default)]
+struct Recorder {
+    calls: Mutex<Vec<(RequestContext, u32, bool)>>,
+}
+
+impl RequestObserver for Recorder {
+    fn on_request(&self, event: &RequestEvent<'_>) {
+        self.calls.lock().unwrap().push((
+            event.context.clone(),
+            event.attempts,
+            event.error.is_some(),
+        ));
+    }
+}
+
+fn client(base_url: &str, observer: Arc<Recorder>) -> ServiceClient {
+    ServiceClientBuilder::new(TritonService::Vmapi, base_url, Duration::from_secs(2))
+        .unwrap()
+        .with_retry_policy(RetryPolicy::no_retry())
+        .with_observer(observer)
+        .build()
+        .unwrap()
+}
+
+async fn get(client: &ServiceClient, route: &str) -> Result<serde_json::Value, Error> {
+    client
+        .execute_json_with_retry(
+            Method::GET,
+            route,
+            &[],
+            |r| r,
+            |status, text| match status {
+                reqwest::StatusCode::NOT_FOUND => Error::NotFound(text),
+                _ => Error::http(status, text),
+            },
+        )
+        .await
+}
+
+async fn server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/vms"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("[]"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/vms/missing"))
+        .respond_with(ResponseTemplate::new(404).set_body_string("no such VM"))
+        .mount(&server)
+        .await;
+    server
+}
+
+fn operation() -> RequestContext {
+    RequestContext::new()
+        .with_tag("operation_id", "provision-env-x")
+        .with_tag("tenant", "acme")
+}
+
+#[tokio::test]
+async fn context_tags_reach_span_and_observer() {
+    let capture = SpanCapture::default();
+    let _guard = tracing::subscriber::set_default(capture.clone());
+    let server = server().await;
+    let recorder = Arc::new(Recorder::default());
+    let client = client(&server.uri(), recorder.clone()).with_context(operation());
+
+    get(&client, "vms").await.unwrap();
+
+    let spans = capture.spans.lock().unwrap();
+    assert_eq!(spans.len(), 1);
+    assert!(spans[0].iter().any(|f| f == "service=\"vmapi\""));
+    assert!(spans[0]
+        .iter()
+        .any(|f| f == "context=operation_id=provision-env-x tenant=acme"));
+
+    let calls = recorder.calls.lock().unwrap();
+    assert_eq!(calls.as_slice(), &[(operation(), 1, false)]);
+}
+
+#[tokio::test]
+async fn task_scope_merges_with_client_context() {
+    let server = server().await;
+    let recorder = Arc::new(Recorder::default());
+    let client = client(&server.uri(), recorder.clone())
+        .with_context(RequestContext::new().with_tag("tenant", "acme"));
+
+    RequestContext::new()
+        .with_tag("operation_id", "provision-env-x")
+        .with_tag("tenant", "ignored")
+        .scope(async { get(&client, "vms").await.unwrap() })
+        .await;
+
+    let calls = recorder.calls.lock().unwrap();
+    assert_eq!(calls[0].0, operation());
+}
+
+#[tokio::test]
+async fn errors_carry_context_tags() {
+    let server = server().await;
+    let recorder = Arc::new(Recorder::default());
+    let client = client(&server.uri(), recorder.clone())
+        .with_context(operation().with_tag(REQUEST_ID_TAG, "req-1"));
+
+    let err = get(&client, "vms/missing").await.unwrap_err();
+    assert_eq!(
+        err,
+        Error::NotFound(
+            "no such VM [operation_id=provision-env-x request_id=req-1 tenant=acme]".to_string()
+        )
+    );
+    assert!(recorder.calls.lock().unwrap()[0].2);
+}
+
+#[tokio::test]
+async fn calls_without_context_only_carry_the_request_id() {
+    let server = server().await;
+    let recorder = Arc::new(Recorder::default());
+    let client = client(&server.uri(), recorder.clone());
+
+    let err = get(&client, "vms/missing").await.unwrap_err();
+    let message = match err {
+        Error::NotFound(message) => message,
+        other => panic!("unexpected error: {other:?}"),
+    };
+    let request_id = message
+        .strip_prefix("no such VM [request_id=")
+        .and_then(|rest| rest.strip_suffix(']'))
+        .unwrap();
+    assert_eq!(request_id.len(), 36);
+    assert!(recorder.calls.lock().unwrap()[0].0.is_empty());
+}
@@ -0,0 +1,128 @@
+//! Integration tests for the per-call request id header.
+
+use reqwest::header::HeaderName;
+use reqwest::Method;
+use std::time::Duration;
+use triton_core::client::{RetryPolicy, ServiceClient, ServiceClientBuilder};
+use triton_core::context::{RequestContext, REQUEST_ID_TAG};
+use triton_core::types::TritonService;
+use triton_core::Error;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn builder(base_url: &str) -> ServiceClientBuilder {
+    ServiceClientBuilder::new(TritonService::Vmapi, base_url, Duration::from_secs(2))
+        .unwrap()
+        .with_retry_policy(
+            RetryPolicy::new()
+                .with_max_retries(2)
+                .with_initial_delay(Duration::from_millis(10)),
+        )
+}
+
+async fn get(client: &ServiceClient) -> Result<serde_json::Value, Error> {
+    client
+        .execute_json_with_retry(
+            Method::GET,
+            "vms",
+            &[],
+            |r| r,
+            |status, text| Error::ServiceUnavailable(format!("{status}: {text}")),
+        )
+        .await
+}
+
+async fn flaky_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/vms"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(2)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/vms"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("[]"))
+        .mount(&server)
+        .await;
+    server
+}
+
+async fn sent_ids(server: &MockServer, header: &str) -> Vec<String> {
+    server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|request| request.headers[header].to_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn same_id_is_sent_on_every_attempt() {
+    let server = flaky_server().await;
+    let client = builder(&server.uri()).build().unwrap();
+
+    get(&client).await.unwrap();
+    let first = sent_ids(&server, "x-request-id").await;
+    assert_eq!(first.len(), 3);
+    assert!(first.iter().all(|id| *id == first[0]));
+    assert!(uuid::Uuid::parse_str(&first[0]).is_ok());
+
+    get(&client).await.unwrap();
+    let second = sent_ids(&server, "x-request-id").await;
+    assert_ne!(second[3], first[0]);
+}
+
+#[tokio::test]
+async fn header_name_and_id_can_be_overridden() {
+    let server = flaky_server().await;
+    let client = builder(&server.uri())
+        .with_request_id_header(HeaderName::from_static("request-id"))
+        .build()
+        .unwrap()
+        .with_context(RequestContext::new().with_tag(REQUEST_ID_TAG, "req-1"));
+
+    get(&client).await.unwrap();
+    assert_eq!(sent_ids(&server, "request-id").await, ["req-1"; 3]);
+}
+
+#[tokio::test]
+async fn final_error_carries_the_request_id() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/vms"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&server)
+        .await;
+    let client = builder(&server.uri()).build().unwrap();
+
+    let err = get(&client).await.unwrap_err();
+    let id = &sent_ids(&server, "x-request-id").await[0];
+    assert!(err.to_string().ends_with(&format!("[request_id={id}]")));
+}
+
+#[tokio::test]
+async fn timeouts_keep_the_request_id() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/vms"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(500)))
+        .mount(&server)
+        .await;
+    let client = ServiceClientBuilder::new(
+        TritonService::Vmapi,
+        server.uri(),
+        Duration::from_millis(50),
+    )
+    .unwrap()
+    .with_retry_policy(RetryPolicy::no_retry())
+    .build()
+    .unwrap()
+    .with_context(RequestContext::new().with_tag(REQUEST_ID_TAG, "req-1"));
+
+    let err = get(&client).await.unwrap_err();
+    assert!(err.timeout_kind().is_some(), "unexpected error: {err:?}");
+    assert_eq!(err.request_id(), Some("req-1"));
+    assert!(err.to_string().ends_with("[request_id=req-1]"));
+}
@@ -0,0 +1,69 @@
+//! Integration tests for per-request timeout overrides.
+
+use reqwest::Method;
+use std::time::Duration;
+use triton_core::client::{RequestOptions, RetryPolicy, ServiceClient, ServiceClientBuilder};
+use triton_core::types::TritonService;
+use triton_core::{Error, TimeoutKind};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn slow_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/images"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_delay(Duration::from_millis(300))
+                .set_body_string("[]"),
+        )
+        .mount(&server)
+        .await;
+    server
+}
+
+fn client(base_url: &str) -> ServiceClient {
+    ServiceClientBuilder::new(TritonService::Imgapi, base_url, Duration::from_secs(5))
+        .unwrap()
+        .with_retry_policy(RetryPolicy::no_retry())
+        .build()
+        .unwrap()
+}
+
+async fn get(client: &ServiceClient) -> Result<serde_json::Value, Error> {
+    client
+        .execute_json_with_retry(Method::GET, "images", &[], |r| r, Error::http)
+        .await
+}
+
+#[tokio::test]
+async fn short_per_request_timeout_fails_fast() {
+    let server = slow_server().await;
+    let client = client(&server.uri());
+
+    let err = get(&client.with_timeout(Duration::from_millis(50)))
+        .await
+        .unwrap_err();
+    assert_eq!(err.timeout_kind(), Some(TimeoutKind::Read));
+
+    // The original handle keeps the client-wide timeout.
+    get(&client).await.unwrap();
+}
+
+#[tokio::test]
+async fn builder_request_options_apply_to_every_call() {
+    let server = slow_server().await;
+    let client =
+        ServiceClientBuilder::new(TritonService::Imgapi, server.uri(), Duration::from_secs(5))
+            .unwrap()
+            .with_retry_policy(RetryPolicy::no_retry())
+            .with_request_options(RequestOptions::new().with_timeout(Duration::from_millis(50)))
+            .build()
+            .unwrap();
+
+    let err = get(&client).await.unwrap_err();
+    assert!(matches!(err, Error::Timeout { .. }));
+
+    let relaxed = client.with_timeout(Duration::from_secs(5));
+    get(&relaxed).await.unwrap();
+}
@@ -0,0 +1,167 @@
+//! Integration tests for serving repeated GETs from a `ResponseCache`.
+
+use reqwest::Method;
+use std::sync::Arc;
+use std::time::Duration;
+use triton_core::cache::ResponseCache;
+use triton_core::client::{RetryPolicy, ServiceClient, ServiceClientBuilder};
+use triton_core::clock::ManualClock;
+use triton_core::query::QueryPair;
+use triton_core::types::TritonService;
+use triton_core::Error;
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const TTL: Duration = Duration::from_secs(10);
+
+fn client(base_url: &str, cache: Arc<ResponseCache>) -> ServiceClient {
+    ServiceClientBuilder::new(TritonService::Vmapi, base_url, Duration::from_secs(2))
+        .unwrap()
+        .with_retry_policy(RetryPolicy::no_retry())
+        .with_response_cache(cache)
+        .build()
+        .unwrap()
+}
+
+async fn call(
+    client: &ServiceClient,
+    method: Method,
+    route: &str,
+    params: &[QueryPair],
+) -> Result<serde_json::Value, Error> {
+    client
+        .execute_json_with_retry(method, route, params, |r| r, Error::http)
+        .await
+}
+
+async fn mount(server: &MockServer, verb: &str, route: &str, status: u16, calls: u64) {
+    Mock::given(method(verb))
+        .and(path(route))
+        .respond_with(
+            ResponseTemplate::new(status).set_body_raw(r#"[{"uuid":"a"}]"#, "application/json"),
+        )
+        .expect(calls)
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn identical_get_within_ttl_hits_the_cache() {
+    let server = MockServer::start().await;
+    mount(&server, "GET", "/vms", 200, 1).await;
+    let client = client(&server.uri(), Arc::new(ResponseCache::new(TTL)));
+
+    let first = call(&client, Method::GET, "vms", &[]).await.unwrap();
+    let second = call(&client, Method::GET, "vms", &[]).await.unwrap();
+    assert_eq!(first, second);
+}
+
+#[tokio::test]
+async fn expired_entries_are_fetched_again() {
+    let server = MockServer::start().await;
+    mount(&server, "GET", "/vms", 200, 2).await;
+    let clock = ManualClock::new();
+    let cache = ResponseCache::new(TTL).with_clock(Arc::new(clock.clone()));
+    let client = client(&server.uri(), Arc::new(cache));
+
+    call(&client, Method::GET, "vms", &[]).await.unwrap();
+    clock.advance(TTL);
+    call(&client, Method::GET, "vms", &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn query_is_part_of_the_key() {
+    let server = MockServer::start().await;
+    for state in ["running", "stopped"] {
+        Mock::given(method("GET"))
+            .and(path("/vms"))
+            .and(query_param("state", state))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("[]", "application/json"))
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+    let client = client(&server.uri(), Arc::new(ResponseCache::new(TTL)));
+
+    for state in ["running", "stopped", "running", "stopped"] {
+        let params: [QueryPair; 1] = [("state".into(), state.to_string())];
+        call(&client, Method::GET, "vms", &params).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn mutations_and_failures_are_not_cached() {
+    let server = MockServer::start().await;
+    mount(&server, "POST", "/vms", 200, 2).await;
+    mount(&server, "GET", "/vms/missing", 404, 2).await;
+    let cache = Arc::new(ResponseCache::new(TTL));
+    let client = client(&server.uri(), cache.clone());
+
+    for _ in 0..2 {
+        call(&client, Method::POST, "vms", &[]).await.unwrap();
+        call(&client, Method::GET, "vms/missing", &[])
+            .await
+            .unwrap_err();
+    }
+    assert!(cache.is_empty());
+}
+
+#[tokio::test]
+async fn mutations_evict_the_path_and_its_collection() {
+    let server = MockServer::start().await;
+    mount(&server, "GET", "/vms", 200, 2).await;
+    mount(&server, "GET", "/vms/a", 200, 2).await;
+    mount(&server, "POST", "/vms/a", 200, 1).await;
+    let client = client(&server.uri(), Arc::new(ResponseCache::new(TTL)));
+
+    for _ in 0..2 {
+        call(&client, Method::GET, "vms", &[]).await.unwrap();
+        call(&client, Method::GET, "vms/a", &[]).await.unwrap();
+    }
+    call(&client, Method::POST, "vms/a", &[]).await.unwrap();
+    call(&client, Method::GET, "vms", &[]).await.unwrap();
+    call(&client, Method::GET, "vms/a", &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn clients_with_different_credentials_do_not_share_entries() {
+    let server = MockServer::start().await;
+    mount(&server, "GET", "/vms", 200, 2).await;
+    let cache = Arc::new(ResponseCache::new(TTL));
+    let build = |user: &str| {
+        ServiceClientBuilder::new(TritonService::Vmapi, server.uri(), Duration::from_secs(2))
+            .unwrap()
+            .with_retry_policy(RetryPolicy::no_retry())
+            .with_basic_auth(user, "secret")
+            .with_response_cache(cache.clone())
+            .build()
+            .unwrap()
+    };
+    let (alice, bob) = (build("alice"), build("bob"));
+
+    for client in [&alice, &bob, &alice, &bob] {
+        call(client, Method::GET, "vms", &[]).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn non_json_bodies_are_not_cached() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/images/a/file"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_raw("\"blob\"", "application/octet-stream"),
+        )
+        .expect(2)
+        .mount(&server)
+        .await;
+    let cache = Arc::new(ResponseCache::new(TTL));
+    let client = client(&server.uri(), cache.clone());
+
+    for _ in 0..2 {
+        call(&client, Method::GET, "images/a/file", &[])
+            .await
+            .unwrap();
+    }
+    assert!(cache.is_empty());
+}
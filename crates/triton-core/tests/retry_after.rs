@@ -0,0 +1,57 @@
+//! Integration tests for honoring `Retry-After` on retryable responses.
+
+use reqwest::Method;
+use std::time::{Duration, Instant};
+use triton_core::client::{RetryPolicy, ServiceClientBuilder};
+use triton_core::types::TritonService;
+use triton_core::Error;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn retry_waits_for_retry_after_before_next_attempt() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/vms"))
+        .respond_with(ResponseTemplate::new(503).insert_header("Retry-After", "2"))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/vms"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("[]"))
+        .mount(&server)
+        .await;
+
+    // The policy alone would retry after 10ms.
+    let client =
+        ServiceClientBuilder::new(TritonService::Vmapi, server.uri(), Duration::from_secs(5))
+            .unwrap()
+            .with_retry_policy(
+                RetryPolicy::new()
+                    .with_max_retries(1)
+                    .with_initial_delay(Duration::from_millis(10)),
+            )
+            .build()
+            .unwrap();
+
+    let started = Instant::now();
+    let vms: Vec<serde_json::Value> = client
+        .execute_json_with_retry(
+            Method::GET,
+            "vms",
+            &[],
+            |r| r,
+            |status, text| Error::ServiceUnavailable(format!("{status}: {text}")),
+        )
+        .await
+        .unwrap();
+
+    assert!(vms.is_empty());
+    assert!(
+        started.elapsed() >= Duration::from_millis(1900),
+        "retried after {:?}",
+        started.elapsed()
+    );
+    assert_eq!(server.received_requests().await.unwrap().len(), 2);
+}
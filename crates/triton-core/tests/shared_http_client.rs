@@ -0,0 +1,82 @@
+//! Integration tests for service clients sharing an injected `reqwest::Client`.
+
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::Method;
+use std::time::Duration;
+use triton_core::client::{RetryPolicy, ServiceClient, ServiceClientBuilder};
+use triton_core::types::TritonService;
+use triton_core::Error;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn shared_http_client() -> reqwest::Client {
+    let mut headers = HeaderMap::new();
+    headers.insert("x-shared-client", HeaderValue::from_static("yes"));
+    reqwest::Client::builder()
+        .default_headers(headers)
+        .user_agent("shared-agent/1.0")
+        .build()
+        .unwrap()
+}
+
+fn client(service: TritonService, base_url: &str, http: reqwest::Client) -> ServiceClient {
+    ServiceClientBuilder::new(service, base_url, Duration::from_secs(2))
+        .unwrap()
+        .with_retry_policy(RetryPolicy::no_retry())
+        .with_user_agent("ignored-agent")
+        .with_http_client(http)
+        .build()
+        .unwrap()
+}
+
+async fn get(client: &ServiceClient, route: &str) -> Result<serde_json::Value, Error> {
+    client
+        .execute_json_with_retry(Method::GET, route, &[], |r| r, Error::http)
+        .await
+}
+
+#[tokio::test]
+async fn injected_client_sends_every_service_request() {
+    let server = MockServer::start().await;
+    for route in ["/vms", "/packages"] {
+        Mock::given(method("GET"))
+            .and(path(route))
+            .and(header("x-shared-client", "yes"))
+            .and(header("user-agent", "shared-agent/1.0"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("[]"))
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+
+    let http = shared_http_client();
+    let vmapi = client(TritonService::Vmapi, &server.uri(), http.clone());
+    let papi = client(TritonService::Papi, &server.uri(), http);
+
+    get(&vmapi, "vms").await.unwrap();
+    get(&papi, "packages").await.unwrap();
+}
+
+#[tokio::test]
+async fn builder_constructs_its_own_client_by_default() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/vms"))
+        .and(header("x-shared-client", "yes"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("[]"))
+        .mount(&server)
+        .await;
+
+    let vmapi =
+        ServiceClientBuilder::new(TritonService::Vmapi, server.uri(), Duration::from_secs(2))
+            .unwrap()
+            .with_retry_policy(RetryPolicy::no_retry())
+            .build()
+            .unwrap();
+
+    let err = get(&vmapi, "vms").await.unwrap_err();
+    assert!(matches!(
+        err,
+        Error::HttpError { status: Some(status), .. } if status == reqwest::StatusCode::NOT_FOUND
+    ));
+}
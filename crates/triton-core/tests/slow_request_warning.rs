@@ -0,0 +1,113 @@
+//! Integration tests for the slow request warning.
+
+use reqwest::Method;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+use triton_core::client::{ClientConfig, RetryPolicy, ServiceClient, ServiceClientBuilder};
+use triton_core::types::TritonService;
+use triton_core::Error;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Records the fields of every WARN event as `name=value` strings.
+#[derive(Clone, Default)]
+struct WarnCapture {
+    events: Arc<Mutex<Vec<Vec<String>>>>,
+}
+
+struct FieldCollector(Vec<String>);
+
+impl Visit for FieldCollector {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.push(format!("{}={value:?}", field.name()));
+    }
+}
+
+impl Subscriber for WarnCapture {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        *metadata.level() == Level::WARN
+    }
+
+    fn new_span(&self, _span: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = FieldCollector(Vec::new());
+        event.record(&mut fields);
+        self.events.lock().unwrap().push(fields.0);
+    }
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+fn client(base_url: &str, threshold: Duration) -> ServiceClient {
+    ServiceClientBuilder::new(TritonService::Vmapi, base_url, Duration::from_secs(2))
+        .unwrap()
+        .with_http_config(
+            ClientConfig::new()
+                .with_retry_policy(RetryPolicy::no_retry())
+                .with_slow_request_threshold(threshold),
+        )
+        .build()
+        .unwrap()
+}
+
+async fn get_vms(client: &ServiceClient) -> Vec<String> {
+    client
+        .execute_json_with_retry(Method::GET, "vms", &[], |r| r, Error::http)
+        .await
+        .unwrap()
+}
+
+async fn server(delay: Duration) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/vms"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("[]")
+                .set_delay(delay),
+        )
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn request_over_threshold_logs_a_warning() {
+    let capture = WarnCapture::default();
+    let _guard = tracing::subscriber::set_default(capture.clone());
+    let server = server(Duration::from_millis(100)).await;
+
+    get_vms(&client(&server.uri(), Duration::from_millis(20))).await;
+
+    let events = capture.events.lock().unwrap();
+    assert_eq!(events.len(), 1);
+    let fields = &events[0];
+    assert!(fields.iter().any(|f| f == "message=Slow service request"));
+    assert!(fields.iter().any(|f| f == "service=\"vmapi\""));
+    assert!(fields.iter().any(|f| f == "path=\"vms\""));
+    assert!(fields.iter().any(|f| f == "attempts=1"));
+    assert!(fields.iter().any(|f| f.starts_with("elapsed=")));
+}
+
+#[tokio::test]
+async fn fast_request_does_not_warn() {
+    let capture = WarnCapture::default();
+    let _guard = tracing::subscriber::set_default(capture.clone());
+    let server = server(Duration::ZERO).await;
+
+    get_vms(&client(&server.uri(), Duration::from_secs(5))).await;
+
+    assert!(capture.events.lock().unwrap().is_empty());
+}
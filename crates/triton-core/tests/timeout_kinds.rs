@@ -0,0 +1,91 @@
+//! Integration tests for classifying timeouts as connect, read, or deadline.
+
+use reqwest::Method;
+use std::time::Duration;
+use triton_core::client::{ClientConfig, RetryPolicy, ServiceClient, ServiceClientBuilder};
+use triton_core::types::TritonService;
+use triton_core::{Error, TimeoutKind};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn client(base_url: &str, config: ClientConfig) -> ServiceClient {
+    ServiceClientBuilder::new(TritonService::Vmapi, base_url, Duration::from_secs(2))
+        .unwrap()
+        .with_http_config(config.with_retry_policy(RetryPolicy::no_retry()))
+        .build()
+        .unwrap()
+}
+
+async fn get(client: &ServiceClient) -> Result<serde_json::Value, Error> {
+    client
+        .execute_json_with_retry(Method::GET, "vms", &[], |r| r, Error::http)
+        .await
+}
+
+async fn slow_server(delay: Duration) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/vms"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("[]")
+                .set_delay(delay),
+        )
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn slow_response_is_a_read_timeout() {
+    let server = slow_server(Duration::from_secs(2)).await;
+    let client = client(
+        &server.uri(),
+        ClientConfig::new().with_timeout(Duration::from_millis(100)),
+    );
+
+    let err = get(&client).await.unwrap_err();
+    assert_eq!(err.timeout_kind(), Some(TimeoutKind::Read));
+    let Error::Timeout { elapsed, .. } = err else {
+        unreachable!()
+    };
+    assert!(elapsed >= Duration::from_millis(100));
+}
+
+#[tokio::test]
+async fn short_deadline_is_a_deadline_timeout() {
+    let server = slow_server(Duration::from_secs(2)).await;
+    let client = client(
+        &server.uri(),
+        ClientConfig::new()
+            .with_timeout(Duration::from_secs(5))
+            .with_deadline(Duration::from_millis(100)),
+    );
+
+    let err = get(&client).await.unwrap_err();
+    assert_eq!(err.timeout_kind(), Some(TimeoutKind::Deadline));
+}
+
+#[tokio::test]
+async fn refused_connection_is_unavailable_not_a_timeout() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+
+    let client = client(&format!("http://{addr}"), ClientConfig::new());
+
+    let err = get(&client).await.unwrap_err();
+    assert!(matches!(err, Error::ServiceUnavailable(_)), "{err:?}");
+}
+
+#[tokio::test]
+#[ignore = "needs a route that silently drops packets to 10.255.255.1"]
+async fn unanswered_connect_is_a_connect_timeout() {
+    let client = client(
+        "http://10.255.255.1:81",
+        ClientConfig::new().with_connect_timeout(Duration::from_millis(100)),
+    );
+
+    let err = get(&client).await.unwrap_err();
+    assert_eq!(err.timeout_kind(), Some(TimeoutKind::Connect));
+}
@@ -1,21 +1,33 @@
 //! Asynchronous FWAPI client implementation.
 
 use crate::models::{
-    CreateFirewallRuleRequest, FirewallRule, FirewallRuleListParams, UpdateFirewallRuleRequest,
+    CreateFirewallRuleRequest, FirewallRule, FirewallRuleListParams, ParsedRule, PolicyDocument,
+    ReconcilePlan, ResolveRequest, ResolveResponse, RuleChange, RuleImportOutcome, RuleScope,
+    UpdateFirewallRuleRequest,
 };
 use crate::Result;
 use async_trait::async_trait;
+use chrono::Utc;
+use reqwest::header::HeaderName;
 use reqwest::{Method, StatusCode};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use std::collections::HashSet;
 use std::sync::Arc;
 use std::time::Duration;
 use triton_core::client::{
-    ClientConfig, RetryPolicy, ServiceClient, ServiceClientBuilder, FWAPI_DEFAULT_TIMEOUT,
+    ClientConfig, ClientObserver, RequestObserver, RequestOptions, RetryPolicy, ServiceClient,
+    ServiceClientBuilder, FWAPI_DEFAULT_TIMEOUT,
 };
+use triton_core::config::TritonClientConfig;
+use triton_core::context::RequestContext;
+use triton_core::path::encode_path_segment;
+use triton_core::preflight::{PreflightCheck, PreflightResult};
+use triton_core::query::QueryPair;
+use triton_core::selection::EndpointSelector;
 use triton_core::services::{DiscoveryStatus, ServiceDiscovery, ServiceDiscoveryProxy};
-use triton_core::types::TritonService;
-use triton_core::uuid::FirewallRuleUuid;
+use triton_core::types::{EndpointList, TritonService};
+use triton_core::uuid::{FirewallRuleUuid, VmUuid};
 use triton_core::Error;
 use url::Url;
 
@@ -40,6 +52,14 @@ impl FwapiClientBuilder {
         Ok(Self { inner: builder })
     }
 
+    /// Create a builder from the FWAPI endpoint, timeout, and retry count in `config`.
+    pub fn from_config(config: &TritonClientConfig) -> Result<Self> {
+        let builder = ServiceClientBuilder::from_config(TritonService::Fwapi, config)?
+            .with_user_agent(USER_AGENT);
+
+        Ok(Self { inner: builder })
+    }
+
     /// Override the retry policy.
     #[must_use]
     pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
@@ -72,6 +92,62 @@ impl FwapiClientBuilder {
         self
     }
 
+    /// Override per-request options such as the JSON content type.
+    #[must_use]
+    pub fn with_request_options(mut self, options: RequestOptions) -> Self {
+        self.inner = self.inner.with_request_options(options);
+        self
+    }
+
+    /// Notify `observer` after every call, retries included.
+    #[must_use]
+    pub fn with_observer(mut self, observer: Arc<dyn RequestObserver>) -> Self {
+        self.inner = self.inner.with_observer(observer);
+        self
+    }
+
+    /// Invoke `observer` around every attempt, retries included.
+    #[must_use]
+    pub fn with_client_observer(mut self, observer: Arc<dyn ClientObserver>) -> Self {
+        self.inner = self.inner.with_client_observer(observer);
+        self
+    }
+
+    /// Send each call's request id in `name` instead of `x-request-id`.
+    #[must_use]
+    pub fn with_request_id_header(mut self, name: HeaderName) -> Self {
+        self.inner = self.inner.with_request_id_header(name);
+        self
+    }
+
+    /// Spread requests over `endpoints`, skipping stale and quarantined ones.
+    ///
+    /// See [`ServiceClientBuilder::with_endpoints`].
+    #[must_use]
+    pub fn with_endpoints(mut self, endpoints: EndpointList, selector: EndpointSelector) -> Self {
+        self.inner = self.inner.with_endpoints(endpoints, selector);
+        self
+    }
+
+    /// Send every request to the endpoint at `url` until it fails or is released.
+    ///
+    /// See [`ServiceClientBuilder::pin_endpoint`].
+    #[must_use]
+    pub fn pin_endpoint(mut self, url: impl Into<String>) -> Self {
+        self.inner = self.inner.pin_endpoint(url);
+        self
+    }
+
+    /// Send requests through a shared `reqwest::Client` instead of building a new one.
+    ///
+    /// The injected client's timeout, TLS and pooling settings take precedence; see
+    /// [`ServiceClientBuilder::with_http_client`].
+    #[must_use]
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
+        self.inner = self.inner.with_http_client(client);
+        self
+    }
+
     /// Build the client.
     pub fn build(self) -> Result<FwapiClient> {
         let inner = self.inner.build()?;
@@ -91,12 +167,36 @@ impl FwapiClient {
         FwapiClientBuilder::new(base_url)?.build()
     }
 
+    /// Returns a handle that tags every call with `context` for logs and observers.
+    #[must_use]
+    pub fn with_context(&self, context: RequestContext) -> Self {
+        Self {
+            inner: self.inner.with_context(context),
+        }
+    }
+
     /// Return the base URL.
     #[must_use]
     pub fn base_url(&self) -> &Url {
         self.inner.base_url()
     }
 
+    /// Identifier of the endpoint requests are pinned to, if any.
+    #[must_use]
+    pub fn pinned_endpoint(&self) -> Option<String> {
+        self.inner.pinned_endpoint()
+    }
+
+    /// Drop the endpoint pin so the next request selects afresh.
+    pub fn release_pinned_endpoint(&self) {
+        self.inner.release_pinned_endpoint();
+    }
+
+    /// Replace the endpoints requests are spread over, e.g. after rediscovery.
+    pub fn update_endpoints(&self, endpoints: EndpointList) {
+        self.inner.update_endpoints(endpoints);
+    }
+
     /// List firewall rules with optional filters.
     pub async fn list_rules(&self, params: &FirewallRuleListParams) -> Result<Vec<FirewallRule>> {
         self.send_json::<(), Vec<FirewallRule>>(Method::GET, "rules", None, &params.to_pairs())
@@ -105,7 +205,7 @@ impl FwapiClient {
 
     /// Fetch a single firewall rule by UUID.
     pub async fn get_rule(&self, uuid: FirewallRuleUuid) -> Result<FirewallRule> {
-        let path = format!("rules/{uuid}");
+        let path = format!("rules/{}", encode_path_segment(uuid)?);
         self.send_json::<(), FirewallRule>(Method::GET, &path, None, &[])
             .await
     }
@@ -122,13 +222,23 @@ impl FwapiClient {
         uuid: FirewallRuleUuid,
         request: &UpdateFirewallRuleRequest,
     ) -> Result<FirewallRule> {
-        let path = format!("rules/{uuid}");
+        let path = format!("rules/{}", encode_path_segment(uuid)?);
         self.send_json(Method::PUT, &path, Some(request), &[]).await
     }
 
+    /// Enable a firewall rule.
+    pub async fn enable_rule(&self, uuid: FirewallRuleUuid) -> Result<FirewallRule> {
+        self.set_rule_enabled(uuid, true).await
+    }
+
+    /// Disable a firewall rule without deleting it.
+    pub async fn disable_rule(&self, uuid: FirewallRuleUuid) -> Result<FirewallRule> {
+        self.set_rule_enabled(uuid, false).await
+    }
+
     /// Delete a firewall rule.
     pub async fn delete_rule(&self, uuid: FirewallRuleUuid) -> Result<()> {
-        let path = format!("rules/{uuid}");
+        let path = format!("rules/{}", encode_path_segment(uuid)?);
         self.inner
             .execute_with_retry(
                 Method::DELETE,
@@ -141,41 +251,237 @@ impl FwapiClient {
             .map(|_| ())
     }
 
+    /// List the firewall rules that apply to a VM.
+    pub async fn list_vm_rules(&self, vm_uuid: VmUuid) -> Result<Vec<FirewallRule>> {
+        let path = format!("firewalls/vms/{}", encode_path_segment(vm_uuid)?);
+        self.send_json::<(), Vec<FirewallRule>>(Method::GET, &path, None, &[])
+            .await
+    }
+
+    /// Ask FWAPI which rules, tags, and VMs apply to the VMs in `request`.
+    ///
+    /// Useful for debugging connectivity: the response lists every rule FWAPI
+    /// would install for VMs with that owner, UUIDs and tags.
+    pub async fn resolve(&self, request: &ResolveRequest) -> Result<ResolveResponse> {
+        self.send_json(Method::POST, "resolve", Some(request), &[])
+            .await
+    }
+
+    /// List the rules affecting a VM, whether they name it directly or match
+    /// it through tags or `all vms`.
+    ///
+    /// Combines `GET /rules?vm=` with FWAPI's per-VM resolution
+    /// ([`list_vm_rules`](Self::list_vm_rules)); rules returned by both appear
+    /// once, in the order first seen.
+    pub async fn list_rules_for_vm(&self, vm_uuid: VmUuid) -> Result<Vec<FirewallRule>> {
+        let params = FirewallRuleListParams {
+            vm: Some(vm_uuid),
+            ..FirewallRuleListParams::default()
+        };
+        let mut rules = self.list_rules(&params).await?;
+        let mut seen: HashSet<FirewallRuleUuid> = rules.iter().map(|rule| rule.uuid).collect();
+        for rule in self.list_vm_rules(vm_uuid).await? {
+            if seen.insert(rule.uuid) {
+                rules.push(rule);
+            }
+        }
+        Ok(rules)
+    }
+
+    /// Export the rules affecting a VM as a portable, diff-friendly document.
+    pub async fn export_vm_policy(&self, vm_uuid: VmUuid) -> Result<PolicyDocument> {
+        let rules = self.list_vm_rules(vm_uuid).await?;
+        PolicyDocument::new(vm_uuid, &rules, Utc::now())
+    }
+
+    /// Recreate or update the rules in `document`, matching on the normalized rule.
+    ///
+    /// Rules are applied one at a time and every outcome is reported alongside
+    /// its rule text; a failure does not stop the remaining rules. Rules present
+    /// on the VM but absent from the document are left untouched.
+    pub async fn import_vm_policy(
+        &self,
+        document: &PolicyDocument,
+    ) -> Result<Vec<(String, Result<RuleImportOutcome>)>> {
+        let current = self.list_vm_rules(document.vm_uuid).await?;
+        let mut results = Vec::with_capacity(document.rules.len());
+
+        for rule in &document.rules {
+            let outcome = self.import_rule(rule, &current).await;
+            results.push((rule.rule.to_string(), outcome));
+        }
+
+        Ok(results)
+    }
+
+    /// Plan the changes that converge the rules in `scope` to `desired`.
+    ///
+    /// Rules are matched on their normalized parse, so keyword case and port
+    /// or target order do not matter. Scope defaults (owner, global, VM)
+    /// are filled into the desired rules before diffing, and only rules the
+    /// scope [owns](RuleScope::owns) are updated or deleted.
+    pub async fn reconcile_rules(
+        &self,
+        desired: Vec<ParsedRule>,
+        scope: RuleScope,
+    ) -> Result<ReconcilePlan> {
+        let mut current = match scope {
+            RuleScope::Vm { vm_uuid, .. } => self.list_vm_rules(vm_uuid).await?,
+            RuleScope::Owner(owner_uuid) => {
+                let params = FirewallRuleListParams {
+                    owner_uuid: Some(owner_uuid),
+                    ..FirewallRuleListParams::default()
+                };
+                self.list_rules(&params).await?
+            }
+            RuleScope::Global => {
+                let params = FirewallRuleListParams {
+                    global: Some(true),
+                    ..FirewallRuleListParams::default()
+                };
+                self.list_rules(&params).await?
+            }
+        };
+
+        current.retain(|rule| scope.owns(rule));
+
+        let desired: Vec<ParsedRule> = desired
+            .into_iter()
+            .map(|rule| scope.apply_to(rule))
+            .collect();
+        Ok(ReconcilePlan::new(&desired, &current))
+    }
+
+    /// Execute a plan, reporting the outcome for each rule.
+    ///
+    /// Creates run first, then updates, then deletes. A failed operation does
+    /// not stop the rest of the plan.
+    pub async fn apply_plan(&self, plan: &ReconcilePlan) -> Vec<(String, Result<RuleChange>)> {
+        let mut results =
+            Vec::with_capacity(plan.to_create.len() + plan.to_update.len() + plan.to_delete.len());
+
+        for rule in &plan.to_create {
+            let outcome = self
+                .create_rule(&rule.to_create_request())
+                .await
+                .map(RuleChange::Created);
+            results.push((rule.rule.to_string(), outcome));
+        }
+        for (existing, rule) in &plan.to_update {
+            let outcome = self
+                .update_rule(existing.uuid, &rule.to_update_request())
+                .await
+                .map(RuleChange::Updated);
+            results.push((rule.rule.to_string(), outcome));
+        }
+        for existing in &plan.to_delete {
+            let outcome = self
+                .delete_rule(existing.uuid)
+                .await
+                .map(|()| RuleChange::Deleted(existing.uuid));
+            results.push((existing.rule.clone(), outcome));
+        }
+
+        results
+    }
+
+    async fn set_rule_enabled(
+        &self,
+        uuid: FirewallRuleUuid,
+        enabled: bool,
+    ) -> Result<FirewallRule> {
+        let request = UpdateFirewallRuleRequest {
+            enabled: Some(enabled),
+            ..UpdateFirewallRuleRequest::default()
+        };
+        self.update_rule(uuid, &request).await
+    }
+
+    async fn import_rule(
+        &self,
+        rule: &ParsedRule,
+        current: &[FirewallRule],
+    ) -> Result<RuleImportOutcome> {
+        let Some(existing) = current.iter().find(|existing| rule.matches(existing)) else {
+            return self
+                .create_rule(&rule.to_create_request())
+                .await
+                .map(RuleImportOutcome::Created);
+        };
+
+        if rule.settings_match(existing) {
+            return Ok(RuleImportOutcome::Unchanged(existing.clone()));
+        }
+
+        self.update_rule(existing.uuid, &rule.to_update_request())
+            .await
+            .map(RuleImportOutcome::Updated)
+    }
+
+    /// Fetch any FWAPI path as raw JSON; see [`ServiceClient::get_raw`].
+    pub async fn get_raw(&self, path: &str, params: &[QueryPair]) -> Result<serde_json::Value> {
+        self.inner.get_raw(path, params, map_status_to_error).await
+    }
+
+    /// Send an arbitrary request to FWAPI and return the raw JSON response;
+    /// see [`ServiceClient::send_raw`].
+    pub async fn send_raw(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        self.inner
+            .send_raw(method, path, body, &[], map_status_to_error)
+            .await
+    }
+
     async fn send_json<B, R>(
         &self,
         method: Method,
         path: &str,
         body: Option<&B>,
-        params: &[(&'static str, String)],
+        params: &[QueryPair],
     ) -> Result<R>
     where
         B: Serialize + ?Sized,
         R: DeserializeOwned,
     {
-        let response = self
-            .inner
-            .execute_with_retry(
+        self.inner
+            .execute_json_with_retry(
                 method,
                 path,
                 params,
                 |mut request| {
                     request = request.header("Accept", "application/json");
                     if let Some(payload) = body {
-                        request = request.json(payload);
+                        request = self.inner.request_options().json_body(request, payload);
                     }
                     request
                 },
                 map_status_to_error,
             )
-            .await?;
+            .await
+    }
+}
 
-        response.json::<R>().await.map_err(Error::from)
+#[async_trait]
+impl PreflightCheck for FwapiClient {
+    fn service(&self) -> TritonService {
+        TritonService::Fwapi
+    }
+
+    async fn preflight(&self) -> PreflightResult {
+        self.inner
+            .preflight("rules", &[("limit".into(), "1".to_string())])
+            .await
     }
 }
 
 fn map_status_to_error(status: StatusCode, text: String) -> Error {
     match status {
         StatusCode::NOT_FOUND => Error::NotFound(text),
+        StatusCode::PAYLOAD_TOO_LARGE => Error::PayloadTooLarge(text),
         StatusCode::BAD_REQUEST => Error::BadRequest(text),
         StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
             Error::InvalidRequest(format!("FWAPI authentication failed: {text}"))
@@ -190,7 +496,7 @@ fn map_status_to_error(status: StatusCode, text: String) -> Error {
         status if status.is_server_error() => {
             Error::ServiceUnavailable(format!("FWAPI server error {status}: {text}"))
         }
-        _ => Error::HttpError(format!("FWAPI error {status}: {text}")),
+        _ => Error::http(status, format!("FWAPI error {status}: {text}")),
     }
 }
 
@@ -232,7 +538,8 @@ impl ServiceDiscovery for FwapiDiscovery {
 mod tests {
     use super::*;
     use serde_json::json;
-    use wiremock::matchers::{body_json, method, path};
+    use triton_core::uuid::OwnerUuid;
+    use wiremock::matchers::{body_json, method, path, query_param};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     fn test_client(server: &MockServer) -> FwapiClient {
@@ -264,6 +571,123 @@ mod tests {
         assert!(rules[0].enabled);
     }
 
+    #[tokio::test]
+    async fn resolve_posts_request_and_parses_rules() {
+        let server = MockServer::start().await;
+        let owner = OwnerUuid::new_v4();
+        let vm = VmUuid::new_v4();
+        let peer = VmUuid::new_v4();
+        let rule_uuid = FirewallRuleUuid::new_v4();
+
+        Mock::given(method("POST"))
+            .and(path("/resolve"))
+            .and(body_json(json!({
+                "owner_uuid": owner,
+                "vms": [vm],
+                "tags": { "role": "web", "monitored": true }
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "allVMs": false,
+                "owner_uuid": owner,
+                "rules": [{
+                    "uuid": rule_uuid,
+                    "rule": format!("FROM vm {peer} TO tag \"role\" = \"web\" ALLOW tcp PORT 80"),
+                    "enabled": true,
+                    "version": "1383205115597.067782",
+                    "owner_uuid": owner
+                }],
+                "tags": { "role": ["web"] },
+                "vms": [peer]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let request = ResolveRequest::new(owner)
+            .with_vm(vm)
+            .with_tag("role", "web")
+            .with_tag("monitored", true);
+        let resolved = test_client(&server).resolve(&request).await.unwrap();
+
+        assert_eq!(resolved.owner_uuid, Some(owner));
+        assert!(!resolved.all_vms);
+        assert_eq!(resolved.rules.len(), 1);
+        assert_eq!(resolved.rules[0].uuid, rule_uuid);
+        assert_eq!(resolved.tags["role"], json!(["web"]));
+        assert_eq!(resolved.vms, vec![peer]);
+    }
+
+    #[tokio::test]
+    async fn enable_and_disable_rule_toggle_enabled_flag() {
+        let server = MockServer::start().await;
+        let uuid = FirewallRuleUuid::new_v4();
+        let rule = |enabled: bool| {
+            json!({
+                "uuid": uuid,
+                "rule": "FROM any TO all vms ALLOW tcp PORT 22",
+                "enabled": enabled,
+                "version": "2"
+            })
+        };
+
+        for enabled in [true, false] {
+            Mock::given(method("PUT"))
+                .and(path(format!("/rules/{uuid}").as_str()))
+                .and(body_json(json!({ "enabled": enabled })))
+                .respond_with(ResponseTemplate::new(200).set_body_json(rule(enabled)))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let client = test_client(&server);
+        assert!(client.enable_rule(uuid).await.unwrap().enabled);
+        assert!(!client.disable_rule(uuid).await.unwrap().enabled);
+    }
+
+    #[tokio::test]
+    async fn list_rules_for_vm_includes_tag_matches() {
+        let server = MockServer::start().await;
+        let vm = VmUuid::new_v4();
+        let direct_text = format!("FROM any TO vm {vm} ALLOW tcp PORT 22");
+        let direct = json!({
+            "uuid": FirewallRuleUuid::new_v4(),
+            "rule": direct_text,
+            "enabled": true,
+            "version": "1"
+        });
+        let tagged = json!({
+            "uuid": FirewallRuleUuid::new_v4(),
+            "rule": "FROM any TO tag \"role\" = \"web\" ALLOW tcp PORT 80",
+            "enabled": true,
+            "version": "1"
+        });
+
+        Mock::given(method("GET"))
+            .and(path("/rules"))
+            .and(query_param("vm", vm.to_string().as_str()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([direct])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/firewalls/vms/{vm}").as_str()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([tagged, direct])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let rules = test_client(&server).list_rules_for_vm(vm).await.unwrap();
+        let texts: Vec<_> = rules.iter().map(|rule| rule.rule.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec![
+                direct_text.as_str(),
+                "FROM any TO tag \"role\" = \"web\" ALLOW tcp PORT 80",
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn get_firewall_rule_not_found() {
         let server = MockServer::start().await;
@@ -356,6 +780,112 @@ mod tests {
         client.delete_rule(uuid).await.unwrap();
     }
 
+    #[tokio::test]
+    async fn vm_policy_round_trips_through_export_and_import() {
+        let source = MockServer::start().await;
+        let vm_uuid = VmUuid::new_v4();
+        let vm_path = format!("/firewalls/vms/{vm_uuid}");
+
+        Mock::given(method("GET"))
+            .and(path(vm_path.as_str()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                {
+                    "uuid": FirewallRuleUuid::new_v4(),
+                    "rule": "FROM any TO all vms ALLOW tcp  PORT 443",
+                    "enabled": true,
+                    "version": "1",
+                    "description": "https"
+                },
+                {
+                    "uuid": FirewallRuleUuid::new_v4(),
+                    "rule": "FROM any TO all vms ALLOW tcp PORT 22",
+                    "enabled": false,
+                    "version": "1"
+                }
+            ])))
+            .mount(&source)
+            .await;
+
+        let document = test_client(&source)
+            .export_vm_policy(vm_uuid)
+            .await
+            .unwrap();
+        assert_eq!(document.vm_uuid, vm_uuid);
+        assert_eq!(document.rules.len(), 2);
+        assert_eq!(
+            document.rules[0].rule.to_string(),
+            "FROM any TO all vms ALLOW tcp PORT 22"
+        );
+        assert_eq!(
+            document.rules[1].rule.to_string(),
+            "FROM any TO all vms ALLOW tcp PORT 443"
+        );
+
+        let serialized = serde_json::to_string_pretty(&document).unwrap();
+        let restored: PolicyDocument = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(restored, document);
+
+        // The target already has the SSH rule, but enabled and with a
+        // description the document does not have; HTTPS is missing.
+        let target = MockServer::start().await;
+        let ssh_uuid = FirewallRuleUuid::new_v4();
+        Mock::given(method("GET"))
+            .and(path(vm_path.as_str()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                {
+                    "uuid": ssh_uuid,
+                    "rule": "FROM any TO all vms ALLOW tcp PORT 22",
+                    "enabled": true,
+                    "version": "1",
+                    "description": "legacy ssh"
+                }
+            ])))
+            .mount(&target)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path(format!("/rules/{ssh_uuid}").as_str()))
+            .and(body_json(json!({ "enabled": false, "description": "" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "uuid": ssh_uuid,
+                "rule": "FROM any TO all vms ALLOW tcp PORT 22",
+                "enabled": false,
+                "version": "2",
+                "description": ""
+            })))
+            .expect(1)
+            .mount(&target)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/rules"))
+            .and(body_json(json!({
+                "rule": "FROM any TO all vms ALLOW tcp PORT 443",
+                "enabled": true,
+                "description": "https"
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                "uuid": FirewallRuleUuid::new_v4(),
+                "rule": "FROM any TO all vms ALLOW tcp PORT 443",
+                "enabled": true,
+                "version": "1",
+                "description": "https"
+            })))
+            .expect(1)
+            .mount(&target)
+            .await;
+
+        let results = test_client(&target)
+            .import_vm_policy(&restored)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(matches!(
+            results[0].1,
+            Ok(RuleImportOutcome::Updated(ref rule))
+                if !rule.enabled && restored.rules[0].settings_match(rule)
+        ));
+        assert!(matches!(results[1].1, Ok(RuleImportOutcome::Created(_))));
+    }
+
     struct MockDiscovery;
 
     #[async_trait]
@@ -375,6 +905,149 @@ mod tests {
         fn clear_cache(&self) {}
     }
 
+    #[tokio::test]
+    async fn reconcile_rules_plans_and_applies_changes() {
+        let server = MockServer::start().await;
+        let owner = OwnerUuid::new_v4();
+        let stale_uuid = FirewallRuleUuid::new_v4();
+
+        Mock::given(method("GET"))
+            .and(path("/rules"))
+            .and(query_param("owner_uuid", owner.to_string().as_str()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                {
+                    "uuid": FirewallRuleUuid::new_v4(),
+                    "rule": "FROM any TO all vms ALLOW tcp PORT 22",
+                    "enabled": true,
+                    "version": "1",
+                    "owner_uuid": owner
+                },
+                {
+                    "uuid": stale_uuid,
+                    "rule": "FROM any TO all vms ALLOW tcp PORT 8080",
+                    "enabled": true,
+                    "version": "1",
+                    "owner_uuid": owner
+                }
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/rules"))
+            .and(body_json(json!({
+                "rule": "FROM any TO all vms ALLOW tcp PORT 443",
+                "enabled": true,
+                "owner_uuid": owner
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "uuid": FirewallRuleUuid::new_v4(),
+                "rule": "FROM any TO all vms ALLOW tcp PORT 443",
+                "enabled": true,
+                "version": "1",
+                "owner_uuid": owner
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path(format!("/rules/{stale_uuid}").as_str()))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let desired = vec![
+            ParsedRule::new("FROM any TO all vms ALLOW tcp PORT 22").unwrap(),
+            ParsedRule::new("FROM any TO all vms ALLOW tcp PORT 443").unwrap(),
+        ];
+        let plan = client
+            .reconcile_rules(desired, RuleScope::Owner(owner))
+            .await
+            .unwrap();
+        assert_eq!(plan.to_create.len(), 1);
+        assert_eq!(plan.to_create[0].owner_uuid, Some(owner));
+        assert!(plan.to_update.is_empty());
+        assert_eq!(plan.to_delete.len(), 1);
+
+        let results = client.apply_plan(&plan).await;
+        assert_eq!(results.len(), 2);
+        assert!(matches!(results[0].1, Ok(RuleChange::Created(_))));
+        assert!(matches!(results[1].1, Ok(RuleChange::Deleted(uuid)) if uuid == stale_uuid));
+    }
+
+    #[tokio::test]
+    async fn reconcile_rules_in_vm_scope_leaves_shared_rules_alone() {
+        let server = MockServer::start().await;
+        let owner = OwnerUuid::new_v4();
+        let vm_uuid = VmUuid::new_v4();
+        let stale_uuid = FirewallRuleUuid::new_v4();
+        let vm_path = format!("/firewalls/vms/{vm_uuid}");
+
+        Mock::given(method("GET"))
+            .and(path(vm_path.as_str()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                {
+                    "uuid": FirewallRuleUuid::new_v4(),
+                    "rule": "FROM any TO all vms ALLOW icmp TYPE 8",
+                    "enabled": true,
+                    "version": "1",
+                    "global": true
+                },
+                {
+                    "uuid": FirewallRuleUuid::new_v4(),
+                    "rule": "FROM any TO all vms ALLOW tcp PORT 80",
+                    "enabled": true,
+                    "version": "1",
+                    "owner_uuid": owner
+                },
+                {
+                    "uuid": FirewallRuleUuid::new_v4(),
+                    "rule": "FROM any TO tag \"role\" = \"web\" ALLOW tcp PORT 443",
+                    "enabled": true,
+                    "version": "1",
+                    "owner_uuid": owner
+                },
+                {
+                    "uuid": FirewallRuleUuid::new_v4(),
+                    "rule": format!("FROM vm {vm_uuid} TO any ALLOW tcp PORT 25"),
+                    "enabled": true,
+                    "version": "1",
+                    "owner_uuid": OwnerUuid::new_v4()
+                },
+                {
+                    "uuid": stale_uuid,
+                    "rule": format!("FROM any TO vm {vm_uuid} ALLOW tcp PORT 8080"),
+                    "enabled": true,
+                    "version": "1",
+                    "owner_uuid": owner
+                }
+            ])))
+            .mount(&server)
+            .await;
+
+        let desired =
+            vec![ParsedRule::new(format!("FROM any TO vm {vm_uuid} ALLOW tcp PORT 22")).unwrap()];
+        let plan = test_client(&server)
+            .reconcile_rules(
+                desired,
+                RuleScope::Vm {
+                    owner_uuid: owner,
+                    vm_uuid,
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(plan.to_create.len(), 1);
+        let request = plan.to_create[0].to_create_request();
+        assert_eq!(request.owner_uuid, Some(owner));
+        assert_eq!(request.vms, Some(vec![OwnerUuid::new(vm_uuid.into_uuid())]));
+        assert!(plan.to_update.is_empty());
+        let deleted: Vec<_> = plan.to_delete.iter().map(|rule| rule.uuid).collect();
+        assert_eq!(deleted, vec![stale_uuid]);
+    }
+
     #[tokio::test]
     async fn fwapi_discovery_delegates_to_proxy() {
         let discovery = Arc::new(MockDiscovery);
@@ -0,0 +1,1221 @@
+//! Parser and typed syntax tree for the FWAPI rule language.
+//!
+//! Covers the subset of the language FWAPI rules are written in day to day:
+//!
+//! ```text
+//! FROM <targets> TO <targets> ALLOW|BLOCK tcp|udp <ports>
+//! FROM <targets> TO <targets> ALLOW|BLOCK icmp|icmp6 TYPE <n|all> [CODE <n>]
+//! ```
+//!
+//! where `<targets>` is a single target or `( target OR target ... )`, a
+//! target is `any`, `all vms`, `vm <uuid>`, `ip <addr>`, `subnet <cidr>` or
+//! `tag <name> [= <value>]`, and `<ports>` is `PORT all`, `PORT <n>`,
+//! `PORTS <n|n-m>, ...` or `( PORT <n> AND PORT <n> ... )`. Keywords are
+//! case-insensitive. [`RuleAst`]'s `Display` output parses back to an equal
+//! tree, and [`RuleAst::normalized`] orders targets and ports so that
+//! equivalent rules compare equal.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+use triton_core::uuid::VmUuid;
+use triton_core::Error;
+
+use crate::models::CreateFirewallRuleRequest;
+use crate::Result;
+
+/// Parsed form of an FWAPI rule string.
+///
+/// Serializes as its canonical rule text.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct RuleAst {
+    /// Traffic sources.
+    pub from: Vec<Target>,
+    /// Traffic destinations.
+    pub to: Vec<Target>,
+    /// Whether matching traffic is allowed or blocked.
+    pub action: Action,
+    /// Protocol the rule applies to.
+    pub protocol: Protocol,
+    /// Ports (TCP/UDP) or message type (ICMP) the rule applies to.
+    pub ports: PortSpec,
+}
+
+/// One side of a rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// Any host.
+    Any,
+    /// Every VM owned by the rule's owner.
+    AllVms,
+    /// A single VM.
+    Vm(VmUuid),
+    /// A single address.
+    Ip(IpAddr),
+    /// An address range in CIDR form.
+    Subnet {
+        /// Network address.
+        addr: IpAddr,
+        /// Prefix length.
+        prefix: u8,
+    },
+    /// VMs carrying a tag, optionally with a specific value.
+    Tag {
+        /// Tag name.
+        name: String,
+        /// Required tag value; `None` matches any value.
+        value: Option<String>,
+    },
+}
+
+/// Rule action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Allow matching traffic.
+    Allow,
+    /// Block matching traffic.
+    Block,
+}
+
+/// Rule protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    /// TCP.
+    Tcp,
+    /// UDP.
+    Udp,
+    /// ICMP.
+    Icmp,
+    /// ICMPv6.
+    Icmp6,
+}
+
+impl Protocol {
+    /// Returns the rule-language spelling.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Tcp => "tcp",
+            Self::Udp => "udp",
+            Self::Icmp => "icmp",
+            Self::Icmp6 => "icmp6",
+        }
+    }
+
+    /// Returns true for ICMP and ICMPv6.
+    #[must_use]
+    pub const fn is_icmp(&self) -> bool {
+        matches!(self, Self::Icmp | Self::Icmp6)
+    }
+}
+
+/// Ports or ICMP type a rule matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PortSpec {
+    /// Every port, or every ICMP type.
+    All,
+    /// The listed TCP/UDP ports and ranges.
+    Ports(Vec<PortRange>),
+    /// A single ICMP type, optionally narrowed to one code.
+    IcmpType {
+        /// ICMP type.
+        icmp_type: u8,
+        /// ICMP code.
+        code: Option<u8>,
+    },
+}
+
+/// Inclusive port range; a single port has `start == end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PortRange {
+    /// First port.
+    pub start: u16,
+    /// Last port.
+    pub end: u16,
+}
+
+impl PortRange {
+    /// A range covering one port.
+    #[must_use]
+    pub const fn single(port: u16) -> Self {
+        Self {
+            start: port,
+            end: port,
+        }
+    }
+}
+
+/// Parse an FWAPI rule string.
+///
+/// Returns [`Error::ValidationError`] describing the first problem found.
+pub fn parse_rule(input: &str) -> Result<RuleAst> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let rule = parser.rule()?;
+    match parser.peek() {
+        None => Ok(rule),
+        Some(token) => Err(invalid(format!("unexpected trailing {token}"))),
+    }
+}
+
+impl RuleAst {
+    /// Returns the rule with sources, destinations and ports sorted and
+    /// duplicates removed.
+    ///
+    /// Two rules FWAPI treats the same, such as `PORTS 443, 80` and
+    /// `PORTS 80, 443`, are equal once normalized.
+    #[must_use]
+    pub fn normalized(mut self) -> Self {
+        for targets in [&mut self.from, &mut self.to] {
+            targets.sort_by_cached_key(ToString::to_string);
+            targets.dedup();
+        }
+        if let PortSpec::Ports(ports) = &mut self.ports {
+            ports.sort_unstable();
+            ports.dedup();
+        }
+        self
+    }
+}
+
+impl FromStr for RuleAst {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        parse_rule(input)
+    }
+}
+
+impl TryFrom<String> for RuleAst {
+    type Error = Error;
+
+    fn try_from(input: String) -> Result<Self> {
+        parse_rule(&input)
+    }
+}
+
+impl From<RuleAst> for String {
+    fn from(rule: RuleAst) -> Self {
+        rule.to_string()
+    }
+}
+
+impl fmt::Display for RuleAst {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FROM ")?;
+        write_targets(f, &self.from)?;
+        write!(f, " TO ")?;
+        write_targets(f, &self.to)?;
+        write!(f, " {} {} ", self.action, self.protocol)?;
+        match &self.ports {
+            PortSpec::All if self.protocol.is_icmp() => write!(f, "TYPE all"),
+            PortSpec::All => write!(f, "PORT all"),
+            PortSpec::Ports(ports) => match ports.as_slice() {
+                [port] if port.start == port.end => write!(f, "PORT {}", port.start),
+                _ => {
+                    write!(f, "PORTS ")?;
+                    for (index, port) in ports.iter().enumerate() {
+                        if index > 0 {
+                            write!(f, ", ")?;
+                        }
+                        write!(f, "{port}")?;
+                    }
+                    Ok(())
+                }
+            },
+            PortSpec::IcmpType { icmp_type, code } => {
+                write!(f, "TYPE {icmp_type}")?;
+                match code {
+                    Some(code) => write!(f, " CODE {code}"),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+fn write_targets(f: &mut fmt::Formatter<'_>, targets: &[Target]) -> fmt::Result {
+    match targets {
+        [target] => write!(f, "{target}"),
+        _ => {
+            write!(f, "(")?;
+            for (index, target) in targets.iter().enumerate() {
+                if index > 0 {
+                    write!(f, " OR ")?;
+                }
+                write!(f, "{target}")?;
+            }
+            write!(f, ")")
+        }
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Any => write!(f, "any"),
+            Self::AllVms => write!(f, "all vms"),
+            Self::Vm(uuid) => write!(f, "vm {uuid}"),
+            Self::Ip(addr) => write!(f, "ip {addr}"),
+            Self::Subnet { addr, prefix } => write!(f, "subnet {addr}/{prefix}"),
+            Self::Tag { name, value } => {
+                write!(f, "tag {}", Quoted(name))?;
+                match value {
+                    Some(value) => write!(f, " = {}", Quoted(value)),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Allow => "ALLOW",
+            Self::Block => "BLOCK",
+        })
+    }
+}
+
+impl fmt::Display for Protocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for PortRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.start == self.end {
+            write!(f, "{}", self.start)
+        } else {
+            write!(f, "{}-{}", self.start, self.end)
+        }
+    }
+}
+
+/// Typed builder producing canonical FWAPI rule text.
+///
+/// ```
+/// use triton_fwapi::fwrule::{Protocol, RuleBuilder};
+///
+/// let rule = RuleBuilder::new()
+///     .from_tag_value("role", "web")
+///     .to_tag_value("role", "db")
+///     .allow(Protocol::Tcp)
+///     .ports([5432])
+///     .to_rule_string()?;
+/// assert_eq!(rule, r#"FROM tag "role" = "web" TO tag "role" = "db" ALLOW tcp PORT 5432"#);
+/// # Ok::<(), triton_core::Error>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuleBuilder {
+    from: Vec<Target>,
+    to: Vec<Target>,
+    action: Option<(Action, Protocol)>,
+    ports: Option<PortSpec>,
+}
+
+// `from_*`/`to_*` read as the rule's FROM/TO clauses here, not as conversions.
+#[allow(clippy::wrong_self_convention)]
+impl RuleBuilder {
+    /// Create an empty builder.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a source; several sources are joined with `OR`.
+    #[must_use]
+    pub fn from(mut self, target: Target) -> Self {
+        self.from.push(target);
+        self
+    }
+
+    /// Add `any` as a source.
+    #[must_use]
+    pub fn from_any(self) -> Self {
+        self.from(Target::Any)
+    }
+
+    /// Add `all vms` as a source.
+    #[must_use]
+    pub fn from_all_vms(self) -> Self {
+        self.from(Target::AllVms)
+    }
+
+    /// Add a VM as a source.
+    #[must_use]
+    pub fn from_vm(self, uuid: VmUuid) -> Self {
+        self.from(Target::Vm(uuid))
+    }
+
+    /// Add an address as a source.
+    #[must_use]
+    pub fn from_ip(self, addr: IpAddr) -> Self {
+        self.from(Target::Ip(addr))
+    }
+
+    /// Add a subnet as a source.
+    #[must_use]
+    pub fn from_subnet(self, addr: IpAddr, prefix: u8) -> Self {
+        self.from(Target::Subnet { addr, prefix })
+    }
+
+    /// Add VMs carrying `name` as a source.
+    #[must_use]
+    pub fn from_tag(self, name: impl Into<String>) -> Self {
+        self.from(Target::Tag {
+            name: name.into(),
+            value: None,
+        })
+    }
+
+    /// Add VMs whose `name` tag equals `value` as a source.
+    #[must_use]
+    pub fn from_tag_value(self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.from(Target::Tag {
+            name: name.into(),
+            value: Some(value.into()),
+        })
+    }
+
+    /// Add a destination; several destinations are joined with `OR`.
+    #[must_use]
+    pub fn to(mut self, target: Target) -> Self {
+        self.to.push(target);
+        self
+    }
+
+    /// Add `any` as a destination.
+    #[must_use]
+    pub fn to_any(self) -> Self {
+        self.to(Target::Any)
+    }
+
+    /// Add `all vms` as a destination.
+    #[must_use]
+    pub fn to_all_vms(self) -> Self {
+        self.to(Target::AllVms)
+    }
+
+    /// Add a VM as a destination.
+    #[must_use]
+    pub fn to_vm(self, uuid: VmUuid) -> Self {
+        self.to(Target::Vm(uuid))
+    }
+
+    /// Add an address as a destination.
+    #[must_use]
+    pub fn to_ip(self, addr: IpAddr) -> Self {
+        self.to(Target::Ip(addr))
+    }
+
+    /// Add a subnet as a destination.
+    #[must_use]
+    pub fn to_subnet(self, addr: IpAddr, prefix: u8) -> Self {
+        self.to(Target::Subnet { addr, prefix })
+    }
+
+    /// Add VMs carrying `name` as a destination.
+    #[must_use]
+    pub fn to_tag(self, name: impl Into<String>) -> Self {
+        self.to(Target::Tag {
+            name: name.into(),
+            value: None,
+        })
+    }
+
+    /// Add VMs whose `name` tag equals `value` as a destination.
+    #[must_use]
+    pub fn to_tag_value(self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.to(Target::Tag {
+            name: name.into(),
+            value: Some(value.into()),
+        })
+    }
+
+    /// Allow `protocol` traffic.
+    #[must_use]
+    pub fn allow(mut self, protocol: Protocol) -> Self {
+        self.action = Some((Action::Allow, protocol));
+        self
+    }
+
+    /// Block `protocol` traffic.
+    #[must_use]
+    pub fn block(mut self, protocol: Protocol) -> Self {
+        self.action = Some((Action::Block, protocol));
+        self
+    }
+
+    /// Match the given ports, in addition to any added earlier.
+    #[must_use]
+    pub fn ports(self, ports: impl IntoIterator<Item = u16>) -> Self {
+        ports
+            .into_iter()
+            .fold(self, |builder, port| builder.port_range(port, port))
+    }
+
+    /// Match an inclusive port range, in addition to any added earlier.
+    #[must_use]
+    pub fn port_range(mut self, start: u16, end: u16) -> Self {
+        let range = PortRange { start, end };
+        match &mut self.ports {
+            Some(PortSpec::Ports(ports)) => ports.push(range),
+            _ => self.ports = Some(PortSpec::Ports(vec![range])),
+        }
+        self
+    }
+
+    /// Match every port, or every ICMP type.
+    #[must_use]
+    pub fn all_ports(mut self) -> Self {
+        self.ports = Some(PortSpec::All);
+        self
+    }
+
+    /// Match an ICMP type, optionally narrowed to one code.
+    #[must_use]
+    pub fn icmp_type(mut self, icmp_type: u8, code: Option<u8>) -> Self {
+        self.ports = Some(PortSpec::IcmpType { icmp_type, code });
+        self
+    }
+
+    /// Validate the rule and return its syntax tree.
+    ///
+    /// Fails with [`Error::ValidationError`] when a source, destination,
+    /// action or port selection is missing, when a port is 0 or a range is
+    /// reversed, or when ports and protocol disagree (ports for ICMP, an ICMP
+    /// type for TCP/UDP).
+    pub fn build(&self) -> Result<RuleAst> {
+        if self.from.is_empty() {
+            return Err(invalid("at least one source is required"));
+        }
+        if self.to.is_empty() {
+            return Err(invalid("at least one destination is required"));
+        }
+        let (action, protocol) = self
+            .action
+            .ok_or_else(|| invalid("an action (`allow` or `block`) is required"))?;
+        let ports = self.ports.clone().ok_or_else(|| {
+            invalid(if protocol.is_icmp() {
+                "an ICMP type is required"
+            } else {
+                "ports are required"
+            })
+        })?;
+
+        match (&ports, protocol.is_icmp()) {
+            (PortSpec::All, _) | (PortSpec::IcmpType { .. }, true) => {}
+            (PortSpec::Ports(ports), false) => {
+                for range in ports {
+                    if range.start == 0 {
+                        return Err(invalid("port 0 is not a valid port"));
+                    }
+                    if range.start > range.end {
+                        return Err(invalid(format!("port range `{range}` is reversed")));
+                    }
+                }
+            }
+            (PortSpec::Ports(_), true) => {
+                return Err(invalid(format!("{protocol} rules take a type, not ports")));
+            }
+            (PortSpec::IcmpType { .. }, false) => {
+                return Err(invalid(format!("{protocol} rules take ports, not a type")));
+            }
+        }
+
+        Ok(RuleAst {
+            from: self.from.clone(),
+            to: self.to.clone(),
+            action,
+            protocol,
+            ports,
+        })
+    }
+
+    /// Validate the rule and render its canonical text.
+    pub fn to_rule_string(&self) -> Result<String> {
+        self.build().map(|rule| rule.to_string())
+    }
+
+    /// Validate the rule and wrap it in an enabled create request.
+    pub fn to_create_request(&self) -> Result<CreateFirewallRuleRequest> {
+        Ok(CreateFirewallRuleRequest {
+            rule: self.to_rule_string()?,
+            enabled: Some(true),
+            description: None,
+            owner_uuid: None,
+            global: None,
+            vms: None,
+        })
+    }
+}
+
+struct Quoted<'a>(&'a str);
+
+impl fmt::Display for Quoted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"")?;
+        for c in self.0.chars() {
+            if c == '"' || c == '\\' {
+                write!(f, "\\")?;
+            }
+            write!(f, "{c}")?;
+        }
+        write!(f, "\"")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    LParen,
+    RParen,
+    Comma,
+    Equals,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Word(word) => write!(f, "`{word}`"),
+            Self::Quoted(text) => write!(f, "{}", Quoted(text)),
+            Self::LParen => write!(f, "`(`"),
+            Self::RParen => write!(f, "`)`"),
+            Self::Comma => write!(f, "`,`"),
+            Self::Equals => write!(f, "`=`"),
+        }
+    }
+}
+
+fn invalid(message: impl fmt::Display) -> Error {
+    Error::ValidationError(format!("invalid firewall rule: {message}"))
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' | ',' | '=' => {
+                chars.next();
+                tokens.push(match c {
+                    '(' => Token::LParen,
+                    ')' => Token::RParen,
+                    ',' => Token::Comma,
+                    _ => Token::Equals,
+                });
+            }
+            '"' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(escaped) => text.push(escaped),
+                            None => return Err(invalid("unterminated quoted string")),
+                        },
+                        Some(c) => text.push(c),
+                        None => return Err(invalid("unterminated quoted string")),
+                    }
+                }
+                tokens.push(Token::Quoted(text));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || matches!(c, '(' | ')' | ',' | '=' | '"') {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn advance(&mut self, expected: &str) -> Result<Token> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| invalid(format!("expected {expected}, found end of rule")))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if self.peek_keyword(keyword) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &Token) -> Result<()> {
+        let found = self.advance(&token.to_string())?;
+        if &found == token {
+            Ok(())
+        } else {
+            Err(invalid(format!("expected {token}, found {found}")))
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<()> {
+        let expected = format!("`{keyword}`");
+        match self.advance(&expected)? {
+            Token::Word(word) if word.eq_ignore_ascii_case(keyword) => Ok(()),
+            found => Err(invalid(format!("expected {expected}, found {found}"))),
+        }
+    }
+
+    fn word(&mut self, expected: &str) -> Result<String> {
+        match self.advance(expected)? {
+            Token::Word(word) => Ok(word),
+            found => Err(invalid(format!("expected {expected}, found {found}"))),
+        }
+    }
+
+    fn text(&mut self, expected: &str) -> Result<String> {
+        match self.advance(expected)? {
+            Token::Word(text) | Token::Quoted(text) => Ok(text),
+            found => Err(invalid(format!("expected {expected}, found {found}"))),
+        }
+    }
+
+    fn rule(&mut self) -> Result<RuleAst> {
+        self.expect_keyword("FROM")?;
+        let from = self.targets()?;
+        self.expect_keyword("TO")?;
+        let to = self.targets()?;
+        let action = self.action()?;
+        let protocol = self.protocol()?;
+        let ports = if protocol.is_icmp() {
+            self.icmp_type()?
+        } else {
+            self.ports()?
+        };
+        Ok(RuleAst {
+            from,
+            to,
+            action,
+            protocol,
+            ports,
+        })
+    }
+
+    fn targets(&mut self) -> Result<Vec<Target>> {
+        if !self.eat(&Token::LParen) {
+            return Ok(vec![self.target()?]);
+        }
+        let mut targets = vec![self.target()?];
+        while self.eat_keyword("OR") {
+            targets.push(self.target()?);
+        }
+        self.expect(&Token::RParen)?;
+        Ok(targets)
+    }
+
+    fn target(&mut self) -> Result<Target> {
+        let keyword = self.word("a target")?;
+        match keyword.to_ascii_lowercase().as_str() {
+            "any" => Ok(Target::Any),
+            "all" => {
+                self.expect_keyword("vms")?;
+                Ok(Target::AllVms)
+            }
+            "vm" => {
+                let uuid = self.word("a VM UUID")?;
+                uuid.parse()
+                    .map(Target::Vm)
+                    .map_err(|_| invalid(format!("`{uuid}` is not a VM UUID")))
+            }
+            "ip" => {
+                let addr = self.word("an IP address")?;
+                addr.parse()
+                    .map(Target::Ip)
+                    .map_err(|_| invalid(format!("`{addr}` is not an IP address")))
+            }
+            "subnet" => {
+                let cidr = self.word("a subnet")?;
+                parse_subnet(&cidr)
+            }
+            "tag" => {
+                let name = self.text("a tag name")?;
+                let value = if self.eat(&Token::Equals) {
+                    Some(self.text("a tag value")?)
+                } else {
+                    None
+                };
+                Ok(Target::Tag { name, value })
+            }
+            _ => Err(invalid(format!("unknown target `{keyword}`"))),
+        }
+    }
+
+    fn action(&mut self) -> Result<Action> {
+        let action = self.word("`ALLOW` or `BLOCK`")?;
+        match action.to_ascii_lowercase().as_str() {
+            "allow" => Ok(Action::Allow),
+            "block" => Ok(Action::Block),
+            _ => Err(invalid(format!(
+                "expected `ALLOW` or `BLOCK`, found `{action}`"
+            ))),
+        }
+    }
+
+    fn protocol(&mut self) -> Result<Protocol> {
+        let protocol = self.word("a protocol")?;
+        match protocol.to_ascii_lowercase().as_str() {
+            "tcp" => Ok(Protocol::Tcp),
+            "udp" => Ok(Protocol::Udp),
+            "icmp" => Ok(Protocol::Icmp),
+            "icmp6" => Ok(Protocol::Icmp6),
+            _ => Err(invalid(format!("unknown protocol `{protocol}`"))),
+        }
+    }
+
+    fn ports(&mut self) -> Result<PortSpec> {
+        if self.eat(&Token::LParen) {
+            let mut ports = Vec::new();
+            loop {
+                self.expect_keyword("PORT")?;
+                ports.push(PortRange::single(self.port()?));
+                if !self.eat_keyword("AND") {
+                    break;
+                }
+            }
+            self.expect(&Token::RParen)?;
+            return Ok(PortSpec::Ports(ports));
+        }
+
+        if self.eat_keyword("PORTS") {
+            let mut ports = vec![self.port_range()?];
+            while self.eat(&Token::Comma) {
+                ports.push(self.port_range()?);
+            }
+            return Ok(PortSpec::Ports(ports));
+        }
+
+        self.expect_keyword("PORT")?;
+        if self.eat_keyword("all") {
+            return Ok(PortSpec::All);
+        }
+        Ok(PortSpec::Ports(vec![PortRange::single(self.port()?)]))
+    }
+
+    fn port(&mut self) -> Result<u16> {
+        let port = self.word("a port")?;
+        parse_port(&port)
+    }
+
+    fn port_range(&mut self) -> Result<PortRange> {
+        let range = self.word("a port or port range")?;
+        let Some((start, end)) = range.split_once('-') else {
+            return Ok(PortRange::single(parse_port(&range)?));
+        };
+        let (start, end) = (parse_port(start)?, parse_port(end)?);
+        if start > end {
+            return Err(invalid(format!("port range `{range}` is reversed")));
+        }
+        Ok(PortRange { start, end })
+    }
+
+    fn icmp_type(&mut self) -> Result<PortSpec> {
+        self.expect_keyword("TYPE")?;
+        if self.eat_keyword("all") {
+            return Ok(PortSpec::All);
+        }
+        let icmp_type = self.icmp_number("an ICMP type")?;
+        let code = if self.eat_keyword("CODE") {
+            Some(self.icmp_number("an ICMP code")?)
+        } else {
+            None
+        };
+        Ok(PortSpec::IcmpType { icmp_type, code })
+    }
+
+    fn icmp_number(&mut self, expected: &str) -> Result<u8> {
+        let value = self.word(expected)?;
+        value
+            .parse()
+            .map_err(|_| invalid(format!("`{value}` is not {expected}")))
+    }
+}
+
+fn parse_port(value: &str) -> Result<u16> {
+    match value.parse::<u16>() {
+        Ok(port) if port > 0 => Ok(port),
+        _ => Err(invalid(format!(
+            "`{value}` is not a port between 1 and 65535"
+        ))),
+    }
+}
+
+fn parse_subnet(cidr: &str) -> Result<Target> {
+    let not_a_subnet = || invalid(format!("`{cidr}` is not a subnet in CIDR form"));
+    let (addr, prefix) = cidr.split_once('/').ok_or_else(not_a_subnet)?;
+    let addr: IpAddr = addr.parse().map_err(|_| not_a_subnet())?;
+    let prefix: u8 = prefix.parse().map_err(|_| not_a_subnet())?;
+    let max_prefix = if addr.is_ipv4() { 32 } else { 128 };
+    if prefix > max_prefix {
+        return Err(not_a_subnet());
+    }
+    Ok(Target::Subnet { addr, prefix })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(name: &str, value: Option<&str>) -> Target {
+        Target::Tag {
+            name: name.into(),
+            value: value.map(Into::into),
+        }
+    }
+
+    fn assert_round_trips(rule: &RuleAst) {
+        let text = rule.to_string();
+        assert_eq!(&parse_rule(&text).unwrap(), rule, "round trip of `{text}`");
+    }
+
+    fn assert_invalid(input: &str, fragment: &str) {
+        match parse_rule(input) {
+            Err(Error::ValidationError(message)) => assert!(
+                message.contains(fragment),
+                "`{input}` failed with `{message}`, expected it to mention `{fragment}`"
+            ),
+            other => panic!("`{input}` parsed as {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parses_tag_to_any() {
+        let rule = parse_rule(r#"FROM tag "role" = "db" TO any ALLOW tcp PORT 5432"#).unwrap();
+        assert_eq!(
+            rule,
+            RuleAst {
+                from: vec![tag("role", Some("db"))],
+                to: vec![Target::Any],
+                action: Action::Allow,
+                protocol: Protocol::Tcp,
+                ports: PortSpec::Ports(vec![PortRange::single(5432)]),
+            }
+        );
+        assert_eq!(
+            rule.to_string(),
+            r#"FROM tag "role" = "db" TO any ALLOW tcp PORT 5432"#
+        );
+    }
+
+    #[test]
+    fn parses_every_target_kind() {
+        let vm = VmUuid::new_v4();
+        let input = format!(
+            "from (any OR all vms OR vm {vm} OR ip 10.0.0.5 OR subnet 10.1.0.0/16 \
+             OR ip fd00::1 OR tag web OR tag \"a b\") to all vms block udp port all"
+        );
+        let rule = parse_rule(&input).unwrap();
+        assert_eq!(
+            rule.from,
+            vec![
+                Target::Any,
+                Target::AllVms,
+                Target::Vm(vm),
+                Target::Ip("10.0.0.5".parse().unwrap()),
+                Target::Subnet {
+                    addr: "10.1.0.0".parse().unwrap(),
+                    prefix: 16,
+                },
+                Target::Ip("fd00::1".parse().unwrap()),
+                tag("web", None),
+                tag("a b", None),
+            ]
+        );
+        assert_eq!(rule.to, vec![Target::AllVms]);
+        assert_eq!(rule.action, Action::Block);
+        assert_eq!(rule.protocol, Protocol::Udp);
+        assert_eq!(rule.ports, PortSpec::All);
+        assert_round_trips(&rule);
+    }
+
+    #[test]
+    fn parses_port_lists_and_ranges() {
+        let rule = parse_rule("FROM any TO all vms ALLOW tcp PORTS 22, 8000-8080,443").unwrap();
+        assert_eq!(
+            rule.ports,
+            PortSpec::Ports(vec![
+                PortRange::single(22),
+                PortRange {
+                    start: 8000,
+                    end: 8080,
+                },
+                PortRange::single(443),
+            ])
+        );
+        assert_eq!(
+            rule.to_string(),
+            "FROM any TO all vms ALLOW tcp PORTS 22, 8000-8080, 443"
+        );
+        assert_round_trips(&rule);
+
+        let rule = parse_rule("FROM any TO all vms ALLOW tcp (PORT 80 AND PORT 443)").unwrap();
+        assert_eq!(
+            rule.ports,
+            PortSpec::Ports(vec![PortRange::single(80), PortRange::single(443)])
+        );
+        assert_round_trips(&rule);
+    }
+
+    #[test]
+    fn parses_icmp_types() {
+        let rule = parse_rule("FROM any TO all vms ALLOW icmp TYPE 8 CODE 0").unwrap();
+        assert_eq!(
+            rule.ports,
+            PortSpec::IcmpType {
+                icmp_type: 8,
+                code: Some(0),
+            }
+        );
+        assert_round_trips(&rule);
+
+        let rule = parse_rule("FROM any TO all vms ALLOW icmp6 TYPE all").unwrap();
+        assert_eq!(rule.ports, PortSpec::All);
+        assert_eq!(rule.to_string(), "FROM any TO all vms ALLOW icmp6 TYPE all");
+    }
+
+    #[test]
+    fn quoted_tags_escape_on_display() {
+        let rule = RuleAst {
+            from: vec![tag(r#"say "hi""#, Some(r"back\slash"))],
+            to: vec![Target::Any],
+            action: Action::Allow,
+            protocol: Protocol::Tcp,
+            ports: PortSpec::All,
+        };
+        assert_eq!(
+            rule.to_string(),
+            r#"FROM tag "say \"hi\"" = "back\\slash" TO any ALLOW tcp PORT all"#
+        );
+        assert_round_trips(&rule);
+    }
+
+    #[test]
+    fn from_str_matches_parse_rule() {
+        let text = "FROM any TO all vms ALLOW tcp PORT 22";
+        assert_eq!(text.parse::<RuleAst>().unwrap(), parse_rule(text).unwrap());
+    }
+
+    #[test]
+    fn normalized_ignores_case_order_and_duplicates() {
+        let a = parse_rule("FROM (tag web OR any) TO all vms ALLOW tcp PORTS 443, 80").unwrap();
+        let b = parse_rule(
+            "from (any or tag web or any) to ALL VMS allow TCP ( PORT 80 AND PORT 443 )",
+        )
+        .unwrap();
+        assert_ne!(a, b);
+        assert_eq!(a.clone().normalized(), b.normalized());
+        assert_eq!(
+            a.normalized().to_string(),
+            r#"FROM (any OR tag "web") TO all vms ALLOW tcp PORTS 80, 443"#
+        );
+    }
+
+    #[test]
+    fn serializes_as_rule_text() {
+        let rule = parse_rule("FROM any TO all vms ALLOW tcp PORT 22").unwrap();
+        let json = serde_json::to_value(&rule).unwrap();
+        assert_eq!(json, "FROM any TO all vms ALLOW tcp PORT 22");
+        assert_eq!(serde_json::from_value::<RuleAst>(json).unwrap(), rule);
+        assert!(serde_json::from_value::<RuleAst>("FROM any".into()).is_err());
+    }
+
+    #[test]
+    fn builder_emits_canonical_rules() {
+        let vm = VmUuid::new_v4();
+        let rule = RuleBuilder::new()
+            .from_tag_value("role", "db")
+            .to_any()
+            .allow(Protocol::Tcp)
+            .ports([5432])
+            .to_rule_string()
+            .unwrap();
+        assert_eq!(rule, r#"FROM tag "role" = "db" TO any ALLOW tcp PORT 5432"#);
+
+        let rule = RuleBuilder::new()
+            .from_any()
+            .to_vm(vm)
+            .allow(Protocol::Tcp)
+            .ports([22, 443])
+            .port_range(8000, 8080)
+            .to_rule_string()
+            .unwrap();
+        assert_eq!(
+            rule,
+            format!("FROM any TO vm {vm} ALLOW tcp PORTS 22, 443, 8000-8080")
+        );
+
+        let rule = RuleBuilder::new()
+            .from_subnet("10.0.0.0".parse().unwrap(), 8)
+            .from_ip("192.168.1.10".parse().unwrap())
+            .to_all_vms()
+            .block(Protocol::Udp)
+            .all_ports()
+            .to_rule_string()
+            .unwrap();
+        assert_eq!(
+            rule,
+            "FROM (subnet 10.0.0.0/8 OR ip 192.168.1.10) TO all vms BLOCK udp PORT all"
+        );
+
+        let rule = RuleBuilder::new()
+            .from_any()
+            .to_tag("www")
+            .allow(Protocol::Icmp)
+            .icmp_type(8, Some(0))
+            .to_rule_string()
+            .unwrap();
+        assert_eq!(rule, r#"FROM any TO tag "www" ALLOW icmp TYPE 8 CODE 0"#);
+        assert_eq!(parse_rule(&rule).unwrap().to_string(), rule);
+    }
+
+    #[test]
+    fn builder_create_request_is_enabled() {
+        let request = RuleBuilder::new()
+            .from_any()
+            .to_all_vms()
+            .allow(Protocol::Tcp)
+            .ports([80])
+            .to_create_request()
+            .unwrap();
+        assert_eq!(request.rule, "FROM any TO all vms ALLOW tcp PORT 80");
+        assert_eq!(request.enabled, Some(true));
+    }
+
+    #[test]
+    fn builder_rejects_incomplete_rules() {
+        let complete = RuleBuilder::new()
+            .from_any()
+            .to_all_vms()
+            .allow(Protocol::Tcp)
+            .ports([22]);
+        assert!(complete.build().is_ok());
+
+        let cases = [
+            (
+                RuleBuilder::new()
+                    .to_all_vms()
+                    .allow(Protocol::Tcp)
+                    .ports([22]),
+                "source",
+            ),
+            (
+                RuleBuilder::new()
+                    .from_any()
+                    .allow(Protocol::Tcp)
+                    .ports([22]),
+                "destination",
+            ),
+            (
+                RuleBuilder::new().from_any().to_all_vms().ports([22]),
+                "action",
+            ),
+            (
+                RuleBuilder::new()
+                    .from_any()
+                    .to_all_vms()
+                    .allow(Protocol::Tcp),
+                "ports are required",
+            ),
+            (complete.clone().ports([0]), "port 0"),
+            (complete.clone().port_range(90, 80), "reversed"),
+            (complete.clone().icmp_type(8, None), "take ports"),
+            (complete.allow(Protocol::Icmp6), "take a type"),
+        ];
+        for (builder, fragment) in cases {
+            match builder.build() {
+                Err(Error::ValidationError(message)) => {
+                    assert!(message.contains(fragment), "{message}");
+                }
+                other => panic!("expected `{fragment}` error, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_rules() {
+        assert_invalid("", "expected `FROM`");
+        assert_invalid("TO any ALLOW tcp PORT 22", "expected `FROM`");
+        assert_invalid("FROM any ALLOW tcp PORT 22", "expected `TO`");
+        assert_invalid("FROM any TO all ALLOW tcp PORT 22", "expected `vms`");
+        assert_invalid(
+            "FROM host x TO any ALLOW tcp PORT 22",
+            "unknown target `host`",
+        );
+        assert_invalid("FROM vm nope TO any ALLOW tcp PORT 22", "not a VM UUID");
+        assert_invalid("FROM ip 10.0.0.256 TO any ALLOW tcp PORT 22", "not an IP");
+        assert_invalid("FROM subnet 10.0.0.0 TO any ALLOW tcp PORT 22", "CIDR");
+        assert_invalid("FROM subnet 10.0.0.0/33 TO any ALLOW tcp PORT 22", "CIDR");
+        assert_invalid(
+            "FROM (any OR tag x TO any ALLOW tcp PORT 22",
+            "expected `)`",
+        );
+        assert_invalid(r#"FROM tag "x TO any ALLOW tcp PORT 22"#, "unterminated");
+        assert_invalid("FROM tag x = TO any ALLOW tcp PORT 22", "expected `TO`");
+        assert_invalid("FROM any TO any DENY tcp PORT 22", "`ALLOW` or `BLOCK`");
+        assert_invalid("FROM any TO any ALLOW sctp PORT 22", "unknown protocol");
+        assert_invalid("FROM any TO any ALLOW tcp", "found end of rule");
+        assert_invalid("FROM any TO any ALLOW tcp PORT 0", "between 1 and 65535");
+        assert_invalid(
+            "FROM any TO any ALLOW tcp PORT 70000",
+            "between 1 and 65535",
+        );
+        assert_invalid("FROM any TO any ALLOW tcp PORTS 90-80", "reversed");
+        assert_invalid("FROM any TO any ALLOW tcp PORTS 80,", "found end of rule");
+        assert_invalid("FROM any TO any ALLOW icmp PORT 22", "expected `TYPE`");
+        assert_invalid("FROM any TO any ALLOW icmp TYPE 300", "not an ICMP type");
+        assert_invalid(
+            "FROM any TO any ALLOW tcp PORT 22 extra",
+            "trailing `extra`",
+        );
+    }
+}
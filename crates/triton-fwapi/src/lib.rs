@@ -6,11 +6,15 @@
 #![deny(missing_docs)]
 
 pub mod client;
+pub mod fwrule;
 pub mod models;
 
 pub use client::{FwapiClient, FwapiClientBuilder, FwapiDiscovery};
+pub use fwrule::{parse_rule, RuleAst, RuleBuilder};
 pub use models::{
-    CreateFirewallRuleRequest, FirewallRule, FirewallRuleListParams, UpdateFirewallRuleRequest,
+    CreateFirewallRuleRequest, FirewallRule, FirewallRuleListParams, ParsedRule, PolicyDocument,
+    ReconcilePlan, ResolveRequest, ResolveResponse, RuleChange, RuleImportOutcome, RuleScope,
+    UpdateFirewallRuleRequest,
 };
 
 /// Convenient result alias that reuses the shared Triton error type.
@@ -1,9 +1,14 @@
 //! FWAPI models shared by client and prospective server implementations.
 
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
-use triton_core::query::QueryParams;
-use triton_core::uuid::{FirewallRuleUuid, OwnerUuid};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use triton_core::query::{QueryPair, QueryParams};
+use triton_core::uuid::{FirewallRuleUuid, OwnerUuid, VmUuid};
+use triton_core::Error;
+
+use crate::fwrule::{parse_rule, RuleAst, Target};
+use crate::Result;
 
 /// Representation of a firewall rule as returned by FWAPI.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -102,17 +107,21 @@ pub struct FirewallRuleListParams {
     /// Filter enabled rules.
     pub enabled: Option<bool>,
     /// Filter by VM UUID.
-    pub vm: Option<OwnerUuid>,
+    pub vm: Option<VmUuid>,
     /// Limit.
     pub limit: Option<u32>,
     /// Offset.
     pub offset: Option<u32>,
+    /// Additional query parameters appended verbatim after the typed ones.
+    ///
+    /// Keys already set by a typed field are skipped.
+    pub extra_params: Vec<(String, String)>,
 }
 
 impl FirewallRuleListParams {
     /// Convert to URL query pairs.
     #[must_use]
-    pub fn to_pairs(&self) -> Vec<(&'static str, String)> {
+    pub fn to_pairs(&self) -> Vec<QueryPair> {
         let mut params = QueryParams::new();
         params.push_opt("owner_uuid", self.owner_uuid.as_ref());
         params.push_opt("global", self.global);
@@ -120,10 +129,391 @@ impl FirewallRuleListParams {
         params.push_opt("vm", self.vm.as_ref());
         params.push_opt("limit", self.limit);
         params.push_opt("offset", self.offset);
+        params.push_extra(&self.extra_params);
         params.into_pairs()
     }
 }
 
+/// Request payload for FWAPI's `/resolve` endpoint.
+///
+/// Describes a VM (or set of VMs) by owner, UUID and tags; FWAPI answers with
+/// the rules that would apply to it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ResolveRequest {
+    /// Owner of the VMs being resolved.
+    pub owner_uuid: OwnerUuid,
+    /// VM UUIDs to resolve rules for.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub vms: Vec<VmUuid>,
+    /// Tags carried by the VMs; `true` stands for "any value".
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, serde_json::Value>,
+}
+
+impl ResolveRequest {
+    /// Create a request for an owner with no VMs or tags.
+    #[must_use]
+    pub fn new(owner_uuid: OwnerUuid) -> Self {
+        Self {
+            owner_uuid,
+            vms: Vec::new(),
+            tags: BTreeMap::new(),
+        }
+    }
+
+    /// Add a VM to resolve.
+    #[must_use]
+    pub fn with_vm(mut self, vm_uuid: VmUuid) -> Self {
+        self.vms.push(vm_uuid);
+        self
+    }
+
+    /// Add a tag carried by the VMs.
+    #[must_use]
+    pub fn with_tag(
+        mut self,
+        name: impl Into<String>,
+        value: impl Into<serde_json::Value>,
+    ) -> Self {
+        self.tags.insert(name.into(), value.into());
+        self
+    }
+}
+
+/// Rules and references FWAPI resolved for a [`ResolveRequest`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ResolveResponse {
+    /// Owner the rules were resolved for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner_uuid: Option<OwnerUuid>,
+    /// Rules that apply to the VMs.
+    #[serde(default)]
+    pub rules: Vec<FirewallRule>,
+    /// Whether any applicable rule targets `all vms`.
+    #[serde(rename = "allVMs", default)]
+    pub all_vms: bool,
+    /// Tags referenced by the rules, with the values they match (`true` for any).
+    #[serde(default)]
+    pub tags: BTreeMap<String, serde_json::Value>,
+    /// Other VMs referenced by the rules.
+    #[serde(default)]
+    pub vms: Vec<VmUuid>,
+}
+
+/// Owner-scoped, UUID-free form of a firewall rule suitable for export.
+///
+/// The rule is kept [normalized](RuleAst::normalized) so that two documents
+/// exported from equivalent rule sets compare and diff cleanly.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ParsedRule {
+    /// Normalized rule, serialized as FWAPI DSL text.
+    #[serde(deserialize_with = "deserialize_normalized")]
+    pub rule: RuleAst,
+    /// Whether the rule is enabled.
+    pub enabled: bool,
+    /// Optional description.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Owner UUID.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner_uuid: Option<OwnerUuid>,
+    /// Whether the rule is global.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub global: Option<bool>,
+    /// VMs the rule is created for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vms: Option<Vec<VmUuid>>,
+}
+
+impl ParsedRule {
+    /// Parse DSL text into an enabled rule.
+    ///
+    /// Fails with [`Error::ValidationError`] when the text is not a valid rule.
+    pub fn new(rule: impl AsRef<str>) -> Result<Self> {
+        parse_rule(rule.as_ref()).map(Self::from)
+    }
+
+    /// Returns true when `rule` parses to the same normalized rule.
+    #[must_use]
+    pub fn matches(&self, rule: &FirewallRule) -> bool {
+        parse_rule(&rule.rule).is_ok_and(|parsed| parsed.normalized() == self.rule)
+    }
+
+    /// Returns true when `rule` already has this rule's enabled flag and
+    /// description. A missing description and an empty one are equal.
+    #[must_use]
+    pub fn settings_match(&self, rule: &FirewallRule) -> bool {
+        rule.enabled == self.enabled
+            && rule.description.as_deref().unwrap_or_default()
+                == self.description.as_deref().unwrap_or_default()
+    }
+
+    /// Build the request that brings a matching rule's settings in line.
+    ///
+    /// A rule without a description sends an empty one so that the existing
+    /// description is cleared.
+    #[must_use]
+    pub fn to_update_request(&self) -> UpdateFirewallRuleRequest {
+        UpdateFirewallRuleRequest {
+            enabled: Some(self.enabled),
+            description: Some(self.description.clone().unwrap_or_default()),
+            ..UpdateFirewallRuleRequest::default()
+        }
+    }
+
+    /// Build the request that recreates this rule.
+    #[must_use]
+    pub fn to_create_request(&self) -> CreateFirewallRuleRequest {
+        CreateFirewallRuleRequest {
+            rule: self.rule.to_string(),
+            enabled: Some(self.enabled),
+            description: self.description.clone(),
+            owner_uuid: self.owner_uuid,
+            global: self.global,
+            vms: self.vms.as_ref().map(|vms| {
+                vms.iter()
+                    .map(|vm| OwnerUuid::new(vm.into_uuid()))
+                    .collect()
+            }),
+        }
+    }
+}
+
+impl From<RuleAst> for ParsedRule {
+    fn from(rule: RuleAst) -> Self {
+        Self {
+            rule: rule.normalized(),
+            enabled: true,
+            description: None,
+            owner_uuid: None,
+            global: None,
+            vms: None,
+        }
+    }
+}
+
+impl TryFrom<&FirewallRule> for ParsedRule {
+    type Error = Error;
+
+    fn try_from(rule: &FirewallRule) -> Result<Self> {
+        let parsed = parse_rule(&rule.rule).map_err(|err| match err {
+            Error::ValidationError(message) => {
+                Error::ValidationError(format!("rule {}: {message}", rule.uuid))
+            }
+            other => other,
+        })?;
+        Ok(Self {
+            rule: parsed.normalized(),
+            enabled: rule.enabled,
+            description: rule.description.clone(),
+            owner_uuid: rule.owner_uuid,
+            global: rule.global,
+            vms: None,
+        })
+    }
+}
+
+fn deserialize_normalized<'de, D>(deserializer: D) -> std::result::Result<RuleAst, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    RuleAst::deserialize(deserializer).map(RuleAst::normalized)
+}
+
+/// Portable snapshot of the firewall rules affecting a VM.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PolicyDocument {
+    /// VM the rules were exported for.
+    pub vm_uuid: VmUuid,
+    /// Rules sorted by normalized rule text, then description.
+    pub rules: Vec<ParsedRule>,
+    /// When the document was exported.
+    pub exported_at: DateTime<Utc>,
+}
+
+impl PolicyDocument {
+    /// Build a document from FWAPI rules, ordering them deterministically.
+    ///
+    /// Fails with [`Error::ValidationError`] naming the first rule whose text
+    /// does not parse.
+    pub fn new(
+        vm_uuid: VmUuid,
+        rules: &[FirewallRule],
+        exported_at: DateTime<Utc>,
+    ) -> Result<Self> {
+        let mut rules = rules
+            .iter()
+            .map(ParsedRule::try_from)
+            .collect::<Result<Vec<_>>>()?;
+        rules.sort_by_cached_key(|rule| {
+            (
+                rule.rule.to_string(),
+                rule.description.clone(),
+                rule.enabled,
+            )
+        });
+        Ok(Self {
+            vm_uuid,
+            rules,
+            exported_at,
+        })
+    }
+}
+
+/// What happened to a single rule during a policy import.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuleImportOutcome {
+    /// No matching rule existed, so one was created.
+    Created(FirewallRule),
+    /// A matching rule existed with different settings and was updated.
+    Updated(FirewallRule),
+    /// A matching rule already had the exported settings.
+    Unchanged(FirewallRule),
+}
+
+/// The set of FWAPI rules a reconcile run owns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleScope {
+    /// Rules owned by an account.
+    Owner(OwnerUuid),
+    /// Global rules.
+    Global,
+    /// An account's rules that name one of its VMs.
+    Vm {
+        /// Account owning the VM.
+        owner_uuid: OwnerUuid,
+        /// The VM.
+        vm_uuid: VmUuid,
+    },
+}
+
+impl RuleScope {
+    /// Fill in the owner, global flag or VM implied by the scope.
+    #[must_use]
+    pub fn apply_to(&self, mut rule: ParsedRule) -> ParsedRule {
+        match self {
+            Self::Owner(owner) => rule.owner_uuid = rule.owner_uuid.or(Some(*owner)),
+            Self::Global => rule.global = Some(true),
+            Self::Vm {
+                owner_uuid,
+                vm_uuid,
+            } => {
+                rule.owner_uuid = rule.owner_uuid.or(Some(*owner_uuid));
+                let vms = rule.vms.get_or_insert_with(Vec::new);
+                if !vms.contains(vm_uuid) {
+                    vms.push(*vm_uuid);
+                }
+            }
+        }
+        rule
+    }
+
+    /// Returns true when a reconcile run in this scope may update or delete
+    /// `rule`.
+    ///
+    /// A VM scope owns only the account's non-global rules that name the VM,
+    /// in the rule text or its `vms`; global, `all vms`, tag-matched and
+    /// other accounts' rules that merely affect the VM are left alone.
+    #[must_use]
+    pub fn owns(&self, rule: &FirewallRule) -> bool {
+        let global = rule.global == Some(true);
+        match self {
+            Self::Owner(owner) => !global && rule.owner_uuid == Some(*owner),
+            Self::Global => global,
+            Self::Vm {
+                owner_uuid,
+                vm_uuid,
+            } => !global && rule.owner_uuid == Some(*owner_uuid) && names_vm(rule, *vm_uuid),
+        }
+    }
+}
+
+fn names_vm(rule: &FirewallRule, vm_uuid: VmUuid) -> bool {
+    let listed = rule
+        .vms
+        .iter()
+        .flatten()
+        .any(|vm| vm.as_uuid() == vm_uuid.as_uuid());
+    listed
+        || parse_rule(&rule.rule).is_ok_and(|parsed| {
+            parsed
+                .from
+                .iter()
+                .chain(&parsed.to)
+                .any(|target| *target == Target::Vm(vm_uuid))
+        })
+}
+
+/// Operations that converge FWAPI to a desired rule set.
+///
+/// Rules are matched on their normalized parse, so once a plan has been applied,
+/// planning again against the same desired set yields an empty plan.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReconcilePlan {
+    /// Desired rules with no matching rule in scope.
+    pub to_create: Vec<ParsedRule>,
+    /// Existing rules whose settings differ, paired with the desired rule.
+    pub to_update: Vec<(FirewallRule, ParsedRule)>,
+    /// Rules in scope that are not desired, including duplicates.
+    pub to_delete: Vec<FirewallRule>,
+}
+
+impl ReconcilePlan {
+    /// Diff `desired` against the rules currently in scope.
+    ///
+    /// Desired rules repeating an earlier rule are ignored.
+    #[must_use]
+    pub fn new(desired: &[ParsedRule], current: &[FirewallRule]) -> Self {
+        let mut plan = Self::default();
+        let mut claimed = vec![false; current.len()];
+        let mut seen = HashSet::new();
+
+        for rule in desired {
+            if !seen.insert(rule.rule.to_string()) {
+                continue;
+            }
+            let existing = current
+                .iter()
+                .enumerate()
+                .find(|(index, existing)| !claimed[*index] && rule.matches(existing));
+            match existing {
+                Some((index, existing)) => {
+                    claimed[index] = true;
+                    if !rule.settings_match(existing) {
+                        plan.to_update.push((existing.clone(), rule.clone()));
+                    }
+                }
+                None => plan.to_create.push(rule.clone()),
+            }
+        }
+
+        plan.to_delete = current
+            .iter()
+            .zip(claimed)
+            .filter(|(_, claimed)| !claimed)
+            .map(|(rule, _)| rule.clone())
+            .collect();
+        plan
+    }
+
+    /// Returns true when nothing needs to change.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.to_create.is_empty() && self.to_update.is_empty() && self.to_delete.is_empty()
+    }
+}
+
+/// What happened to a single rule when a [`ReconcilePlan`] was applied.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuleChange {
+    /// The rule was created.
+    Created(FirewallRule),
+    /// The rule's settings were updated.
+    Updated(FirewallRule),
+    /// The rule was deleted.
+    Deleted(FirewallRuleUuid),
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -139,7 +529,191 @@ mod tests {
 
         let pairs = params.to_pairs();
         assert!(pairs.iter().any(|(k, _)| *k == "owner_uuid"));
-        assert!(pairs.contains(&("global", "true".into())));
-        assert!(pairs.contains(&("limit", "25".into())));
+        assert!(pairs.contains(&("global".into(), "true".into())));
+        assert!(pairs.contains(&("limit".into(), "25".into())));
+    }
+
+    fn rule(text: &str, description: Option<&str>) -> FirewallRule {
+        FirewallRule {
+            uuid: FirewallRuleUuid::new_v4(),
+            rule: text.into(),
+            enabled: true,
+            version: "1".into(),
+            description: description.map(Into::into),
+            owner_uuid: None,
+            global: None,
+            vms: None,
+            created_by: None,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn policy_document_is_normalized_and_sorted() {
+        let vm_uuid = VmUuid::new_v4();
+        let exported_at = Utc::now();
+        let rules = vec![
+            rule("FROM any TO  all vms\tALLOW tcp PORT 80", None),
+            rule("FROM any TO all vms ALLOW tcp PORT 22", Some("ssh")),
+        ];
+
+        let document = PolicyDocument::new(vm_uuid, &rules, exported_at).unwrap();
+        let texts: Vec<_> = document.rules.iter().map(|r| r.rule.to_string()).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "FROM any TO all vms ALLOW tcp PORT 22",
+                "FROM any TO all vms ALLOW tcp PORT 80",
+            ]
+        );
+
+        let mut reversed = rules.clone();
+        reversed.reverse();
+        assert_eq!(
+            PolicyDocument::new(vm_uuid, &reversed, exported_at).unwrap(),
+            document
+        );
+        assert!(document.rules[1].matches(&rules[0]));
+    }
+
+    #[test]
+    fn reconcile_plan_creates_missing_and_deletes_extra() {
+        let keep = rule("FROM any TO all vms ALLOW tcp PORT 22", None);
+        let stale = rule("FROM any TO all vms ALLOW tcp PORT 8080", None);
+        let desired = vec![
+            ParsedRule::new("FROM any TO  all vms ALLOW tcp PORT 22").unwrap(),
+            ParsedRule::new("FROM any TO all vms ALLOW tcp PORT 443").unwrap(),
+        ];
+
+        let plan = ReconcilePlan::new(&desired, &[keep, stale.clone()]);
+        assert_eq!(plan.to_create, vec![desired[1].clone()]);
+        assert!(plan.to_update.is_empty());
+        assert_eq!(plan.to_delete, vec![stale]);
+    }
+
+    #[test]
+    fn reconcile_plan_updates_changed_settings() {
+        let existing = rule("FROM any TO all vms ALLOW tcp PORT 22", None);
+        let mut desired = ParsedRule::new(&existing.rule).unwrap();
+        desired.description = Some("ssh".into());
+
+        let plan = ReconcilePlan::new(
+            std::slice::from_ref(&desired),
+            std::slice::from_ref(&existing),
+        );
+        assert_eq!(plan.to_update, vec![(existing, desired)]);
+        assert!(plan.to_create.is_empty());
+        assert!(plan.to_delete.is_empty());
+    }
+
+    #[test]
+    fn reconcile_plan_clears_descriptions_missing_from_the_document() {
+        let existing = rule("FROM any TO all vms ALLOW tcp PORT 22", Some("ssh"));
+        let desired = ParsedRule::new(&existing.rule).unwrap();
+
+        let plan = ReconcilePlan::new(
+            std::slice::from_ref(&desired),
+            std::slice::from_ref(&existing),
+        );
+        assert_eq!(plan.to_update, vec![(existing, desired.clone())]);
+        assert_eq!(desired.to_update_request().description.as_deref(), Some(""));
+
+        let cleared = rule("FROM any TO all vms ALLOW tcp PORT 22", Some(""));
+        assert!(desired.settings_match(&cleared));
+    }
+
+    #[test]
+    fn reconcile_plan_is_empty_once_converged() {
+        let current = vec![
+            rule("FROM any TO all vms ALLOW tcp PORT 22", Some("ssh")),
+            rule("FROM any TO all vms ALLOW tcp PORT 443", None),
+        ];
+        let desired: Vec<ParsedRule> = current
+            .iter()
+            .map(|rule| ParsedRule::try_from(rule).unwrap())
+            .collect();
+
+        assert!(ReconcilePlan::new(&desired, &current).is_empty());
+
+        let mut duplicated = current.clone();
+        duplicated.push(rule("FROM any TO all vms ALLOW tcp PORT 22", Some("ssh")));
+        let plan = ReconcilePlan::new(&desired, &duplicated);
+        assert_eq!(plan.to_delete, vec![duplicated[2].clone()]);
+    }
+
+    #[test]
+    fn reconcile_plan_matches_case_and_order_variants() {
+        let current = vec![
+            rule("from any to all vms allow TCP PORTS 443, 80", None),
+            rule("FROM (tag b OR tag a) TO all vms BLOCK udp PORT 53", None),
+        ];
+        let desired = vec![
+            ParsedRule::new("FROM any TO all vms ALLOW tcp ( PORT 80 AND PORT 443 )").unwrap(),
+            ParsedRule::new("FROM (tag a OR tag b) TO all vms BLOCK udp PORT 53").unwrap(),
+        ];
+
+        assert!(ReconcilePlan::new(&desired, &current).is_empty());
+    }
+
+    #[test]
+    fn policy_document_rejects_unparseable_rules() {
+        let bad = rule("ALLOW everything", None);
+        let err = PolicyDocument::new(VmUuid::new_v4(), std::slice::from_ref(&bad), Utc::now())
+            .unwrap_err();
+        assert!(
+            matches!(err, Error::ValidationError(message) if message.contains(&bad.uuid.to_string()))
+        );
+    }
+
+    #[test]
+    fn vm_scope_owns_only_the_owners_rules_naming_the_vm() {
+        let owner = OwnerUuid::new_v4();
+        let vm_uuid = VmUuid::new_v4();
+        let scope = RuleScope::Vm {
+            owner_uuid: owner,
+            vm_uuid,
+        };
+        let owned = |text: &str, owner_uuid: Option<OwnerUuid>, global: Option<bool>| {
+            let mut rule = rule(text, None);
+            rule.owner_uuid = owner_uuid;
+            rule.global = global;
+            scope.owns(&rule)
+        };
+        let names_vm = format!("FROM any TO vm {vm_uuid} ALLOW tcp PORT 22");
+
+        assert!(owned(&names_vm, Some(owner), None));
+        assert!(!owned(&names_vm, Some(OwnerUuid::new_v4()), None));
+        assert!(!owned(&names_vm, Some(owner), Some(true)));
+        assert!(!owned(
+            "FROM any TO all vms ALLOW tcp PORT 22",
+            Some(owner),
+            None
+        ));
+        assert!(!owned(
+            "FROM any TO tag web ALLOW tcp PORT 22",
+            Some(owner),
+            None
+        ));
+
+        let mut listed = rule("FROM any TO tag web ALLOW tcp PORT 22", None);
+        listed.owner_uuid = Some(owner);
+        listed.vms = Some(vec![OwnerUuid::new(vm_uuid.into_uuid())]);
+        assert!(scope.owns(&listed));
+    }
+
+    #[test]
+    fn vm_scope_sets_owner_and_vm() {
+        let owner = OwnerUuid::new_v4();
+        let vm_uuid = VmUuid::new_v4();
+        let scope = RuleScope::Vm {
+            owner_uuid: owner,
+            vm_uuid,
+        };
+        let rule = scope.apply_to(
+            scope.apply_to(ParsedRule::new("FROM any TO all vms ALLOW tcp PORT 22").unwrap()),
+        );
+        assert_eq!(rule.owner_uuid, Some(owner));
+        assert_eq!(rule.vms, Some(vec![vm_uuid]));
     }
 }
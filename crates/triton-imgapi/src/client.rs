@@ -2,23 +2,36 @@
 
 use crate::models::{
     CreateImageRequest, ExportImageRequest, Image, ImageAction, ImageImportRequest,
-    ImageListParams, UpdateImageRequest,
+    ImageListParams, ImageState, UpdateImageRequest, VisibleImage,
 };
 use crate::Result;
 use async_trait::async_trait;
 use bytes::Bytes;
+use reqwest::header::{HeaderName, ETAG, IF_NONE_MATCH};
 use reqwest::{Method, StatusCode};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use serde_json::Value;
-use std::sync::Arc;
+use sha1::{Digest, Sha1};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use triton_core::cache::{LruCache, DEFAULT_CACHE_CAPACITY};
 use triton_core::client::{
-    ClientConfig, RetryPolicy, ServiceClient, ServiceClientBuilder, IMGAPI_DEFAULT_TIMEOUT,
+    ClientConfig, ClientObserver, RequestObserver, RequestOptions, RetryPolicy, ServiceClient,
+    ServiceClientBuilder, ServiceResponse, IMGAPI_DEFAULT_TIMEOUT,
 };
+use triton_core::config::TritonClientConfig;
+use triton_core::context::RequestContext;
+use triton_core::path::encode_path_segment;
+use triton_core::preflight::{PreflightCheck, PreflightResult};
+use triton_core::query::QueryPair;
+use triton_core::selection::EndpointSelector;
 use triton_core::services::{DiscoveryStatus, ServiceDiscovery, ServiceDiscoveryProxy};
-use triton_core::types::TritonService;
-use triton_core::uuid::ImageUuid;
+use triton_core::types::{EndpointList, TritonService};
+use triton_core::uuid::{ImageUuid, OwnerUuid};
+use triton_core::wait::{poll_until, WaitOptions};
 use triton_core::Error;
 use url::Url;
 
@@ -28,6 +41,7 @@ const USER_AGENT: &str = concat!("triton-imgapi/", env!("CARGO_PKG_VERSION"));
 #[derive(Debug, Clone)]
 pub struct ImgapiClientBuilder {
     inner: ServiceClientBuilder,
+    cache_capacity: usize,
 }
 
 impl ImgapiClientBuilder {
@@ -40,7 +54,21 @@ impl ImgapiClientBuilder {
         )?
         .with_user_agent(USER_AGENT);
 
-        Ok(Self { inner: builder })
+        Ok(Self {
+            inner: builder,
+            cache_capacity: DEFAULT_CACHE_CAPACITY,
+        })
+    }
+
+    /// Create a builder from the IMGAPI endpoint, timeout, and retry count in `config`.
+    pub fn from_config(config: &TritonClientConfig) -> Result<Self> {
+        let builder = ServiceClientBuilder::from_config(TritonService::Imgapi, config)?
+            .with_user_agent(USER_AGENT);
+
+        Ok(Self {
+            inner: builder,
+            cache_capacity: DEFAULT_CACHE_CAPACITY,
+        })
     }
 
     /// Override the retry policy.
@@ -75,17 +103,91 @@ impl ImgapiClientBuilder {
         self
     }
 
+    /// Override per-request options such as the JSON content type.
+    #[must_use]
+    pub fn with_request_options(mut self, options: RequestOptions) -> Self {
+        self.inner = self.inner.with_request_options(options);
+        self
+    }
+
+    /// Notify `observer` after every call, retries included.
+    #[must_use]
+    pub fn with_observer(mut self, observer: Arc<dyn RequestObserver>) -> Self {
+        self.inner = self.inner.with_observer(observer);
+        self
+    }
+
+    /// Invoke `observer` around every attempt, retries included.
+    #[must_use]
+    pub fn with_client_observer(mut self, observer: Arc<dyn ClientObserver>) -> Self {
+        self.inner = self.inner.with_client_observer(observer);
+        self
+    }
+
+    /// Send each call's request id in `name` instead of `x-request-id`.
+    #[must_use]
+    pub fn with_request_id_header(mut self, name: HeaderName) -> Self {
+        self.inner = self.inner.with_request_id_header(name);
+        self
+    }
+
+    /// Spread requests over `endpoints`, skipping stale and quarantined ones.
+    ///
+    /// See [`ServiceClientBuilder::with_endpoints`].
+    #[must_use]
+    pub fn with_endpoints(mut self, endpoints: EndpointList, selector: EndpointSelector) -> Self {
+        self.inner = self.inner.with_endpoints(endpoints, selector);
+        self
+    }
+
+    /// Send every request to the endpoint at `url` until it fails or is released.
+    ///
+    /// See [`ServiceClientBuilder::pin_endpoint`].
+    #[must_use]
+    pub fn pin_endpoint(mut self, url: impl Into<String>) -> Self {
+        self.inner = self.inner.pin_endpoint(url);
+        self
+    }
+
+    /// Send requests through a shared `reqwest::Client` instead of building a new one.
+    ///
+    /// The injected client's timeout, TLS and pooling settings take precedence; see
+    /// [`ServiceClientBuilder::with_http_client`].
+    #[must_use]
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
+        self.inner = self.inner.with_http_client(client);
+        self
+    }
+
+    /// Limit how many image manifests are kept for conditional requests.
+    #[must_use]
+    pub fn with_cache_capacity(mut self, capacity: usize) -> Self {
+        self.cache_capacity = capacity;
+        self
+    }
+
     /// Build the client.
     pub fn build(self) -> Result<ImgapiClient> {
         let inner = self.inner.build()?;
-        Ok(ImgapiClient { inner })
+        Ok(ImgapiClient {
+            inner,
+            manifests: Arc::new(Mutex::new(LruCache::new(self.cache_capacity))),
+        })
     }
 }
 
+/// Image manifest remembered alongside the ETag it was served with.
+#[derive(Debug, Clone)]
+struct CachedManifest {
+    etag: String,
+    image: Image,
+}
+
 /// Asynchronous IMGAPI client.
 #[derive(Clone)]
 pub struct ImgapiClient {
     inner: ServiceClient,
+    manifests: Arc<Mutex<LruCache<ImageUuid, CachedManifest>>>,
 }
 
 impl ImgapiClient {
@@ -94,23 +196,192 @@ impl ImgapiClient {
         ImgapiClientBuilder::new(base_url)?.build()
     }
 
+    /// Returns a handle that tags every call with `context` for logs and observers.
+    #[must_use]
+    pub fn with_context(&self, context: RequestContext) -> Self {
+        Self {
+            inner: self.inner.with_context(context),
+            ..self.clone()
+        }
+    }
+
+    /// Returns a handle whose requests each time out after `timeout` instead
+    /// of the client-wide timeout.
+    #[must_use]
+    pub fn with_timeout(&self, timeout: Duration) -> Self {
+        Self {
+            inner: self.inner.with_timeout(timeout),
+            ..self.clone()
+        }
+    }
+
     /// Return the base URL.
     #[must_use]
     pub fn base_url(&self) -> &Url {
         self.inner.base_url()
     }
 
+    /// Identifier of the endpoint requests are pinned to, if any.
+    #[must_use]
+    pub fn pinned_endpoint(&self) -> Option<String> {
+        self.inner.pinned_endpoint()
+    }
+
+    /// Drop the endpoint pin so the next request selects afresh.
+    pub fn release_pinned_endpoint(&self) {
+        self.inner.release_pinned_endpoint();
+    }
+
+    /// Replace the endpoints requests are spread over, e.g. after rediscovery.
+    pub fn update_endpoints(&self, endpoints: EndpointList) {
+        self.inner.update_endpoints(endpoints);
+    }
+
     /// List images.
     pub async fn list_images(&self, params: &ImageListParams) -> Result<Vec<Image>> {
         self.send_json::<(), Vec<Image>>(Method::GET, "images", None, &params.to_pairs())
             .await
     }
 
+    /// List images that can be provisioned with the given memory and disk (MiB).
+    ///
+    /// Filtering happens client-side using [`Image::fits`].
+    pub async fn list_compatible_images(
+        &self,
+        params: &ImageListParams,
+        available_memory_mib: u64,
+        available_disk_mib: u64,
+    ) -> Result<Vec<Image>> {
+        let mut images = self.list_images(params).await?;
+        images.retain(|image| image.fits(available_memory_mib, available_disk_mib));
+        Ok(images)
+    }
+
+    /// List the images `account` can provision: its own, ACL-shared, and public.
+    ///
+    /// Queries with the `account` scope, then supplements with private images
+    /// whose `acl` names the account, since not every IMGAPI version includes
+    /// ACL-shared images in the scoped listing. The supplement is skipped when
+    /// `params.public` is `Some(true)`. Results are de-duplicated by UUID,
+    /// keep the scoped listing's order, and are classified with
+    /// [`Image::visibility_for`].
+    pub async fn list_visible_images(
+        &self,
+        account: OwnerUuid,
+        params: &ImageListParams,
+    ) -> Result<Vec<VisibleImage>> {
+        let mut scoped = params.clone();
+        scoped.account = Some(account);
+        let mut images = self.list_images(&scoped).await?;
+
+        if params.public != Some(true) {
+            let mut private = params.clone();
+            private.account = None;
+            private.public = Some(false);
+            let shared = self.list_images(&private).await?;
+            images.extend(
+                shared
+                    .into_iter()
+                    .filter(|image| image.acl.as_ref().is_some_and(|acl| acl.contains(&account))),
+            );
+        }
+
+        let mut seen = HashSet::new();
+        Ok(images
+            .into_iter()
+            .filter(|image| seen.insert(image.uuid))
+            .filter_map(|image| {
+                image
+                    .visibility_for(account)
+                    .map(|visibility| VisibleImage { image, visibility })
+            })
+            .collect())
+    }
+
     /// Fetch a single image by UUID.
+    ///
+    /// Manifests served with an ETag are cached and revalidated with
+    /// `If-None-Match`, so unchanged images are not transferred again.
     pub async fn get_image(&self, uuid: ImageUuid) -> Result<Image> {
-        let path = format!("images/{uuid}");
-        self.send_json::<(), Image>(Method::GET, &path, None, &[])
+        self.get_image_with_headers(uuid)
             .await
+            .map(|response| response.body)
+    }
+
+    /// Fetch an image along with the response status and headers.
+    ///
+    /// When the cached manifest is still current the status is
+    /// `304 Not Modified` and the body is the cached image.
+    pub async fn get_image_with_headers(&self, uuid: ImageUuid) -> Result<ServiceResponse<Image>> {
+        let path = format!("images/{}", encode_path_segment(uuid)?);
+        let cached = self
+            .manifests
+            .lock()
+            .ok()
+            .and_then(|mut manifests| manifests.get(&uuid).cloned());
+
+        let response = self
+            .inner
+            .execute_with_retry(
+                Method::GET,
+                &path,
+                &[],
+                |mut request| {
+                    request = request.header("Accept", "application/json");
+                    if let Some(cached) = &cached {
+                        request = request.header(IF_NONE_MATCH, cached.etag.as_str());
+                    }
+                    request
+                },
+                map_status_to_error,
+            )
+            .await?;
+
+        let status = response.status();
+        let headers = response.headers().clone();
+        if status == StatusCode::NOT_MODIFIED {
+            let image = cached.map(|cached| cached.image).ok_or_else(|| {
+                Error::http(
+                    status,
+                    format!("IMGAPI returned 304 for uncached image {uuid}"),
+                )
+            })?;
+            return Ok(ServiceResponse {
+                body: image,
+                status,
+                headers,
+            });
+        }
+
+        let etag = headers
+            .get(ETAG)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let body = response.bytes().await.map_err(Error::from)?;
+        let image: Image = serde_json::from_slice(&body)?;
+
+        if let Ok(mut manifests) = self.manifests.lock() {
+            match etag {
+                Some(etag) => {
+                    manifests.put(
+                        uuid,
+                        CachedManifest {
+                            etag,
+                            image: image.clone(),
+                        },
+                    );
+                }
+                None => {
+                    manifests.remove(&uuid);
+                }
+            }
+        }
+
+        Ok(ServiceResponse {
+            body: image,
+            status,
+            headers,
+        })
     }
 
     /// Create a new image record.
@@ -125,13 +396,13 @@ impl ImgapiClient {
         uuid: ImageUuid,
         request: &UpdateImageRequest,
     ) -> Result<Image> {
-        let path = format!("images/{uuid}");
+        let path = format!("images/{}", encode_path_segment(uuid)?);
         self.send_json(Method::PUT, &path, Some(request), &[]).await
     }
 
     /// Delete an image.
     pub async fn delete_image(&self, uuid: ImageUuid) -> Result<()> {
-        let path = format!("images/{uuid}");
+        let path = format!("images/{}", encode_path_segment(uuid)?);
         self.inner
             .execute_with_retry(
                 Method::DELETE,
@@ -146,31 +417,60 @@ impl ImgapiClient {
 
     /// Perform an action (activate/disable/enable) on an image.
     pub async fn perform_action(&self, uuid: ImageUuid, action: ImageAction) -> Result<Image> {
-        let path = format!("images/{uuid}/{}", action.as_str());
+        let path = format!("images/{}/{}", encode_path_segment(uuid)?, action.as_str());
         let empty = Value::Object(Default::default());
         self.send_json(Method::POST, &path, Some(&empty), &[]).await
     }
 
+    /// Disable or re-enable an image, returning the updated image.
+    ///
+    /// Uses the `disable`/`enable` actions rather than a field update. A
+    /// disabled image is hidden from default listings but keeps its `state`
+    /// and files; disabling is fully reversible and is not a delete.
+    pub async fn set_image_disabled(&self, uuid: ImageUuid, disabled: bool) -> Result<Image> {
+        let action = if disabled {
+            ImageAction::Disable
+        } else {
+            ImageAction::Enable
+        };
+        self.perform_action(uuid, action).await
+    }
+
     /// Import an image from an external source.
     pub async fn import_image(&self, request: &ImageImportRequest) -> Result<Image> {
         self.send_json(Method::POST, "images/import", Some(request), &[])
             .await
     }
 
+    /// Like [`import_image`](Self::import_image), but waits up to `timeout`
+    /// for IMGAPI to answer, since imports of large images can outlast the
+    /// client-wide timeout.
+    pub async fn import_image_with_timeout(
+        &self,
+        request: &ImageImportRequest,
+        timeout: Duration,
+    ) -> Result<Image> {
+        self.with_timeout(timeout).import_image(request).await
+    }
+
     /// Export an image to Manta or other storage.
     pub async fn export_image(
         &self,
         uuid: ImageUuid,
         request: &ExportImageRequest,
     ) -> Result<Image> {
-        let path = format!("images/{uuid}/export");
+        let path = format!("images/{}/export", encode_path_segment(uuid)?);
         self.send_json(Method::POST, &path, Some(request), &[])
             .await
     }
 
     /// Download the binary contents of an image file.
+    ///
+    /// The whole file is buffered in memory, so prefer
+    /// [`download_image_file_to`](Self::download_image_file_to) for image
+    /// files, which are often several gigabytes.
     pub async fn download_image_file(&self, uuid: ImageUuid) -> Result<Bytes> {
-        let path = format!("images/{uuid}/file");
+        let path = format!("images/{}/file", encode_path_segment(uuid)?);
         let response = self
             .inner
             .execute_with_retry(
@@ -185,6 +485,99 @@ impl ImgapiClient {
         response.bytes().await.map_err(Error::from)
     }
 
+    /// Stream the binary contents of an image file into `writer`.
+    ///
+    /// The body is written chunk by chunk as it arrives, so memory use stays
+    /// flat regardless of image size. Returns the number of bytes written.
+    pub async fn download_image_file_to<W>(&self, uuid: ImageUuid, writer: W) -> Result<u64>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        self.copy_image_file(uuid, writer, None).await
+    }
+
+    /// Download an image file and check it against `expected_sha1`.
+    ///
+    /// The whole file is buffered in memory; see
+    /// [`download_image_file_verified_to`](Self::download_image_file_verified_to)
+    /// for the streaming variant.
+    pub async fn download_image_file_verified(
+        &self,
+        uuid: ImageUuid,
+        expected_sha1: &str,
+    ) -> Result<Bytes> {
+        let mut data = Vec::new();
+        self.download_image_file_verified_to(uuid, expected_sha1, &mut data)
+            .await?;
+        Ok(Bytes::from(data))
+    }
+
+    /// Stream an image file into `writer` and check it against `expected_sha1`.
+    ///
+    /// The digest is computed over each chunk as it arrives. Returns
+    /// [`Error::BadRequest`] if it does not match `expected_sha1` (usually
+    /// [`ImageFile::sha1`](crate::models::ImageFile::sha1)); by then the whole
+    /// body has been written, so the caller should discard it. Returns the
+    /// number of bytes written.
+    pub async fn download_image_file_verified_to<W>(
+        &self,
+        uuid: ImageUuid,
+        expected_sha1: &str,
+        writer: W,
+    ) -> Result<u64>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let mut hasher = Sha1::new();
+        let written = self
+            .copy_image_file(uuid, writer, Some(&mut hasher))
+            .await?;
+
+        let actual = format!("{:x}", hasher.finalize());
+        if !actual.eq_ignore_ascii_case(expected_sha1.trim()) {
+            return Err(Error::BadRequest(format!(
+                "SHA-1 mismatch for image {uuid} file: expected {expected_sha1}, got {actual}"
+            )));
+        }
+        Ok(written)
+    }
+
+    async fn copy_image_file<W>(
+        &self,
+        uuid: ImageUuid,
+        mut writer: W,
+        mut hasher: Option<&mut Sha1>,
+    ) -> Result<u64>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let path = format!("images/{}/file", encode_path_segment(uuid)?);
+        let mut response = self
+            .inner
+            .execute_with_retry(
+                Method::GET,
+                &path,
+                &[],
+                |request| request.header("Accept", "application/octet-stream"),
+                map_status_to_error,
+            )
+            .await?;
+
+        let write_failed = |err: std::io::Error| {
+            Error::InternalError(format!("Failed to write image {uuid} file: {err}"))
+        };
+        let mut written = 0u64;
+        while let Some(chunk) = response.chunk().await? {
+            if let Some(hasher) = hasher.as_deref_mut() {
+                hasher.update(&chunk);
+            }
+            writer.write_all(&chunk).await.map_err(write_failed)?;
+            written += chunk.len() as u64;
+        }
+        writer.flush().await.map_err(write_failed)?;
+        Ok(written)
+    }
+
     /// Upload an image file.
     pub async fn upload_image_file(
         &self,
@@ -192,12 +585,39 @@ impl ImgapiClient {
         data: Bytes,
         content_type: Option<&str>,
     ) -> Result<()> {
-        let path = format!("images/{uuid}/file");
+        self.put_image_file(uuid, data, content_type, &[]).await
+    }
+
+    /// Upload an image file and return its hex SHA-1 digest.
+    ///
+    /// The digest is also sent as the `sha1` parameter, so IMGAPI rejects the
+    /// upload if the bytes it stores do not match.
+    pub async fn upload_image_file_verified(
+        &self,
+        uuid: ImageUuid,
+        data: Bytes,
+        content_type: Option<&str>,
+    ) -> Result<String> {
+        let digest = format!("{:x}", Sha1::digest(&data));
+        let params: [QueryPair; 1] = [("sha1".into(), digest.clone())];
+        self.put_image_file(uuid, data, content_type, &params)
+            .await?;
+        Ok(digest)
+    }
+
+    async fn put_image_file(
+        &self,
+        uuid: ImageUuid,
+        data: Bytes,
+        content_type: Option<&str>,
+        params: &[QueryPair],
+    ) -> Result<()> {
+        let path = format!("images/{}/file", encode_path_segment(uuid)?);
         self.inner
             .execute_with_retry(
                 Method::PUT,
                 &path,
-                &[],
+                params,
                 move |request| {
                     let mut request = request.body(data.clone());
                     if let Some(ct) = content_type {
@@ -211,41 +631,115 @@ impl ImgapiClient {
             .map(|_| ())
     }
 
+    /// Poll an image until it reaches `desired`.
+    ///
+    /// Returns early with the image's embedded [`ImageError`](crate::models::ImageError)
+    /// if it moves to `failed`, and with a deadline timeout once
+    /// `options.timeout` has elapsed.
+    pub async fn wait_for_image_state(
+        &self,
+        uuid: ImageUuid,
+        desired: ImageState,
+        options: WaitOptions,
+    ) -> Result<Image> {
+        poll_until(options, || async move {
+            let image = self.get_image(uuid).await?;
+            if image.is_in_state(desired) {
+                return Ok(Some(image));
+            }
+            if image.is_in_state(ImageState::Failed) {
+                let message = image.error.as_ref().map_or_else(
+                    || format!("image {uuid} entered state failed"),
+                    |error| format!("image {uuid} failed: {}: {}", error.code, error.message),
+                );
+                return Err(Error::ExternalServiceError {
+                    service: "imgapi".to_string(),
+                    message,
+                });
+            }
+            Ok(None)
+        })
+        .await
+    }
+
+    /// Upload an image file, activate the image, and wait until it is active.
+    pub async fn upload_and_activate(
+        &self,
+        uuid: ImageUuid,
+        data: Bytes,
+        content_type: Option<&str>,
+        options: WaitOptions,
+    ) -> Result<Image> {
+        self.upload_image_file(uuid, data, content_type).await?;
+        self.perform_action(uuid, ImageAction::Activate).await?;
+        self.wait_for_image_state(uuid, ImageState::Active, options)
+            .await
+    }
+
+    /// Fetch any IMGAPI path as raw JSON; see [`ServiceClient::get_raw`].
+    pub async fn get_raw(&self, path: &str, params: &[QueryPair]) -> Result<serde_json::Value> {
+        self.inner.get_raw(path, params, map_status_to_error).await
+    }
+
+    /// Send an arbitrary request to IMGAPI and return the raw JSON response;
+    /// see [`ServiceClient::send_raw`].
+    pub async fn send_raw(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        self.inner
+            .send_raw(method, path, body, &[], map_status_to_error)
+            .await
+    }
+
     async fn send_json<B, R>(
         &self,
         method: Method,
         path: &str,
         body: Option<&B>,
-        params: &[(&'static str, String)],
+        params: &[QueryPair],
     ) -> Result<R>
     where
         B: Serialize + ?Sized,
         R: DeserializeOwned,
     {
-        let response = self
-            .inner
-            .execute_with_retry(
+        self.inner
+            .execute_json_with_retry(
                 method,
                 path,
                 params,
                 |mut request| {
                     request = request.header("Accept", "application/json");
                     if let Some(payload) = body {
-                        request = request.json(payload);
+                        request = self.inner.request_options().json_body(request, payload);
                     }
                     request
                 },
                 map_status_to_error,
             )
-            .await?;
+            .await
+    }
+}
 
-        response.json::<R>().await.map_err(Error::from)
+#[async_trait]
+impl PreflightCheck for ImgapiClient {
+    fn service(&self) -> TritonService {
+        TritonService::Imgapi
+    }
+
+    async fn preflight(&self) -> PreflightResult {
+        self.inner
+            .preflight("images", &[("limit".into(), "1".to_string())])
+            .await
     }
 }
 
 fn map_status_to_error(status: StatusCode, text: String) -> Error {
     match status {
         StatusCode::NOT_FOUND => Error::NotFound(text),
+        StatusCode::PAYLOAD_TOO_LARGE => Error::PayloadTooLarge(text),
         StatusCode::BAD_REQUEST => Error::BadRequest(text),
         StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
             Error::InvalidRequest(format!("IMGAPI authentication failed: {text}"))
@@ -260,7 +754,7 @@ fn map_status_to_error(status: StatusCode, text: String) -> Error {
         status if status.is_server_error() => {
             Error::ServiceUnavailable(format!("IMGAPI server error {status}: {text}"))
         }
-        _ => Error::HttpError(format!("IMGAPI error {status}: {text}")),
+        _ => Error::http(status, format!("IMGAPI error {status}: {text}")),
     }
 }
 
@@ -301,15 +795,127 @@ impl ServiceDiscovery for ImgapiDiscovery {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::Visibility;
     use serde_json::json;
-    use triton_core::uuid::OwnerUuid;
-    use wiremock::matchers::{body_json, header, method, path};
+    use wiremock::matchers::{body_json, header, method, path, query_param};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     fn test_client(server: &MockServer) -> ImgapiClient {
         ImgapiClient::new(server.uri()).unwrap()
     }
 
+    #[tokio::test]
+    async fn list_visible_images_classifies_and_dedupes() {
+        let server = MockServer::start().await;
+        let account = OwnerUuid::new_v4();
+        let other = OwnerUuid::new_v4();
+        let own = ImageUuid::new_v4();
+        let shared = ImageUuid::new_v4();
+        let public = ImageUuid::new_v4();
+        let hidden = ImageUuid::new_v4();
+        let image = |uuid: ImageUuid, owner: OwnerUuid, is_public: bool, acl: Vec<OwnerUuid>| {
+            json!({
+                "uuid": uuid,
+                "name": "img",
+                "os": "smartos",
+                "type": "zone-dataset",
+                "state": "active",
+                "owner": owner,
+                "public": is_public,
+                "acl": acl
+            })
+        };
+
+        Mock::given(method("GET"))
+            .and(path("/images"))
+            .and(query_param("account", account.to_string()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                image(own, account, false, vec![]),
+                image(public, other, true, vec![]),
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/images"))
+            .and(query_param("public", "false"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                image(own, account, false, vec![]),
+                image(shared, other, false, vec![account]),
+                image(hidden, other, false, vec![]),
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let visible: Vec<_> = client
+            .list_visible_images(account, &ImageListParams::default())
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.image.uuid, entry.visibility))
+            .collect();
+
+        assert_eq!(
+            visible,
+            vec![
+                (own, Visibility::Own),
+                (public, Visibility::Public),
+                (shared, Visibility::Shared),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn list_compatible_images_filters_requirements() {
+        let server = MockServer::start().await;
+        let small = ImageUuid::new_v4();
+        let big = ImageUuid::new_v4();
+        let capped = ImageUuid::new_v4();
+        Mock::given(method("GET"))
+            .and(path("/images"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                {
+                    "uuid": small,
+                    "name": "base-64",
+                    "os": "smartos",
+                    "type": "zone-dataset",
+                    "state": "active",
+                    "min_memory": 256,
+                    "min_disk": 1024
+                },
+                {
+                    "uuid": big,
+                    "name": "windows",
+                    "os": "windows",
+                    "type": "zvol",
+                    "state": "active",
+                    "min_memory": 8192,
+                    "min_disk": 40960
+                },
+                {
+                    "uuid": capped,
+                    "name": "legacy-32",
+                    "os": "linux",
+                    "type": "zvol",
+                    "state": "active",
+                    "requirements": { "max_physical_memory": 2048 }
+                }
+            ])))
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let images = client
+            .list_compatible_images(&ImageListParams::default(), 4096, 20480)
+            .await
+            .unwrap();
+
+        let uuids: Vec<_> = images.iter().map(|image| image.uuid).collect();
+        assert_eq!(uuids, vec![small]);
+    }
+
     #[tokio::test]
     async fn list_images_success() {
         let server = MockServer::start().await;
@@ -336,6 +942,109 @@ mod tests {
         assert_eq!(images[0].name, "ubuntu-22.04");
     }
 
+    #[tokio::test]
+    async fn get_image_revalidates_with_etag() {
+        let server = MockServer::start().await;
+        let uuid = ImageUuid::new_v4();
+        let image = json!({
+            "uuid": uuid,
+            "name": "base-64",
+            "os": "smartos",
+            "type": "zone-dataset",
+            "state": "active"
+        });
+
+        Mock::given(method("GET"))
+            .and(path(format!("/images/{uuid}").as_str()))
+            .and(header("if-none-match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/images/{uuid}").as_str()))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("ETag", "\"v1\"")
+                    .set_body_json(image),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let first = client.get_image(uuid).await.unwrap();
+        let second = client.get_image(uuid).await.unwrap();
+        assert_eq!(first, second);
+        assert_eq!(second.name, "base-64");
+    }
+
+    #[tokio::test]
+    async fn get_image_with_headers_surfaces_metadata() {
+        let server = MockServer::start().await;
+        let uuid = ImageUuid::new_v4();
+        Mock::given(method("GET"))
+            .and(path(format!("/images/{uuid}").as_str()))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("ETag", "\"v2\"")
+                    .insert_header("x-resource-count", "1")
+                    .set_body_json(json!({
+                        "uuid": uuid,
+                        "name": "base-64",
+                        "os": "smartos",
+                        "type": "zone-dataset",
+                        "state": "active"
+                    })),
+            )
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let response = client.get_image_with_headers(uuid).await.unwrap();
+        assert_eq!(response.status, StatusCode::OK);
+        assert_eq!(response.header("etag"), Some("\"v2\""));
+        assert_eq!(response.header("x-resource-count"), Some("1"));
+        assert_eq!(response.body.name, "base-64");
+    }
+
+    #[tokio::test]
+    async fn manifest_cache_is_bounded() {
+        let server = MockServer::start().await;
+        let uuids = [ImageUuid::new_v4(), ImageUuid::new_v4()];
+        for uuid in uuids {
+            Mock::given(method("GET"))
+                .and(path(format!("/images/{uuid}").as_str()))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .insert_header("ETag", "\"v1\"")
+                        .set_body_json(json!({
+                            "uuid": uuid,
+                            "name": "base-64",
+                            "os": "smartos",
+                            "type": "zone-dataset",
+                            "state": "active"
+                        })),
+                )
+                .mount(&server)
+                .await;
+        }
+
+        let client = ImgapiClientBuilder::new(server.uri())
+            .unwrap()
+            .with_cache_capacity(1)
+            .build()
+            .unwrap();
+        for uuid in uuids {
+            client.get_image(uuid).await.unwrap();
+        }
+
+        let mut manifests = client.manifests.lock().unwrap();
+        assert_eq!(manifests.len(), 1);
+        assert!(manifests.get(&uuids[0]).is_none());
+        assert!(manifests.get(&uuids[1]).is_some());
+    }
+
     #[tokio::test]
     async fn get_image_not_found() {
         let server = MockServer::start().await;
@@ -412,6 +1121,52 @@ mod tests {
         assert_eq!(image.state, "unactivated");
     }
 
+    #[tokio::test]
+    async fn import_image_with_timeout_overrides_default() {
+        let server = MockServer::start().await;
+        let uuid = ImageUuid::new_v4();
+        Mock::given(method("POST"))
+            .and(path("/images/import"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_delay(Duration::from_millis(500))
+                    .set_body_json(json!({
+                        "uuid": uuid,
+                        "name": "base-64",
+                        "os": "smartos",
+                        "type": "zone-dataset",
+                        "state": "active"
+                    })),
+            )
+            .mount(&server)
+            .await;
+
+        let client = ImgapiClientBuilder::new(server.uri())
+            .unwrap()
+            .with_retry_policy(RetryPolicy::no_retry())
+            .build()
+            .unwrap();
+        let request = ImageImportRequest {
+            uuid,
+            compression: None,
+            sha1: "da39a3ee5e6b4b0d3255bfef95601890afd80709".into(),
+            storage: None,
+            file_path: "/var/tmp/image.zfs.gz".into(),
+            size: 0,
+            source: None,
+            md5: None,
+        };
+
+        let err = client
+            .import_image_with_timeout(&request, Duration::from_millis(50))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Timeout { .. }));
+
+        let image = client.import_image(&request).await.unwrap();
+        assert_eq!(image.uuid, uuid);
+    }
+
     #[tokio::test]
     async fn perform_action_posts_to_action_endpoint() {
         let server = MockServer::start().await;
@@ -438,6 +1193,32 @@ mod tests {
         assert_eq!(image.state, "active");
     }
 
+    #[tokio::test]
+    async fn set_image_disabled_routes_to_actions() {
+        let server = MockServer::start().await;
+        let uuid = ImageUuid::new_v4();
+
+        for (action, disabled) in [("disable", true), ("enable", false)] {
+            let mut image = image_in_state(uuid, "active");
+            image["disabled"] = json!(disabled);
+            Mock::given(method("POST"))
+                .and(path(format!("/images/{uuid}/{action}")))
+                .and(body_json(json!({})))
+                .respond_with(ResponseTemplate::new(200).set_body_json(image))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let client = test_client(&server);
+        let image = client.set_image_disabled(uuid, true).await.unwrap();
+        assert!(image.is_disabled());
+        assert!(image.is_in_state(ImageState::Active));
+
+        let image = client.set_image_disabled(uuid, false).await.unwrap();
+        assert!(!image.is_disabled());
+    }
+
     #[tokio::test]
     async fn delete_image_handles_no_content() {
         let server = MockServer::start().await;
@@ -469,6 +1250,25 @@ mod tests {
         assert_eq!(bytes, Bytes::from_static(b"binary"));
     }
 
+    #[tokio::test]
+    async fn download_image_file_to_streams_into_writer() {
+        let server = MockServer::start().await;
+        let uuid = ImageUuid::new_v4();
+        let body: Vec<u8> = (0..512 * 1024).map(|i| (i % 251) as u8).collect();
+
+        Mock::given(method("GET"))
+            .and(path(format!("/images/{uuid}/file").as_str()))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(body.clone()))
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let mut out = Vec::new();
+        let written = client.download_image_file_to(uuid, &mut out).await.unwrap();
+        assert_eq!(written, body.len() as u64);
+        assert_eq!(out, body);
+    }
+
     #[tokio::test]
     async fn upload_image_file_sends_bytes() {
         let server = MockServer::start().await;
@@ -492,6 +1292,86 @@ mod tests {
             .unwrap();
     }
 
+    // SHA-1 of b"data".
+    const DATA_SHA1: &str = "a17c9aaa61e80a1bf71d0d850af4e5baa9800bbd";
+
+    #[tokio::test]
+    async fn download_image_file_verified_checks_sha1() {
+        let server = MockServer::start().await;
+        let uuid = ImageUuid::new_v4();
+
+        Mock::given(method("GET"))
+            .and(path(format!("/images/{uuid}/file").as_str()))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"data"))
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let bytes = client
+            .download_image_file_verified(uuid, &DATA_SHA1.to_uppercase())
+            .await
+            .unwrap();
+        assert_eq!(bytes, Bytes::from_static(b"data"));
+
+        let err = client
+            .download_image_file_verified(uuid, "0000000000000000000000000000000000000000")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::BadRequest(message) if message.contains(DATA_SHA1)));
+    }
+
+    #[tokio::test]
+    async fn download_image_file_verified_to_streams_and_checks_sha1() {
+        let server = MockServer::start().await;
+        let uuid = ImageUuid::new_v4();
+
+        Mock::given(method("GET"))
+            .and(path(format!("/images/{uuid}/file").as_str()))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"data"))
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let mut out = Vec::new();
+        let written = client
+            .download_image_file_verified_to(uuid, DATA_SHA1, &mut out)
+            .await
+            .unwrap();
+        assert_eq!(written, 4);
+        assert_eq!(out, b"data");
+
+        let err = client
+            .download_image_file_verified_to(
+                uuid,
+                "0000000000000000000000000000000000000000",
+                &mut Vec::new(),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::BadRequest(message) if message.contains(DATA_SHA1)));
+    }
+
+    #[tokio::test]
+    async fn upload_image_file_verified_sends_and_returns_sha1() {
+        let server = MockServer::start().await;
+        let uuid = ImageUuid::new_v4();
+
+        Mock::given(method("PUT"))
+            .and(path(format!("/images/{uuid}/file").as_str()))
+            .and(query_param("sha1", DATA_SHA1))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let digest = client
+            .upload_image_file_verified(uuid, Bytes::from_static(b"data"), None)
+            .await
+            .unwrap();
+        assert_eq!(digest, DATA_SHA1);
+    }
+
     struct MockDiscovery;
 
     #[async_trait]
@@ -521,4 +1401,110 @@ mod tests {
             .unwrap();
         assert_eq!(endpoints.len(), 1);
     }
+
+    fn image_in_state(uuid: ImageUuid, state: &str) -> Value {
+        json!({
+            "uuid": uuid,
+            "name": "base-64",
+            "os": "smartos",
+            "type": "zone-dataset",
+            "state": state
+        })
+    }
+
+    fn fast_wait() -> WaitOptions {
+        WaitOptions::default()
+            .with_poll_interval(Duration::from_millis(10))
+            .with_timeout(Duration::from_secs(5))
+    }
+
+    #[tokio::test]
+    async fn upload_and_activate_polls_until_active() {
+        let server = MockServer::start().await;
+        let uuid = ImageUuid::new_v4();
+        let image_path = format!("/images/{uuid}");
+
+        Mock::given(method("PUT"))
+            .and(path(format!("{image_path}/file")))
+            .and(header("Content-Type", "application/octet-stream"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(format!("{image_path}/activate")))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(image_in_state(uuid, "unactivated")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(image_path.clone()))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(image_in_state(uuid, "unactivated")),
+            )
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(image_path))
+            .respond_with(ResponseTemplate::new(200).set_body_json(image_in_state(uuid, "active")))
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let image = client
+            .upload_and_activate(
+                uuid,
+                Bytes::from_static(b"image-bytes"),
+                Some("application/octet-stream"),
+                fast_wait(),
+            )
+            .await
+            .unwrap();
+
+        assert!(image.is_in_state(ImageState::Active));
+        let polls = server
+            .received_requests()
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|request| request.method.as_str() == "GET")
+            .count();
+        assert_eq!(polls, 2);
+    }
+
+    #[tokio::test]
+    async fn wait_for_image_state_surfaces_failure() {
+        let server = MockServer::start().await;
+        let uuid = ImageUuid::new_v4();
+        let mut failed = image_in_state(uuid, "failed");
+        failed["error"] = json!({
+            "code": "ImportFailed",
+            "message": "source image not found"
+        });
+
+        Mock::given(method("GET"))
+            .and(path(format!("/images/{uuid}")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(failed))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let err = client
+            .wait_for_image_state(uuid, ImageState::Active, fast_wait())
+            .await
+            .unwrap_err();
+
+        match err {
+            Error::ExternalServiceError { service, message } => {
+                assert_eq!(service, "imgapi");
+                assert!(message.contains("ImportFailed"));
+                assert!(message.contains("source image not found"));
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
 }
@@ -11,7 +11,8 @@ pub mod models;
 pub use client::{ImgapiClient, ImgapiClientBuilder, ImgapiDiscovery};
 pub use models::{
     CreateImageRequest, ExportImageRequest, Image, ImageAction, ImageFile, ImageImportRequest,
-    ImageListParams, ImageRequirements, ImageUser, ImportImageSource, UpdateImageRequest,
+    ImageListParams, ImageOs, ImageRequirements, ImageState, ImageType, ImageUser,
+    ImportImageSource, UpdateImageRequest, Visibility, VisibleImage,
 };
 
 /// Convenient result alias using the shared Triton error type.
@@ -1,9 +1,11 @@
 //! IMGAPI models shared by client and prospective server implementations.
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
-use triton_core::query::QueryParams;
+use std::fmt;
+use triton_core::query::{QueryPair, QueryParams};
 use triton_core::uuid::{ImageUuid, OwnerUuid};
 
 /// Deserialize a map whose values may be strings, booleans, or numbers into string values.
@@ -58,6 +60,94 @@ fn value_to_bool(value: Value) -> Option<bool> {
     }
 }
 
+/// Declare a string-backed enum that keeps unrecognised values in `Other`.
+macro_rules! lenient_string_enum {
+    (
+        $(#[$meta:meta])*
+        $name:ident { $($(#[$vmeta:meta])* $variant:ident => $value:literal),+ $(,)? }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+        #[serde(from = "String", into = "String")]
+        pub enum $name {
+            $($(#[$vmeta])* $variant,)+
+            /// Value not known to this client, preserved verbatim.
+            Other(String),
+        }
+
+        impl $name {
+            /// Wire representation of the value.
+            #[must_use]
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Self::$variant => $value,)+
+                    Self::Other(value) => value,
+                }
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(value: String) -> Self {
+                match value.as_str() {
+                    $($value => Self::$variant,)+
+                    _ => Self::Other(value),
+                }
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(value: &str) -> Self {
+                Self::from(value.to_string())
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> Self {
+                match value {
+                    $name::Other(value) => value,
+                    known => known.as_str().to_string(),
+                }
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+    };
+}
+
+lenient_string_enum! {
+    /// Image type as reported by IMGAPI.
+    ImageType {
+        /// SmartOS zone dataset.
+        ZoneDataset => "zone-dataset",
+        /// LX-branded zone dataset.
+        LxDataset => "lx-dataset",
+        /// Hardware VM disk image.
+        Zvol => "zvol",
+        /// Docker image layer.
+        Docker => "docker",
+    }
+}
+
+lenient_string_enum! {
+    /// Image operating system as reported by IMGAPI.
+    ImageOs {
+        /// SmartOS.
+        Smartos => "smartos",
+        /// Linux.
+        Linux => "linux",
+        /// Windows.
+        Windows => "windows",
+        /// BSD variants.
+        Bsd => "bsd",
+        /// illumos distributions.
+        Illumos => "illumos",
+    }
+}
+
 /// Parameters supported by the `/images` list endpoint.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct ImageListParams {
@@ -66,7 +156,7 @@ pub struct ImageListParams {
     /// Filter by version.
     pub version: Option<String>,
     /// Filter by operating system.
-    pub os: Option<String>,
+    pub os: Option<ImageOs>,
     /// Filter by owner UUID.
     pub owner: Option<OwnerUuid>,
     /// Account visibility scope.
@@ -76,7 +166,7 @@ pub struct ImageListParams {
     /// Filter by public flag.
     pub public: Option<bool>,
     /// Filter by type.
-    pub type_filter: Option<String>,
+    pub type_filter: Option<ImageType>,
     /// Filter by tag key/value.
     pub tag: Option<String>,
     /// Filter by billing tag.
@@ -97,21 +187,25 @@ pub struct ImageListParams {
     pub sort_by: Option<String>,
     /// Sort order.
     pub sort_order: Option<String>,
+    /// Additional query parameters appended verbatim after the typed ones.
+    ///
+    /// Keys already set by a typed field are skipped.
+    pub extra_params: Vec<(String, String)>,
 }
 
 impl ImageListParams {
     /// Convert the parameters into URL query pairs.
     #[must_use]
-    pub fn to_pairs(&self) -> Vec<(&'static str, String)> {
+    pub fn to_pairs(&self) -> Vec<QueryPair> {
         let mut params = QueryParams::new();
         params.push_opt("name", self.name.as_deref());
         params.push_opt("version", self.version.as_deref());
-        params.push_opt("os", self.os.as_deref());
+        params.push_opt("os", self.os.as_ref());
         params.push_opt("owner", self.owner.as_ref());
         params.push_opt("account", self.account.as_ref());
         params.push_opt("state", self.state.as_deref());
         params.push_opt("public", self.public);
-        params.push_opt("type", self.type_filter.as_deref());
+        params.push_opt("type", self.type_filter.as_ref());
         params.push_opt("tag", self.tag.as_deref());
         params.push_opt("billing_tag", self.billing_tag.as_deref());
         params.push_opt("trait", self.trait_filter.as_deref());
@@ -122,6 +216,7 @@ impl ImageListParams {
         params.push_opt("latest_only", self.latest_only);
         params.push_opt("sort_by", self.sort_by.as_deref());
         params.push_opt("sort_order", self.sort_order.as_deref());
+        params.push_extra(&self.extra_params);
 
         params.into_pairs()
     }
@@ -138,10 +233,10 @@ pub struct Image {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub version: Option<String>,
     /// Operating system.
-    pub os: String,
+    pub os: ImageOs,
     /// Image type.
     #[serde(rename = "type")]
-    pub r#type: String,
+    pub r#type: ImageType,
     /// Current state.
     pub state: String,
     /// Description.
@@ -169,7 +264,10 @@ pub struct Image {
         deserialize_with = "deserialize_string_map"
     )]
     pub tags: Option<HashMap<String, String>>,
-    /// Disabled flag.
+    /// Whether the image is hidden from default listings.
+    ///
+    /// Independent of [`Image::state`]; toggle it with
+    /// [`ImgapiClient::set_image_disabled`](crate::ImgapiClient::set_image_disabled).
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub disabled: Option<bool>,
     /// Origin image UUID.
@@ -248,6 +346,101 @@ pub struct Image {
     pub updated: Option<String>,
 }
 
+impl Image {
+    /// Returns true if the image can be provisioned with the given memory and disk (MiB).
+    ///
+    /// Checks `min_memory`, `min_disk`, and `requirements.max_physical_memory`.
+    #[must_use]
+    pub fn fits(&self, memory_mib: u64, disk_mib: u64) -> bool {
+        let max_memory = self
+            .requirements
+            .as_ref()
+            .and_then(|requirements| requirements.max_physical_memory);
+
+        !(self.min_memory.is_some_and(|min| min > memory_mib)
+            || self.min_disk.is_some_and(|min| min > disk_mib)
+            || max_memory.is_some_and(|max| memory_mib > max))
+    }
+
+    /// Classify how `account` can see this image, or `None` if it cannot.
+    ///
+    /// Ownership wins over ACL membership, which wins over the public flag.
+    #[must_use]
+    pub fn visibility_for(&self, account: OwnerUuid) -> Option<Visibility> {
+        if self.owner == Some(account) {
+            Some(Visibility::Own)
+        } else if self.acl.as_ref().is_some_and(|acl| acl.contains(&account)) {
+            Some(Visibility::Shared)
+        } else if self.public == Some(true) {
+            Some(Visibility::Public)
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if the image is disabled (a missing flag means enabled).
+    #[must_use]
+    pub fn is_disabled(&self) -> bool {
+        self.disabled == Some(true)
+    }
+
+    /// Returns true if the image currently reports the given state.
+    #[must_use]
+    pub fn is_in_state(&self, state: ImageState) -> bool {
+        self.state == state.as_str()
+    }
+
+    /// Parses [`Image::published_at`], returning `None` if missing or malformed.
+    #[must_use]
+    pub fn published_at_datetime(&self) -> Option<DateTime<Utc>> {
+        parse_timestamp(self.published_at.as_deref())
+    }
+
+    /// Parses [`Image::created`], returning `None` if missing or malformed.
+    #[must_use]
+    pub fn created_datetime(&self) -> Option<DateTime<Utc>> {
+        parse_timestamp(self.created.as_deref())
+    }
+
+    /// Parses [`Image::updated`], returning `None` if missing or malformed.
+    #[must_use]
+    pub fn updated_datetime(&self) -> Option<DateTime<Utc>> {
+        parse_timestamp(self.updated.as_deref())
+    }
+
+    /// Parses [`Image::expires_at`], returning `None` if missing or malformed.
+    #[must_use]
+    pub fn expires_at_datetime(&self) -> Option<DateTime<Utc>> {
+        parse_timestamp(self.expires_at.as_deref())
+    }
+}
+
+fn parse_timestamp(value: Option<&str>) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value?)
+        .ok()
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+}
+
+/// How an account came to see an image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Visibility {
+    /// The account owns the image.
+    Own,
+    /// The account is listed in the image's ACL.
+    Shared,
+    /// The image is public.
+    Public,
+}
+
+/// An image annotated with how the requesting account sees it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VisibleImage {
+    /// The image manifest.
+    pub image: Image,
+    /// Why the image is visible to the account.
+    pub visibility: Visibility,
+}
+
 /// Error details embedded within image responses.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ImageError {
@@ -345,10 +538,10 @@ pub struct CreateImageRequest {
     /// Version identifier.
     pub version: String,
     /// Operating system.
-    pub os: String,
+    pub os: ImageOs,
     /// Image type.
     #[serde(rename = "type")]
-    pub r#type: String,
+    pub r#type: ImageType,
     /// Optional description.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
@@ -444,6 +637,9 @@ pub struct UpdateImageRequest {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub state: Option<String>,
     /// Disabled flag.
+    ///
+    /// Prefer [`ImgapiClient::set_image_disabled`](crate::ImgapiClient::set_image_disabled),
+    /// which uses IMGAPI's dedicated `disable`/`enable` actions.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub disabled: Option<bool>,
     /// Tags.
@@ -520,9 +716,9 @@ pub struct ExportImageRequest {
 pub enum ImageAction {
     /// Activate an image.
     Activate,
-    /// Disable an image.
+    /// Disable an image, hiding it from default listings (reversible; not a delete).
     Disable,
-    /// Enable an image.
+    /// Re-enable a disabled image.
     Enable,
 }
 
@@ -538,6 +734,35 @@ impl ImageAction {
     }
 }
 
+/// Lifecycle states reported in [`Image::state`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImageState {
+    /// Image is usable for provisioning.
+    Active,
+    /// Manifest exists but the image has not been activated yet.
+    Unactivated,
+    /// Image has been disabled.
+    Disabled,
+    /// Image is being created from a VM.
+    Creating,
+    /// Image creation or import failed; see [`Image::error`].
+    Failed,
+}
+
+impl ImageState {
+    /// Return the IMGAPI string for this state.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Unactivated => "unactivated",
+            Self::Disabled => "disabled",
+            Self::Creating => "creating",
+            Self::Failed => "failed",
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -556,10 +781,99 @@ mod tests {
         };
 
         let pairs = params.to_pairs();
-        assert!(pairs.contains(&("name", "ubuntu".into())));
+        assert!(pairs.contains(&("name".into(), "ubuntu".into())));
         assert!(pairs.iter().any(|(k, _)| *k == "owner"));
-        assert!(pairs.contains(&("limit", "10".into())));
-        assert!(pairs.contains(&("latest_only", "true".into())));
+        assert!(pairs.contains(&("limit".into(), "10".into())));
+        assert!(pairs.contains(&("latest_only".into(), "true".into())));
+    }
+
+    #[test]
+    fn image_list_params_accepts_typed_filters() {
+        let params = ImageListParams {
+            os: Some(ImageOs::Smartos),
+            type_filter: Some(ImageType::LxDataset),
+            ..ImageListParams::default()
+        };
+
+        let pairs = params.to_pairs();
+        assert!(pairs.contains(&("os".into(), "smartos".into())));
+        assert!(pairs.contains(&("type".into(), "lx-dataset".into())));
+    }
+
+    #[test]
+    fn image_type_known_variants_round_trip() {
+        for (variant, wire) in [
+            (ImageType::ZoneDataset, "zone-dataset"),
+            (ImageType::LxDataset, "lx-dataset"),
+            (ImageType::Zvol, "zvol"),
+            (ImageType::Docker, "docker"),
+        ] {
+            assert_eq!(variant.as_str(), wire);
+            assert_eq!(variant.to_string(), wire);
+            assert_eq!(serde_json::to_value(&variant).unwrap(), json!(wire));
+            assert_eq!(
+                serde_json::from_value::<ImageType>(json!(wire)).unwrap(),
+                variant
+            );
+        }
+    }
+
+    #[test]
+    fn image_os_known_variants_round_trip() {
+        for (variant, wire) in [
+            (ImageOs::Smartos, "smartos"),
+            (ImageOs::Linux, "linux"),
+            (ImageOs::Windows, "windows"),
+            (ImageOs::Bsd, "bsd"),
+            (ImageOs::Illumos, "illumos"),
+        ] {
+            assert_eq!(variant.as_str(), wire);
+            assert_eq!(variant.to_string(), wire);
+            assert_eq!(serde_json::to_value(&variant).unwrap(), json!(wire));
+            assert_eq!(
+                serde_json::from_value::<ImageOs>(json!(wire)).unwrap(),
+                variant
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_image_type_and_os_are_preserved() {
+        let kind: ImageType = serde_json::from_value(json!("lx-bhyve")).unwrap();
+        assert_eq!(kind, ImageType::Other("lx-bhyve".into()));
+        assert_eq!(serde_json::to_value(&kind).unwrap(), json!("lx-bhyve"));
+
+        let os: ImageOs = serde_json::from_value(json!("plan9")).unwrap();
+        assert_eq!(os, ImageOs::Other("plan9".into()));
+        assert_eq!(os.as_str(), "plan9");
+        assert_eq!(serde_json::to_value(&os).unwrap(), json!("plan9"));
+    }
+
+    #[test]
+    fn image_timestamps_parse_on_demand() {
+        let image: Image = serde_json::from_value(json!({
+            "uuid": "2b683a82-a066-11e3-97ab-2faa44701c5a",
+            "name": "base-64",
+            "os": "smartos",
+            "type": "zone-dataset",
+            "state": "active",
+            "published_at": "2014-02-28T10:50:31Z",
+            "created": "2014-02-28T10:40:00.123+01:00",
+            "updated": "last tuesday"
+        }))
+        .unwrap();
+
+        assert_eq!(
+            image.published_at_datetime().unwrap().to_rfc3339(),
+            "2014-02-28T10:50:31+00:00"
+        );
+        assert_eq!(
+            image.created_datetime().unwrap().to_rfc3339(),
+            "2014-02-28T09:40:00.123+00:00"
+        );
+        assert_eq!(image.updated_datetime(), None);
+        assert_eq!(image.expires_at_datetime(), None);
+        assert_eq!(image.updated.as_deref(), Some("last tuesday"));
     }
 
     #[test]
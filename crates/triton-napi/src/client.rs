@@ -1,75 +1,68 @@
 //! Asynchronous NAPI client implementation.
 
 use crate::models::{
-    CreateNetworkRequest, Network, NetworkListParams, NetworkPool, Nic, UpdateNetworkRequest,
+    CreateNetworkPoolRequest, CreateNetworkRequest, CreateNicTagRequest, IpRecord, MacAddress,
+    Network, NetworkListParams, NetworkNicRequest, NetworkPool, NetworkVm, NetworkVmMap, Nic,
+    NicState, NicTag, UnresolvedNic, UpdateNetworkPoolRequest, UpdateNetworkRequest,
 };
 use crate::Result;
 use async_trait::async_trait;
-use reqwest::{Client, ClientBuilder, Method, StatusCode};
+use reqwest::header::HeaderName;
+use reqwest::{Client, Method, StatusCode};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use std::future::Future;
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
-use tokio::time::sleep;
-use tracing::{debug, info};
 use triton_core::client::{
-    ClientConfig, RetryPolicy, DEFAULT_POOL_IDLE_TIMEOUT, DEFAULT_POOL_MAX_IDLE_PER_HOST,
-    NAPI_DEFAULT_TIMEOUT,
+    ClientConfig, ClientObserver, RequestObserver, RequestOptions, RetryPolicy, ServiceClient,
+    ServiceClientBuilder, NAPI_DEFAULT_TIMEOUT,
 };
+use triton_core::concurrency::run_bounded;
+use triton_core::path::encode_path_segment;
+use triton_core::preflight::{PreflightCheck, PreflightResult};
+use triton_core::query::QueryPair;
+use triton_core::selection::EndpointSelector;
 use triton_core::services::{DiscoveryStatus, ServiceDiscovery};
-use triton_core::types::TritonService;
-use triton_core::uuid::NetworkUuid;
+use triton_core::types::{EndpointList, TritonService};
+use triton_core::uuid::{InstanceUuid, NetworkUuid, OwnerUuid};
+use triton_core::wait::{poll_until, WaitOptions};
 use triton_core::Error;
 use url::Url;
 
 const USER_AGENT: &str = concat!("triton-napi/", env!("CARGO_PKG_VERSION"));
+const VM_LOOKUP_CONCURRENCY: usize = 8;
 
 /// Builder for [`NapiClient`].
 #[derive(Debug, Clone)]
 pub struct NapiClientBuilder {
-    base_url: Url,
-    http_config: ClientConfig,
-    retry_policy: RetryPolicy,
-    basic_auth: Option<(String, String)>,
-    token: Option<String>,
+    inner: ServiceClientBuilder,
 }
 
 impl NapiClientBuilder {
     /// Create a new builder from the provided base URL.
     pub fn new(base_url: impl AsRef<str>) -> Result<Self> {
-        let url = Url::parse(base_url.as_ref()).map_err(|err| {
-            Error::ConfigError(format!(
-                "Invalid NAPI base URL `{}`: {err}",
-                base_url.as_ref()
-            ))
-        })?;
-
-        let config = ClientConfig::new()
-            .with_timeout(Duration::from_secs(NAPI_DEFAULT_TIMEOUT))
-            .with_pool_idle_timeout(Duration::from_secs(DEFAULT_POOL_IDLE_TIMEOUT))
-            .with_pool_max_idle(DEFAULT_POOL_MAX_IDLE_PER_HOST);
-
-        Ok(Self {
-            base_url: url,
-            retry_policy: config.retry_policy,
-            http_config: config,
-            basic_auth: None,
-            token: None,
-        })
+        let builder = ServiceClientBuilder::new(
+            TritonService::Napi,
+            base_url,
+            Duration::from_secs(NAPI_DEFAULT_TIMEOUT),
+        )?
+        .with_user_agent(USER_AGENT);
+
+        Ok(Self { inner: builder })
     }
 
     /// Override the retry policy.
     #[must_use]
     pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
-        self.retry_policy = retry;
+        self.inner = self.inner.with_retry_policy(retry);
         self
     }
 
     /// Override the HTTP client configuration.
     #[must_use]
     pub fn with_http_config(mut self, config: ClientConfig) -> Self {
-        self.retry_policy = config.retry_policy;
-        self.http_config = config;
+        self.inner = self.inner.with_http_config(config);
         self
     }
 
@@ -80,52 +73,84 @@ impl NapiClientBuilder {
         username: impl Into<String>,
         password: impl Into<String>,
     ) -> Self {
-        self.basic_auth = Some((username.into(), password.into()));
+        self.inner = self.inner.with_basic_auth(username, password);
         self
     }
 
     /// Configure the `X-Auth-Token` header.
     #[must_use]
     pub fn with_token(mut self, token: impl Into<String>) -> Self {
-        self.token = Some(token.into());
+        self.inner = self.inner.with_token(token);
         self
     }
 
-    /// Build the client instance.
-    pub fn build(self) -> Result<NapiClient> {
-        let mut builder = ClientBuilder::new()
-            .timeout(self.http_config.timeout)
-            .user_agent(USER_AGENT)
-            .pool_idle_timeout(self.http_config.pool_idle_timeout)
-            .pool_max_idle_per_host(self.http_config.pool_max_idle_per_host)
-            .connect_timeout(Duration::from_secs(10));
-
-        if !self.http_config.enable_compression {
-            builder = builder.no_gzip();
-        }
+    /// Override per-request options such as the JSON content type.
+    #[must_use]
+    pub fn with_request_options(mut self, options: RequestOptions) -> Self {
+        self.inner = self.inner.with_request_options(options);
+        self
+    }
 
-        let http = builder.build().map_err(|err| {
-            Error::ConfigError(format!("Failed to build NAPI HTTP client: {err}"))
-        })?;
+    /// Notify `observer` after every call, retries included.
+    #[must_use]
+    pub fn with_observer(mut self, observer: Arc<dyn RequestObserver>) -> Self {
+        self.inner = self.inner.with_observer(observer);
+        self
+    }
 
-        Ok(NapiClient {
-            http,
-            base_url: self.base_url,
-            retry_policy: self.retry_policy,
-            basic_auth: self.basic_auth,
-            token: self.token,
-        })
+    /// Invoke `observer` around every attempt, retries included.
+    #[must_use]
+    pub fn with_client_observer(mut self, observer: Arc<dyn ClientObserver>) -> Self {
+        self.inner = self.inner.with_client_observer(observer);
+        self
+    }
+
+    /// Send requests through a shared `reqwest::Client` instead of building a new one.
+    ///
+    /// The injected client's timeout, TLS and pooling settings take precedence; see
+    /// [`ServiceClientBuilder::with_http_client`].
+    #[must_use]
+    pub fn with_http_client(mut self, client: Client) -> Self {
+        self.inner = self.inner.with_http_client(client);
+        self
+    }
+
+    /// Send each call's request id in `name` instead of `x-request-id`.
+    #[must_use]
+    pub fn with_request_id_header(mut self, name: HeaderName) -> Self {
+        self.inner = self.inner.with_request_id_header(name);
+        self
+    }
+
+    /// Spread requests over `endpoints`, skipping stale and quarantined ones.
+    ///
+    /// See [`ServiceClientBuilder::with_endpoints`].
+    #[must_use]
+    pub fn with_endpoints(mut self, endpoints: EndpointList, selector: EndpointSelector) -> Self {
+        self.inner = self.inner.with_endpoints(endpoints, selector);
+        self
+    }
+
+    /// Send every request to the endpoint at `url` until it fails or is released.
+    ///
+    /// See [`ServiceClientBuilder::pin_endpoint`].
+    #[must_use]
+    pub fn pin_endpoint(mut self, url: impl Into<String>) -> Self {
+        self.inner = self.inner.pin_endpoint(url);
+        self
+    }
+
+    /// Build the client instance.
+    pub fn build(self) -> Result<NapiClient> {
+        let inner = self.inner.build()?;
+        Ok(NapiClient { inner })
     }
 }
 
 /// Asynchronous client for NAPI.
 #[derive(Clone)]
 pub struct NapiClient {
-    http: Client,
-    base_url: Url,
-    retry_policy: RetryPolicy,
-    basic_auth: Option<(String, String)>,
-    token: Option<String>,
+    inner: ServiceClient,
 }
 
 impl NapiClient {
@@ -137,7 +162,23 @@ impl NapiClient {
     /// Access the base URL.
     #[must_use]
     pub fn base_url(&self) -> &Url {
-        &self.base_url
+        self.inner.base_url()
+    }
+
+    /// Identifier of the endpoint requests are pinned to, if any.
+    #[must_use]
+    pub fn pinned_endpoint(&self) -> Option<String> {
+        self.inner.pinned_endpoint()
+    }
+
+    /// Drop the endpoint pin so the next request selects afresh.
+    pub fn release_pinned_endpoint(&self) {
+        self.inner.release_pinned_endpoint();
+    }
+
+    /// Replace the endpoints requests are spread over, e.g. after rediscovery.
+    pub fn update_endpoints(&self, endpoints: EndpointList) {
+        self.inner.update_endpoints(endpoints);
     }
 
     /// List networks.
@@ -147,12 +188,16 @@ impl NapiClient {
 
     /// Fetch a network by UUID.
     pub async fn get_network(&self, uuid: NetworkUuid) -> Result<Network> {
-        let path = format!("networks/{uuid}");
+        let path = format!("networks/{}", encode_path_segment(uuid)?);
         self.get_json(&path, &[]).await
     }
 
     /// Create a new network.
+    ///
+    /// The gateway and provision range are checked with
+    /// [`CreateNetworkRequest::validate_ranges`] before the request is sent.
     pub async fn create_network(&self, request: &CreateNetworkRequest) -> Result<Network> {
+        request.validate_ranges()?;
         self.send_json(Method::POST, "networks", Some(request), &[])
             .await
     }
@@ -163,13 +208,13 @@ impl NapiClient {
         uuid: NetworkUuid,
         request: &UpdateNetworkRequest,
     ) -> Result<Network> {
-        let path = format!("networks/{uuid}");
+        let path = format!("networks/{}", encode_path_segment(uuid)?);
         self.send_json(Method::PUT, &path, Some(request), &[]).await
     }
 
     /// Delete a network.
     pub async fn delete_network(&self, uuid: NetworkUuid) -> Result<()> {
-        let path = format!("networks/{uuid}");
+        let path = format!("networks/{}", encode_path_segment(uuid)?);
         self.send_empty(Method::DELETE, &path, &[]).await
     }
 
@@ -180,21 +225,207 @@ impl NapiClient {
 
     /// Fetch a specific network pool by UUID.
     pub async fn get_network_pool(&self, uuid: &str) -> Result<NetworkPool> {
-        let path = format!("network_pools/{uuid}");
+        let path = format!("network_pools/{}", encode_path_segment(uuid)?);
+        self.get_json(&path, &[]).await
+    }
+
+    /// Create a network pool.
+    ///
+    /// A pool without networks is rejected with [`Error::ValidationError`]
+    /// before the request is sent.
+    pub async fn create_network_pool(
+        &self,
+        request: &CreateNetworkPoolRequest,
+    ) -> Result<NetworkPool> {
+        request.validate()?;
+        self.send_json(Method::POST, "network_pools", Some(request), &[])
+            .await
+    }
+
+    /// Update a network pool.
+    ///
+    /// Replacing the network list with an empty one is rejected with
+    /// [`Error::ValidationError`] before the request is sent.
+    pub async fn update_network_pool(
+        &self,
+        uuid: &str,
+        request: &UpdateNetworkPoolRequest,
+    ) -> Result<NetworkPool> {
+        request.validate()?;
+        let path = format!("network_pools/{}", encode_path_segment(uuid)?);
+        self.send_json(Method::PUT, &path, Some(request), &[]).await
+    }
+
+    /// Delete a network pool.
+    pub async fn delete_network_pool(&self, uuid: &str) -> Result<()> {
+        let path = format!("network_pools/{}", encode_path_segment(uuid)?);
+        self.send_empty(Method::DELETE, &path, &[]).await
+    }
+
+    /// List NIC tags.
+    pub async fn list_nic_tags(&self) -> Result<Vec<NicTag>> {
+        self.get_json("nic_tags", &[]).await
+    }
+
+    /// Fetch a NIC tag by name.
+    pub async fn get_nic_tag(&self, name: &str) -> Result<NicTag> {
+        let path = format!("nic_tags/{}", encode_path_segment(name)?);
         self.get_json(&path, &[]).await
     }
 
+    /// Create a NIC tag, with NAPI's default MTU when `mtu` is `None`.
+    ///
+    /// An MTU outside [`NicTag::MIN_MTU`]..=[`NicTag::MAX_MTU`] is rejected
+    /// with [`Error::ValidationError`] before the request is sent.
+    pub async fn create_nic_tag(&self, name: &str, mtu: Option<u32>) -> Result<NicTag> {
+        if let Some(mtu) = mtu {
+            NicTag::validate_mtu(mtu)?;
+        }
+        let request = CreateNicTagRequest { name, mtu };
+        self.send_json(Method::POST, "nic_tags", Some(&request), &[])
+            .await
+    }
+
+    /// Delete a NIC tag by name.
+    pub async fn delete_nic_tag(&self, name: &str) -> Result<()> {
+        let path = format!("nic_tags/{}", encode_path_segment(name)?);
+        self.send_empty(Method::DELETE, &path, &[]).await
+    }
+
     /// List NICs (optionally filtered by query parameters).
-    pub async fn list_nics(&self, params: &[(&'static str, String)]) -> Result<Vec<Nic>> {
+    pub async fn list_nics(&self, params: &[QueryPair]) -> Result<Vec<Nic>> {
         self.get_json("nics", params).await
     }
 
+    /// List every NIC provisioned on the given network.
+    pub async fn list_network_nics(&self, network_uuid: NetworkUuid) -> Result<Vec<Nic>> {
+        self.list_nics(&[("network_uuid".into(), network_uuid.to_string())])
+            .await
+    }
+
+    /// Resolve the VMs holding NICs on a network, grouping NICs per VM.
+    ///
+    /// `lookup` fetches one VM by UUID, typically `|uuid| vmapi.get_vm(uuid)`.
+    /// NICs that belong to servers or other non-VM resources are skipped. A
+    /// NIC with a malformed owner UUID, or whose VM lookup fails, is reported
+    /// in [`NetworkVmMap::unresolved`] rather than failing the whole audit.
+    pub async fn map_network_to_vms<V, F, Fut>(
+        &self,
+        network_uuid: NetworkUuid,
+        lookup: F,
+    ) -> Result<NetworkVmMap<V>>
+    where
+        F: Fn(InstanceUuid) -> Fut,
+        Fut: Future<Output = Result<V>>,
+    {
+        let nics = self.list_network_nics(network_uuid).await?;
+
+        let mut grouped: Vec<(InstanceUuid, Vec<Nic>)> = Vec::new();
+        let mut unresolved = Vec::new();
+        for nic in nics {
+            let is_vm = matches!(nic.belongs_to_type.as_deref(), None | Some("zone" | "vm"));
+            let Some(owner) = nic.belongs_to_uuid.as_deref().filter(|_| is_vm) else {
+                continue;
+            };
+            let uuid = match InstanceUuid::parse_str(owner) {
+                Ok(uuid) => uuid,
+                Err(error) => {
+                    unresolved.push(UnresolvedNic { nic, error });
+                    continue;
+                }
+            };
+            match grouped.iter_mut().find(|(existing, _)| *existing == uuid) {
+                Some((_, group)) => group.push(nic),
+                None => grouped.push((uuid, vec![nic])),
+            }
+        }
+
+        let lookups = run_bounded(&grouped, VM_LOOKUP_CONCURRENCY, |(uuid, _)| lookup(*uuid)).await;
+
+        let mut vms = Vec::with_capacity(grouped.len());
+        for ((_, nics), vm) in grouped.into_iter().zip(lookups) {
+            match vm {
+                Ok(vm) => vms.push(NetworkVm { vm, nics }),
+                Err(error) => unresolved.extend(nics.into_iter().map(|nic| UnresolvedNic {
+                    nic,
+                    error: error.clone(),
+                })),
+            }
+        }
+
+        Ok(NetworkVmMap { vms, unresolved })
+    }
+
+    /// Take the next free IP on `network` for `owner`.
+    ///
+    /// NAPI only hands out the next free address when provisioning a NIC, so
+    /// this provisions a NIC on the network (`belongs_to_type` `other`,
+    /// belonging to `owner`) and returns its address. NAPI picks and assigns
+    /// the IP in one step, so concurrent callers never get the same address.
+    /// Free the IP by deleting the NIC with [`delete_nic`](Self::delete_nic).
+    /// A full network fails with [`Error::Conflict`].
+    pub async fn provision_next_ip(
+        &self,
+        network: NetworkUuid,
+        owner: OwnerUuid,
+    ) -> Result<IpRecord> {
+        let path = format!("networks/{}/nics", encode_path_segment(network)?);
+        let request = NetworkNicRequest {
+            owner_uuid: owner,
+            belongs_to_uuid: owner.to_string(),
+            belongs_to_type: "other",
+        };
+        let nic: Nic = self
+            .send_json(Method::POST, &path, Some(&request), &[])
+            .await?;
+        let ip = nic.ip.ok_or_else(|| Error::ExternalServiceError {
+            service: "napi".to_string(),
+            message: format!("NIC {} was provisioned without an IP", nic.mac),
+        })?;
+
+        Ok(IpRecord {
+            ip,
+            network_uuid: nic.network_uuid.unwrap_or(network),
+            owner_uuid: nic.owner_uuid.or(Some(owner)),
+            belongs_to_uuid: nic.belongs_to_uuid,
+            belongs_to_type: nic.belongs_to_type,
+            reserved: false,
+            free: false,
+        })
+    }
+
     /// Fetch a NIC by MAC address.
     pub async fn get_nic(&self, mac: &str) -> Result<Nic> {
-        let path = format!("nics/{mac}");
+        let path = format!("nics/{}", encode_path_segment(mac)?);
         self.get_json(&path, &[]).await
     }
 
+    /// Poll a NIC until it reaches `desired`.
+    ///
+    /// Returns early with [`Error::ExternalServiceError`] if the NIC moves to
+    /// `failed`, and with a deadline timeout once `options.timeout` has elapsed.
+    pub async fn wait_for_nic_state(
+        &self,
+        mac: &str,
+        desired: NicState,
+        options: WaitOptions,
+    ) -> Result<Nic> {
+        poll_until(options, || async move {
+            let nic = self.get_nic(mac).await?;
+            if nic.is_in_state(desired) {
+                return Ok(Some(nic));
+            }
+            if nic.is_in_state(NicState::Failed) {
+                return Err(Error::ExternalServiceError {
+                    service: "napi".to_string(),
+                    message: format!("NIC {mac} entered state failed"),
+                });
+            }
+            Ok(None)
+        })
+        .await
+    }
+
     /// Create a NIC.
     pub async fn create_nic(&self, nic: &Nic) -> Result<Nic> {
         self.send_json(Method::POST, "nics", Some(nic), &[]).await
@@ -202,28 +433,63 @@ impl NapiClient {
 
     /// Update a NIC by MAC address.
     pub async fn update_nic(&self, mac: &str, nic: &Nic) -> Result<Nic> {
-        let path = format!("nics/{mac}");
+        let path = format!("nics/{}", encode_path_segment(mac)?);
         self.send_json(Method::PUT, &path, Some(nic), &[]).await
     }
 
     /// Delete a NIC by MAC address.
     pub async fn delete_nic(&self, mac: &str) -> Result<()> {
-        let path = format!("nics/{mac}");
+        let path = format!("nics/{}", encode_path_segment(mac)?);
         self.send_empty(Method::DELETE, &path, &[]).await
     }
 
-    fn build_url(&self, path: &str) -> Result<Url> {
-        let normalized = if path.starts_with('/') {
-            &path[1..]
-        } else {
-            path
-        };
-        self.base_url
-            .join(normalized)
-            .map_err(|err| Error::InvalidEndpoint(format!("Invalid NAPI path `{path}`: {err}")))
+    /// Delete many NICs with at most `concurrency` requests in flight.
+    ///
+    /// Every MAC is attempted even when earlier deletes fail. A NIC that is
+    /// already gone (404) counts as deleted, so the call is safe to repeat
+    /// during VM teardown. Outcomes are returned in input order.
+    pub async fn delete_nics(
+        &self,
+        macs: &[MacAddress],
+        concurrency: usize,
+    ) -> Result<Vec<(String, Result<()>)>> {
+        if concurrency == 0 {
+            return Err(Error::ValidationError(
+                "delete_nics concurrency must be at least 1".into(),
+            ));
+        }
+
+        let outcomes = run_bounded(macs, concurrency, |mac| async move {
+            let outcome = match self.delete_nic(mac.as_str()).await {
+                Err(Error::NotFound(_)) => Ok(()),
+                other => other,
+            };
+            (mac.to_string(), outcome)
+        })
+        .await;
+
+        Ok(outcomes)
     }
 
-    async fn get_json<T>(&self, path: &str, params: &[(&'static str, String)]) -> Result<T>
+    /// Fetch any NAPI path as raw JSON; see [`ServiceClient::get_raw`].
+    pub async fn get_raw(&self, path: &str, params: &[QueryPair]) -> Result<serde_json::Value> {
+        self.inner.get_raw(path, params, map_status_to_error).await
+    }
+
+    /// Send an arbitrary request to NAPI and return the raw JSON response;
+    /// see [`ServiceClient::send_raw`].
+    pub async fn send_raw(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        self.inner
+            .send_raw(method, path, body, &[], map_status_to_error)
+            .await
+    }
+
+    async fn get_json<T>(&self, path: &str, params: &[QueryPair]) -> Result<T>
     where
         T: DeserializeOwned,
     {
@@ -231,12 +497,7 @@ impl NapiClient {
             .await
     }
 
-    async fn send_empty(
-        &self,
-        method: Method,
-        path: &str,
-        params: &[(&'static str, String)],
-    ) -> Result<()> {
+    async fn send_empty(&self, method: Method, path: &str, params: &[QueryPair]) -> Result<()> {
         self.send_json::<(), serde_json::Value>(method, path, None, params)
             .await
             .map(|_| ())
@@ -247,111 +508,65 @@ impl NapiClient {
         method: Method,
         path: &str,
         body: Option<&B>,
-        params: &[(&'static str, String)],
+        params: &[QueryPair],
     ) -> Result<R>
     where
         B: Serialize + ?Sized,
         R: DeserializeOwned,
     {
-        #[allow(unused_assignments)]
-        let mut last_error: Option<Error> = None;
-        let mut attempt = 0;
-
-        loop {
-            let url = self.build_url(path)?;
-            let mut request = self.http.request(method.clone(), url).query(params);
-
-            if let Some((user, pass)) = &self.basic_auth {
-                request = request.basic_auth(user, Some(pass));
-            }
-            if let Some(token) = &self.token {
-                request = request.header("X-Auth-Token", token);
-            }
-            request = request.header("Accept", "application/json");
-            if let Some(payload) = body {
-                request = request.json(payload);
-            }
-
-            info!(path, attempt, "NAPI request");
-
-            match request.send().await {
-                Ok(response) => {
-                    let status = response.status();
-                    let bytes = response.bytes().await.map_err(|err| {
-                        Error::HttpError(format!("Failed to read NAPI response body: {err}"))
-                    })?;
-
-                    if status.is_success() {
-                        return deserialize_body(path, status, &bytes);
-                    }
-
-                    let text = String::from_utf8_lossy(&bytes).into_owned();
-                    let error = match status {
-                        StatusCode::NOT_FOUND => return Err(Error::NotFound(text)),
-                        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
-                            Error::InvalidRequest(format!("NAPI authentication failed: {text}"))
-                        }
-                        StatusCode::TOO_MANY_REQUESTS
-                        | StatusCode::BAD_GATEWAY
-                        | StatusCode::SERVICE_UNAVAILABLE
-                        | StatusCode::GATEWAY_TIMEOUT => Error::ServiceUnavailable(format!(
-                            "NAPI temporarily unavailable: {text}"
-                        )),
-                        status if status.is_server_error() => {
-                            Error::ServiceUnavailable(format!("NAPI server error {status}: {text}"))
-                        }
-                        _ => Error::HttpError(format!("NAPI error {status}: {text}")),
-                    };
-                    last_error = Some(error);
-                }
-                Err(err) => {
-                    let error = Error::from(err);
-                    if matches!(
-                        error,
-                        Error::Timeout(_) | Error::ServiceUnavailable(_) | Error::HttpError(_)
-                    ) {
-                        last_error = Some(error);
-                    } else {
-                        return Err(error);
+        self.inner
+            .execute_json_with_retry(
+                method,
+                path,
+                params,
+                |mut request| {
+                    request = request.header("Accept", "application/json");
+                    if let Some(payload) = body {
+                        request = self.inner.request_options().json_body(request, payload);
                     }
-                }
-            }
+                    request
+                },
+                map_status_to_error,
+            )
+            .await
+    }
+}
 
-            attempt += 1;
-            if attempt > self.retry_policy.max_retries {
-                break;
-            }
-            let delay = self.retry_policy.delay_for_attempt(attempt);
-            if delay > Duration::from_millis(0) {
-                debug!("Retrying NAPI request after {:?}", delay);
-                sleep(delay).await;
-            }
-        }
+#[async_trait]
+impl PreflightCheck for NapiClient {
+    fn service(&self) -> TritonService {
+        TritonService::Napi
+    }
 
-        if let Some(error) = last_error {
-            Err(error)
-        } else {
-            Err(Error::ServiceUnavailable(
-                "NAPI request failed after retries".to_string(),
-            ))
-        }
+    async fn preflight(&self) -> PreflightResult {
+        self.inner
+            .preflight("networks", &[("limit".into(), "1".to_string())])
+            .await
     }
 }
 
-fn deserialize_body<R>(path: &str, status: StatusCode, bytes: &[u8]) -> Result<R>
-where
-    R: DeserializeOwned,
-{
-    if status == StatusCode::NO_CONTENT || bytes.is_empty() {
-        serde_json::from_value(serde_json::Value::Null).map_err(|err| {
-            Error::SapiParseError(format!(
-                "Failed to parse empty NAPI response for `{path}`: {err}"
-            ))
-        })
-    } else {
-        serde_json::from_slice(bytes).map_err(|err| {
-            Error::SapiParseError(format!("Failed to parse NAPI response for `{path}`: {err}"))
-        })
+fn map_status_to_error(status: StatusCode, text: String) -> Error {
+    match status {
+        StatusCode::NOT_FOUND => Error::NotFound(text),
+        StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => Error::BadRequest(text),
+        StatusCode::PAYLOAD_TOO_LARGE => Error::PayloadTooLarge(text),
+        // NAPI's SubnetFull: no free addresses are left.
+        StatusCode::INSUFFICIENT_STORAGE => {
+            Error::Conflict(format!("NAPI network is full: {text}"))
+        }
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+            Error::InvalidRequest(format!("NAPI authentication failed: {text}"))
+        }
+        StatusCode::TOO_MANY_REQUESTS
+        | StatusCode::BAD_GATEWAY
+        | StatusCode::SERVICE_UNAVAILABLE
+        | StatusCode::GATEWAY_TIMEOUT => {
+            Error::ServiceUnavailable(format!("NAPI temporarily unavailable: {text}"))
+        }
+        status if status.is_server_error() => {
+            Error::ServiceUnavailable(format!("NAPI server error {status}: {text}"))
+        }
+        _ => Error::http(status, format!("NAPI error {status}: {text}")),
     }
 }
 
@@ -425,14 +640,250 @@ pub type NetworkQuery = NetworkListParams;
 mod tests {
     use super::*;
     use serde_json::json;
-    use triton_core::uuid::OwnerUuid;
-    use wiremock::matchers::{body_json, method, path};
+    use wiremock::matchers::{body_json, method, path, query_param};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     fn client(server: &MockServer) -> NapiClient {
         NapiClient::new(server.uri()).unwrap()
     }
 
+    fn pool_json(uuid: &str, name: &str, networks: &[NetworkUuid]) -> serde_json::Value {
+        json!({
+            "uuid": uuid,
+            "name": name,
+            "networks": networks,
+            "nic_tag": "external"
+        })
+    }
+
+    #[tokio::test]
+    async fn create_network_pool_posts_request() {
+        let server = MockServer::start().await;
+        let networks = [NetworkUuid::new_v4(), NetworkUuid::new_v4()];
+        Mock::given(method("POST"))
+            .and(path("/network_pools"))
+            .and(body_json(json!({
+                "name": "public",
+                "networks": networks,
+                "nic_tag": "external"
+            })))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(pool_json("pool-1", "public", &networks)),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let request = CreateNetworkPoolRequest {
+            name: "public".into(),
+            networks: networks.to_vec(),
+            nic_tag: Some("external".into()),
+            description: None,
+            owner_uuids: None,
+        };
+        let pool = client(&server).create_network_pool(&request).await.unwrap();
+        assert_eq!(pool.uuid, "pool-1");
+        assert_eq!(pool.networks, networks);
+    }
+
+    #[tokio::test]
+    async fn create_network_pool_requires_networks() {
+        let server = MockServer::start().await;
+        let request = CreateNetworkPoolRequest {
+            name: "empty".into(),
+            networks: Vec::new(),
+            nic_tag: None,
+            description: None,
+            owner_uuids: None,
+        };
+        let err = client(&server)
+            .create_network_pool(&request)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::ValidationError(_)));
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn update_network_pool_puts_changes() {
+        let server = MockServer::start().await;
+        let networks = [NetworkUuid::new_v4()];
+        Mock::given(method("PUT"))
+            .and(path("/network_pools/pool-1"))
+            .and(body_json(
+                json!({ "name": "public-v2", "networks": networks }),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(pool_json(
+                "pool-1",
+                "public-v2",
+                &networks,
+            )))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let request = UpdateNetworkPoolRequest {
+            name: Some("public-v2".into()),
+            networks: Some(networks.to_vec()),
+            ..UpdateNetworkPoolRequest::default()
+        };
+        let pool = client(&server)
+            .update_network_pool("pool-1", &request)
+            .await
+            .unwrap();
+        assert_eq!(pool.name, "public-v2");
+    }
+
+    #[tokio::test]
+    async fn update_network_pool_maps_validation_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/network_pools/pool-1"))
+            .respond_with(ResponseTemplate::new(422).set_body_json(json!({
+                "code": "InvalidParameters",
+                "message": "Invalid parameters",
+                "errors": [{ "field": "networks", "code": "InvalidParameter" }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let request = UpdateNetworkPoolRequest {
+            nic_tag: Some("internal".into()),
+            ..UpdateNetworkPoolRequest::default()
+        };
+        let err = client(&server)
+            .update_network_pool("pool-1", &request)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::BadRequest(message) if message.contains("InvalidParameters")));
+    }
+
+    #[tokio::test]
+    async fn delete_network_pool_sends_delete() {
+        let server = MockServer::start().await;
+        Mock::given(method("DELETE"))
+            .and(path("/network_pools/pool-1"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        client(&server).delete_network_pool("pool-1").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn list_nic_tags_success() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/nic_tags"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "name": "admin", "uuid": "tag-1", "mtu": 1500 },
+                { "name": "sdc_underlay", "uuid": "tag-2", "mtu": 9000, "mac": "90:b8:d0:00:00:01" }
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let tags = client(&server).list_nic_tags().await.unwrap();
+        assert_eq!(tags.len(), 2);
+        assert_eq!(tags[1].name, "sdc_underlay");
+        assert_eq!(tags[1].mtu, Some(9000));
+        assert_eq!(tags[1].mac.as_deref(), Some("90:b8:d0:00:00:01"));
+    }
+
+    #[tokio::test]
+    async fn create_nic_tag_posts_name_and_mtu() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/nic_tags"))
+            .and(body_json(json!({ "name": "external", "mtu": 9000 })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "name": "external",
+                "uuid": "tag-3",
+                "mtu": 9000
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let tag = client(&server)
+            .create_nic_tag("external", Some(9000))
+            .await
+            .unwrap();
+        assert_eq!(tag.uuid, "tag-3");
+    }
+
+    #[tokio::test]
+    async fn create_nic_tag_rejects_out_of_range_mtu() {
+        let server = MockServer::start().await;
+        let napi = client(&server);
+        for mtu in [1499, 9001] {
+            let err = napi
+                .create_nic_tag("external", Some(mtu))
+                .await
+                .unwrap_err();
+            assert!(matches!(err, Error::ValidationError(_)));
+        }
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn provision_next_ip_returns_assigned_address() {
+        let server = MockServer::start().await;
+        let network = NetworkUuid::new_v4();
+        let owner = OwnerUuid::new_v4();
+        Mock::given(method("POST"))
+            .and(path(format!("/networks/{network}/nics").as_str()))
+            .and(body_json(json!({
+                "owner_uuid": owner,
+                "belongs_to_uuid": owner,
+                "belongs_to_type": "other"
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "mac": "90:b8:d0:aa:bb:cc",
+                "ip": "10.0.0.17",
+                "network_uuid": network,
+                "owner_uuid": owner,
+                "belongs_to_uuid": owner,
+                "belongs_to_type": "other",
+                "state": "running"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let record = client(&server)
+            .provision_next_ip(network, owner)
+            .await
+            .unwrap();
+        assert_eq!(record.ip, "10.0.0.17");
+        assert_eq!(record.network_uuid, network);
+        assert_eq!(record.owner_uuid, Some(owner));
+        assert!(!record.free);
+    }
+
+    #[tokio::test]
+    async fn provision_next_ip_on_full_network_conflicts() {
+        let server = MockServer::start().await;
+        let network = NetworkUuid::new_v4();
+        Mock::given(method("POST"))
+            .and(path(format!("/networks/{network}/nics").as_str()))
+            .respond_with(ResponseTemplate::new(507).set_body_json(json!({
+                "code": "SubnetFull",
+                "message": "No more IPs available"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let err = client(&server)
+            .provision_next_ip(network, OwnerUuid::new_v4())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Conflict(message) if message.contains("SubnetFull")));
+    }
+
     #[tokio::test]
     async fn list_networks_success() {
         let server = MockServer::start().await;
@@ -523,6 +974,91 @@ mod tests {
         assert_eq!(network.name, "admin");
     }
 
+    #[tokio::test]
+    async fn list_network_nics_filters_by_network() {
+        let server = MockServer::start().await;
+        let network = NetworkUuid::new_v4();
+
+        Mock::given(method("GET"))
+            .and(path("/nics"))
+            .and(query_param("network_uuid", network.to_string().as_str()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "mac": "90:b8:d0:00:00:01", "network_uuid": network }
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let nics = client(&server).list_network_nics(network).await.unwrap();
+        assert_eq!(nics.len(), 1);
+        assert_eq!(nics[0].network_uuid, Some(network));
+    }
+
+    #[tokio::test]
+    async fn map_network_to_vms_resolves_owners() {
+        let napi_server = MockServer::start().await;
+        let network = NetworkUuid::new_v4();
+        let web = InstanceUuid::new_v4();
+        let db = InstanceUuid::new_v4();
+        let orphan = InstanceUuid::new_v4();
+
+        Mock::given(method("GET"))
+            .and(path("/nics"))
+            .and(query_param("network_uuid", network.to_string().as_str()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                {
+                    "mac": "90:b8:d0:00:00:01",
+                    "belongs_to_uuid": web,
+                    "belongs_to_type": "zone"
+                },
+                {
+                    "mac": "90:b8:d0:00:00:02",
+                    "belongs_to_uuid": db,
+                    "belongs_to_type": "zone"
+                },
+                {
+                    "mac": "90:b8:d0:00:00:03",
+                    "belongs_to_uuid": InstanceUuid::new_v4(),
+                    "belongs_to_type": "server"
+                },
+                {
+                    "mac": "90:b8:d0:00:00:04",
+                    "belongs_to_uuid": orphan,
+                    "belongs_to_type": "zone"
+                },
+                {
+                    "mac": "90:b8:d0:00:00:05",
+                    "belongs_to_uuid": "not-a-uuid",
+                    "belongs_to_type": "zone"
+                }
+            ])))
+            .mount(&napi_server)
+            .await;
+
+        let aliases = [(web, "web01"), (db, "db01")];
+        let mapped = client(&napi_server)
+            .map_network_to_vms(network, |uuid| async move {
+                aliases
+                    .iter()
+                    .find(|(known, _)| *known == uuid)
+                    .map(|(_, alias)| (*alias).to_string())
+                    .ok_or_else(|| Error::NotFound(format!("VM {uuid}")))
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(mapped.vms.len(), 2);
+        assert_eq!(mapped.vms[0].vm, "web01");
+        assert_eq!(mapped.vms[1].vm, "db01");
+        assert_eq!(mapped.vms[1].nics[0].mac, "90:b8:d0:00:00:02");
+
+        assert_eq!(mapped.unresolved.len(), 2);
+        assert_eq!(mapped.unresolved[0].nic.mac, "90:b8:d0:00:00:05");
+        assert!(matches!(mapped.unresolved[0].error, Error::InvalidUuid(_)));
+        assert_eq!(mapped.unresolved[1].nic.mac, "90:b8:d0:00:00:04");
+        assert!(matches!(mapped.unresolved[1].error, Error::NotFound(_)));
+    }
+
     #[tokio::test]
     async fn discovery_delegates_to_sapi() {
         struct MockDiscovery;
@@ -549,4 +1085,84 @@ mod tests {
         let endpoints = discovery.discover_service("napi").await.unwrap();
         assert_eq!(endpoints, vec!["http://napi.local:80"]);
     }
+
+    #[tokio::test]
+    async fn wait_for_nic_state_polls_until_running() {
+        let server = MockServer::start().await;
+        let mac = "90:b8:d0:00:00:01";
+        let nic = |state: &str| {
+            json!({
+                "mac": mac,
+                "owner_uuid": OwnerUuid::new_v4(),
+                "belongs_to_uuid": InstanceUuid::new_v4(),
+                "belongs_to_type": "zone",
+                "state": state
+            })
+        };
+        Mock::given(method("GET"))
+            .and(path(format!("/nics/{mac}")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(nic("provisioning")))
+            .up_to_n_times(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/nics/{mac}")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(nic("running")))
+            .mount(&server)
+            .await;
+
+        let options = WaitOptions::default()
+            .with_poll_interval(Duration::from_millis(10))
+            .with_timeout(Duration::from_secs(5));
+        let nic = client(&server)
+            .wait_for_nic_state(mac, NicState::Running, options)
+            .await
+            .unwrap();
+
+        assert_eq!(nic.state.as_deref(), Some("running"));
+        assert_eq!(server.received_requests().await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn delete_nics_reports_per_mac_outcomes() {
+        let server = MockServer::start().await;
+        let macs: Vec<MacAddress> = [
+            "90:b8:d0:00:00:01",
+            "90:b8:d0:00:00:02",
+            "90:b8:d0:00:00:03",
+        ]
+        .iter()
+        .map(|mac| mac.parse().unwrap())
+        .collect();
+
+        Mock::given(method("DELETE"))
+            .and(path("/nics/90:b8:d0:00:00:01"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/nics/90:b8:d0:00:00:02"))
+            .respond_with(ResponseTemplate::new(404).set_body_string("gone"))
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/nics/90:b8:d0:00:00:03"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+
+        let client = client(&server);
+        let results = client.delete_nics(&macs, 2).await.unwrap();
+
+        let order: Vec<&str> = results.iter().map(|(mac, _)| mac.as_str()).collect();
+        assert_eq!(
+            order,
+            vec![
+                "90:b8:d0:00:00:01",
+                "90:b8:d0:00:00:02",
+                "90:b8:d0:00:00:03"
+            ]
+        );
+        assert!(results.iter().all(|(_, outcome)| outcome.is_ok()));
+    }
 }
@@ -9,7 +9,9 @@ pub mod models;
 
 pub use client::{NapiClient, NapiClientBuilder, NetworkQuery};
 pub use models::{
-    CreateNetworkRequest, Network, NetworkListParams, NetworkPool, Nic, UpdateNetworkRequest,
+    CreateNetworkPoolRequest, CreateNetworkRequest, IpRecord, MacAddress, Network,
+    NetworkListParams, NetworkPool, NetworkVm, NetworkVmMap, Nic, NicState, NicTag, UnresolvedNic,
+    UpdateNetworkPoolRequest, UpdateNetworkRequest,
 };
 
 /// Convenient result alias sharing the `triton-core` error type.
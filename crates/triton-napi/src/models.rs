@@ -3,7 +3,14 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
+use std::net::Ipv4Addr;
+use std::str::FromStr;
+use triton_core::query::{append_extra, QueryPair};
 use triton_core::uuid::{NetworkUuid, OwnerUuid};
+use triton_core::Error;
+
+use crate::Result;
 
 /// Query parameters supported by `/networks`.
 #[derive(Debug, Default, Clone)]
@@ -24,38 +31,43 @@ pub struct NetworkListParams {
     pub limit: Option<u32>,
     /// Offset for pagination.
     pub offset: Option<u32>,
+    /// Additional query parameters appended verbatim after the typed ones.
+    ///
+    /// Keys already set by a typed field are skipped.
+    pub extra_params: Vec<(String, String)>,
 }
 
 impl NetworkListParams {
     /// Convert the params into URL query pairs.
     #[must_use]
-    pub fn to_pairs(&self) -> Vec<(&'static str, String)> {
+    pub fn to_pairs(&self) -> Vec<QueryPair> {
         let mut pairs = Vec::new();
 
         if let Some(name) = &self.name {
-            pairs.push(("name", name.clone()));
+            pairs.push(("name".into(), name.clone()));
         }
         if let Some(uuid) = &self.uuid {
-            pairs.push(("uuid", uuid.to_string()));
+            pairs.push(("uuid".into(), uuid.to_string()));
         }
         if let Some(vlan_id) = self.vlan_id {
-            pairs.push(("vlan_id", vlan_id.to_string()));
+            pairs.push(("vlan_id".into(), vlan_id.to_string()));
         }
         if let Some(owner_uuid) = &self.owner_uuid {
-            pairs.push(("owner_uuid", owner_uuid.to_string()));
+            pairs.push(("owner_uuid".into(), owner_uuid.to_string()));
         }
         if let Some(provisionable_by) = &self.provisionable_by {
-            pairs.push(("provisionable_by", provisionable_by.clone()));
+            pairs.push(("provisionable_by".into(), provisionable_by.clone()));
         }
         if let Some(fabric) = self.fabric {
-            pairs.push(("fabric", fabric.to_string()));
+            pairs.push(("fabric".into(), fabric.to_string()));
         }
         if let Some(limit) = self.limit {
-            pairs.push(("limit", limit.to_string()));
+            pairs.push(("limit".into(), limit.to_string()));
         }
         if let Some(offset) = self.offset {
-            pairs.push(("offset", offset.to_string()));
+            pairs.push(("offset".into(), offset.to_string()));
         }
+        append_extra(&mut pairs, &self.extra_params);
 
         pairs
     }
@@ -162,6 +174,83 @@ pub struct CreateNetworkRequest {
     pub mtu: Option<u32>,
 }
 
+impl CreateNetworkRequest {
+    /// Check that the gateway and provision range sit inside the subnet.
+    ///
+    /// NAPI rejects inconsistent ranges only after the request is submitted;
+    /// this catches the common mistakes locally.
+    pub fn validate_ranges(&self) -> Result<()> {
+        let (base, prefix) = match self.subnet.split_once('/') {
+            Some((addr, prefix)) => {
+                let prefix: u32 = prefix.parse().map_err(|_| {
+                    Error::InvalidNetwork(format!("invalid subnet prefix `{}`", self.subnet))
+                })?;
+                if prefix > 32 {
+                    return Err(Error::InvalidNetwork(format!(
+                        "invalid subnet prefix `{}`",
+                        self.subnet
+                    )));
+                }
+                (parse_ipv4("subnet", addr)?, Some(prefix))
+            }
+            None => (parse_ipv4("subnet", &self.subnet)?, None),
+        };
+
+        let mask = u32::from(parse_ipv4("netmask", &self.netmask)?);
+        if mask.leading_ones() + mask.trailing_zeros() != 32 {
+            return Err(Error::InvalidNetwork(format!(
+                "netmask `{}` is not contiguous",
+                self.netmask
+            )));
+        }
+        if let Some(prefix) = prefix {
+            if prefix != mask.leading_ones() {
+                return Err(Error::InvalidNetwork(format!(
+                    "netmask `{}` does not match subnet `{}`",
+                    self.netmask, self.subnet
+                )));
+            }
+        }
+
+        let network = u32::from(base) & mask;
+        let contains = |ip: Ipv4Addr| u32::from(ip) & mask == network;
+
+        let check = |field: &str, value: Option<&String>| -> Result<Option<Ipv4Addr>> {
+            let Some(value) = value else {
+                return Ok(None);
+            };
+            let ip = parse_ipv4(field, value)?;
+            if !contains(ip) {
+                return Err(Error::InvalidNetwork(format!(
+                    "{field} `{value}` is outside subnet `{}`",
+                    self.subnet
+                )));
+            }
+            Ok(Some(ip))
+        };
+
+        check("gateway", self.gateway.as_ref())?;
+        let start = check("provision_start_ip", self.provision_start_ip.as_ref())?;
+        let end = check("provision_end_ip", self.provision_end_ip.as_ref())?;
+
+        if let (Some(start), Some(end)) = (start, end) {
+            if start > end {
+                return Err(Error::InvalidNetwork(format!(
+                    "provision_start_ip `{start}` is after provision_end_ip `{end}`"
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_ipv4(field: &str, value: &str) -> Result<Ipv4Addr> {
+    value
+        .parse()
+        .map_err(|_| Error::InvalidNetwork(format!("invalid {field} `{value}`")))
+}
+
 /// Request payload to update a network.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct UpdateNetworkRequest {
@@ -211,6 +300,167 @@ pub struct NetworkPool {
     pub owner_uuids: Option<Vec<OwnerUuid>>,
 }
 
+/// Request payload to create a network pool.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CreateNetworkPoolRequest {
+    /// Pool name.
+    pub name: String,
+    /// Networks in the pool, tried in order when provisioning.
+    pub networks: Vec<NetworkUuid>,
+    /// NIC tag shared by the networks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nic_tag: Option<String>,
+    /// Optional description.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Owner UUIDs allowed to use the pool.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner_uuids: Option<Vec<OwnerUuid>>,
+}
+
+impl CreateNetworkPoolRequest {
+    /// Check that the pool names at least one network.
+    pub fn validate(&self) -> Result<()> {
+        validate_pool_networks(&self.networks)
+    }
+}
+
+/// Request payload to update a network pool.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct UpdateNetworkPoolRequest {
+    /// New pool name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Replacement network list.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub networks: Option<Vec<NetworkUuid>>,
+    /// Updated NIC tag.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nic_tag: Option<String>,
+    /// Updated description.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Updated owner UUIDs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner_uuids: Option<Vec<OwnerUuid>>,
+}
+
+impl UpdateNetworkPoolRequest {
+    /// Check that a replacement network list, if given, is not empty.
+    pub fn validate(&self) -> Result<()> {
+        self.networks
+            .as_deref()
+            .map_or(Ok(()), validate_pool_networks)
+    }
+}
+
+fn validate_pool_networks(networks: &[NetworkUuid]) -> Result<()> {
+    if networks.is_empty() {
+        return Err(Error::ValidationError(
+            "a network pool needs at least one network".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// NIC tag: a named physical network that NICs and networks attach to.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NicTag {
+    /// Tag name, e.g. `external`.
+    pub name: String,
+    /// Tag UUID.
+    pub uuid: String,
+    /// MTU of the underlying link.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mtu: Option<u32>,
+    /// MAC address of the interface providing the tag, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mac: Option<String>,
+}
+
+impl NicTag {
+    /// Smallest MTU NAPI accepts for a NIC tag.
+    pub const MIN_MTU: u32 = 1500;
+    /// Largest MTU NAPI accepts for a NIC tag.
+    pub const MAX_MTU: u32 = 9000;
+
+    /// Check that `mtu` lies within [`MIN_MTU`](Self::MIN_MTU)..=[`MAX_MTU`](Self::MAX_MTU).
+    pub fn validate_mtu(mtu: u32) -> Result<()> {
+        if !(Self::MIN_MTU..=Self::MAX_MTU).contains(&mtu) {
+            return Err(Error::ValidationError(format!(
+                "NIC tag MTU {mtu} is outside {}-{}",
+                Self::MIN_MTU,
+                Self::MAX_MTU
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Body for `POST /nic_tags`.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct CreateNicTagRequest<'a> {
+    pub(crate) name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) mtu: Option<u32>,
+}
+
+/// Validated MAC address in lowercase, colon-separated form.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct MacAddress(String);
+
+impl MacAddress {
+    /// Parse a MAC address, accepting `:` or `-` separators in any case.
+    pub fn parse(value: &str) -> Result<Self> {
+        let octets: Vec<&str> = value.split([':', '-']).collect();
+        let valid = octets.len() == 6
+            && octets
+                .iter()
+                .all(|octet| octet.len() == 2 && octet.chars().all(|c| c.is_ascii_hexdigit()));
+        if !valid {
+            return Err(Error::ValidationError(format!(
+                "invalid MAC address `{value}`"
+            )));
+        }
+        Ok(Self(octets.join(":").to_ascii_lowercase()))
+    }
+
+    /// Borrow the normalized address.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for MacAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for MacAddress {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl TryFrom<String> for MacAddress {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        Self::parse(&value)
+    }
+}
+
+impl From<MacAddress> for String {
+    fn from(mac: MacAddress) -> Self {
+        mac.0
+    }
+}
+
 /// NIC metadata returned by NAPI.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Nic {
@@ -266,6 +516,99 @@ pub struct Nic {
     pub allow_dhcp_spoofing: Option<bool>,
 }
 
+impl Nic {
+    /// Returns true if the NIC currently reports the given state.
+    #[must_use]
+    pub fn is_in_state(&self, state: NicState) -> bool {
+        self.state.as_deref() == Some(state.as_str())
+    }
+}
+
+/// An IP address on a NAPI network and what holds it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct IpRecord {
+    /// The address.
+    pub ip: String,
+    /// Network the address belongs to.
+    pub network_uuid: NetworkUuid,
+    /// Account the address is assigned to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner_uuid: Option<OwnerUuid>,
+    /// UUID of the resource holding the address.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub belongs_to_uuid: Option<String>,
+    /// Type of resource holding the address (`zone`, `server`, `other`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub belongs_to_type: Option<String>,
+    /// Whether the address is reserved against automatic assignment.
+    #[serde(default)]
+    pub reserved: bool,
+    /// Whether the address is unassigned.
+    #[serde(default)]
+    pub free: bool,
+}
+
+/// Body for `POST /networks/:uuid/nics`, which lets NAPI pick the IP.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct NetworkNicRequest {
+    pub(crate) owner_uuid: OwnerUuid,
+    pub(crate) belongs_to_uuid: String,
+    pub(crate) belongs_to_type: &'static str,
+}
+
+/// Lifecycle states reported in [`Nic::state`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NicState {
+    /// NIC is being set up.
+    Provisioning,
+    /// NIC is in use.
+    Running,
+    /// NIC exists but its VM is stopped.
+    Stopped,
+    /// NIC provisioning failed.
+    Failed,
+}
+
+impl NicState {
+    /// Return the NAPI string for this state.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Provisioning => "provisioning",
+            Self::Running => "running",
+            Self::Stopped => "stopped",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// A VM attached to a network, together with its NICs on that network.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkVm<V> {
+    /// VM record returned by the caller's lookup.
+    pub vm: V,
+    /// NICs the VM holds on the network.
+    pub nics: Vec<Nic>,
+}
+
+/// A NIC whose owning VM could not be resolved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnresolvedNic {
+    /// NIC as listed by NAPI.
+    pub nic: Nic,
+    /// Why the owner could not be resolved.
+    pub error: Error,
+}
+
+/// VMs on a network, plus the NICs whose owners could not be resolved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkVmMap<V> {
+    /// Resolved VMs, in the order their first NIC was listed.
+    pub vms: Vec<NetworkVm<V>>,
+    /// NICs with a malformed owner UUID or a failed VM lookup.
+    pub unresolved: Vec<UnresolvedNic>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -284,4 +627,78 @@ mod tests {
         assert!(pairs.iter().any(|(k, v)| *k == "vlan_id" && v == "100"));
         assert!(pairs.iter().any(|(k, v)| *k == "limit" && v == "20"));
     }
+
+    fn ranged_request() -> CreateNetworkRequest {
+        CreateNetworkRequest {
+            name: "external".into(),
+            vlan_id: 10,
+            subnet: "10.1.0.0/24".into(),
+            netmask: "255.255.255.0".into(),
+            gateway: Some("10.1.0.1".into()),
+            provision_start_ip: Some("10.1.0.10".into()),
+            provision_end_ip: Some("10.1.0.250".into()),
+            nic_tag: "external".into(),
+            description: None,
+            owner_uuids: None,
+            routes: None,
+            resolvers: None,
+            fabric: None,
+            internet_nat: None,
+            mtu: None,
+        }
+    }
+
+    #[test]
+    fn validate_ranges_accepts_consistent_request() {
+        assert!(ranged_request().validate_ranges().is_ok());
+    }
+
+    #[test]
+    fn validate_ranges_rejects_gateway_outside_subnet() {
+        let request = CreateNetworkRequest {
+            gateway: Some("10.2.0.1".into()),
+            ..ranged_request()
+        };
+        let err = request.validate_ranges().unwrap_err();
+        assert!(matches!(err, Error::InvalidNetwork(msg) if msg.contains("gateway")));
+    }
+
+    #[test]
+    fn validate_ranges_rejects_provision_range_outside_subnet() {
+        let request = CreateNetworkRequest {
+            provision_end_ip: Some("10.1.1.5".into()),
+            ..ranged_request()
+        };
+        let err = request.validate_ranges().unwrap_err();
+        assert!(matches!(err, Error::InvalidNetwork(msg) if msg.contains("provision_end_ip")));
+    }
+
+    #[test]
+    fn validate_ranges_rejects_inverted_provision_range() {
+        let request = CreateNetworkRequest {
+            provision_start_ip: Some("10.1.0.200".into()),
+            provision_end_ip: Some("10.1.0.20".into()),
+            ..ranged_request()
+        };
+        let err = request.validate_ranges().unwrap_err();
+        assert!(matches!(err, Error::InvalidNetwork(msg) if msg.contains("is after")));
+    }
+
+    #[test]
+    fn mac_address_normalizes_separators() {
+        let mac: MacAddress = "90:B8:D0:1A:2B:3C".parse().unwrap();
+        assert_eq!(mac.as_str(), "90:b8:d0:1a:2b:3c");
+        assert_eq!(MacAddress::parse("90-b8-d0-1a-2b-3c").unwrap(), mac);
+        assert!(MacAddress::parse("90:b8:d0:1a:2b").is_err());
+        assert!(MacAddress::parse("zz:b8:d0:1a:2b:3c").is_err());
+    }
+
+    #[test]
+    fn validate_ranges_rejects_mismatched_netmask() {
+        let request = CreateNetworkRequest {
+            netmask: "255.255.0.0".into(),
+            ..ranged_request()
+        };
+        assert!(request.validate_ranges().is_err());
+    }
 }
@@ -3,16 +3,25 @@
 use crate::models::{CreatePackageRequest, Package, PackageListParams, UpdatePackageRequest};
 use crate::Result;
 use async_trait::async_trait;
+use futures::stream::{self, Stream, StreamExt};
+use reqwest::header::HeaderName;
 use reqwest::{Method, StatusCode};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::sync::Arc;
 use std::time::Duration;
 use triton_core::client::{
-    ClientConfig, RetryPolicy, ServiceClient, ServiceClientBuilder, PAPI_DEFAULT_TIMEOUT,
+    ClientConfig, ClientObserver, RequestObserver, RequestOptions, RetryPolicy, ServiceClient,
+    ServiceClientBuilder, PAPI_DEFAULT_TIMEOUT,
 };
+use triton_core::config::TritonClientConfig;
+use triton_core::context::RequestContext;
+use triton_core::path::encode_path_segment;
+use triton_core::preflight::{PreflightCheck, PreflightResult};
+use triton_core::query::QueryPair;
+use triton_core::selection::EndpointSelector;
 use triton_core::services::{DiscoveryStatus, ServiceDiscovery, ServiceDiscoveryProxy};
-use triton_core::types::TritonService;
+use triton_core::types::{EndpointList, TritonService};
 use triton_core::uuid::PackageUuid;
 use triton_core::Error;
 use url::Url;
@@ -38,6 +47,14 @@ impl PapiClientBuilder {
         Ok(Self { inner: builder })
     }
 
+    /// Create a builder from the PAPI endpoint, timeout, and retry count in `config`.
+    pub fn from_config(config: &TritonClientConfig) -> Result<Self> {
+        let builder = ServiceClientBuilder::from_config(TritonService::Papi, config)?
+            .with_user_agent(USER_AGENT);
+
+        Ok(Self { inner: builder })
+    }
+
     /// Override the retry policy.
     #[must_use]
     pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
@@ -70,6 +87,62 @@ impl PapiClientBuilder {
         self
     }
 
+    /// Override per-request options such as the JSON content type.
+    #[must_use]
+    pub fn with_request_options(mut self, options: RequestOptions) -> Self {
+        self.inner = self.inner.with_request_options(options);
+        self
+    }
+
+    /// Notify `observer` after every call, retries included.
+    #[must_use]
+    pub fn with_observer(mut self, observer: Arc<dyn RequestObserver>) -> Self {
+        self.inner = self.inner.with_observer(observer);
+        self
+    }
+
+    /// Invoke `observer` around every attempt, retries included.
+    #[must_use]
+    pub fn with_client_observer(mut self, observer: Arc<dyn ClientObserver>) -> Self {
+        self.inner = self.inner.with_client_observer(observer);
+        self
+    }
+
+    /// Send each call's request id in `name` instead of `x-request-id`.
+    #[must_use]
+    pub fn with_request_id_header(mut self, name: HeaderName) -> Self {
+        self.inner = self.inner.with_request_id_header(name);
+        self
+    }
+
+    /// Spread requests over `endpoints`, skipping stale and quarantined ones.
+    ///
+    /// See [`ServiceClientBuilder::with_endpoints`].
+    #[must_use]
+    pub fn with_endpoints(mut self, endpoints: EndpointList, selector: EndpointSelector) -> Self {
+        self.inner = self.inner.with_endpoints(endpoints, selector);
+        self
+    }
+
+    /// Send every request to the endpoint at `url` until it fails or is released.
+    ///
+    /// See [`ServiceClientBuilder::pin_endpoint`].
+    #[must_use]
+    pub fn pin_endpoint(mut self, url: impl Into<String>) -> Self {
+        self.inner = self.inner.pin_endpoint(url);
+        self
+    }
+
+    /// Send requests through a shared `reqwest::Client` instead of building a new one.
+    ///
+    /// The injected client's timeout, TLS and pooling settings take precedence; see
+    /// [`ServiceClientBuilder::with_http_client`].
+    #[must_use]
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
+        self.inner = self.inner.with_http_client(client);
+        self
+    }
+
     /// Build the client.
     pub fn build(self) -> Result<PapiClient> {
         let inner = self.inner.build()?;
@@ -89,21 +162,78 @@ impl PapiClient {
         PapiClientBuilder::new(base_url)?.build()
     }
 
+    /// Returns a handle that tags every call with `context` for logs and observers.
+    #[must_use]
+    pub fn with_context(&self, context: RequestContext) -> Self {
+        Self {
+            inner: self.inner.with_context(context),
+        }
+    }
+
     /// Return the base URL.
     #[must_use]
     pub fn base_url(&self) -> &Url {
         self.inner.base_url()
     }
 
+    /// Identifier of the endpoint requests are pinned to, if any.
+    #[must_use]
+    pub fn pinned_endpoint(&self) -> Option<String> {
+        self.inner.pinned_endpoint()
+    }
+
+    /// Drop the endpoint pin so the next request selects afresh.
+    pub fn release_pinned_endpoint(&self) {
+        self.inner.release_pinned_endpoint();
+    }
+
+    /// Replace the endpoints requests are spread over, e.g. after rediscovery.
+    pub fn update_endpoints(&self, endpoints: EndpointList) {
+        self.inner.update_endpoints(endpoints);
+    }
+
     /// List packages with optional filters.
     pub async fn list_packages(&self, params: &PackageListParams) -> Result<Vec<Package>> {
         self.send_json::<(), Vec<Package>>(Method::GET, "packages", None, &params.to_pairs())
             .await
     }
 
+    /// Stream every package matching `params`, one page per request.
+    ///
+    /// `params.limit` sets the page size (default
+    /// [`PackageListParams::DEFAULT_PAGE_SIZE`]) and `params.offset` the
+    /// starting point; each request advances `offset` by a page. The stream
+    /// ends after a short (or empty) page, or after yielding the first error.
+    pub fn list_packages_stream(
+        &self,
+        params: &PackageListParams,
+    ) -> impl Stream<Item = Result<Package>> + '_ {
+        let mut params = params.clone();
+        let page_size = params
+            .limit
+            .unwrap_or(PackageListParams::DEFAULT_PAGE_SIZE)
+            .max(1);
+        params.limit = Some(page_size);
+        let start = params.offset.unwrap_or(0);
+
+        stream::unfold(Some((params, start)), move |state| async move {
+            let (mut params, offset) = state?;
+            params.offset = Some(offset);
+            match self.list_packages(&params).await {
+                Ok(page) => {
+                    let fetched = u32::try_from(page.len()).unwrap_or(u32::MAX);
+                    let next = (fetched >= page_size).then(|| (params, offset + page_size));
+                    Some((page.into_iter().map(Ok).collect::<Vec<_>>(), next))
+                }
+                Err(err) => Some((vec![Err(err)], None)),
+            }
+        })
+        .flat_map(stream::iter)
+    }
+
     /// Fetch a single package by UUID.
     pub async fn get_package(&self, uuid: PackageUuid) -> Result<Package> {
-        let path = format!("packages/{uuid}");
+        let path = format!("packages/{}", encode_path_segment(uuid)?);
         self.send_json::<(), Package>(Method::GET, &path, None, &[])
             .await
     }
@@ -120,13 +250,13 @@ impl PapiClient {
         uuid: PackageUuid,
         request: &UpdatePackageRequest,
     ) -> Result<Package> {
-        let path = format!("packages/{uuid}");
+        let path = format!("packages/{}", encode_path_segment(uuid)?);
         self.send_json(Method::PUT, &path, Some(request), &[]).await
     }
 
     /// Delete a package by UUID.
     pub async fn delete_package(&self, uuid: PackageUuid) -> Result<()> {
-        let path = format!("packages/{uuid}");
+        let path = format!("packages/{}", encode_path_segment(uuid)?);
         self.inner
             .execute_with_retry(
                 Method::DELETE,
@@ -139,41 +269,70 @@ impl PapiClient {
             .map(|_| ())
     }
 
+    /// Fetch any PAPI path as raw JSON; see [`ServiceClient::get_raw`].
+    pub async fn get_raw(&self, path: &str, params: &[QueryPair]) -> Result<serde_json::Value> {
+        self.inner.get_raw(path, params, map_status_to_error).await
+    }
+
+    /// Send an arbitrary request to PAPI and return the raw JSON response;
+    /// see [`ServiceClient::send_raw`].
+    pub async fn send_raw(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        self.inner
+            .send_raw(method, path, body, &[], map_status_to_error)
+            .await
+    }
+
     async fn send_json<B, R>(
         &self,
         method: Method,
         path: &str,
         body: Option<&B>,
-        params: &[(&'static str, String)],
+        params: &[QueryPair],
     ) -> Result<R>
     where
         B: Serialize + ?Sized,
         R: DeserializeOwned,
     {
-        let response = self
-            .inner
-            .execute_with_retry(
+        self.inner
+            .execute_json_with_retry(
                 method,
                 path,
                 params,
                 |mut request| {
                     request = request.header("Accept", "application/json");
                     if let Some(payload) = body {
-                        request = request.json(payload);
+                        request = self.inner.request_options().json_body(request, payload);
                     }
                     request
                 },
                 map_status_to_error,
             )
-            .await?;
+            .await
+    }
+}
+
+#[async_trait]
+impl PreflightCheck for PapiClient {
+    fn service(&self) -> TritonService {
+        TritonService::Papi
+    }
 
-        response.json::<R>().await.map_err(Error::from)
+    async fn preflight(&self) -> PreflightResult {
+        self.inner
+            .preflight("packages", &[("limit".into(), "1".to_string())])
+            .await
     }
 }
 
 fn map_status_to_error(status: StatusCode, text: String) -> Error {
     match status {
         StatusCode::NOT_FOUND => Error::NotFound(text),
+        StatusCode::PAYLOAD_TOO_LARGE => Error::PayloadTooLarge(text),
         StatusCode::BAD_REQUEST => Error::BadRequest(text),
         StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
             Error::InvalidRequest(format!("PAPI authentication failed: {text}"))
@@ -188,7 +347,7 @@ fn map_status_to_error(status: StatusCode, text: String) -> Error {
         status if status.is_server_error() => {
             Error::ServiceUnavailable(format!("PAPI server error {status}: {text}"))
         }
-        _ => Error::HttpError(format!("PAPI error {status}: {text}")),
+        _ => Error::http(status, format!("PAPI error {status}: {text}")),
     }
 }
 
@@ -230,7 +389,7 @@ impl ServiceDiscovery for PapiDiscovery {
 mod tests {
     use super::*;
     use serde_json::json;
-    use wiremock::matchers::{body_json, method, path};
+    use wiremock::matchers::{body_json, method, path, query_param};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     fn test_client(server: &MockServer) -> PapiClient {
@@ -261,6 +420,94 @@ mod tests {
         assert_eq!(packages[0].name, "standard-2cpu-4gb");
     }
 
+    #[tokio::test]
+    async fn list_packages_stream_concatenates_pages_in_order() {
+        let server = MockServer::start().await;
+        let pages = [
+            ("10", vec!["pkg-1", "pkg-2"]),
+            ("12", vec!["pkg-3", "pkg-4"]),
+            ("14", vec!["pkg-5"]),
+        ];
+        for (offset, names) in pages {
+            let page: Vec<_> = names
+                .into_iter()
+                .map(|name| {
+                    json!({
+                        "uuid": PackageUuid::new_v4(),
+                        "name": name,
+                        "max_physical_memory": 1024
+                    })
+                })
+                .collect();
+            Mock::given(method("GET"))
+                .and(path("/packages"))
+                .and(query_param("active", "true"))
+                .and(query_param("limit", "2"))
+                .and(query_param("offset", offset))
+                .respond_with(ResponseTemplate::new(200).set_body_json(page))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let client = test_client(&server);
+        let params = PackageListParams {
+            active: Some(true),
+            limit: Some(2),
+            offset: Some(10),
+            ..PackageListParams::default()
+        };
+        let names: Vec<String> = client
+            .list_packages_stream(&params)
+            .map(|package| package.unwrap().name)
+            .collect()
+            .await;
+        assert_eq!(names, vec!["pkg-1", "pkg-2", "pkg-3", "pkg-4", "pkg-5"]);
+    }
+
+    #[tokio::test]
+    async fn list_packages_stream_uses_default_page_size() {
+        let server = MockServer::start().await;
+        let default_limit = PackageListParams::DEFAULT_PAGE_SIZE.to_string();
+        Mock::given(method("GET"))
+            .and(path("/packages"))
+            .and(query_param("limit", default_limit.as_str()))
+            .and(query_param("offset", "0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let packages: Vec<_> = client
+            .list_packages_stream(&PackageListParams::default())
+            .collect()
+            .await;
+        assert!(packages.is_empty());
+    }
+
+    #[tokio::test]
+    async fn get_package_success() {
+        let server = MockServer::start().await;
+        let uuid = PackageUuid::new_v4();
+
+        Mock::given(method("GET"))
+            .and(path(format!("/packages/{uuid}").as_str()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "uuid": uuid,
+                "name": "standard-2cpu-4gb",
+                "max_physical_memory": 4096
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let package = client.get_package(uuid).await.unwrap();
+        assert_eq!(package.uuid, uuid);
+        assert_eq!(package.name, "standard-2cpu-4gb");
+    }
+
     #[tokio::test]
     async fn get_package_not_found() {
         let server = MockServer::start().await;
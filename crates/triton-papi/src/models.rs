@@ -2,7 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use triton_core::query::QueryParams;
+use triton_core::query::{QueryPair, QueryParams};
 use triton_core::uuid::{NetworkUuid, OwnerUuid, PackageUuid};
 
 /// Representation of a package as returned by PAPI.
@@ -348,12 +348,19 @@ pub struct PackageListParams {
     pub limit: Option<u32>,
     /// Offset.
     pub offset: Option<u32>,
+    /// Additional query parameters appended verbatim after the typed ones.
+    ///
+    /// Keys already set by a typed field are skipped.
+    pub extra_params: Vec<(String, String)>,
 }
 
 impl PackageListParams {
+    /// Page size used by `list_packages_stream` when `limit` is unset.
+    pub const DEFAULT_PAGE_SIZE: u32 = 100;
+
     /// Convert the parameters into URL query pairs.
     #[must_use]
-    pub fn to_pairs(&self) -> Vec<(&'static str, String)> {
+    pub fn to_pairs(&self) -> Vec<QueryPair> {
         let mut params = QueryParams::new();
 
         params.push_opt("name", self.name.as_deref());
@@ -370,6 +377,7 @@ impl PackageListParams {
         params.push_opt("trait_val", self.trait_val);
         params.push_opt("limit", self.limit);
         params.push_opt("offset", self.offset);
+        params.push_extra(&self.extra_params);
 
         params.into_pairs()
     }
@@ -390,9 +398,9 @@ mod tests {
         };
 
         let pairs = params.to_pairs();
-        assert!(pairs.contains(&("name", "standard".into())));
-        assert!(pairs.contains(&("memory", "4096".into())));
-        assert!(pairs.contains(&("active", "true".into())));
-        assert!(pairs.contains(&("limit", "50".into())));
+        assert!(pairs.contains(&("name".into(), "standard".into())));
+        assert!(pairs.contains(&("memory".into(), "4096".into())));
+        assert!(pairs.contains(&("active".into(), "true".into())));
+        assert!(pairs.contains(&("limit".into(), "50".into())));
     }
 }
@@ -1,27 +1,37 @@
 //! Asynchronous SAPI client implementation and discovery utilities.
 
-use crate::models::{Application, Instance, InstanceType, Service};
+use crate::models::{
+    Application, CreateInstanceRequest, CreateServiceRequest, Instance, InstanceChange,
+    InstanceType, MetadataChange, Service, UpdateRequest,
+};
 use crate::Result;
 use async_trait::async_trait;
-use reqwest::{Client, ClientBuilder, StatusCode};
+use futures::future;
+use futures::stream::{self, Stream};
+use reqwest::header::HeaderName;
+use reqwest::{Client, ClientBuilder, Method, RequestBuilder, StatusCode};
 use serde::de::DeserializeOwned;
+use serde::Serialize;
 use std::collections::{BTreeSet, HashMap};
 use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
 use tokio::time::sleep;
 use tracing::{debug, info, warn};
 use triton_core::client::{
-    ClientConfig, RetryPolicy, DEFAULT_POOL_IDLE_TIMEOUT, DEFAULT_POOL_MAX_IDLE_PER_HOST,
-    SAPI_DEFAULT_TIMEOUT,
-};
-use triton_core::config::{
-    ServiceDiscoveryConfig, ServiceEndpointConfig, ServiceEndpoints, TritonClientConfig,
+    ClientConfig, ClientObserver, RequestObserver, RequestOptions, RetryPolicy, ServiceClient,
+    ServiceClientBuilder, DEFAULT_POOL_IDLE_TIMEOUT, DEFAULT_POOL_MAX_IDLE_PER_HOST,
+    DEFAULT_REQUEST_ID_HEADER, SAPI_DEFAULT_TIMEOUT,
 };
+use triton_core::config::{ServiceDiscoveryConfig, ServiceEndpoints, TritonClientConfig};
+use triton_core::diff::diff_keyed;
+use triton_core::path::encode_path_segment;
+use triton_core::preflight::{PreflightCheck, PreflightResult};
+use triton_core::query::QueryPair;
 use triton_core::services::{DiscoveryStatus, ServiceDiscovery};
+use triton_core::shutdown::{Shutdown, ShutdownSignal};
 use triton_core::types::TritonService;
 use triton_core::uuid::{AppUuid, InstanceUuid, ServiceUuid};
 use triton_core::Error;
-use url::Url;
 
 const USER_AGENT: &str = concat!("triton-sapi/", env!("CARGO_PKG_VERSION"));
 const ACCEPT_VERSION: &str = "2.0.0";
@@ -32,6 +42,11 @@ pub struct SapiClientBuilder {
     config: TritonClientConfig,
     http_config: ClientConfig,
     accept_version: String,
+    observer: Option<Arc<dyn RequestObserver>>,
+    client_observer: Option<Arc<dyn ClientObserver>>,
+    http_client: Option<Client>,
+    request_id_header: HeaderName,
+    request_options: RequestOptions,
 }
 
 impl SapiClientBuilder {
@@ -47,6 +62,11 @@ impl SapiClientBuilder {
             config,
             http_config,
             accept_version: ACCEPT_VERSION.to_string(),
+            observer: None,
+            client_observer: None,
+            http_client: None,
+            request_id_header: HeaderName::from_static(DEFAULT_REQUEST_ID_HEADER),
+            request_options: RequestOptions::default(),
         }
     }
 
@@ -64,22 +84,92 @@ impl SapiClientBuilder {
         self
     }
 
+    /// Notify `observer` after every call, retries included.
+    #[must_use]
+    pub fn with_observer(mut self, observer: Arc<dyn RequestObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Invoke `observer` around every attempt, retries included.
+    #[must_use]
+    pub fn with_client_observer(mut self, observer: Arc<dyn ClientObserver>) -> Self {
+        self.client_observer = Some(observer);
+        self
+    }
+
+    /// Send requests through a shared `reqwest::Client` instead of building a new one.
+    ///
+    /// Clones of a `reqwest::Client` share one connection pool. The injected client's timeout,
+    /// TLS and user agent settings take precedence over [`ClientConfig`] and the TLS options
+    /// in [`TritonClientConfig`]; the retry policy and `Accept-Version` header still apply.
+    #[must_use]
+    pub fn with_http_client(mut self, client: Client) -> Self {
+        self.http_client = Some(client);
+        self
+    }
+
+    /// Send each call's request id in `name` instead of `x-request-id`.
+    #[must_use]
+    pub fn with_request_id_header(mut self, name: HeaderName) -> Self {
+        self.request_id_header = name;
+        self
+    }
+
+    /// Override per-request options such as the JSON content type.
+    #[must_use]
+    pub fn with_request_options(mut self, options: RequestOptions) -> Self {
+        self.request_options = options;
+        self
+    }
+
     /// Finalise the builder and create the [`SapiClient`].
     pub fn build(self) -> Result<SapiClient> {
         let base_url = self.config.parse_sapi_url()?;
 
-        let mut http_config = self.http_config;
-        http_config.timeout = self.config.timeout();
+        let mut http_config = self.http_config.clone();
+        http_config.timeout = self.config.timeout_for(TritonService::Sapi);
         http_config.retry_policy = http_config
             .retry_policy
             .with_max_retries(self.config.max_retries);
 
+        let http = match &self.http_client {
+            Some(http) => http.clone(),
+            None => self.build_http_client(&http_config)?,
+        };
+
+        let retry_policy = http_config.retry_policy;
+        let mut inner =
+            ServiceClientBuilder::new(TritonService::Sapi, base_url, http_config.timeout)?
+                .with_user_agent(USER_AGENT)
+                .with_http_config(http_config)
+                .with_http_client(http)
+                .with_request_id_header(self.request_id_header)
+                .with_request_options(self.request_options);
+        if let Some(observer) = self.observer {
+            inner = inner.with_observer(observer);
+        }
+        if let Some(observer) = self.client_observer {
+            inner = inner.with_client_observer(observer);
+        }
+
+        Ok(SapiClient {
+            inner: inner.build()?,
+            api_key: self.config.sapi_key.clone(),
+            retry_policy,
+            accept_version: self.accept_version,
+            discovery_config: self.config.service_discovery.clone(),
+        })
+    }
+
+    fn build_http_client(&self, http_config: &ClientConfig) -> Result<Client> {
         let mut builder = ClientBuilder::new()
             .user_agent(USER_AGENT)
             .timeout(http_config.timeout)
             .pool_idle_timeout(http_config.pool_idle_timeout)
             .pool_max_idle_per_host(http_config.pool_max_idle_per_host)
-            .connect_timeout(Duration::from_secs(10));
+            .connect_timeout(http_config.connect_timeout);
+        builder = http_config.apply_compression(builder);
 
         if !self.config.tls_verify {
             warn!("TLS verification disabled for SAPI client");
@@ -98,29 +188,19 @@ impl SapiClientBuilder {
                 .map_err(|err| Error::ConfigError(format!("Invalid SAPI CA certificate: {err}")))?;
             builder = builder.add_root_certificate(cert);
         }
+        let builder = http_config.apply_client_identity(builder)?;
+        let builder = http_config.apply_proxy(builder)?;
 
-        let http = builder.build().map_err(|err| {
-            Error::ConfigError(format!("Failed to build SAPI HTTP client: {err}"))
-        })?;
-
-        let api_key = self.config.sapi_key.clone();
-
-        Ok(SapiClient {
-            http,
-            base_url,
-            api_key,
-            retry_policy: http_config.retry_policy,
-            accept_version: self.accept_version,
-            discovery_config: self.config.service_discovery.clone(),
-        })
+        builder
+            .build()
+            .map_err(|err| Error::ConfigError(format!("Failed to build SAPI HTTP client: {err}")))
     }
 }
 
 /// Asynchronous client for the Triton Services API (SAPI).
 #[derive(Clone)]
 pub struct SapiClient {
-    http: Client,
-    base_url: Url,
+    inner: ServiceClient,
     api_key: Option<String>,
     retry_policy: RetryPolicy,
     accept_version: String,
@@ -158,10 +238,25 @@ impl SapiClient {
 
     /// Fetch a specific application by UUID.
     pub async fn get_application(&self, uuid: AppUuid) -> Result<Application> {
-        let path = format!("applications/{uuid}");
+        let path = format!("applications/{}", encode_path_segment(uuid)?);
         self.get_json(&path, &[]).await
     }
 
+    /// Set and remove keys in an application's metadata, leaving other keys
+    /// alone, and return the updated application.
+    ///
+    /// Sends an `update` for [`MetadataChange::set`] and then a `delete` for
+    /// [`MetadataChange::remove`], so keys changed concurrently by others are
+    /// not overwritten. An empty change just fetches the application.
+    pub async fn update_application_metadata(
+        &self,
+        uuid: AppUuid,
+        change: &MetadataChange,
+    ) -> Result<Application> {
+        let path = format!("applications/{}", encode_path_segment(uuid)?);
+        self.apply_metadata_change(&path, change).await
+    }
+
     /// List services, optionally filtered using a query.
     pub async fn list_services<'a>(&self, query: &ServiceQuery<'a>) -> Result<Vec<Service>> {
         let params = query.to_params();
@@ -170,19 +265,152 @@ impl SapiClient {
 
     /// Fetch a specific service by UUID.
     pub async fn get_service(&self, uuid: ServiceUuid) -> Result<Service> {
-        let path = format!("services/{uuid}");
+        let path = format!("services/{}", encode_path_segment(uuid)?);
         self.get_json(&path, &[]).await
     }
 
+    /// Create a service under an application.
+    pub async fn create_service(&self, request: &CreateServiceRequest) -> Result<Service> {
+        self.send_json(Method::POST, "services", Some(request), &[])
+            .await
+    }
+
+    /// Update a service's params, metadata, or manifests.
+    ///
+    /// How the given maps combine with the stored ones is set by
+    /// [`UpdateRequest::action`].
+    pub async fn update_service(
+        &self,
+        uuid: ServiceUuid,
+        request: &UpdateRequest,
+    ) -> Result<Service> {
+        let path = format!("services/{}", encode_path_segment(uuid)?);
+        self.send_json(Method::PUT, &path, Some(request), &[]).await
+    }
+
+    /// Service counterpart of
+    /// [`update_application_metadata`](Self::update_application_metadata).
+    pub async fn update_service_metadata(
+        &self,
+        uuid: ServiceUuid,
+        change: &MetadataChange,
+    ) -> Result<Service> {
+        let path = format!("services/{}", encode_path_segment(uuid)?);
+        self.apply_metadata_change(&path, change).await
+    }
+
+    /// Delete a service. SAPI refuses while the service still has instances.
+    pub async fn delete_service(&self, uuid: ServiceUuid) -> Result<()> {
+        let path = format!("services/{}", encode_path_segment(uuid)?);
+        self.send_json::<(), serde_json::Value>(Method::DELETE, &path, None, &[])
+            .await
+            .map(drop)
+    }
+
     /// List instances, optionally filtered using a query.
     pub async fn list_instances(&self, query: &InstanceQuery) -> Result<Vec<Instance>> {
         let params = query.to_params();
         self.get_json("instances", &params).await
     }
 
+    /// Poll a service's instances every `interval`, yielding what changed.
+    ///
+    /// The first item reports every existing instance as added. Later items are
+    /// only produced when the listing differs from the previous poll. A failed
+    /// poll yields the error and the next poll diffs against the last good
+    /// snapshot. The stream ends once `signal` is raised, which is checked
+    /// before every poll and interrupts the wait between polls; dropping the
+    /// stream also stops polling.
+    pub fn watch_instances(
+        &self,
+        service_uuid: ServiceUuid,
+        interval: Duration,
+        signal: ShutdownSignal,
+    ) -> impl Stream<Item = Result<Vec<InstanceChange>>> + '_ {
+        let query = InstanceQuery::new().with_service_uuid(service_uuid);
+        let initial: (Option<Vec<Instance>>, bool) = (None, false);
+
+        stream::unfold(initial, move |(mut previous, mut polled)| {
+            let query = query.clone();
+            let signal = signal.clone();
+            async move {
+                loop {
+                    if polled {
+                        tokio::select! {
+                            () = signal.raised() => return None,
+                            () = sleep(interval) => {}
+                        }
+                    }
+                    if signal.is_raised() {
+                        return None;
+                    }
+                    polled = true;
+
+                    match self.list_instances(&query).await {
+                        Ok(current) => {
+                            let first = previous.is_none();
+                            let changes = diff_keyed(
+                                previous.as_deref().unwrap_or_default(),
+                                &current,
+                                |instance| instance.uuid,
+                            );
+                            previous = Some(current);
+                            if first || !changes.is_empty() {
+                                let changes = changes
+                                    .into_iter()
+                                    .map(|change| InstanceChange {
+                                        kind: change.kind,
+                                        instance: change.item,
+                                    })
+                                    .collect();
+                                return Some((Ok(changes), (previous, polled)));
+                            }
+                        }
+                        Err(err) => return Some((Err(err), (previous, polled))),
+                    }
+                }
+            }
+        })
+    }
+
+    /// Create an instance of a service; for VM services this provisions a zone.
+    pub async fn create_instance(&self, request: &CreateInstanceRequest) -> Result<Instance> {
+        self.send_json(Method::POST, "instances", Some(request), &[])
+            .await
+    }
+
+    /// Update an instance's params, metadata, or manifests.
+    pub async fn update_instance(
+        &self,
+        uuid: InstanceUuid,
+        request: &UpdateRequest,
+    ) -> Result<Instance> {
+        let path = format!("instances/{}", encode_path_segment(uuid)?);
+        self.send_json(Method::PUT, &path, Some(request), &[]).await
+    }
+
+    /// Instance counterpart of
+    /// [`update_application_metadata`](Self::update_application_metadata).
+    pub async fn update_instance_metadata(
+        &self,
+        uuid: InstanceUuid,
+        change: &MetadataChange,
+    ) -> Result<Instance> {
+        let path = format!("instances/{}", encode_path_segment(uuid)?);
+        self.apply_metadata_change(&path, change).await
+    }
+
+    /// Delete an instance; for VM services this destroys its zone.
+    pub async fn delete_instance(&self, uuid: InstanceUuid) -> Result<()> {
+        let path = format!("instances/{}", encode_path_segment(uuid)?);
+        self.send_json::<(), serde_json::Value>(Method::DELETE, &path, None, &[])
+            .await
+            .map(drop)
+    }
+
     /// Fetch a specific instance by UUID.
     pub async fn get_instance(&self, uuid: InstanceUuid) -> Result<Instance> {
-        let path = format!("instances/{uuid}");
+        let path = format!("instances/{}", encode_path_segment(uuid)?);
         self.get_json(&path, &[]).await
     }
 
@@ -270,108 +498,119 @@ impl SapiClient {
         endpoints.into_iter().collect()
     }
 
-    fn build_url(&self, path: &str) -> Result<Url> {
-        self.base_url
-            .join(path)
-            .map_err(|err| Error::InvalidEndpoint(format!("Invalid SAPI path `{path}`: {err}")))
+    /// Fetch any SAPI path as raw JSON, like [`ServiceClient::get_raw`] but
+    /// with the SAPI version and API key headers.
+    pub async fn get_raw(&self, path: &str, params: &[QueryPair]) -> Result<serde_json::Value> {
+        self.get_json(path, params).await
+    }
+
+    /// Send an arbitrary request to SAPI and return the raw JSON response,
+    /// like [`ServiceClient::send_raw`] but with the SAPI version and API key
+    /// headers.
+    pub async fn send_raw(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        self.send_json(method, path, body, &[]).await
     }
 
-    async fn get_json<T>(&self, path: &str, params: &[(&'static str, String)]) -> Result<T>
+    async fn apply_metadata_change<T>(&self, path: &str, change: &MetadataChange) -> Result<T>
     where
         T: DeserializeOwned,
     {
-        let mut attempt = 0;
-        #[allow(unused_assignments)]
-        let mut last_error: Option<Error> = None;
-
-        loop {
-            let url = self.build_url(path)?;
-            let mut request = self.http.get(url).query(&params);
-            request = request.header("Accept-Version", &self.accept_version);
-            request = request.header("Accept", "application/json");
-
-            if let Some(api_key) = &self.api_key {
-                request = request.header("X-Api-Key", api_key);
-            }
+        let mut updated = None;
+        for request in change.to_requests() {
+            updated = Some(
+                self.send_json(Method::PUT, path, Some(&request), &[])
+                    .await?,
+            );
+        }
+        match updated {
+            Some(updated) => Ok(updated),
+            None => self.get_json(path, &[]).await,
+        }
+    }
 
-            info!(path = %path, ?params, attempt, "Sending SAPI request");
+    /// Add the `Accept-Version`, `Accept` and `X-Api-Key` headers SAPI expects.
+    fn with_headers(&self, request: RequestBuilder) -> RequestBuilder {
+        let request = request
+            .header("Accept-Version", &self.accept_version)
+            .header("Accept", "application/json");
+        match &self.api_key {
+            Some(api_key) => request.header("X-Api-Key", api_key),
+            None => request,
+        }
+    }
 
-            match request.send().await {
-                Ok(response) => {
-                    let status = response.status();
+    async fn get_json<T>(&self, path: &str, params: &[QueryPair]) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        self.send_json::<(), T>(Method::GET, path, None, params)
+            .await
+    }
 
-                    if status.is_success() {
-                        return response.json::<T>().await.map_err(|err| {
-                            Error::SapiParseError(format!(
-                                "Failed to parse SAPI response for `{path}`: {err}"
-                            ))
-                        });
+    async fn send_json<B, R>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&B>,
+        params: &[QueryPair],
+    ) -> Result<R>
+    where
+        B: Serialize + ?Sized,
+        R: DeserializeOwned,
+    {
+        self.inner
+            .execute_json_with_retry(
+                method,
+                path,
+                params,
+                |request| {
+                    let mut request = self.with_headers(request);
+                    if let Some(payload) = body {
+                        request = self.inner.request_options().json_body(request, payload);
                     }
+                    request
+                },
+                map_status_to_error,
+            )
+            .await
+    }
+}
 
-                    let message = response
-                        .text()
-                        .await
-                        .unwrap_or_else(|_| "Unknown error".to_string());
-
-                    let error = match status {
-                        StatusCode::NOT_FOUND => {
-                            return Err(Error::NotFound(message));
-                        }
-                        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
-                            return Err(Error::InvalidRequest(format!(
-                                "SAPI authentication failed: {message}"
-                            )));
-                        }
-                        StatusCode::TOO_MANY_REQUESTS
-                        | StatusCode::BAD_GATEWAY
-                        | StatusCode::SERVICE_UNAVAILABLE
-                        | StatusCode::GATEWAY_TIMEOUT => Error::ServiceUnavailable(format!(
-                            "SAPI temporarily unavailable: {message}"
-                        )),
-                        status if status.is_server_error() => Error::ServiceUnavailable(format!(
-                            "SAPI server error {status}: {message}"
-                        )),
-                        _ => {
-                            return Err(Error::HttpError(format!(
-                                "SAPI error {status}: {message}"
-                            )));
-                        }
-                    };
-
-                    last_error = Some(error);
-                }
-                Err(err) => {
-                    let error = Error::from(err);
-                    if matches!(
-                        error,
-                        Error::Timeout(_) | Error::ServiceUnavailable(_) | Error::HttpError(_)
-                    ) {
-                        last_error = Some(error);
-                    } else {
-                        return Err(error);
-                    }
-                }
-            }
+#[async_trait]
+impl PreflightCheck for SapiClient {
+    fn service(&self) -> TritonService {
+        TritonService::Sapi
+    }
 
-            attempt += 1;
-            if attempt > self.retry_policy.max_retries {
-                break;
-            }
+    async fn preflight(&self) -> PreflightResult {
+        self.inner
+            .preflight_with("applications", &[], |request| self.with_headers(request))
+            .await
+    }
+}
 
-            let delay = self.retry_policy.delay_for_attempt(attempt);
-            if delay > Duration::from_millis(0) {
-                debug!("Retrying SAPI request after {:?}", delay);
-                sleep(delay).await;
-            }
+fn map_status_to_error(status: StatusCode, text: String) -> Error {
+    match status {
+        StatusCode::NOT_FOUND => Error::NotFound(text),
+        StatusCode::PAYLOAD_TOO_LARGE => Error::PayloadTooLarge(text),
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+            Error::InvalidRequest(format!("SAPI authentication failed: {text}"))
         }
-
-        if let Some(error) = last_error {
-            Err(error)
-        } else {
-            Err(Error::ServiceUnavailable(
-                "SAPI request failed after retries".to_string(),
-            ))
+        StatusCode::TOO_MANY_REQUESTS
+        | StatusCode::BAD_GATEWAY
+        | StatusCode::SERVICE_UNAVAILABLE
+        | StatusCode::GATEWAY_TIMEOUT => {
+            Error::ServiceUnavailable(format!("SAPI temporarily unavailable: {text}"))
         }
+        status if status.is_server_error() => {
+            Error::ServiceUnavailable(format!("SAPI server error {status}: {text}"))
+        }
+        _ => Error::http(status, format!("SAPI error {status}: {text}")),
     }
 }
 
@@ -424,23 +663,23 @@ impl<'a> ServiceQuery<'a> {
         self
     }
 
-    fn to_params(&self) -> Vec<(&'static str, String)> {
+    fn to_params(&self) -> Vec<QueryPair> {
         let mut params = Vec::new();
 
         if let Some(name) = self.name {
-            params.push(("name", name.to_string()))
+            params.push(("name".into(), name.to_string()))
         }
 
         if let Some(uuid) = self.application_uuid {
-            params.push(("application_uuid", uuid.to_string()));
+            params.push(("application_uuid".into(), uuid.to_string()));
         }
 
         if let Some(service_type) = self.service_type {
-            params.push(("type", service_type.as_str().to_string()));
+            params.push(("type".into(), service_type.as_str().to_string()));
         }
 
         if self.include_master {
-            params.push(("include_master", "true".to_string()));
+            params.push(("include_master".into(), "true".to_string()));
         }
 
         params
@@ -448,7 +687,7 @@ impl<'a> ServiceQuery<'a> {
 }
 
 /// Query parameters for listing instances.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct InstanceQuery {
     service_uuid: Option<ServiceUuid>,
     service_type: Option<InstanceType>,
@@ -487,19 +726,19 @@ impl InstanceQuery {
         self
     }
 
-    fn to_params(&self) -> Vec<(&'static str, String)> {
+    fn to_params(&self) -> Vec<QueryPair> {
         let mut params = Vec::new();
 
         if let Some(uuid) = self.service_uuid {
-            params.push(("service_uuid", uuid.to_string()));
+            params.push(("service_uuid".into(), uuid.to_string()));
         }
 
         if let Some(instance_type) = self.service_type {
-            params.push(("type", instance_type.as_str().to_string()));
+            params.push(("type".into(), instance_type.as_str().to_string()));
         }
 
         if self.include_master {
-            params.push(("include_master", "true".to_string()));
+            params.push(("include_master".into(), "true".to_string()));
         }
 
         params
@@ -538,9 +777,43 @@ impl SapiDiscovery {
         }
     }
 
+    /// Spawn a background task that refreshes every cached service each `interval`.
+    ///
+    /// The task checks its shutdown signal between refresh rounds and between
+    /// services, so [`Shutdown::drain`] returns without waiting for the next tick.
+    #[must_use]
+    pub fn spawn_refresh(self: &Arc<Self>, interval: Duration) -> Shutdown {
+        let discovery = Arc::clone(self);
+        Shutdown::spawn(move |signal| async move {
+            loop {
+                tokio::select! {
+                    () = signal.raised() => break,
+                    () = sleep(interval) => {}
+                }
+
+                let services: Vec<String> =
+                    discovery.cache.read().unwrap().keys().cloned().collect();
+                for service in services {
+                    if signal.is_raised() {
+                        break;
+                    }
+                    if let Err(err) = discovery.refresh_service(&service).await {
+                        warn!("Background refresh of {} failed: {}", service, err);
+                    }
+                }
+
+                if signal.is_raised() {
+                    break;
+                }
+            }
+            debug!("Discovery refresh task stopped");
+        })
+    }
+
     async fn refresh_service(&self, service: &str) -> Result<Vec<String>> {
         let triton_service = service.parse::<TritonService>()?;
         let mut attempt = 0;
+        let mut delay = Duration::ZERO;
         let mut last_error: Option<Error> = None;
 
         while attempt <= self.retry_attempts {
@@ -563,7 +836,7 @@ impl SapiDiscovery {
                     if attempt > self.retry_attempts {
                         break;
                     }
-                    let delay = self.client.retry_policy.delay_for_attempt(attempt);
+                    delay = self.client.retry_policy.next_delay(attempt, delay);
                     if delay > Duration::from_millis(0) {
                         sleep(delay).await;
                     }
@@ -640,12 +913,17 @@ impl ServiceDiscovery for SapiDiscovery {
     }
 
     async fn discover_all_services(&self) -> Result<Vec<String>> {
-        let mut endpoints = Vec::new();
-        for service in TritonService::all() {
-            if let Ok(mut discovered) = self.discover_service(service.name()).await {
-                endpoints.append(&mut discovered);
-            }
-        }
+        // Look every service up at once; results come back in `TritonService::all()`
+        // order and failures are skipped, as `refresh_service` already logged them.
+        let lookups = TritonService::all()
+            .iter()
+            .map(|service| self.discover_service(service.name()));
+        let endpoints = future::join_all(lookups)
+            .await
+            .into_iter()
+            .filter_map(Result::ok)
+            .flatten()
+            .collect();
         Ok(endpoints)
     }
 
@@ -662,35 +940,27 @@ impl ServiceDiscovery for SapiDiscovery {
 }
 
 fn build_fallback_map(endpoints: &ServiceEndpoints) -> HashMap<String, Vec<String>> {
-    let mut map = HashMap::new();
-
-    fn insert_endpoint(
-        map: &mut HashMap<String, Vec<String>>,
-        service: &'static str,
-        endpoint: &Option<ServiceEndpointConfig>,
-    ) {
-        if let Some(config) = endpoint {
-            map.entry(service.to_string())
-                .or_default()
-                .push(config.url.clone());
-        }
-    }
-
-    insert_endpoint(&mut map, "vmapi", &endpoints.vmapi);
-    insert_endpoint(&mut map, "cnapi", &endpoints.cnapi);
-    insert_endpoint(&mut map, "napi", &endpoints.napi);
-    insert_endpoint(&mut map, "imgapi", &endpoints.imgapi);
-    insert_endpoint(&mut map, "papi", &endpoints.papi);
-    insert_endpoint(&mut map, "fwapi", &endpoints.fwapi);
-
-    map
+    TritonService::all()
+        .iter()
+        .filter_map(|service| {
+            let urls: Vec<String> = endpoints
+                .endpoints(*service)
+                .iter()
+                .map(|config| config.url.clone())
+                .collect();
+            (!urls.is_empty()).then(|| (service.name().to_string(), urls))
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use triton_core::config::TritonClientConfig;
-    use wiremock::matchers::{method, path, query_param};
+    use futures::StreamExt;
+    use std::collections::BTreeMap;
+    use triton_core::config::{ServiceEndpointConfig, ServiceTimeouts, TritonClientConfig};
+    use triton_core::diff::ChangeKind;
+    use wiremock::matchers::{body_json, header, method, path, query_param};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     fn test_config(base_url: &str) -> TritonClientConfig {
@@ -699,6 +969,116 @@ mod tests {
             .with_tls_verify(true)
     }
 
+    #[tokio::test]
+    async fn configured_connect_timeout_bounds_unanswered_connects() {
+        let mut config = TritonClientConfig::new("http://10.255.255.1:81").unwrap();
+        config.max_retries = 0;
+        let client = SapiClientBuilder::new(config)
+            .with_http_config(ClientConfig::new().with_connect_timeout(Duration::from_millis(200)))
+            .build()
+            .unwrap();
+
+        let started = Instant::now();
+        assert!(client.list_applications().await.is_err());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn sapi_timeout_override_applies() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/applications"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_delay(Duration::from_millis(500))
+                    .set_body_json(serde_json::json!([])),
+            )
+            .mount(&server)
+            .await;
+
+        let mut config = test_config(&server.uri()).with_service_timeouts(
+            ServiceTimeouts::new().with_timeout(TritonService::Sapi, Duration::from_millis(50)),
+        );
+        config.max_retries = 0;
+        let client = SapiClient::from_config(&config).unwrap();
+
+        let err = client.list_applications().await.unwrap_err();
+        assert!(matches!(err, Error::Timeout { .. }));
+    }
+
+    #[tokio::test]
+    async fn preflight_reads_applications_with_sapi_headers() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/ping"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/applications"))
+            .and(header("Accept-Version", ACCEPT_VERSION))
+            .and(header("X-Api-Key", "secret"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = test_config(&server.uri()).with_api_key("secret");
+        let client = SapiClient::from_config(&config).unwrap();
+        assert_eq!(PreflightCheck::service(&client), TritonService::Sapi);
+        assert!(client.preflight().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn send_raw_round_trips_json_with_sapi_headers() {
+        let server = MockServer::start().await;
+        let body = serde_json::json!({ "action": "update", "metadata": { "x": { "y": 1 } } });
+        Mock::given(method("PUT"))
+            .and(path("/applications/custom"))
+            .and(header("Accept-Version", ACCEPT_VERSION))
+            .and(header("X-Api-Key", "secret"))
+            .and(body_json(body.clone()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body.clone()))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = test_config(&server.uri()).with_api_key("secret");
+        let client = SapiClient::from_config(&config).unwrap();
+        let raw = client
+            .send_raw(Method::PUT, "applications/custom", Some(&body))
+            .await
+            .unwrap();
+        assert_eq!(raw, body);
+    }
+
+    #[tokio::test]
+    async fn custom_content_type_is_sent_with_json_body() {
+        let server = MockServer::start().await;
+        let body = serde_json::json!({ "action": "update", "metadata": { "x": 1 } });
+        Mock::given(method("PUT"))
+            .and(path("/applications/custom"))
+            .and(header("content-type", "application/json; charset=utf-8"))
+            .and(body_json(body.clone()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body.clone()))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = SapiClientBuilder::new(test_config(&server.uri()))
+            .with_request_options(
+                RequestOptions::new().with_content_type("application/json; charset=utf-8"),
+            )
+            .build()
+            .unwrap();
+        let raw = client
+            .send_raw(Method::PUT, "applications/custom", Some(&body))
+            .await
+            .unwrap();
+        assert_eq!(raw, body);
+    }
+
     #[tokio::test]
     async fn test_list_services_with_name_filter() {
         let server = MockServer::start().await;
@@ -733,6 +1113,228 @@ mod tests {
         assert_eq!(services[0].uuid, service_uuid);
     }
 
+    #[tokio::test]
+    async fn test_create_service_and_update_metadata() {
+        let server = MockServer::start().await;
+        let app_uuid = AppUuid::new_v4();
+        let service_uuid = ServiceUuid::new_v4();
+        let created = serde_json::json!({
+            "uuid": service_uuid,
+            "name": "portal",
+            "application_uuid": app_uuid,
+            "params": { "image_uuid": "img-1" },
+            "metadata": { "SERVICE_NAME": "portal" }
+        });
+
+        Mock::given(method("POST"))
+            .and(path("/services"))
+            .and(header("Accept-Version", ACCEPT_VERSION))
+            .and(header("X-Api-Key", "secret"))
+            .and(body_json(serde_json::json!({
+                "name": "portal",
+                "application_uuid": app_uuid,
+                "params": { "image_uuid": "img-1" },
+                "metadata": { "SERVICE_NAME": "portal" }
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(created))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path(format!("/services/{service_uuid}").as_str()))
+            .and(header("X-Api-Key", "secret"))
+            .and(body_json(serde_json::json!({
+                "action": "update",
+                "metadata": { "LOG_LEVEL": "debug" }
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "uuid": service_uuid,
+                "name": "portal",
+                "application_uuid": app_uuid,
+                "metadata": { "SERVICE_NAME": "portal", "LOG_LEVEL": "debug" }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = test_config(&server.uri()).with_api_key("secret");
+        let client = SapiClient::from_config(&config).unwrap();
+        let request = CreateServiceRequest {
+            name: "portal".to_string(),
+            application_uuid: app_uuid,
+            params: BTreeMap::from([("image_uuid".to_string(), serde_json::json!("img-1"))]),
+            metadata: BTreeMap::from([("SERVICE_NAME".to_string(), serde_json::json!("portal"))]),
+            manifests: BTreeMap::new(),
+            r#type: None,
+        };
+        let service = client.create_service(&request).await.unwrap();
+        assert_eq!(service.uuid, service_uuid);
+
+        let update = UpdateRequest::merge_metadata(BTreeMap::from([(
+            "LOG_LEVEL".to_string(),
+            serde_json::json!("debug"),
+        )]));
+        let updated = client.update_service(service_uuid, &update).await.unwrap();
+        assert_eq!(updated.metadata["LOG_LEVEL"], "debug");
+        assert_eq!(updated.metadata["SERVICE_NAME"], "portal");
+    }
+
+    #[tokio::test]
+    async fn test_update_service_metadata_sets_then_removes() {
+        let server = MockServer::start().await;
+        let app_uuid = AppUuid::new_v4();
+        let service_uuid = ServiceUuid::new_v4();
+        let service = |metadata: serde_json::Value| {
+            serde_json::json!({
+                "uuid": service_uuid,
+                "name": "portal",
+                "application_uuid": app_uuid,
+                "metadata": metadata
+            })
+        };
+
+        Mock::given(method("PUT"))
+            .and(path(format!("/services/{service_uuid}").as_str()))
+            .and(body_json(serde_json::json!({
+                "action": "update",
+                "metadata": { "LOG_LEVEL": "debug" }
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(service(
+                serde_json::json!({ "LOG_LEVEL": "debug", "OLD_FLAG": true }),
+            )))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path(format!("/services/{service_uuid}").as_str()))
+            .and(body_json(serde_json::json!({
+                "action": "delete",
+                "metadata": { "OLD_FLAG": null }
+            })))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(service(serde_json::json!({ "LOG_LEVEL": "debug" }))),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = SapiClient::from_config(&test_config(&server.uri())).unwrap();
+        let change = MetadataChange::new()
+            .with_set("LOG_LEVEL", "debug")
+            .with_remove("OLD_FLAG");
+        let updated = client
+            .update_service_metadata(service_uuid, &change)
+            .await
+            .unwrap();
+        assert_eq!(updated.metadata.len(), 1);
+        assert_eq!(updated.metadata["LOG_LEVEL"], "debug");
+    }
+
+    #[tokio::test]
+    async fn test_delete_instance_accepts_no_content() {
+        let server = MockServer::start().await;
+        let instance_uuid = InstanceUuid::new_v4();
+        Mock::given(method("DELETE"))
+            .and(path(format!("/instances/{instance_uuid}").as_str()))
+            .and(header("Accept-Version", ACCEPT_VERSION))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = SapiClient::from_config(&test_config(&server.uri())).unwrap();
+        client.delete_instance(instance_uuid).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_watch_instances_reports_added_and_removed() {
+        let server = MockServer::start().await;
+        let service_uuid = ServiceUuid::new_v4();
+        let [first, second, third] = [
+            InstanceUuid::new_v4(),
+            InstanceUuid::new_v4(),
+            InstanceUuid::new_v4(),
+        ];
+        let instance = |uuid: InstanceUuid| {
+            serde_json::json!({
+                "uuid": uuid.to_string(),
+                "service_uuid": service_uuid.to_string()
+            })
+        };
+
+        Mock::given(method("GET"))
+            .and(path("/instances"))
+            .and(query_param("service_uuid", service_uuid.to_string()))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!([instance(first), instance(second)])),
+            )
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/instances"))
+            .and(query_param("service_uuid", service_uuid.to_string()))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!([instance(second), instance(third)])),
+            )
+            .mount(&server)
+            .await;
+
+        let client = SapiClient::from_config(&test_config(&server.uri())).unwrap();
+        let polls: Vec<_> = client
+            .watch_instances(
+                service_uuid,
+                Duration::from_millis(10),
+                ShutdownSignal::new(),
+            )
+            .take(2)
+            .collect()
+            .await;
+
+        let initial = polls[0].as_ref().unwrap();
+        assert_eq!(initial.len(), 2);
+        assert!(initial
+            .iter()
+            .all(|change| change.kind == ChangeKind::Added));
+
+        let changes = polls[1].as_ref().unwrap();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].kind, ChangeKind::Added);
+        assert_eq!(changes[0].instance.uuid, third);
+        assert_eq!(changes[1].kind, ChangeKind::Removed);
+        assert_eq!(changes[1].instance.uuid, first);
+    }
+
+    #[tokio::test]
+    async fn watch_instances_stops_when_drained() {
+        let server = MockServer::start().await;
+        let service_uuid = ServiceUuid::new_v4();
+        Mock::given(method("GET"))
+            .and(path("/instances"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([])))
+            .mount(&server)
+            .await;
+
+        let client = SapiClient::from_config(&test_config(&server.uri())).unwrap();
+        let (polled, mut first_poll) = tokio::sync::mpsc::channel(1);
+        let shutdown = Shutdown::spawn(move |signal| async move {
+            let watch = client.watch_instances(service_uuid, Duration::from_secs(3600), signal);
+            futures::pin_mut!(watch);
+            while let Some(changes) = watch.next().await {
+                let _ = polled.send(changes.is_ok()).await;
+            }
+        });
+
+        assert_eq!(first_poll.recv().await, Some(true));
+        tokio::time::timeout(Duration::from_secs(1), shutdown.drain())
+            .await
+            .expect("watch did not stop")
+            .unwrap();
+    }
+
     #[tokio::test]
     async fn test_list_instances_with_service_uuid() {
         let server = MockServer::start().await;
@@ -753,7 +1355,7 @@ mod tests {
 
         Mock::given(method("GET"))
             .and(path("/instances"))
-            .and(query_param("service_uuid", &service_uuid.to_string()))
+            .and(query_param("service_uuid", service_uuid.to_string()))
             .respond_with(ResponseTemplate::new(200).set_body_json(response_body))
             .mount(&server)
             .await;
@@ -805,7 +1407,7 @@ mod tests {
 
         Mock::given(method("GET"))
             .and(path("/instances"))
-            .and(query_param("service_uuid", &service_uuid.to_string()))
+            .and(query_param("service_uuid", service_uuid.to_string()))
             .and(query_param("include_master", "true"))
             .respond_with(ResponseTemplate::new(200).set_body_json(instance_response))
             .expect(1)
@@ -859,7 +1461,7 @@ mod tests {
 
         Mock::given(method("GET"))
             .and(path("/instances"))
-            .and(query_param("service_uuid", &service_uuid.to_string()))
+            .and(query_param("service_uuid", service_uuid.to_string()))
             .and(query_param("include_master", "true"))
             .respond_with(ResponseTemplate::new(200).set_body_json(instance_response))
             .expect(1)
@@ -880,6 +1482,76 @@ mod tests {
         assert_eq!(status.cache_hits, 1);
     }
 
+    #[tokio::test]
+    async fn test_discover_all_services_runs_concurrently() {
+        let server = MockServer::start().await;
+        let latency = Duration::from_millis(200);
+
+        for service in TritonService::all() {
+            let service_uuid = ServiceUuid::new_v4();
+            Mock::given(method("GET"))
+                .and(path("/services"))
+                .and(query_param("name", service.name()))
+                .respond_with(ResponseTemplate::new(200).set_delay(latency).set_body_json(
+                    serde_json::json!([{
+                        "uuid": service_uuid,
+                        "name": service.name(),
+                        "application_uuid": AppUuid::new_v4()
+                    }]),
+                ))
+                .expect(1)
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/instances"))
+                .and(query_param(
+                    "service_uuid",
+                    service_uuid.to_string().as_str(),
+                ))
+                .respond_with(ResponseTemplate::new(200).set_delay(latency).set_body_json(
+                    serde_json::json!([{
+                        "uuid": InstanceUuid::new_v4(),
+                        "service_uuid": service_uuid,
+                        "hostname": format!("{}.local", service.name())
+                    }]),
+                ))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let client = SapiClient::from_config(&test_config(&server.uri())).unwrap();
+        let discovery = client.discovery();
+
+        let started = Instant::now();
+        let endpoints = discovery.discover_all_services().await.unwrap();
+        let elapsed = started.elapsed();
+
+        assert_eq!(endpoints.len(), TritonService::all().len());
+        assert_eq!(endpoints[0], "http://vmapi.local:80");
+        // Each service needs two sequential requests; run one after another the
+        // ten services would take at least 20 * latency.
+        assert!(
+            elapsed < latency * 5,
+            "discovery took {elapsed:?}, expected about two round-trips"
+        );
+        assert!(discovery.get_status().failed_services.is_empty());
+    }
+
+    #[tokio::test]
+    async fn spawn_refresh_drains_promptly() {
+        let server = MockServer::start().await;
+        let config = test_config(&server.uri());
+        let client = SapiClient::from_config(&config).unwrap();
+        let discovery = Arc::new(client.discovery());
+
+        let shutdown = discovery.spawn_refresh(Duration::from_secs(3600));
+        tokio::time::timeout(Duration::from_secs(1), shutdown.drain())
+            .await
+            .expect("refresh task did not stop")
+            .unwrap();
+    }
+
     #[tokio::test]
     async fn test_sapi_discovery_fallback() {
         let config = TritonClientConfig::new("http://localhost:1234")
@@ -899,4 +1571,49 @@ mod tests {
         let endpoints = discovery.discover_service("vmapi").await.unwrap();
         assert_eq!(endpoints, vec!["http://fallback:80".to_string()]);
     }
+
+    #[tokio::test]
+    async fn test_sapi_discovery_returns_every_fallback() {
+        let fallbacks = ["http://vmapi0:80", "http://vmapi1:80", "http://vmapi2:80"];
+        let config = TritonClientConfig::new("http://localhost:1234")
+            .unwrap()
+            .with_service_discovery(ServiceDiscoveryConfig {
+                enabled: false,
+                cache_ttl_secs: 1,
+                timeout_secs: 1,
+                retry_attempts: 0,
+                services: ServiceEndpoints::new().with_endpoints(
+                    TritonService::Vmapi,
+                    fallbacks.map(|url| ServiceEndpointConfig::new(url).unwrap()),
+                ),
+            });
+
+        let client = SapiClient::from_config(&config).unwrap();
+        let endpoints = client.discovery().discover_service("vmapi").await.unwrap();
+        assert_eq!(endpoints, fallbacks.map(String::from).to_vec());
+    }
+
+    #[tokio::test]
+    async fn test_sapi_discovery_ufds_fallback() {
+        let config = TritonClientConfig::new("http://localhost:1234")
+            .unwrap()
+            .with_service_discovery(ServiceDiscoveryConfig {
+                enabled: false,
+                cache_ttl_secs: 1,
+                timeout_secs: 1,
+                retry_attempts: 0,
+                services: ServiceEndpoints::new()
+                    .with_ufds(ServiceEndpointConfig::new("ldaps://ufds.example.com:636").unwrap())
+                    .with_workflow(ServiceEndpointConfig::new("http://workflow:80").unwrap()),
+            });
+
+        let client = SapiClient::from_config(&config).unwrap();
+        let discovery = client.discovery();
+
+        let endpoints = discovery.discover_service("ufds").await.unwrap();
+        assert_eq!(endpoints, vec!["ldaps://ufds.example.com:636".to_string()]);
+        let endpoints = discovery.discover_service("workflow").await.unwrap();
+        assert_eq!(endpoints, vec!["http://workflow:80".to_string()]);
+        assert!(discovery.discover_service("amon").await.is_err());
+    }
 }
@@ -9,7 +9,10 @@ pub mod client;
 pub mod models;
 
 pub use client::{InstanceQuery, SapiClient, SapiClientBuilder, SapiDiscovery, ServiceQuery};
-pub use models::{Application, Instance, InstanceType, Service};
+pub use models::{
+    Application, CreateInstanceRequest, CreateServiceRequest, Instance, InstanceChange,
+    InstanceType, MetadataChange, Service, UpdateAction, UpdateRequest,
+};
 
 /// Convenient result alias that reuses the shared Triton error type.
 pub type Result<T> = triton_core::Result<T>;
@@ -1,7 +1,8 @@
 //! Core SAPI domain models.
 
 use serde::{Deserialize, Deserializer, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
+use triton_core::diff::ChangeKind;
 use triton_core::uuid::{AppUuid, InstanceUuid, JobUuid, OwnerUuid, ServiceUuid};
 
 /// Represents a SAPI application definition.
@@ -45,10 +46,11 @@ pub struct Application {
 }
 
 /// Enumeration of known instance types.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum InstanceType {
     /// VM instance that runs on a CN.
+    #[default]
     Vm,
     /// Non-VM agent instance.
     Agent,
@@ -57,12 +59,6 @@ pub enum InstanceType {
     Other,
 }
 
-impl Default for InstanceType {
-    fn default() -> Self {
-        Self::Vm
-    }
-}
-
 impl InstanceType {
     /// Returns the instance type as a lowercase string.
     #[must_use]
@@ -177,6 +173,163 @@ pub struct Instance {
     pub updated_at: Option<String>,
 }
 
+/// Request payload to create a SAPI service.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CreateServiceRequest {
+    /// Service name.
+    pub name: String,
+    /// Application the service belongs to.
+    pub application_uuid: AppUuid,
+    /// Service parameters (image, package, networks, ...).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, serde_json::Value>,
+    /// Service metadata.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, serde_json::Value>,
+    /// Manifest UUIDs keyed by manifest name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub manifests: BTreeMap<String, serde_json::Value>,
+    /// Service type; SAPI defaults to `vm`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<InstanceType>,
+}
+
+/// Request payload to create a SAPI instance.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CreateInstanceRequest {
+    /// Service to instantiate.
+    pub service_uuid: ServiceUuid,
+    /// UUID to give the instance; SAPI generates one when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<InstanceUuid>,
+    /// Instance parameters, layered over the service's.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, serde_json::Value>,
+    /// Instance metadata, layered over the service's.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, serde_json::Value>,
+    /// Instance type; SAPI defaults to the service's type.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<InstanceType>,
+}
+
+/// How an [`UpdateRequest`] combines with the stored maps.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateAction {
+    /// Merge the given keys into the stored maps.
+    #[default]
+    Update,
+    /// Replace the stored maps with the given ones.
+    Replace,
+    /// Remove the given keys from the stored maps.
+    Delete,
+}
+
+/// Request payload to update a SAPI service or instance.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct UpdateRequest {
+    /// How the maps below are applied.
+    pub action: UpdateAction,
+    /// Parameters to change.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, serde_json::Value>,
+    /// Metadata to change.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, serde_json::Value>,
+    /// Manifests to change.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub manifests: BTreeMap<String, serde_json::Value>,
+}
+
+impl UpdateRequest {
+    /// Merge `metadata` into the stored metadata.
+    #[must_use]
+    pub fn merge_metadata(metadata: BTreeMap<String, serde_json::Value>) -> Self {
+        Self {
+            metadata,
+            ..Self::default()
+        }
+    }
+}
+
+/// Keys to set and remove in a SAPI metadata map, applied without
+/// rewriting the keys it does not mention.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetadataChange {
+    /// Keys to add or overwrite.
+    pub set: HashMap<String, serde_json::Value>,
+    /// Keys to remove.
+    pub remove: Vec<String>,
+}
+
+impl MetadataChange {
+    /// Create an empty change.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add or overwrite `key`.
+    #[must_use]
+    pub fn with_set(mut self, key: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.set.insert(key.into(), value.into());
+        self
+    }
+
+    /// Remove `key`.
+    #[must_use]
+    pub fn with_remove(mut self, key: impl Into<String>) -> Self {
+        self.remove.push(key.into());
+        self
+    }
+
+    /// Returns true when the change sets and removes nothing.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty() && self.remove.is_empty()
+    }
+
+    /// The SAPI updates that apply this change: an `update` for the keys to
+    /// set, then a `delete` for the keys to remove. Empty halves are skipped.
+    #[must_use]
+    pub fn to_requests(&self) -> Vec<UpdateRequest> {
+        let mut requests = Vec::new();
+        if !self.set.is_empty() {
+            requests.push(UpdateRequest {
+                action: UpdateAction::Update,
+                metadata: self
+                    .set
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect(),
+                ..UpdateRequest::default()
+            });
+        }
+        if !self.remove.is_empty() {
+            requests.push(UpdateRequest {
+                action: UpdateAction::Delete,
+                metadata: self
+                    .remove
+                    .iter()
+                    .map(|key| (key.clone(), serde_json::Value::Null))
+                    .collect(),
+                ..UpdateRequest::default()
+            });
+        }
+        requests
+    }
+}
+
+/// A change to a service's instances observed between two polls.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstanceChange {
+    /// Whether the instance was added, removed, or modified.
+    pub kind: ChangeKind,
+    /// Current instance, or the last seen instance when removed.
+    pub instance: Instance,
+}
+
 fn deserialize_map<'de, D>(deserializer: D) -> Result<BTreeMap<String, serde_json::Value>, D::Error>
 where
     D: Deserializer<'de>,
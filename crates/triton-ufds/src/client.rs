@@ -2,16 +2,17 @@
 
 use crate::{
     config::UfdsConfig,
-    dn::DistinguishedName,
+    dn::{DistinguishedName, RelativeDistinguishedName},
     group::Group,
-    user::{AccountStatus, User, UserFlags},
+    pool::{PooledSession, SessionPool},
+    ssh_key::{ssh_key_fingerprint, SshKey},
+    user::{AccountStatus, CreateUserRequest, User, UserFlags},
     Result,
 };
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use ldap3::{
-    result::Result as LdapResult, LdapConnAsync, LdapConnSettings, Mod, Scope, SearchEntry,
-};
+use ldap3::controls::{Control, ControlType, PagedResults};
+use ldap3::{LdapConnAsync, LdapConnSettings, Mod, ResultEntry, Scope, SearchEntry};
 use native_tls::{Certificate, TlsConnector};
 use std::collections::{HashMap, HashSet};
 use std::fs;
@@ -19,7 +20,7 @@ use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::timeout;
 use tracing::warn;
-use triton_core::error::Error;
+use triton_core::error::{Error, TimeoutKind};
 use triton_core::uuid::OwnerUuid;
 
 const USER_ATTRIBUTES: &[&str] = &[
@@ -44,6 +45,11 @@ const USER_ATTRIBUTES: &[&str] = &[
 
 const GROUP_ATTRIBUTES: &[&str] = &["cn", "description", "member"];
 
+const SSH_KEY_ATTRIBUTES: &[&str] = &["fingerprint", "name", "openssh"];
+
+/// Page size used when listing groups, kept below the usual server size limits.
+const GROUP_PAGE_SIZE: u32 = 500;
+
 /// Represents the search scope for LDAP queries.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SearchScope {
@@ -137,7 +143,22 @@ pub(crate) trait LdapSession: Send {
         filter: &str,
         attributes: &[&'static str],
     ) -> Result<Vec<LdapEntry>>;
+    /// Fetches one page of a search using the RFC 2696 simple paged results control.
+    ///
+    /// Returns the page's entries and the server cookie for the next page, which is empty
+    /// once the result set is exhausted.
+    async fn search_page(
+        &mut self,
+        base_dn: &str,
+        scope: SearchScope,
+        filter: &str,
+        attributes: &[&'static str],
+        page_size: i32,
+        cookie: Vec<u8>,
+    ) -> Result<(Vec<LdapEntry>, Vec<u8>)>;
+    async fn add(&mut self, dn: &str, attributes: &[(String, Vec<String>)]) -> Result<()>;
     async fn modify(&mut self, dn: &str, modifications: &[DirectoryModification]) -> Result<()>;
+    async fn delete(&mut self, dn: &str) -> Result<()>;
     async fn unbind(&mut self) -> Result<()>;
 }
 
@@ -151,6 +172,7 @@ pub(crate) trait LdapConnector: Send + Sync {
 pub struct UfdsClient {
     config: Arc<UfdsConfig>,
     connector: Box<dyn LdapConnector>,
+    pool: SessionPool,
 }
 
 impl UfdsClient {
@@ -159,13 +181,19 @@ impl UfdsClient {
     pub fn new(config: UfdsConfig) -> Self {
         let config = Arc::new(config);
         let connector: Box<dyn LdapConnector> = Box::new(RealLdapConnector::new(config.clone()));
-        Self { config, connector }
+        let pool = SessionPool::new(config.pool_size());
+        Self {
+            config,
+            connector,
+            pool,
+        }
     }
 
     #[cfg(test)]
     #[must_use]
     pub(crate) fn with_connector(config: UfdsConfig, connector: Box<dyn LdapConnector>) -> Self {
         Self {
+            pool: SessionPool::new(config.pool_size()),
             config: Arc::new(config),
             connector,
         }
@@ -178,22 +206,44 @@ impl UfdsClient {
     /// Returns [`Error::NotFound`] if the user does not exist or [`Error::InvalidRequest`]
     /// when credentials are invalid.
     pub async fn authenticate(&self, login: &str, password: &str) -> Result<User> {
-        let mut admin_session = self.admin_session().await?;
-        let user_entry = self.lookup_user(&mut *admin_session, login).await?;
-        admin_session.unbind().await?;
+        let user_entry = self.verify_credentials(login, password).await?;
+        parse_user_entry(&user_entry, self.config.admin_group_dns())
+    }
 
-        // Verify user credentials by binding as the user.
-        let mut user_session = self.connector.connect().await?;
-        self.execute_with_timeout(async {
-            user_session
-                .simple_bind(user_entry.dn.as_str(), password)
-                .await
-        })
-        .await
-        .map_err(|_| Error::InvalidRequest("invalid credentials".to_string()))?;
-        user_session.unbind().await?;
+    /// Changes a user's password after verifying their current one.
+    ///
+    /// The old password is checked by binding as the user, exactly as [`Self::authenticate`]
+    /// does; the new one is then written with an admin `modify` replacing `userpassword`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ValidationError`] for an empty new password, [`Error::NotFound`] if the
+    /// user does not exist, and [`Error::InvalidRequest`] when the old password is wrong.
+    pub async fn change_password(
+        &self,
+        login: &str,
+        old_password: &str,
+        new_password: &str,
+    ) -> Result<()> {
+        if new_password.is_empty() {
+            return Err(Error::ValidationError(
+                "new password must not be empty".to_string(),
+            ));
+        }
+
+        let user_entry = self.verify_credentials(login, old_password).await?;
 
-        parse_user_entry(&user_entry)
+        let mut session = self.admin_session().await?;
+        self.execute_with_timeout(session.modify(
+            user_entry.dn.as_str(),
+            &[DirectoryModification::Replace {
+                attribute: "userpassword".to_string(),
+                values: vec![new_password.to_string()],
+            }],
+        ))
+        .await?;
+        session.release();
+        Ok(())
     }
 
     /// Fetches a user entry without performing authentication.
@@ -204,22 +254,21 @@ impl UfdsClient {
     pub async fn fetch_user(&self, login: &str) -> Result<User> {
         let mut admin_session = self.admin_session().await?;
         let entry = self.lookup_user(&mut *admin_session, login).await?;
-        admin_session.unbind().await?;
-        parse_user_entry(&entry)
+        admin_session.release();
+        parse_user_entry(&entry, self.config.admin_group_dns())
     }
 
     /// Lists groups within the configured group search base.
     pub async fn list_groups(&self) -> Result<Vec<Group>> {
-        let mut session = self.admin_session().await?;
         let entries = self
-            .execute_with_timeout(session.search(
+            .paged_search(
                 self.config.group_base_dn().as_str(),
                 SearchScope::Subtree,
                 "(|(objectClass=groupOfNames)(objectClass=groupOfUniqueNames))",
                 GROUP_ATTRIBUTES,
-            ))
+                GROUP_PAGE_SIZE,
+            )
             .await?;
-        session.unbind().await?;
 
         entries
             .into_iter()
@@ -227,6 +276,37 @@ impl UfdsClient {
             .collect()
     }
 
+    /// Checks whether `user_dn` is a direct member of the group at `group_dn`.
+    ///
+    /// Performs a base-scope search on the group filtered by `member`, so the directory answers
+    /// authoritatively without listing every group. Returned member values are compared to
+    /// `user_dn` using normalized DN matching.
+    pub async fn is_member(
+        &self,
+        user_dn: &DistinguishedName,
+        group_dn: &DistinguishedName,
+    ) -> Result<bool> {
+        let filter = format!("(member={})", escape_filter_value(user_dn.as_str()));
+        let mut session = self.admin_session().await?;
+        let entries = self
+            .execute_with_timeout(session.search(
+                group_dn.as_str(),
+                SearchScope::Base,
+                &filter,
+                &["member"],
+            ))
+            .await?;
+        session.release();
+
+        Ok(entries.iter().any(|entry| {
+            entry.values("member").is_some_and(|members| {
+                members.iter().any(|member| {
+                    DistinguishedName::parse(member).is_ok_and(|dn| dn.is_equivalent(user_dn))
+                })
+            })
+        }))
+    }
+
     /// Adds a user to the specified group.
     pub async fn add_user_to_group(
         &self,
@@ -243,7 +323,7 @@ impl UfdsClient {
                 }],
             )
             .await?;
-        session.unbind().await?;
+        session.release();
         Ok(())
     }
 
@@ -263,18 +343,248 @@ impl UfdsClient {
                 }],
             )
             .await?;
-        session.unbind().await?;
+        session.release();
         Ok(())
     }
 
-    async fn admin_session(&self) -> Result<Box<dyn LdapSession>> {
-        let mut session = self.connector.connect().await?;
-        self.execute_with_timeout(session.simple_bind(
-            self.config.credentials().bind_dn(),
-            self.config.credentials().bind_password(),
-        ))
-        .await?;
-        Ok(session)
+    /// Creates a user entry at `uuid=<uuid>` under the user search base.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ValidationError`] before contacting UFDS when a required attribute is
+    /// missing or malformed, and [`Error::Conflict`] when the entry already exists.
+    pub async fn create_user(&self, request: CreateUserRequest) -> Result<User> {
+        request.validate()?;
+        let dn = self
+            .config
+            .user_base_dn()
+            .clone()
+            .with_prefix(RelativeDistinguishedName::new(
+                "uuid",
+                request.uuid.to_string(),
+            ));
+        let attributes = request.to_attributes();
+
+        let mut session = self.admin_session().await?;
+        self.execute_with_timeout(session.add(dn.as_str(), &attributes))
+            .await?;
+        session.release();
+
+        let entry = LdapEntry {
+            dn: dn.as_str().to_string(),
+            attributes: attributes.into_iter().collect(),
+        };
+        parse_user_entry(&entry, self.config.admin_group_dns())
+    }
+
+    /// Applies `modifications` to the user entry at `dn`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ValidationError`] when `modifications` is empty or would remove the
+    /// `uuid` or `login` attribute, and [`Error::NotFound`] when the entry does not exist.
+    pub async fn update_user(
+        &self,
+        dn: &DistinguishedName,
+        modifications: &[DirectoryModification],
+    ) -> Result<()> {
+        if modifications.is_empty() {
+            return Err(Error::ValidationError(
+                "no modifications supplied for UFDS user".to_string(),
+            ));
+        }
+        for modification in modifications {
+            if let DirectoryModification::Delete { attribute, .. } = modification {
+                if ["uuid", "login"]
+                    .iter()
+                    .any(|required| attribute.eq_ignore_ascii_case(required))
+                {
+                    return Err(Error::ValidationError(format!(
+                        "cannot delete required user attribute `{attribute}`"
+                    )));
+                }
+            }
+        }
+
+        let mut session = self.admin_session().await?;
+        self.execute_with_timeout(session.modify(dn.as_str(), modifications))
+            .await?;
+        session.release();
+        Ok(())
+    }
+
+    /// Deletes the user entry at `dn`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NotFound`] when the entry does not exist.
+    pub async fn delete_user(&self, dn: &DistinguishedName) -> Result<()> {
+        let mut session = self.admin_session().await?;
+        self.execute_with_timeout(session.delete(dn.as_str()))
+            .await?;
+        session.release();
+        Ok(())
+    }
+
+    /// Runs a directory search with a caller-supplied filter and returns the raw entries.
+    ///
+    /// `filter` is sent as-is, so any values interpolated into it must first go through
+    /// [`escape_filter_value`]. The filter must be a single parenthesized expression with
+    /// balanced parentheses. The operation timeout applies as for every other query.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ValidationError`] for an empty or unbalanced filter.
+    pub async fn search(
+        &self,
+        base_dn: &DistinguishedName,
+        scope: SearchScope,
+        filter: &str,
+        attributes: &[&'static str],
+    ) -> Result<Vec<LdapEntry>> {
+        validate_filter(filter)?;
+        let mut session = self.admin_session().await?;
+        let entries = self
+            .execute_with_timeout(session.search(base_dn.as_str(), scope, filter, attributes))
+            .await?;
+        session.release();
+        Ok(entries)
+    }
+
+    /// Runs a directory search in pages of `page_size` entries and returns every entry.
+    ///
+    /// Uses the RFC 2696 simple paged results control, following the server cookie until the
+    /// result set is exhausted, so large trees are not truncated by server size limits. The
+    /// filter rules of [`UfdsClient::search`] apply, and the operation timeout covers each page.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ValidationError`] for an invalid filter or a zero `page_size`.
+    pub async fn search_paged(
+        &self,
+        base_dn: &DistinguishedName,
+        scope: SearchScope,
+        filter: &str,
+        attributes: &[&'static str],
+        page_size: u32,
+    ) -> Result<Vec<LdapEntry>> {
+        validate_filter(filter)?;
+        self.paged_search(base_dn.as_str(), scope, filter, attributes, page_size)
+            .await
+    }
+
+    /// Lists the SSH keys (`sdcKey` entries) stored directly beneath `user_dn`.
+    pub async fn list_ssh_keys(&self, user_dn: &DistinguishedName) -> Result<Vec<SshKey>> {
+        let mut session = self.admin_session().await?;
+        let entries = self
+            .execute_with_timeout(session.search(
+                user_dn.as_str(),
+                SearchScope::OneLevel,
+                "(objectclass=sdckey)",
+                SSH_KEY_ATTRIBUTES,
+            ))
+            .await?;
+        session.release();
+
+        entries
+            .into_iter()
+            .map(|entry| parse_ssh_key_entry(&entry))
+            .collect()
+    }
+
+    /// Adds an OpenSSH public key beneath `user_dn`, keyed by its MD5 fingerprint.
+    ///
+    /// An empty `name` defaults to the fingerprint.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ValidationError`] before contacting UFDS when `openssh_key` is not a
+    /// public key, and [`Error::Conflict`] when the user already has the key.
+    pub async fn add_ssh_key(
+        &self,
+        user_dn: &DistinguishedName,
+        name: &str,
+        openssh_key: &str,
+    ) -> Result<SshKey> {
+        let fingerprint = ssh_key_fingerprint(openssh_key)?;
+        let key = SshKey {
+            dn: SshKey::key_dn(user_dn, &fingerprint),
+            name: if name.is_empty() {
+                fingerprint.clone()
+            } else {
+                name.to_string()
+            },
+            fingerprint,
+            openssh: openssh_key.trim().to_string(),
+        };
+        let attributes = [
+            ("objectclass".to_string(), vec!["sdckey".to_string()]),
+            ("fingerprint".to_string(), vec![key.fingerprint.clone()]),
+            ("name".to_string(), vec![key.name.clone()]),
+            ("openssh".to_string(), vec![key.openssh.clone()]),
+        ];
+
+        let mut session = self.admin_session().await?;
+        self.execute_with_timeout(session.add(key.dn.as_str(), &attributes))
+            .await?;
+        session.release();
+        Ok(key)
+    }
+
+    /// Deletes the SSH key with `fingerprint` beneath `user_dn`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NotFound`] when the user has no such key.
+    pub async fn delete_ssh_key(
+        &self,
+        user_dn: &DistinguishedName,
+        fingerprint: &str,
+    ) -> Result<()> {
+        let dn = SshKey::key_dn(user_dn, fingerprint);
+        let mut session = self.admin_session().await?;
+        self.execute_with_timeout(session.delete(dn.as_str()))
+            .await?;
+        session.release();
+        Ok(())
+    }
+
+    /// Looks up `login` and binds as that user, returning the entry when `password` is correct.
+    async fn verify_credentials(&self, login: &str, password: &str) -> Result<LdapEntry> {
+        let mut admin_session = self.admin_session().await?;
+        let user_entry = self.lookup_user(&mut *admin_session, login).await?;
+        admin_session.release();
+
+        // Verify user credentials by binding as the user.
+        let mut user_session = self.connector.connect().await?;
+        self.execute_with_timeout(async {
+            user_session
+                .simple_bind(user_entry.dn.as_str(), password)
+                .await
+        })
+        .await
+        .map_err(|_| Error::InvalidRequest("invalid credentials".to_string()))?;
+        user_session.unbind().await?;
+
+        Ok(user_entry)
+    }
+
+    /// Checks out a bound admin session from the pool, connecting and binding a new one when
+    /// none is idle.
+    ///
+    /// Release the session after a successful operation; dropping it discards the connection.
+    async fn admin_session(&self) -> Result<PooledSession<'_>> {
+        self.pool
+            .checkout(self.config.connection_timeout(), move || async move {
+                let mut session = self.connector.connect().await?;
+                self.execute_with_timeout(session.simple_bind(
+                    self.config.credentials().bind_dn(),
+                    self.config.credentials().bind_password(),
+                ))
+                .await?;
+                Ok(session)
+            })
+            .await
     }
 
     async fn lookup_user(&self, session: &mut dyn LdapSession, login: &str) -> Result<LdapEntry> {
@@ -301,13 +611,47 @@ impl UfdsClient {
             .replace("{login}", &escaped)
     }
 
+    async fn paged_search(
+        &self,
+        base_dn: &str,
+        scope: SearchScope,
+        filter: &str,
+        attributes: &[&'static str],
+        page_size: u32,
+    ) -> Result<Vec<LdapEntry>> {
+        if page_size == 0 {
+            return Err(Error::ValidationError(
+                "page size must be greater than zero".to_string(),
+            ));
+        }
+        let page_size = i32::try_from(page_size).unwrap_or(i32::MAX);
+
+        let mut session = self.admin_session().await?;
+        let mut entries = Vec::new();
+        let mut cookie = Vec::new();
+        loop {
+            let (page, next) = self
+                .execute_with_timeout(
+                    session.search_page(base_dn, scope, filter, attributes, page_size, cookie),
+                )
+                .await?;
+            entries.extend(page);
+            if next.is_empty() {
+                break;
+            }
+            cookie = next;
+        }
+        session.release();
+        Ok(entries)
+    }
+
     async fn execute_with_timeout<F, T>(&self, fut: F) -> Result<T>
     where
         F: std::future::Future<Output = Result<T>>,
     {
         timeout(self.config.operation_timeout(), fut)
             .await
-            .map_err(|_| Error::Timeout("UFDS operation timed out".to_string()))?
+            .map_err(|_| Error::timeout(TimeoutKind::Deadline, self.config.operation_timeout()))?
     }
 }
 
@@ -349,7 +693,7 @@ impl LdapSession for RealLdapSession {
     async fn simple_bind(&mut self, dn: &str, password: &str) -> Result<()> {
         let result = timeout(self.operation_timeout, self.inner.simple_bind(dn, password))
             .await
-            .map_err(|_| Error::Timeout("UFDS bind timed out".to_string()))?
+            .map_err(|_| Error::timeout(TimeoutKind::Deadline, self.operation_timeout))?
             .map_err(map_ldap_error)?;
         ensure_ldap_success(result)?;
         Ok(())
@@ -368,17 +712,66 @@ impl LdapSession for RealLdapSession {
                 .search(base_dn, scope.into(), filter, attributes.to_vec()),
         )
         .await
-        .map_err(|_| Error::Timeout("UFDS search timed out".to_string()))?
+        .map_err(|_| Error::timeout(TimeoutKind::Deadline, self.operation_timeout))?
         .map_err(map_ldap_error)?;
         let (entries, _) = result.success().map_err(map_ldap_error)?;
-        Ok(entries
+        Ok(into_ldap_entries(entries))
+    }
+
+    async fn search_page(
+        &mut self,
+        base_dn: &str,
+        scope: SearchScope,
+        filter: &str,
+        attributes: &[&'static str],
+        page_size: i32,
+        cookie: Vec<u8>,
+    ) -> Result<(Vec<LdapEntry>, Vec<u8>)> {
+        let control = PagedResults {
+            size: page_size,
+            cookie,
+        };
+        let result = timeout(
+            self.operation_timeout,
+            self.inner.with_controls(control).search(
+                base_dn,
+                scope.into(),
+                filter,
+                attributes.to_vec(),
+            ),
+        )
+        .await
+        .map_err(|_| Error::timeout(TimeoutKind::Deadline, self.operation_timeout))?
+        .map_err(map_ldap_error)?;
+        let (entries, result) = result.success().map_err(map_ldap_error)?;
+
+        let next_cookie = result
+            .ctrls
             .into_iter()
-            .map(SearchEntry::construct)
-            .map(|entry| LdapEntry {
-                dn: entry.dn,
-                attributes: entry.attrs,
+            .find_map(|Control(kind, raw)| {
+                matches!(kind, Some(ControlType::PagedResults))
+                    .then(|| raw.parse::<PagedResults>().cookie)
+            })
+            .unwrap_or_default();
+        Ok((into_ldap_entries(entries), next_cookie))
+    }
+
+    async fn add(&mut self, dn: &str, attributes: &[(String, Vec<String>)]) -> Result<()> {
+        let attrs = attributes
+            .iter()
+            .map(|(attribute, values)| {
+                (
+                    attribute.clone(),
+                    values.iter().cloned().collect::<HashSet<_>>(),
+                )
             })
-            .collect())
+            .collect::<Vec<_>>();
+
+        let result = timeout(self.operation_timeout, self.inner.add(dn, attrs))
+            .await
+            .map_err(|_| Error::timeout(TimeoutKind::Deadline, self.operation_timeout))?
+            .map_err(map_ldap_error)?;
+        ensure_ldap_success(result)
     }
 
     async fn modify(&mut self, dn: &str, modifications: &[DirectoryModification]) -> Result<()> {
@@ -402,16 +795,24 @@ impl LdapSession for RealLdapSession {
 
         let result = timeout(self.operation_timeout, self.inner.modify(dn, mods))
             .await
-            .map_err(|_| Error::Timeout("UFDS modify timed out".to_string()))?
+            .map_err(|_| Error::timeout(TimeoutKind::Deadline, self.operation_timeout))?
             .map_err(map_ldap_error)?;
         ensure_ldap_success(result)?;
         Ok(())
     }
 
+    async fn delete(&mut self, dn: &str) -> Result<()> {
+        let result = timeout(self.operation_timeout, self.inner.delete(dn))
+            .await
+            .map_err(|_| Error::timeout(TimeoutKind::Deadline, self.operation_timeout))?
+            .map_err(map_ldap_error)?;
+        ensure_ldap_success(result)
+    }
+
     async fn unbind(&mut self) -> Result<()> {
         timeout(self.operation_timeout, self.inner.unbind())
             .await
-            .map_err(|_| Error::Timeout("UFDS unbind timed out".to_string()))?
+            .map_err(|_| Error::timeout(TimeoutKind::Deadline, self.operation_timeout))?
             .map_err(map_ldap_error)?;
         Ok(())
     }
@@ -449,13 +850,6 @@ fn build_ldap_settings(config: &UfdsConfig) -> Result<LdapConnSettings> {
     Ok(settings)
 }
 
-fn handle_ldap_result<T>(result: LdapResult<T>) -> Result<T> {
-    result.map_err(|err| Error::ExternalServiceError {
-        service: "ufds".to_string(),
-        message: err.to_string(),
-    })
-}
-
 fn map_ldap_error(err: ldap3::LdapError) -> Error {
     Error::ExternalServiceError {
         service: "ufds".to_string(),
@@ -463,11 +857,38 @@ fn map_ldap_error(err: ldap3::LdapError) -> Error {
     }
 }
 
+fn into_ldap_entries(entries: Vec<ResultEntry>) -> Vec<LdapEntry> {
+    entries
+        .into_iter()
+        .map(SearchEntry::construct)
+        .map(|entry| LdapEntry {
+            dn: entry.dn,
+            attributes: entry.attrs,
+        })
+        .collect()
+}
+
+/// LDAP result code for `noSuchObject`.
+const LDAP_NO_SUCH_OBJECT: u32 = 32;
+/// LDAP result code for `entryAlreadyExists`.
+const LDAP_ENTRY_ALREADY_EXISTS: u32 = 68;
+
 fn ensure_ldap_success(result: ldap3::LdapResult) -> Result<()> {
-    handle_ldap_result::<ldap3::LdapResult>(Ok(result)).map(|_| ())
+    match result.rc {
+        0 => Ok(()),
+        LDAP_NO_SUCH_OBJECT => Err(Error::NotFound(format!(
+            "UFDS entry not found: {}",
+            result.text
+        ))),
+        LDAP_ENTRY_ALREADY_EXISTS => Err(Error::Conflict(format!(
+            "UFDS entry already exists: {}",
+            result.text
+        ))),
+        _ => Err(map_ldap_error(ldap3::LdapError::LdapResult { result })),
+    }
 }
 
-fn parse_user_entry(entry: &LdapEntry) -> Result<User> {
+fn parse_user_entry(entry: &LdapEntry, admin_group_dns: &[DistinguishedName]) -> Result<User> {
     let dn = DistinguishedName::parse(&entry.dn)?;
     let uuid_str = entry
         .first("uuid")
@@ -479,22 +900,31 @@ fn parse_user_entry(entry: &LdapEntry) -> Result<User> {
         .or_else(|| entry.first("uid"))
         .ok_or_else(|| missing_attribute("login"))?;
 
-    let groups = entry
+    let group_dns = entry
         .values("memberof")
         .map(|values| {
             values
                 .iter()
                 .filter_map(|value| DistinguishedName::parse(value).ok())
-                .filter_map(|dn| dn.get("cn").map(str::to_owned))
-                .collect::<Vec<String>>()
+                .collect::<Vec<_>>()
         })
         .unwrap_or_default();
+    let groups = group_dns
+        .iter()
+        .filter_map(|dn| dn.get("cn").map(str::to_owned))
+        .collect::<Vec<String>>();
 
     let mut status = AccountStatus::new()
         .with_locked(entry.first("pwdAccountLockedTime").is_some())
         .with_password_expired(entry.bool_value("password_expired"));
 
-    let is_admin = groups.iter().any(|group| matches_admin_group(group));
+    let is_admin = if admin_group_dns.is_empty() {
+        groups.iter().any(|group| matches_admin_group(group))
+    } else {
+        group_dns
+            .iter()
+            .any(|dn| admin_group_dns.iter().any(|admin| admin.is_equivalent(dn)))
+    };
     status = status.with_admin(is_admin);
 
     let flags = UserFlags::default()
@@ -531,7 +961,11 @@ fn parse_user_entry(entry: &LdapEntry) -> Result<User> {
         builder = builder.updated_at(updated);
     }
 
-    builder = builder.status(status).flags(flags).groups(groups);
+    builder = builder
+        .status(status)
+        .flags(flags)
+        .groups(groups)
+        .group_dns(group_dns);
 
     Ok(builder.build())
 }
@@ -565,6 +999,22 @@ fn parse_group_entry(entry: &LdapEntry) -> Result<Group> {
     Ok(builder.build())
 }
 
+fn parse_ssh_key_entry(entry: &LdapEntry) -> Result<SshKey> {
+    let fingerprint = entry
+        .first("fingerprint")
+        .ok_or_else(|| missing_attribute("fingerprint"))?
+        .to_string();
+    Ok(SshKey {
+        dn: DistinguishedName::parse(&entry.dn)?,
+        name: entry.first("name").unwrap_or(&fingerprint).to_string(),
+        openssh: entry
+            .first("openssh")
+            .ok_or_else(|| missing_attribute("openssh"))?
+            .to_string(),
+        fingerprint,
+    })
+}
+
 fn parse_timestamp(value: Option<&str>) -> Option<DateTime<Utc>> {
     value
         .and_then(|val| DateTime::parse_from_rfc3339(val).ok())
@@ -579,7 +1029,34 @@ fn missing_attribute(attribute: &str) -> Error {
     Error::InvalidRequest(format!("UFDS entry missing attribute `{attribute}`"))
 }
 
-fn escape_filter_value(value: &str) -> String {
+fn validate_filter(filter: &str) -> Result<()> {
+    let invalid = || Error::ValidationError(format!("invalid LDAP filter `{filter}`"));
+    let filter = filter.trim();
+    if !filter.starts_with('(') || !filter.ends_with(')') {
+        return Err(invalid());
+    }
+    let mut depth = 0usize;
+    for (index, ch) in filter.char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' => depth = depth.checked_sub(1).ok_or_else(invalid)?,
+            _ => {}
+        }
+        // The outermost parentheses must wrap the whole filter.
+        if depth == 0 && index + 1 < filter.len() {
+            return Err(invalid());
+        }
+    }
+    if depth == 0 {
+        Ok(())
+    } else {
+        Err(invalid())
+    }
+}
+
+/// Escapes a value for interpolation into an LDAP search filter (RFC 4515).
+#[must_use]
+pub fn escape_filter_value(value: &str) -> String {
     value
         .chars()
         .flat_map(|ch| match ch {
@@ -657,6 +1134,91 @@ mod tests {
         assert!(user.is_admin());
     }
 
+    /// Connector handing out an admin lookup session and a user bind session that accepts only
+    /// `password`. When `new_password` is given, the pooled admin session is reused for the
+    /// `modify` that writes it.
+    fn password_change_connector(
+        password: &'static str,
+        new_password: Option<&'static str>,
+    ) -> MockLdapConnector {
+        let mut connector = MockLdapConnector::new();
+        let mut sequence = mockall::Sequence::new();
+
+        let mut admin_session = MockLdapSession::new();
+        admin_session
+            .expect_simple_bind()
+            .withf(|dn, _| dn == "cn=admin,dc=example,dc=com")
+            .times(1)
+            .returning(|_, _| Ok(()));
+        admin_session
+            .expect_search()
+            .returning(|_, _, _, _| Ok(vec![sample_entry()]));
+        if let Some(new_password) = new_password {
+            admin_session
+                .expect_modify()
+                .withf(move |dn, mods| {
+                    dn == "uid=jdoe,dc=example,dc=com"
+                        && mods
+                            == [DirectoryModification::Replace {
+                                attribute: "userpassword".to_string(),
+                                values: vec![new_password.to_string()],
+                            }]
+                })
+                .times(1)
+                .returning(|_, _| Ok(()));
+        }
+
+        let mut user_session = MockLdapSession::new();
+        user_session
+            .expect_simple_bind()
+            .withf(|dn, _| dn == "uid=jdoe,dc=example,dc=com")
+            .returning(move |_, given| {
+                if given == password {
+                    Ok(())
+                } else {
+                    Err(Error::ExternalServiceError {
+                        service: "ufds".to_string(),
+                        message: "invalidCredentials".to_string(),
+                    })
+                }
+            });
+        user_session.expect_unbind().returning(|| Ok(()));
+
+        connector
+            .expect_connect()
+            .times(1)
+            .in_sequence(&mut sequence)
+            .return_once(move || Ok(Box::new(admin_session)));
+        connector
+            .expect_connect()
+            .times(1)
+            .in_sequence(&mut sequence)
+            .return_once(move || Ok(Box::new(user_session)));
+        connector
+    }
+
+    #[tokio::test]
+    async fn change_password_replaces_user_password() {
+        let connector = password_change_connector("old-secret", Some("n3w-secret"));
+        let client = UfdsClient::with_connector(sample_config(), Box::new(connector));
+        client
+            .change_password("jdoe", "old-secret", "n3w-secret")
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn change_password_rejects_wrong_old_password() {
+        let connector = password_change_connector("old-secret", None);
+        let client = UfdsClient::with_connector(sample_config(), Box::new(connector));
+
+        let err = client
+            .change_password("jdoe", "guess", "n3w-secret")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidRequest(_)));
+    }
+
     #[tokio::test]
     async fn authenticate_unknown_user() {
         let mut connector = MockLdapConnector::new();
@@ -675,4 +1237,517 @@ mod tests {
         let result = client.authenticate("unknown", "password").await;
         assert!(matches!(result, Err(Error::NotFound(_))));
     }
+
+    fn entry_with_groups(memberof: &[&str]) -> LdapEntry {
+        let mut entry = sample_entry();
+        entry.attributes.insert(
+            "memberof".to_string(),
+            memberof.iter().map(|dn| (*dn).to_string()).collect(),
+        );
+        entry
+    }
+
+    #[test]
+    fn parse_user_keeps_same_cn_groups_distinct() {
+        let entry = entry_with_groups(&[
+            "cn=operators,ou=groups,o=smartdc",
+            "cn=operators,ou=groups,ou=tenant-a,o=smartdc",
+        ]);
+
+        let user = parse_user_entry(&entry, &[]).unwrap();
+        assert_eq!(user.groups, vec!["operators", "operators"]);
+        assert_eq!(user.group_dns.len(), 2);
+        assert_ne!(user.group_dns[0], user.group_dns[1]);
+        assert!(user.in_group_dn(
+            &DistinguishedName::parse("cn=operators,ou=groups,ou=tenant-a,o=smartdc").unwrap()
+        ));
+    }
+
+    #[test]
+    fn admin_detection_matches_configured_group_dn() {
+        let admin_dn = DistinguishedName::parse("cn=operators,ou=groups,o=smartdc").unwrap();
+        let tenant_only = entry_with_groups(&["cn=operators,ou=groups,ou=tenant-a,o=smartdc"]);
+        let operator = entry_with_groups(&[
+            "cn=operators,ou=groups,ou=tenant-a,o=smartdc",
+            "CN=Operators,OU=groups,O=smartdc",
+        ]);
+
+        // Without configuration any `operators` CN grants admin.
+        assert!(parse_user_entry(&tenant_only, &[]).unwrap().is_admin());
+
+        let admins = [admin_dn];
+        assert!(!parse_user_entry(&tenant_only, &admins).unwrap().is_admin());
+        assert!(parse_user_entry(&operator, &admins).unwrap().is_admin());
+    }
+
+    fn group_entry(members: &[&str]) -> LdapEntry {
+        let mut attributes = HashMap::new();
+        attributes.insert(
+            "member".to_string(),
+            members.iter().map(|member| (*member).to_string()).collect(),
+        );
+        LdapEntry {
+            dn: "cn=operators,ou=groups,dc=example,dc=com".to_string(),
+            attributes,
+        }
+    }
+
+    fn membership_client(entries: Vec<LdapEntry>) -> UfdsClient {
+        let mut connector = MockLdapConnector::new();
+        let mut session = MockLdapSession::new();
+        session.expect_simple_bind().returning(|_, _| Ok(()));
+        session
+            .expect_search()
+            .withf(|base, scope, filter, _| {
+                base == "cn=operators,ou=groups,dc=example,dc=com"
+                    && *scope == SearchScope::Base
+                    && filter == "(member=uid=jdoe,ou=users,dc=example,dc=com)"
+            })
+            .return_once(move |_, _, _, _| Ok(entries));
+        session.expect_unbind().returning(|| Ok(()));
+        connector
+            .expect_connect()
+            .return_once(move || Ok(Box::new(session)));
+
+        UfdsClient::with_connector(sample_config(), Box::new(connector))
+    }
+
+    #[tokio::test]
+    async fn is_member_true_when_group_lists_user() {
+        let client = membership_client(vec![group_entry(&[
+            "uid=other,ou=users,dc=example,dc=com",
+            "UID=JDoe, OU=users, DC=example, DC=com",
+        ])]);
+        let user = DistinguishedName::parse("uid=jdoe,ou=users,dc=example,dc=com").unwrap();
+        let group = DistinguishedName::parse("cn=operators,ou=groups,dc=example,dc=com").unwrap();
+
+        assert!(client.is_member(&user, &group).await.unwrap());
+    }
+
+    fn admin_client(session: MockLdapSession) -> UfdsClient {
+        let mut connector = MockLdapConnector::new();
+        connector
+            .expect_connect()
+            .return_once(move || Ok(Box::new(session)));
+        UfdsClient::with_connector(sample_config(), Box::new(connector))
+    }
+
+    fn bound_session() -> MockLdapSession {
+        let mut session = MockLdapSession::new();
+        session.expect_simple_bind().returning(|_, _| Ok(()));
+        session
+    }
+
+    #[tokio::test]
+    async fn create_user_adds_entry_under_user_base() {
+        let uuid = OwnerUuid::new_v4();
+        let expected_dn = format!("uuid={uuid},dc=example,dc=com");
+        let mut sequence = mockall::Sequence::new();
+        let mut session = MockLdapSession::new();
+        session
+            .expect_simple_bind()
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|_, _| Ok(()));
+        session
+            .expect_add()
+            .withf(move |dn, attributes| {
+                dn == expected_dn
+                    && attributes.contains(&("objectclass".to_string(), vec!["sdcperson".into()]))
+                    && attributes.contains(&("login".to_string(), vec!["jdoe".into()]))
+                    && attributes.contains(&("email".to_string(), vec!["jdoe@example.com".into()]))
+            })
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|_, _| Ok(()));
+
+        let client = admin_client(session);
+        let request = CreateUserRequest::new(uuid, "jdoe", "jdoe@example.com").with_cn("J Doe");
+        let user = client.create_user(request).await.unwrap();
+        assert_eq!(user.uuid, uuid);
+        assert_eq!(user.login, "jdoe");
+        assert_eq!(user.email.as_deref(), Some("jdoe@example.com"));
+        assert_eq!(user.cn.as_deref(), Some("J Doe"));
+    }
+
+    #[tokio::test]
+    async fn create_user_rejects_invalid_request_without_connecting() {
+        let mut connector = MockLdapConnector::new();
+        connector.expect_connect().never();
+        let client = UfdsClient::with_connector(sample_config(), Box::new(connector));
+
+        let request = CreateUserRequest::new(OwnerUuid::new_v4(), "jdoe", "not-an-email");
+        let err = client.create_user(request).await.unwrap_err();
+        assert!(matches!(err, Error::ValidationError(_)));
+    }
+
+    #[tokio::test]
+    async fn create_user_surfaces_conflict() {
+        let mut session = bound_session();
+        session
+            .expect_add()
+            .returning(|_, _| Err(Error::Conflict("UFDS entry already exists".into())));
+
+        let client = admin_client(session);
+        let request = CreateUserRequest::new(OwnerUuid::new_v4(), "jdoe", "jdoe@example.com");
+        let err = client.create_user(request).await.unwrap_err();
+        assert!(matches!(err, Error::Conflict(_)));
+    }
+
+    #[tokio::test]
+    async fn update_user_sends_modifications() {
+        let dn = DistinguishedName::parse("uuid=abc,ou=users,dc=example,dc=com").unwrap();
+        let modifications = vec![
+            DirectoryModification::Replace {
+                attribute: "email".to_string(),
+                values: vec!["new@example.com".to_string()],
+            },
+            DirectoryModification::Delete {
+                attribute: "phone".to_string(),
+                values: Vec::new(),
+            },
+        ];
+        let expected = modifications.clone();
+        let mut session = bound_session();
+        session
+            .expect_modify()
+            .withf(move |dn, mods| dn == "uuid=abc,ou=users,dc=example,dc=com" && mods == expected)
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let client = admin_client(session);
+        client.update_user(&dn, &modifications).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn update_user_rejects_empty_or_required_attribute_deletes() {
+        let mut connector = MockLdapConnector::new();
+        connector.expect_connect().never();
+        let client = UfdsClient::with_connector(sample_config(), Box::new(connector));
+        let dn = DistinguishedName::parse("uuid=abc,ou=users,dc=example,dc=com").unwrap();
+
+        let err = client.update_user(&dn, &[]).await.unwrap_err();
+        assert!(matches!(err, Error::ValidationError(_)));
+
+        let delete_login = [DirectoryModification::Delete {
+            attribute: "Login".to_string(),
+            values: Vec::new(),
+        }];
+        let err = client.update_user(&dn, &delete_login).await.unwrap_err();
+        assert!(matches!(err, Error::ValidationError(_)));
+    }
+
+    #[tokio::test]
+    async fn delete_user_removes_entry() {
+        let dn = DistinguishedName::parse("uuid=abc,ou=users,dc=example,dc=com").unwrap();
+        let mut session = bound_session();
+        session
+            .expect_delete()
+            .withf(|dn| dn == "uuid=abc,ou=users,dc=example,dc=com")
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let client = admin_client(session);
+        client.delete_user(&dn).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn admin_session_is_reused_across_calls() {
+        let dn = DistinguishedName::parse("uuid=abc,ou=users,dc=example,dc=com").unwrap();
+        let mut session = MockLdapSession::new();
+        session
+            .expect_simple_bind()
+            .times(1)
+            .returning(|_, _| Ok(()));
+        session.expect_delete().times(3).returning(|_| Ok(()));
+
+        let mut connector = MockLdapConnector::new();
+        connector
+            .expect_connect()
+            .times(1)
+            .return_once(move || Ok(Box::new(session)));
+        let client = UfdsClient::with_connector(sample_config(), Box::new(connector));
+
+        for _ in 0..3 {
+            client.delete_user(&dn).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn failed_admin_session_is_discarded() {
+        let dn = DistinguishedName::parse("uuid=abc,ou=users,dc=example,dc=com").unwrap();
+        let mut broken = bound_session();
+        broken.expect_delete().times(1).returning(|_| {
+            Err(Error::ExternalServiceError {
+                service: "ufds".to_string(),
+                message: "connection reset".to_string(),
+            })
+        });
+        let mut fresh = bound_session();
+        fresh.expect_delete().times(1).returning(|_| Ok(()));
+
+        let mut sequence = mockall::Sequence::new();
+        let mut connector = MockLdapConnector::new();
+        connector
+            .expect_connect()
+            .times(1)
+            .in_sequence(&mut sequence)
+            .return_once(move || Ok(Box::new(broken)));
+        connector
+            .expect_connect()
+            .times(1)
+            .in_sequence(&mut sequence)
+            .return_once(move || Ok(Box::new(fresh)));
+        let client = UfdsClient::with_connector(sample_config(), Box::new(connector));
+
+        assert!(client.delete_user(&dn).await.is_err());
+        client.delete_user(&dn).await.unwrap();
+    }
+
+    const ED25519_KEY: &str = concat!(
+        "ssh-ed25519 ",
+        "AAAAC3NzaC1lZDI1NTE5AAAAIAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8g ",
+        "jdoe@laptop"
+    );
+    const ED25519_FINGERPRINT: &str = "05:6d:11:6f:aa:82:7f:17:e4:a2:92:09:ca:cf:9d:42";
+
+    #[tokio::test]
+    async fn list_ssh_keys_searches_beneath_user() {
+        let mut session = bound_session();
+        session
+            .expect_search()
+            .withf(|base, scope, filter, _| {
+                base == "uuid=abc,ou=users,dc=example,dc=com"
+                    && *scope == SearchScope::OneLevel
+                    && filter == "(objectclass=sdckey)"
+            })
+            .return_once(|_, _, _, _| {
+                let mut attributes = HashMap::new();
+                attributes.insert("fingerprint".to_string(), vec![ED25519_FINGERPRINT.into()]);
+                attributes.insert("name".to_string(), vec!["laptop".to_string()]);
+                attributes.insert("openssh".to_string(), vec![ED25519_KEY.to_string()]);
+                Ok(vec![LdapEntry {
+                    dn: format!(
+                        "fingerprint={ED25519_FINGERPRINT},uuid=abc,ou=users,dc=example,dc=com"
+                    ),
+                    attributes,
+                }])
+            });
+        session.expect_unbind().returning(|| Ok(()));
+
+        let client = admin_client(session);
+        let user = DistinguishedName::parse("uuid=abc,ou=users,dc=example,dc=com").unwrap();
+        let keys = client.list_ssh_keys(&user).await.unwrap();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].name, "laptop");
+        assert_eq!(keys[0].fingerprint, ED25519_FINGERPRINT);
+        assert_eq!(keys[0].openssh, ED25519_KEY);
+    }
+
+    #[tokio::test]
+    async fn add_ssh_key_creates_fingerprint_entry() {
+        let expected_dn =
+            format!("fingerprint={ED25519_FINGERPRINT},uuid=abc,ou=users,dc=example,dc=com");
+        let mut session = bound_session();
+        session
+            .expect_add()
+            .withf(move |dn, attributes| {
+                dn == expected_dn
+                    && attributes
+                        == [
+                            ("objectclass".to_string(), vec!["sdckey".to_string()]),
+                            (
+                                "fingerprint".to_string(),
+                                vec![ED25519_FINGERPRINT.to_string()],
+                            ),
+                            ("name".to_string(), vec![ED25519_FINGERPRINT.to_string()]),
+                            ("openssh".to_string(), vec![ED25519_KEY.to_string()]),
+                        ]
+            })
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let client = admin_client(session);
+        let user = DistinguishedName::parse("uuid=abc,ou=users,dc=example,dc=com").unwrap();
+        let key = client
+            .add_ssh_key(&user, "", &format!("  {ED25519_KEY}\n"))
+            .await
+            .unwrap();
+        assert_eq!(key.fingerprint, ED25519_FINGERPRINT);
+        assert_eq!(key.name, ED25519_FINGERPRINT);
+    }
+
+    #[tokio::test]
+    async fn add_ssh_key_rejects_invalid_key_without_connecting() {
+        let mut connector = MockLdapConnector::new();
+        connector.expect_connect().never();
+        let client = UfdsClient::with_connector(sample_config(), Box::new(connector));
+        let user = DistinguishedName::parse("uuid=abc,ou=users,dc=example,dc=com").unwrap();
+
+        let err = client
+            .add_ssh_key(&user, "laptop", "ssh-ed25519 garbage")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::ValidationError(_)));
+    }
+
+    #[tokio::test]
+    async fn search_passes_filter_through_and_returns_entries() {
+        let filter = format!(
+            "(&(objectclass=sdcperson)(company={}))",
+            escape_filter_value("Acme (EU)")
+        );
+        let expected_filter = filter.clone();
+        let mut session = bound_session();
+        session
+            .expect_search()
+            .withf(move |base, scope, filter, attributes| {
+                base == "ou=users,dc=example,dc=com"
+                    && *scope == SearchScope::Subtree
+                    && filter == expected_filter
+                    && attributes == ["login", "company"]
+            })
+            .return_once(|_, _, _, _| Ok(vec![sample_entry()]));
+
+        let client = admin_client(session);
+        let base = DistinguishedName::parse("ou=users,dc=example,dc=com").unwrap();
+        let entries = client
+            .search(&base, SearchScope::Subtree, &filter, &["login", "company"])
+            .await
+            .unwrap();
+        assert_eq!(
+            filter,
+            "(&(objectclass=sdcperson)(company=Acme \\28EU\\29))"
+        );
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].first("login"), Some("jdoe"));
+    }
+
+    #[tokio::test]
+    async fn search_rejects_malformed_filters_without_connecting() {
+        let mut connector = MockLdapConnector::new();
+        connector.expect_connect().never();
+        let client = UfdsClient::with_connector(sample_config(), Box::new(connector));
+        let base = DistinguishedName::parse("dc=example,dc=com").unwrap();
+
+        for filter in [
+            "",
+            "uid=jdoe",
+            "(company=Acme (EU)",
+            "(uid=a)(uid=b)",
+            "((uid=a)",
+        ] {
+            let err = client
+                .search(&base, SearchScope::Subtree, filter, &["uid"])
+                .await
+                .unwrap_err();
+            assert!(matches!(err, Error::ValidationError(_)), "{filter}");
+        }
+    }
+
+    fn login_entry(login: &str) -> LdapEntry {
+        let mut attributes = HashMap::new();
+        attributes.insert("login".to_string(), vec![login.to_string()]);
+        LdapEntry {
+            dn: format!("uid={login},ou=users,dc=example,dc=com"),
+            attributes,
+        }
+    }
+
+    #[tokio::test]
+    async fn search_paged_reassembles_entries_across_pages() {
+        let mut sequence = mockall::Sequence::new();
+        let mut session = bound_session();
+        session
+            .expect_search_page()
+            .times(1)
+            .in_sequence(&mut sequence)
+            .withf(|base, scope, filter, attributes, page_size, cookie| {
+                base == "ou=users,dc=example,dc=com"
+                    && *scope == SearchScope::OneLevel
+                    && filter == "(objectclass=sdcperson)"
+                    && attributes == ["login"]
+                    && *page_size == 2
+                    && cookie.is_empty()
+            })
+            .return_once(|_, _, _, _, _, _| {
+                Ok((
+                    vec![login_entry("alice"), login_entry("bob")],
+                    b"page-2".to_vec(),
+                ))
+            });
+        session
+            .expect_search_page()
+            .times(1)
+            .in_sequence(&mut sequence)
+            .withf(|_, _, _, _, page_size, cookie| *page_size == 2 && cookie == b"page-2")
+            .return_once(|_, _, _, _, _, _| Ok((vec![login_entry("carol")], Vec::new())));
+
+        let client = admin_client(session);
+        let base = DistinguishedName::parse("ou=users,dc=example,dc=com").unwrap();
+        let entries = client
+            .search_paged(
+                &base,
+                SearchScope::OneLevel,
+                "(objectclass=sdcperson)",
+                &["login"],
+                2,
+            )
+            .await
+            .unwrap();
+
+        let logins: Vec<_> = entries
+            .iter()
+            .filter_map(|entry| entry.first("login"))
+            .collect();
+        assert_eq!(logins, ["alice", "bob", "carol"]);
+    }
+
+    #[tokio::test]
+    async fn search_paged_rejects_zero_page_size() {
+        let mut connector = MockLdapConnector::new();
+        connector.expect_connect().never();
+        let client = UfdsClient::with_connector(sample_config(), Box::new(connector));
+        let base = DistinguishedName::parse("dc=example,dc=com").unwrap();
+
+        let err = client
+            .search_paged(&base, SearchScope::Subtree, "(uid=jdoe)", &["uid"], 0)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::ValidationError(_)));
+    }
+
+    #[test]
+    fn ldap_result_codes_map_to_errors() {
+        let result = |rc| ldap3::LdapResult {
+            rc,
+            matched: String::new(),
+            text: "detail".to_string(),
+            refs: Vec::new(),
+            ctrls: Vec::new(),
+        };
+
+        assert!(ensure_ldap_success(result(0)).is_ok());
+        assert!(matches!(
+            ensure_ldap_success(result(LDAP_ENTRY_ALREADY_EXISTS)),
+            Err(Error::Conflict(_))
+        ));
+        assert!(matches!(
+            ensure_ldap_success(result(LDAP_NO_SUCH_OBJECT)),
+            Err(Error::NotFound(_))
+        ));
+        assert!(matches!(
+            ensure_ldap_success(result(50)),
+            Err(Error::ExternalServiceError { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn is_member_false_when_user_absent() {
+        let client = membership_client(Vec::new());
+        let user = DistinguishedName::parse("uid=jdoe,ou=users,dc=example,dc=com").unwrap();
+        let group = DistinguishedName::parse("cn=operators,ou=groups,dc=example,dc=com").unwrap();
+
+        assert!(!client.is_member(&user, &group).await.unwrap());
+    }
 }
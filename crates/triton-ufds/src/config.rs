@@ -10,6 +10,8 @@ use url::Url;
 pub const DEFAULT_CONNECTION_TIMEOUT_SECS: u64 = 10;
 /// Default operation timeout (seconds).
 pub const DEFAULT_OPERATION_TIMEOUT_SECS: u64 = 10;
+/// Default number of pooled admin connections.
+pub const DEFAULT_POOL_SIZE: usize = 4;
 
 /// Configuration for connecting to UFDS.
 #[derive(Debug, Clone)]
@@ -24,6 +26,8 @@ pub struct UfdsConfig {
     tls_ca_cert: Option<PathBuf>,
     connection_timeout_secs: u64,
     operation_timeout_secs: u64,
+    pool_size: usize,
+    admin_group_dns: Vec<DistinguishedName>,
 }
 
 impl UfdsConfig {
@@ -52,6 +56,8 @@ impl UfdsConfig {
             tls_ca_cert: None,
             connection_timeout_secs: DEFAULT_CONNECTION_TIMEOUT_SECS,
             operation_timeout_secs: DEFAULT_OPERATION_TIMEOUT_SECS,
+            pool_size: DEFAULT_POOL_SIZE,
+            admin_group_dns: Vec::new(),
         })
     }
 
@@ -97,6 +103,12 @@ impl UfdsConfig {
         Duration::from_secs(self.operation_timeout_secs)
     }
 
+    /// Returns the maximum number of admin connections kept open and reused.
+    #[must_use]
+    pub const fn pool_size(&self) -> usize {
+        self.pool_size
+    }
+
     /// Returns the filter template used for user lookups.
     #[must_use]
     pub fn user_filter_template(&self) -> &str {
@@ -109,6 +121,14 @@ impl UfdsConfig {
         self.tls_verify
     }
 
+    /// Group DNs whose members are treated as administrators.
+    ///
+    /// When empty, any group with a `cn` of `admins` or `operators` grants admin status.
+    #[must_use]
+    pub fn admin_group_dns(&self) -> &[DistinguishedName] {
+        &self.admin_group_dns
+    }
+
     /// Optional custom CA certificate path.
     #[must_use]
     pub fn tls_ca_cert(&self) -> Option<&PathBuf> {
@@ -152,6 +172,15 @@ impl UfdsConfig {
         self
     }
 
+    /// Adds a group DN whose members are treated as administrators.
+    ///
+    /// Once any admin group DN is configured, admin detection matches on full DNs only.
+    #[must_use]
+    pub fn with_admin_group_dn(mut self, dn: DistinguishedName) -> Self {
+        self.admin_group_dns.push(dn);
+        self
+    }
+
     /// Overrides the connection timeout in seconds.
     #[must_use]
     pub const fn with_connection_timeout_secs(mut self, seconds: u64) -> Self {
@@ -165,6 +194,15 @@ impl UfdsConfig {
         self.operation_timeout_secs = seconds;
         self
     }
+
+    /// Overrides the maximum number of pooled admin connections.
+    ///
+    /// Values below one are treated as one.
+    #[must_use]
+    pub const fn with_pool_size(mut self, size: usize) -> Self {
+        self.pool_size = if size == 0 { 1 } else { size };
+        self
+    }
 }
 
 #[cfg(test)]
@@ -191,7 +229,11 @@ mod tests {
             .with_user_filter_template("(uid={login})")
             .with_connection_timeout_secs(20)
             .with_operation_timeout_secs(30)
-            .with_tls_verification(false);
+            .with_pool_size(8)
+            .with_tls_verification(false)
+            .with_admin_group_dn(
+                DistinguishedName::parse("cn=operators,ou=Groups,dc=example,dc=com").unwrap(),
+            );
 
         assert_eq!(config.user_base_dn(), &user_dn);
         assert_eq!(
@@ -201,7 +243,9 @@ mod tests {
         assert_eq!(config.user_filter_template(), "(uid={login})");
         assert_eq!(config.connection_timeout(), Duration::from_secs(20));
         assert_eq!(config.operation_timeout(), Duration::from_secs(30));
+        assert_eq!(config.pool_size(), 8);
         assert!(!config.tls_verify());
         assert_eq!(config.base_dn(), &base_dn);
+        assert_eq!(config.admin_group_dns().len(), 1);
     }
 }
@@ -135,7 +135,6 @@ impl DistinguishedName {
     }
 
     /// Returns an iterator over all relative distinguished names in order.
-    #[must_use]
     pub fn components(&self) -> impl Iterator<Item = &RelativeDistinguishedName> + '_ {
         self.rdns.iter().flat_map(|rdn| rdn.iter())
     }
@@ -155,6 +154,34 @@ impl DistinguishedName {
             .any(|rdn| rdn.matches_attribute(attribute) && rdn.value.eq_ignore_ascii_case(value))
     }
 
+    /// Returns the DN with attribute names and values lowercased, suitable for comparisons that
+    /// follow LDAP's case-insensitive matching rules.
+    #[must_use]
+    pub fn normalized(&self) -> String {
+        self.rdns
+            .iter()
+            .map(|rdn| {
+                rdn.iter()
+                    .map(|component| {
+                        format!(
+                            "{}={}",
+                            component.attribute().to_ascii_lowercase(),
+                            escape(&component.value().to_ascii_lowercase())
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("+")
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Returns true if both names refer to the same entry, ignoring case and escaping differences.
+    #[must_use]
+    pub fn is_equivalent(&self, other: &DistinguishedName) -> bool {
+        self.normalized() == other.normalized()
+    }
+
     /// Creates a new distinguished name by prefixing the provided RDN.
     #[must_use]
     pub fn with_prefix(mut self, rdn: RelativeDistinguishedName) -> Self {
@@ -368,6 +395,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn equivalence_ignores_case_and_spacing() {
+        let a = DistinguishedName::parse("UID=JDoe, OU=People, DC=Example, DC=com").unwrap();
+        let b = DistinguishedName::parse("uid=jdoe,ou=people,dc=example,dc=com").unwrap();
+        assert!(a.is_equivalent(&b));
+        assert_eq!(a.normalized(), "uid=jdoe,ou=people,dc=example,dc=com");
+
+        let c = DistinguishedName::parse("uid=jsmith,ou=people,dc=example,dc=com").unwrap();
+        assert!(!a.is_equivalent(&c));
+    }
+
     #[test]
     fn invalid_trailing_delimiter() {
         let err = DistinguishedName::parse("cn=John,").unwrap_err();
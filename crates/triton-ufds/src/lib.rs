@@ -9,13 +9,18 @@ mod client;
 mod config;
 mod dn;
 mod group;
+mod pool;
+mod ssh_key;
 mod user;
 
-pub use client::{DirectoryModification, LdapEntry, SearchScope, UfdsClient};
-pub use config::{UfdsConfig, DEFAULT_CONNECTION_TIMEOUT_SECS, DEFAULT_OPERATION_TIMEOUT_SECS};
+pub use client::{escape_filter_value, DirectoryModification, LdapEntry, SearchScope, UfdsClient};
+pub use config::{
+    UfdsConfig, DEFAULT_CONNECTION_TIMEOUT_SECS, DEFAULT_OPERATION_TIMEOUT_SECS, DEFAULT_POOL_SIZE,
+};
 pub use dn::{DistinguishedName, DistinguishedNameError, RelativeDistinguishedName};
 pub use group::Group;
-pub use user::{AccountStatus, User, UserFlags};
+pub use ssh_key::{ssh_key_fingerprint, SshKey};
+pub use user::{AccountStatus, CreateUserRequest, User, UserFlags};
 
 /// Convenient result alias that reuses the core error type.
 pub type Result<T> = triton_core::Result<T>;
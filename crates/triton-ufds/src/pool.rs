@@ -0,0 +1,180 @@
+//! Bounded pool of bound admin LDAP sessions.
+
+use crate::client::LdapSession;
+use crate::Result;
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tokio::time::timeout;
+use triton_core::error::{Error, TimeoutKind};
+
+/// Caps the number of open admin sessions and keeps released ones for reuse.
+///
+/// A permit is held for every checked-out session, so at most `size` sessions exist at once;
+/// callers beyond the cap wait for a session to be released or discarded.
+pub(crate) struct SessionPool {
+    idle: Mutex<Vec<Box<dyn LdapSession>>>,
+    permits: Semaphore,
+}
+
+impl SessionPool {
+    /// Creates an empty pool holding at most `size` sessions (minimum one).
+    pub(crate) fn new(size: usize) -> Self {
+        let size = size.max(1);
+        Self {
+            idle: Mutex::new(Vec::with_capacity(size)),
+            permits: Semaphore::new(size),
+        }
+    }
+
+    /// Checks out an idle session, or calls `open` to connect and bind a new one when none is
+    /// idle.
+    ///
+    /// Waiting for a free slot is bounded by `wait`.
+    pub(crate) async fn checkout<F, Fut>(
+        &self,
+        wait: Duration,
+        open: F,
+    ) -> Result<PooledSession<'_>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Box<dyn LdapSession>>>,
+    {
+        let permit = timeout(wait, self.permits.acquire())
+            .await
+            .map_err(|_| Error::timeout(TimeoutKind::Connect, wait))?
+            .map_err(|_| Error::InternalError("UFDS session pool is closed".to_string()))?;
+
+        let session = match self.take_idle() {
+            Some(session) => session,
+            None => open().await?,
+        };
+        Ok(PooledSession {
+            session: Some(session),
+            pool: self,
+            _permit: permit,
+        })
+    }
+
+    /// Number of idle sessions ready for reuse.
+    #[cfg(test)]
+    pub(crate) fn idle_count(&self) -> usize {
+        self.idle.lock().map_or(0, |idle| idle.len())
+    }
+
+    fn take_idle(&self) -> Option<Box<dyn LdapSession>> {
+        self.idle.lock().ok()?.pop()
+    }
+
+    fn put_idle(&self, session: Box<dyn LdapSession>) {
+        if let Ok(mut idle) = self.idle.lock() {
+            idle.push(session);
+        }
+    }
+}
+
+/// A session checked out of a [`SessionPool`].
+///
+/// Call [`PooledSession::release`] once the operation succeeds to return the session for
+/// reuse. Dropping the guard instead discards the session, so one that hit an error is never
+/// handed out again.
+pub(crate) struct PooledSession<'a> {
+    session: Option<Box<dyn LdapSession>>,
+    pool: &'a SessionPool,
+    _permit: SemaphorePermit<'a>,
+}
+
+impl PooledSession<'_> {
+    /// Returns the session to the pool for reuse.
+    pub(crate) fn release(mut self) {
+        if let Some(session) = self.session.take() {
+            self.pool.put_idle(session);
+        }
+    }
+}
+
+impl Deref for PooledSession<'_> {
+    type Target = dyn LdapSession;
+
+    fn deref(&self) -> &Self::Target {
+        self.session
+            .as_deref()
+            .expect("pooled session is present until released")
+    }
+}
+
+impl DerefMut for PooledSession<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.session
+            .as_deref_mut()
+            .expect("pooled session is present until released")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::MockLdapSession;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const WAIT: Duration = Duration::from_millis(50);
+
+    async fn open(opened: &AtomicUsize) -> Result<Box<dyn LdapSession>> {
+        opened.fetch_add(1, Ordering::SeqCst);
+        Ok(Box::new(MockLdapSession::new()))
+    }
+
+    #[tokio::test]
+    async fn released_sessions_are_reused() {
+        let pool = SessionPool::new(2);
+        let opened = AtomicUsize::new(0);
+
+        for _ in 0..3 {
+            let session = pool.checkout(WAIT, || open(&opened)).await.unwrap();
+            session.release();
+        }
+
+        assert_eq!(opened.load(Ordering::SeqCst), 1);
+        assert_eq!(pool.idle_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn sessions_are_capped_at_pool_size() {
+        let pool = SessionPool::new(2);
+        let opened = AtomicUsize::new(0);
+
+        let first = pool.checkout(WAIT, || open(&opened)).await.unwrap();
+        let second = pool.checkout(WAIT, || open(&opened)).await.unwrap();
+        let err = pool
+            .checkout(WAIT, || open(&opened))
+            .await
+            .err()
+            .expect("third checkout should wait for a free slot");
+        assert!(matches!(err, Error::Timeout { .. }));
+
+        first.release();
+        second.release();
+        let third = pool.checkout(WAIT, || open(&opened)).await.unwrap();
+        third.release();
+
+        assert_eq!(opened.load(Ordering::SeqCst), 2);
+        assert_eq!(pool.idle_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn dropped_sessions_are_discarded() {
+        let pool = SessionPool::new(1);
+        let opened = AtomicUsize::new(0);
+
+        drop(pool.checkout(WAIT, || open(&opened)).await.unwrap());
+        assert_eq!(pool.idle_count(), 0);
+
+        pool.checkout(WAIT, || open(&opened))
+            .await
+            .unwrap()
+            .release();
+        assert_eq!(opened.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,130 @@
+//! UFDS SSH key (`sdcKey`) representation and OpenSSH public key parsing.
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use md5::{Digest, Md5};
+use serde::{Deserialize, Serialize};
+
+use crate::dn::{DistinguishedName, RelativeDistinguishedName};
+use crate::Result;
+use triton_core::Error;
+
+/// Key types accepted in the `openssh` attribute.
+const KEY_TYPES: &[&str] = &[
+    "ssh-rsa",
+    "ssh-dss",
+    "ssh-ed25519",
+    "ecdsa-sha2-nistp256",
+    "ecdsa-sha2-nistp384",
+    "ecdsa-sha2-nistp521",
+    "sk-ssh-ed25519@openssh.com",
+    "sk-ecdsa-sha2-nistp256@openssh.com",
+];
+
+/// An SSH public key stored as an `sdcKey` entry beneath a UFDS user.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SshKey {
+    /// Distinguished name of the key entry (`fingerprint=<fp>,<user dn>`).
+    pub dn: DistinguishedName,
+    /// Key name; defaults to the fingerprint.
+    pub name: String,
+    /// MD5 fingerprint in colon-separated hex (`aa:bb:...`).
+    pub fingerprint: String,
+    /// Public key in OpenSSH `authorized_keys` format.
+    pub openssh: String,
+}
+
+impl SshKey {
+    /// Returns the DN of the key with `fingerprint` beneath `user_dn`.
+    #[must_use]
+    pub fn key_dn(user_dn: &DistinguishedName, fingerprint: &str) -> DistinguishedName {
+        user_dn
+            .clone()
+            .with_prefix(RelativeDistinguishedName::new("fingerprint", fingerprint))
+    }
+}
+
+/// Parses an OpenSSH public key line and returns its MD5 fingerprint.
+///
+/// The line must be `<type> <base64 blob> [comment]`, with a known key type
+/// that matches the type encoded at the start of the blob.
+///
+/// # Errors
+///
+/// Returns [`Error::ValidationError`] when the line is not a public key.
+pub fn ssh_key_fingerprint(openssh_key: &str) -> Result<String> {
+    let mut fields = openssh_key.split_whitespace();
+    let (Some(key_type), Some(encoded)) = (fields.next(), fields.next()) else {
+        return Err(invalid_key("expected `<type> <base64 key> [comment]`"));
+    };
+    if !KEY_TYPES.contains(&key_type) {
+        return Err(invalid_key(&format!("unsupported key type `{key_type}`")));
+    }
+
+    let blob = STANDARD
+        .decode(encoded)
+        .map_err(|_| invalid_key("key data is not valid base64"))?;
+    if blob_key_type(&blob) != Some(key_type.as_bytes()) {
+        return Err(invalid_key(&format!("key data is not a `{key_type}` key")));
+    }
+
+    Ok(Md5::digest(&blob)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<Vec<_>>()
+        .join(":"))
+}
+
+/// Reads the length-prefixed key type string that starts every public key blob.
+fn blob_key_type(blob: &[u8]) -> Option<&[u8]> {
+    let length = u32::from_be_bytes(blob.get(..4)?.try_into().ok()?);
+    blob.get(4..4usize.checked_add(usize::try_from(length).ok()?)?)
+}
+
+fn invalid_key(reason: &str) -> Error {
+    Error::ValidationError(format!("invalid SSH public key: {reason}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ED25519_KEY: &str = concat!(
+        "ssh-ed25519 ",
+        "AAAAC3NzaC1lZDI1NTE5AAAAIAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8g ",
+        "jdoe@laptop"
+    );
+    const ED25519_FINGERPRINT: &str = "05:6d:11:6f:aa:82:7f:17:e4:a2:92:09:ca:cf:9d:42";
+
+    #[test]
+    fn fingerprint_matches_ssh_keygen_md5() {
+        assert_eq!(
+            ssh_key_fingerprint(ED25519_KEY).unwrap(),
+            ED25519_FINGERPRINT
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_keys() {
+        for key in [
+            "",
+            "ssh-ed25519",
+            "ssh-foo AAAAC3NzaC1lZDI1NTE5AAAAIAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8g",
+            "ssh-ed25519 not*base64",
+            "ssh-rsa AAAAC3NzaC1lZDI1NTE5AAAAIAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8g",
+            "ssh-ed25519 AAAA",
+        ] {
+            assert!(
+                matches!(ssh_key_fingerprint(key), Err(Error::ValidationError(_))),
+                "accepted `{key}`"
+            );
+        }
+    }
+
+    #[test]
+    fn key_dn_is_child_of_user() {
+        let user = DistinguishedName::parse("uuid=abc,ou=users,o=smartdc").unwrap();
+        let dn = SshKey::key_dn(&user, ED25519_FINGERPRINT);
+        assert_eq!(dn.get("fingerprint"), Some(ED25519_FINGERPRINT));
+        assert!(dn.as_str().ends_with("uuid=abc,ou=users,o=smartdc"));
+    }
+}
@@ -2,9 +2,12 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 use crate::dn::DistinguishedName;
+use crate::Result;
 use triton_core::uuid::OwnerUuid;
+use triton_core::Error;
 
 /// Account status flags that reflect the LDAP operational state.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -151,6 +154,9 @@ pub struct User {
     /// Group names the user belongs to.
     #[serde(default)]
     pub groups: Vec<String>,
+    /// Full distinguished names of the groups the user belongs to.
+    #[serde(default)]
+    pub group_dns: Vec<DistinguishedName>,
 }
 
 impl User {
@@ -176,6 +182,7 @@ impl User {
             created_at: None,
             updated_at: None,
             groups: Vec::new(),
+            group_dns: Vec::new(),
         }
     }
 
@@ -197,6 +204,12 @@ impl User {
         self.groups.iter().any(|g| g.eq_ignore_ascii_case(group))
     }
 
+    /// Returns true if the user belongs to the group with the given DN.
+    #[must_use]
+    pub fn in_group_dn(&self, group_dn: &DistinguishedName) -> bool {
+        self.group_dns.iter().any(|dn| dn.is_equivalent(group_dn))
+    }
+
     /// Returns the preferred display name (common name when available).
     #[must_use]
     pub fn display_name(&self) -> Option<String> {
@@ -230,6 +243,7 @@ pub struct UserBuilder {
     created_at: Option<DateTime<Utc>>,
     updated_at: Option<DateTime<Utc>>,
     groups: Vec<String>,
+    group_dns: Vec<DistinguishedName>,
 }
 
 impl UserBuilder {
@@ -320,6 +334,16 @@ impl UserBuilder {
         self
     }
 
+    /// Replaces the group DN list.
+    #[must_use]
+    pub fn group_dns<I>(mut self, group_dns: I) -> Self
+    where
+        I: IntoIterator<Item = DistinguishedName>,
+    {
+        self.group_dns = group_dns.into_iter().collect();
+        self
+    }
+
     /// Finalises the builder and returns the [`User`].
     #[must_use]
     pub fn build(self) -> User {
@@ -338,10 +362,201 @@ impl UserBuilder {
             created_at: self.created_at,
             updated_at: self.updated_at,
             groups: self.groups,
+            group_dns: self.group_dns,
         }
     }
 }
 
+/// Attributes for a new UFDS user (`sdcperson`) entry.
+///
+/// `uuid`, `login` and `email` are required; [`validate`](Self::validate)
+/// checks them before anything is sent to the directory.
+#[derive(Clone, PartialEq, Eq)]
+pub struct CreateUserRequest {
+    /// Account UUID; also names the entry (`uuid=<uuid>,<user base>`).
+    pub uuid: OwnerUuid,
+    /// Login name.
+    pub login: String,
+    /// Primary email address.
+    pub email: String,
+    /// Initial password.
+    pub password: Option<String>,
+    /// Common name.
+    pub cn: Option<String>,
+    /// Surname (last name).
+    pub sn: Option<String>,
+    /// Given name (first name).
+    pub given_name: Option<String>,
+    /// Company affiliation.
+    pub company: Option<String>,
+    /// Phone number.
+    pub phone: Option<String>,
+    /// Feature flags.
+    pub flags: UserFlags,
+}
+
+impl CreateUserRequest {
+    /// Creates a request with the required attributes.
+    #[must_use]
+    pub fn new(uuid: OwnerUuid, login: impl Into<String>, email: impl Into<String>) -> Self {
+        Self {
+            uuid,
+            login: login.into(),
+            email: email.into(),
+            password: None,
+            cn: None,
+            sn: None,
+            given_name: None,
+            company: None,
+            phone: None,
+            flags: UserFlags::default(),
+        }
+    }
+
+    /// Sets the initial password.
+    #[must_use]
+    pub fn with_password(mut self, password: impl Into<String>) -> Self {
+        self.password = Some(password.into());
+        self
+    }
+
+    /// Sets the common name.
+    #[must_use]
+    pub fn with_cn(mut self, cn: impl Into<String>) -> Self {
+        self.cn = Some(cn.into());
+        self
+    }
+
+    /// Sets the surname.
+    #[must_use]
+    pub fn with_sn(mut self, sn: impl Into<String>) -> Self {
+        self.sn = Some(sn.into());
+        self
+    }
+
+    /// Sets the given name.
+    #[must_use]
+    pub fn with_given_name(mut self, given_name: impl Into<String>) -> Self {
+        self.given_name = Some(given_name.into());
+        self
+    }
+
+    /// Sets the company.
+    #[must_use]
+    pub fn with_company(mut self, company: impl Into<String>) -> Self {
+        self.company = Some(company.into());
+        self
+    }
+
+    /// Sets the phone number.
+    #[must_use]
+    pub fn with_phone(mut self, phone: impl Into<String>) -> Self {
+        self.phone = Some(phone.into());
+        self
+    }
+
+    /// Overrides the feature flags.
+    #[must_use]
+    pub fn with_flags(mut self, flags: UserFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Checks the required attributes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ValidationError`] when the UUID is nil, the login is
+    /// empty or does not start with a letter followed by letters, digits,
+    /// `_`, `.` or `@`, or the email is not of the form `local@domain`.
+    pub fn validate(&self) -> Result<()> {
+        if self.uuid.as_uuid().is_nil() {
+            return Err(Error::ValidationError(
+                "user uuid must not be nil".to_string(),
+            ));
+        }
+
+        let mut login = self.login.chars();
+        let valid_login = login
+            .next()
+            .is_some_and(|first| first.is_ascii_alphabetic())
+            && login.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '@'));
+        if !valid_login {
+            return Err(Error::ValidationError(format!(
+                "invalid user login `{}`",
+                self.login
+            )));
+        }
+
+        let valid_email = self.email.split_once('@').is_some_and(|(local, domain)| {
+            !local.is_empty() && !domain.is_empty() && !domain.contains('@')
+        });
+        if !valid_email {
+            return Err(Error::ValidationError(format!(
+                "invalid user email `{}`",
+                self.email
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// LDAP attributes for the new entry, including `objectclass`.
+    #[must_use]
+    pub fn to_attributes(&self) -> Vec<(String, Vec<String>)> {
+        let mut attributes = vec![
+            ("objectclass".to_string(), vec!["sdcperson".to_string()]),
+            ("uuid".to_string(), vec![self.uuid.to_string()]),
+            ("login".to_string(), vec![self.login.clone()]),
+            ("email".to_string(), vec![self.email.clone()]),
+        ];
+        let optional = [
+            ("userpassword", &self.password),
+            ("cn", &self.cn),
+            ("sn", &self.sn),
+            ("givenName", &self.given_name),
+            ("company", &self.company),
+            ("phone", &self.phone),
+        ];
+        for (attribute, value) in optional {
+            if let Some(value) = value {
+                attributes.push((attribute.to_string(), vec![value.clone()]));
+            }
+        }
+        let flags = [
+            (
+                "approved_for_provisioning",
+                self.flags.approved_for_provisioning,
+            ),
+            ("registered_developer", self.flags.registered_developer),
+            ("triton_cns_enabled", self.flags.triton_cns_enabled),
+        ];
+        for (attribute, enabled) in flags {
+            if enabled {
+                attributes.push((attribute.to_string(), vec!["true".to_string()]));
+            }
+        }
+        attributes
+    }
+}
+
+impl fmt::Debug for CreateUserRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CreateUserRequest")
+            .field("uuid", &self.uuid)
+            .field("login", &self.login)
+            .field("email", &self.email)
+            .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+            .field("cn", &self.cn)
+            .field("sn", &self.sn)
+            .field("given_name", &self.given_name)
+            .field("company", &self.company)
+            .field("phone", &self.phone)
+            .field("flags", &self.flags)
+            .finish()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -385,4 +600,69 @@ mod tests {
         assert!(user.in_group("Admins"));
         assert_eq!(user.display_name().unwrap(), "John Doe");
     }
+
+    #[test]
+    fn create_user_request_validates_required_attributes() {
+        let valid = CreateUserRequest::new(OwnerUuid::new_v4(), "jdoe", "jdoe@example.com");
+        assert!(valid.validate().is_ok());
+
+        let invalid = [
+            CreateUserRequest {
+                uuid: OwnerUuid::parse_str("00000000-0000-0000-0000-000000000000").unwrap(),
+                ..valid.clone()
+            },
+            CreateUserRequest {
+                login: String::new(),
+                ..valid.clone()
+            },
+            CreateUserRequest {
+                login: "1jdoe".to_string(),
+                ..valid.clone()
+            },
+            CreateUserRequest {
+                login: "j doe".to_string(),
+                ..valid.clone()
+            },
+            CreateUserRequest {
+                email: "jdoe".to_string(),
+                ..valid.clone()
+            },
+            CreateUserRequest {
+                email: "@example.com".to_string(),
+                ..valid
+            },
+        ];
+        for request in invalid {
+            assert!(
+                matches!(request.validate(), Err(Error::ValidationError(_))),
+                "{request:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn create_user_request_attributes_skip_unset_values() {
+        let uuid = OwnerUuid::new_v4();
+        let request = CreateUserRequest::new(uuid, "jdoe", "jdoe@example.com")
+            .with_password("hunter22")
+            .with_cn("John Doe")
+            .with_flags(UserFlags::default().with_provisioning(true));
+
+        let attributes = request.to_attributes();
+        let names: Vec<_> = attributes.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "objectclass",
+                "uuid",
+                "login",
+                "email",
+                "userpassword",
+                "cn",
+                "approved_for_provisioning"
+            ]
+        );
+        assert_eq!(attributes[1].1, vec![uuid.to_string()]);
+        assert!(!format!("{request:?}").contains("hunter22"));
+    }
 }
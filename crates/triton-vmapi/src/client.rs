@@ -1,22 +1,37 @@
 //! Asynchronous VMAPI client implementation.
 
 use crate::models::{
-    BatchVMRequest, BatchVMResponse, CreateSnapshotRequest, CreateVMRequest, JobListParams,
-    SnapshotActionResponse, UpdateVMRequest, VMListParams, Vm, VmSnapshot, VmapiJob,
+    AddNicsRequest, BatchSummary, BatchVMAction, BatchVMRequest, BatchVMResponse,
+    CreateSnapshotRequest, CreateVMRequest, JobListParams, MetadataNamespace, NetworkConfig,
+    RemoveNicsRequest, ReprovisionVmRequest, ResizeVmRequest, RoleTagsRequest,
+    SnapshotActionResponse, StopVmOptions, UpdateVMRequest, VMBatchResult, VMListParams, Vm,
+    VmActionResponse, VmSnapshot, VmapiJob,
 };
 use crate::Result;
 use async_trait::async_trait;
+use futures::stream::{self, Stream, StreamExt};
+use reqwest::header::{HeaderMap, HeaderName};
 use reqwest::{Method, StatusCode};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use std::time::Duration;
+use tracing::debug;
 use triton_core::client::{
-    ClientConfig, RetryPolicy, ServiceClient, ServiceClientBuilder, VMAPI_DEFAULT_TIMEOUT,
+    ClientConfig, ClientObserver, RequestObserver, RequestOptions, RetryPolicy, ServiceClient,
+    ServiceClientBuilder, ServiceResponse, VMAPI_DEFAULT_TIMEOUT,
 };
+use triton_core::config::TritonClientConfig;
+use triton_core::context::RequestContext;
+use triton_core::path::encode_path_segment;
+use triton_core::preflight::{PreflightCheck, PreflightResult};
+use triton_core::query::QueryPair;
+use triton_core::selection::EndpointSelector;
 use triton_core::services::{DiscoveryStatus, ServiceDiscovery, ServiceDiscoveryProxy};
-use triton_core::types::TritonService;
-use triton_core::uuid::InstanceUuid;
+use triton_core::types::{EndpointList, TritonService};
+use triton_core::uuid::{ImageUuid, InstanceUuid, OwnerUuid, PackageUuid};
+use triton_core::wait::{poll_until, WaitOptions};
 use triton_core::Error;
 use url::Url;
 
@@ -26,6 +41,8 @@ const USER_AGENT: &str = concat!("triton-vmapi/", env!("CARGO_PKG_VERSION"));
 #[derive(Debug, Clone)]
 pub struct VmapiClientBuilder {
     inner: ServiceClientBuilder,
+    scoped_owner: Option<OwnerUuid>,
+    batch_split_min: Option<usize>,
 }
 
 impl VmapiClientBuilder {
@@ -38,7 +55,23 @@ impl VmapiClientBuilder {
         )?
         .with_user_agent(USER_AGENT);
 
-        Ok(Self { inner: builder })
+        Ok(Self {
+            inner: builder,
+            scoped_owner: None,
+            batch_split_min: None,
+        })
+    }
+
+    /// Create a builder from the VMAPI endpoint, timeout, and retry count in `config`.
+    pub fn from_config(config: &TritonClientConfig) -> Result<Self> {
+        let builder = ServiceClientBuilder::from_config(TritonService::Vmapi, config)?
+            .with_user_agent(USER_AGENT);
+
+        Ok(Self {
+            inner: builder,
+            scoped_owner: None,
+            batch_split_min: None,
+        })
     }
 
     /// Override the retry policy.
@@ -73,10 +106,100 @@ impl VmapiClientBuilder {
         self
     }
 
+    /// Override per-request options such as the JSON content type.
+    #[must_use]
+    pub fn with_request_options(mut self, options: RequestOptions) -> Self {
+        self.inner = self.inner.with_request_options(options);
+        self
+    }
+
+    /// Notify `observer` after every call, retries included.
+    #[must_use]
+    pub fn with_observer(mut self, observer: Arc<dyn RequestObserver>) -> Self {
+        self.inner = self.inner.with_observer(observer);
+        self
+    }
+
+    /// Invoke `observer` around every attempt, retries included.
+    #[must_use]
+    pub fn with_client_observer(mut self, observer: Arc<dyn ClientObserver>) -> Self {
+        self.inner = self.inner.with_client_observer(observer);
+        self
+    }
+
+    /// Send each call's request id in `name` instead of `x-request-id`.
+    #[must_use]
+    pub fn with_request_id_header(mut self, name: HeaderName) -> Self {
+        self.inner = self.inner.with_request_id_header(name);
+        self
+    }
+
+    /// Spread requests over `endpoints`, skipping stale and quarantined ones.
+    ///
+    /// See [`ServiceClientBuilder::with_endpoints`].
+    #[must_use]
+    pub fn with_endpoints(mut self, endpoints: EndpointList, selector: EndpointSelector) -> Self {
+        self.inner = self.inner.with_endpoints(endpoints, selector);
+        self
+    }
+
+    /// Send every request to the endpoint at `url` until it fails or is released.
+    ///
+    /// See [`ServiceClientBuilder::pin_endpoint`].
+    #[must_use]
+    pub fn pin_endpoint(mut self, url: impl Into<String>) -> Self {
+        self.inner = self.inner.pin_endpoint(url);
+        self
+    }
+
+    /// Send requests through a shared `reqwest::Client` instead of building a new one.
+    ///
+    /// The injected client's timeout, TLS and pooling settings take precedence; see
+    /// [`ServiceClientBuilder::with_http_client`].
+    #[must_use]
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
+        self.inner = self.inner.with_http_client(client);
+        self
+    }
+
+    /// Record responses to, or replay them from, `cassette`.
+    #[cfg(feature = "record-replay")]
+    #[must_use]
+    pub fn with_cassette(mut self, cassette: triton_core::cassette::Cassette) -> Self {
+        self.inner = self.inner.with_cassette(cassette);
+        self
+    }
+
+    /// Restrict every operation to VMs owned by `owner`.
+    ///
+    /// Single-VM calls send `owner_uuid` so VMAPI answers 404 for other
+    /// accounts' VMs, and requests naming a different owner are rejected
+    /// before they are sent.
+    #[must_use]
+    pub fn with_scoped_owner(mut self, owner: OwnerUuid) -> Self {
+        self.scoped_owner = Some(owner);
+        self
+    }
+
+    /// Split batches that VMAPI rejects with `413 Payload Too Large`.
+    ///
+    /// [`VmapiClient::batch_action`] halves a rejected batch and retries each
+    /// half, down to `min_chunk` VMs per request, merging the responses.
+    /// Without this, the [`Error::PayloadTooLarge`] is returned as-is.
+    #[must_use]
+    pub fn with_batch_split(mut self, min_chunk: usize) -> Self {
+        self.batch_split_min = Some(min_chunk.max(1));
+        self
+    }
+
     /// Build the client.
     pub fn build(self) -> Result<VmapiClient> {
         let inner = self.inner.build()?;
-        Ok(VmapiClient { inner })
+        Ok(VmapiClient {
+            inner,
+            scoped_owner: self.scoped_owner,
+            batch_split_min: self.batch_split_min,
+        })
     }
 }
 
@@ -84,6 +207,8 @@ impl VmapiClientBuilder {
 #[derive(Clone)]
 pub struct VmapiClient {
     inner: ServiceClient,
+    scoped_owner: Option<OwnerUuid>,
+    batch_split_min: Option<usize>,
 }
 
 impl VmapiClient {
@@ -92,25 +217,201 @@ impl VmapiClient {
         VmapiClientBuilder::new(base_url)?.build()
     }
 
+    /// Returns a handle that tags every call with `context` for logs and observers.
+    #[must_use]
+    pub fn with_context(&self, context: RequestContext) -> Self {
+        Self {
+            inner: self.inner.with_context(context),
+            ..self.clone()
+        }
+    }
+
     /// Return the base URL.
     #[must_use]
     pub fn base_url(&self) -> &Url {
         self.inner.base_url()
     }
 
+    /// Identifier of the endpoint requests are pinned to, if any.
+    #[must_use]
+    pub fn pinned_endpoint(&self) -> Option<String> {
+        self.inner.pinned_endpoint()
+    }
+
+    /// Drop the endpoint pin so the next request selects afresh.
+    pub fn release_pinned_endpoint(&self) {
+        self.inner.release_pinned_endpoint();
+    }
+
+    /// Replace the endpoints requests are spread over, e.g. after rediscovery.
+    pub fn update_endpoints(&self, endpoints: EndpointList) {
+        self.inner.update_endpoints(endpoints);
+    }
+
+    /// Return the owner this client is scoped to, if any.
+    #[must_use]
+    pub fn scoped_owner(&self) -> Option<OwnerUuid> {
+        self.scoped_owner
+    }
+
     /// List virtual machines.
+    ///
+    /// A scoped client fills in `owner_uuid` when the params leave it unset.
     pub async fn list_vms(&self, params: &VMListParams) -> Result<Vec<Vm>> {
-        self.get_json("vms", &params.to_pairs()).await
+        self.list_vms_with_headers(params)
+            .await
+            .map(|response| response.body)
+    }
+
+    /// List VMs along with the response headers, e.g. `x-resource-count`.
+    ///
+    /// A `limit` above [`VMListParams::MAX_LIMIT`] is rejected rather than
+    /// silently clamped by VMAPI; use [`Self::list_vms_stream`] to fetch more.
+    pub async fn list_vms_with_headers(
+        &self,
+        params: &VMListParams,
+    ) -> Result<ServiceResponse<Vec<Vm>>> {
+        self.ensure_owner(params.owner_uuid)?;
+        if let Some(limit) = params
+            .limit
+            .filter(|limit| *limit > VMListParams::MAX_LIMIT)
+        {
+            return Err(Error::BadRequest(format!(
+                "VMAPI limit {limit} exceeds the maximum of {}",
+                VMListParams::MAX_LIMIT
+            )));
+        }
+        let mut params = params.clone();
+        params.owner_uuid = params.owner_uuid.or(self.scoped_owner);
+        self.send_json_response::<(), _>(Method::GET, "vms", None, &params.to_pairs())
+            .await
+    }
+
+    /// Count the VMs matching `params` without fetching them.
+    ///
+    /// Sends `HEAD /vms` and reads the `x-joyent-resource-count` header
+    /// (or `x-resource-count`). If VMAPI rejects `HEAD` with `405` or omits
+    /// the header, a `GET` with `limit=1` is tried instead. `limit` and
+    /// `offset` in `params` are ignored. Fails with [`Error::HttpError`] when
+    /// neither response carries a numeric count.
+    pub async fn count_vms(&self, params: &VMListParams) -> Result<u64> {
+        self.ensure_owner(params.owner_uuid)?;
+        let mut params = params.clone();
+        params.owner_uuid = params.owner_uuid.or(self.scoped_owner);
+        params.limit = Some(1);
+        params.offset = None;
+        let pairs = params.to_pairs();
+
+        match self
+            .inner
+            .execute_with_retry(
+                Method::HEAD,
+                "vms",
+                &pairs,
+                |request| request,
+                map_status_to_error,
+            )
+            .await
+        {
+            Ok(response) => {
+                if let Some(count) = resource_count(response.headers()) {
+                    return parse_resource_count(response.status(), count);
+                }
+            }
+            Err(err) if err.status_code() == Some(StatusCode::METHOD_NOT_ALLOWED) => {}
+            Err(err) => return Err(err),
+        }
+
+        let response = self
+            .send_json_response::<(), Vec<Vm>>(Method::GET, "vms", None, &pairs)
+            .await?;
+        match resource_count(&response.headers) {
+            Some(count) => parse_resource_count(response.status, count),
+            None => Err(Error::http(
+                response.status,
+                "VMAPI response has no x-joyent-resource-count header",
+            )),
+        }
+    }
+
+    /// Stream every VM matching `params`, one page per request.
+    ///
+    /// `params.limit` sets the page size (default and cap
+    /// [`VMListParams::MAX_LIMIT`], so an oversized `limit` is split into
+    /// capped requests). Each page is requested with the same filters and
+    /// `sort`, advancing `offset` by the page size, so a sorted listing keeps
+    /// its order across pages. The stream ends after a short (or empty) page,
+    /// or after yielding the first error.
+    pub fn list_vms_stream(&self, params: VMListParams) -> impl Stream<Item = Result<Vm>> + '_ {
+        let page_size = params
+            .limit
+            .unwrap_or(VMListParams::MAX_LIMIT)
+            .clamp(1, VMListParams::MAX_LIMIT);
+        let start = params.offset.unwrap_or(0);
+
+        stream::unfold(Some((params, start)), move |state| async move {
+            let (mut params, offset) = state?;
+            params.limit = Some(page_size);
+            params.offset = Some(offset);
+            match self.list_vms(&params).await {
+                Ok(page) => {
+                    let full = page.len() >= page_size as usize;
+                    let next = offset
+                        .checked_add(page_size)
+                        .filter(|_| full)
+                        .map(|next| (params, next));
+                    Some((page.into_iter().map(Ok).collect::<Vec<_>>(), next))
+                }
+                Err(err) => Some((vec![Err(err)], None)),
+            }
+        })
+        .flat_map(stream::iter)
     }
 
     /// Fetch a single VM by UUID.
     pub async fn get_vm(&self, uuid: InstanceUuid) -> Result<Vm> {
-        let path = format!("vms/{uuid}");
-        self.get_json(&path, &[]).await
+        let path = format!("vms/{}", encode_path_segment(uuid)?);
+        self.get_json(&path, &self.owner_params()).await
+    }
+
+    /// Fetch the single active VM owned by `owner` whose alias is `alias`.
+    ///
+    /// Lists with `owner_uuid`, `alias`, and `state=active`, keeping only
+    /// exact alias matches. Returns [`Error::NotFound`] when nothing matches
+    /// and [`Error::Conflict`] naming the candidates when several do.
+    pub async fn get_vm_by_alias(&self, owner: OwnerUuid, alias: &str) -> Result<Vm> {
+        let params = VMListParams {
+            owner_uuid: Some(owner),
+            alias: Some(alias.to_string()),
+            state: Some("active".to_string()),
+            ..VMListParams::default()
+        };
+        let mut vms: Vec<Vm> = self
+            .list_vms(&params)
+            .await?
+            .into_iter()
+            .filter(|vm| vm.alias.as_deref() == Some(alias))
+            .collect();
+
+        match vms.len() {
+            0 => Err(Error::NotFound(format!(
+                "no VM with alias '{alias}' for owner {owner}"
+            ))),
+            1 => Ok(vms.remove(0)),
+            _ => {
+                let uuids: Vec<String> = vms.iter().map(|vm| vm.uuid.to_string()).collect();
+                Err(Error::Conflict(format!(
+                    "alias '{alias}' matches {} VMs for owner {owner}: {}",
+                    vms.len(),
+                    uuids.join(", ")
+                )))
+            }
+        }
     }
 
     /// Create a VM (returns the provisioning job).
     pub async fn create_vm(&self, request: &CreateVMRequest) -> Result<VmapiJob> {
+        self.ensure_owner(Some(request.owner_uuid))?;
         self.send_json(Method::POST, "vms", Some(request), &[])
             .await
     }
@@ -121,21 +422,222 @@ impl VmapiClient {
         uuid: InstanceUuid,
         request: &UpdateVMRequest,
     ) -> Result<VmapiJob> {
-        let path = format!("vms/{uuid}");
-        self.send_json(Method::PUT, &path, Some(request), &[]).await
+        let path = format!("vms/{}", encode_path_segment(uuid)?);
+        self.send_json(Method::PUT, &path, Some(request), &self.owner_params())
+            .await
+    }
+
+    /// Enable or disable deletion protection on a VM.
+    pub async fn set_deletion_protection(
+        &self,
+        uuid: InstanceUuid,
+        enabled: bool,
+    ) -> Result<VmapiJob> {
+        let request = UpdateVMRequest {
+            deletion_protection: Some(enabled),
+            ..UpdateVMRequest::default()
+        };
+        self.update_vm(uuid, &request).await
     }
 
     /// Delete a VM.
     pub async fn delete_vm(&self, uuid: InstanceUuid) -> Result<VmapiJob> {
-        let path = format!("vms/{uuid}");
-        self.send_json::<(), VmapiJob>(Method::DELETE, &path, None, &[])
+        let path = format!("vms/{}", encode_path_segment(uuid)?);
+        self.send_json::<(), VmapiJob>(Method::DELETE, &path, None, &self.owner_params())
+            .await
+    }
+
+    /// Start a stopped VM.
+    pub async fn start_vm(&self, uuid: InstanceUuid) -> Result<VmActionResponse> {
+        self.vm_action::<()>(uuid, "start", Vec::new(), None).await
+    }
+
+    /// Stop a running VM, gracefully unless `options.force` is set.
+    pub async fn stop_vm(
+        &self,
+        uuid: InstanceUuid,
+        options: &StopVmOptions,
+    ) -> Result<VmActionResponse> {
+        self.vm_action::<()>(uuid, "stop", options.to_pairs(), None)
+            .await
+    }
+
+    /// Reboot a running VM.
+    pub async fn reboot_vm(&self, uuid: InstanceUuid) -> Result<VmActionResponse> {
+        self.vm_action::<()>(uuid, "reboot", Vec::new(), None).await
+    }
+
+    /// Move a VM to another package with `POST /vms/:uuid?action=update`.
+    ///
+    /// VMAPI applies the package's RAM, CPU, and quota and records it as the
+    /// VM's `billing_id`. Use [`update_vm`](Self::update_vm) to override
+    /// individual limits without changing the package.
+    pub async fn resize_vm(
+        &self,
+        uuid: InstanceUuid,
+        package_uuid: PackageUuid,
+    ) -> Result<VmActionResponse> {
+        let request = ResizeVmRequest {
+            billing_id: package_uuid,
+        };
+        self.vm_action(uuid, "update", Vec::new(), Some(&request))
+            .await
+    }
+
+    /// Rebuild a VM's root dataset from `image_uuid`, keeping its UUID, NICs,
+    /// and delegated dataset.
+    pub async fn reprovision_vm(
+        &self,
+        uuid: InstanceUuid,
+        image_uuid: ImageUuid,
+    ) -> Result<VmActionResponse> {
+        let request = ReprovisionVmRequest { image_uuid };
+        self.vm_action(uuid, "reprovision", Vec::new(), Some(&request))
+            .await
+    }
+
+    /// Attach NICs on `networks` with `POST /vms/:uuid?action=add_nics`.
+    ///
+    /// VMAPI only changes NICs on a stopped VM; otherwise it answers `409`,
+    /// surfaced as [`Error::Conflict`].
+    pub async fn add_nics(
+        &self,
+        uuid: InstanceUuid,
+        networks: Vec<NetworkConfig>,
+    ) -> Result<VmActionResponse> {
+        let request = AddNicsRequest { networks };
+        self.vm_action(uuid, "add_nics", Vec::new(), Some(&request))
+            .await
+    }
+
+    /// Detach the NICs with the given MAC addresses with
+    /// `POST /vms/:uuid?action=remove_nics`.
+    ///
+    /// Like [`add_nics`](Self::add_nics), this fails with [`Error::Conflict`]
+    /// unless the VM is stopped.
+    pub async fn remove_nics(
+        &self,
+        uuid: InstanceUuid,
+        macs: Vec<String>,
+    ) -> Result<VmActionResponse> {
+        let request = RemoveNicsRequest { macs };
+        self.vm_action(uuid, "remove_nics", Vec::new(), Some(&request))
+            .await
+    }
+
+    /// `POST /vms/:uuid?action=<action>` with any action-specific parameters.
+    async fn vm_action<B>(
+        &self,
+        uuid: InstanceUuid,
+        action: &'static str,
+        options: Vec<QueryPair>,
+        body: Option<&B>,
+    ) -> Result<VmActionResponse>
+    where
+        B: Serialize + ?Sized,
+    {
+        let path = format!("vms/{}", encode_path_segment(uuid)?);
+        let mut params = vec![("action".into(), action.to_string())];
+        params.extend(options);
+        params.extend(self.owner_params());
+        self.send_json(Method::POST, &path, body, &params).await
+    }
+
+    /// Replace the RBAC role tags on a VM, returning the tags VMAPI now holds.
+    pub async fn set_vm_role_tags(
+        &self,
+        uuid: InstanceUuid,
+        role_tags: Vec<String>,
+    ) -> Result<Vec<String>> {
+        let path = format!("vms/{}/role_tags", encode_path_segment(uuid)?);
+        let request = RoleTagsRequest { role_tags };
+        self.send_json(Method::PUT, &path, Some(&request), &self.owner_params())
+            .await
+    }
+
+    /// Merge `metadata` into the VM's `customer_metadata` and return the result.
+    ///
+    /// Uses VMAPI's `POST /vms/:uuid/customer_metadata` sub-resource, which
+    /// adds or overwrites only the given keys, so keys set concurrently by
+    /// others survive. Keys are sent verbatim; dotted keys such as
+    /// `app.config.version` are flat names, not nested paths. Use
+    /// [`UpdateVMRequest::customer_metadata`] to replace the whole map.
+    pub async fn merge_customer_metadata(
+        &self,
+        uuid: InstanceUuid,
+        metadata: HashMap<String, String>,
+    ) -> Result<HashMap<String, String>> {
+        let path = format!("vms/{}/customer_metadata", encode_path_segment(uuid)?);
+        self.send_json(Method::POST, &path, Some(&metadata), &self.owner_params())
+            .await
+    }
+
+    /// Delete `keys` from the VM's `customer_metadata` and return what remains.
+    ///
+    /// Each key is removed with `DELETE /vms/:uuid/customer_metadata/:key`,
+    /// leaving every other key untouched. Keys are percent-encoded as a single
+    /// path segment, so dotted or `/`-containing keys address exactly one entry.
+    pub async fn delete_customer_metadata_keys(
+        &self,
+        uuid: InstanceUuid,
+        keys: Vec<String>,
+    ) -> Result<HashMap<String, String>> {
+        for key in keys {
+            self.delete_metadata(uuid, MetadataNamespace::Customer, &key)
+                .await?;
+        }
+        let base = format!("vms/{}/customer_metadata", encode_path_segment(uuid)?);
+        self.get_json(&base, &self.owner_params()).await
+    }
+
+    /// Read one metadata key with `GET /vms/:uuid/:namespace/:key`.
+    ///
+    /// The key is percent-encoded as a single path segment. A missing key
+    /// surfaces as the VMAPI `404` error.
+    pub async fn get_metadata(
+        &self,
+        uuid: InstanceUuid,
+        namespace: MetadataNamespace,
+        key: &str,
+    ) -> Result<String> {
+        let path = metadata_key_path(uuid, namespace, key)?;
+        self.get_json(&path, &self.owner_params()).await
+    }
+
+    /// Set one metadata key and return the namespace's updated map.
+    ///
+    /// Posts `{key: value}` to `/vms/:uuid/:namespace`, which adds or
+    /// overwrites only that key; other keys are left as they are.
+    pub async fn set_metadata(
+        &self,
+        uuid: InstanceUuid,
+        namespace: MetadataNamespace,
+        key: &str,
+        value: &str,
+    ) -> Result<HashMap<String, String>> {
+        let path = format!("vms/{}/{namespace}", encode_path_segment(uuid)?);
+        let body = HashMap::from([(key, value)]);
+        self.send_json(Method::POST, &path, Some(&body), &self.owner_params())
+            .await
+    }
+
+    /// Delete one metadata key with `DELETE /vms/:uuid/:namespace/:key`.
+    pub async fn delete_metadata(
+        &self,
+        uuid: InstanceUuid,
+        namespace: MetadataNamespace,
+        key: &str,
+    ) -> Result<()> {
+        let path = metadata_key_path(uuid, namespace, key)?;
+        self.send_json::<(), serde_json::Value>(Method::DELETE, &path, None, &self.owner_params())
             .await
+            .map(|_| ())
     }
 
     /// List VM snapshots.
     pub async fn list_snapshots(&self, uuid: InstanceUuid) -> Result<Vec<VmSnapshot>> {
-        let path = format!("vms/{uuid}/snapshots");
-        self.get_json(&path, &[]).await
+        let path = format!("vms/{}/snapshots", encode_path_segment(uuid)?);
+        self.get_json(&path, &self.owner_params()).await
     }
 
     /// Create a snapshot.
@@ -144,9 +646,33 @@ impl VmapiClient {
         uuid: InstanceUuid,
         request: &CreateSnapshotRequest,
     ) -> Result<SnapshotActionResponse> {
-        let path = format!("vms/{uuid}/snapshots");
-        self.send_json(Method::POST, &path, Some(request), &[])
+        let path = format!("vms/{}/snapshots", encode_path_segment(uuid)?);
+        self.send_json(Method::POST, &path, Some(request), &self.owner_params())
+            .await
+    }
+
+    /// Roll a VM back to a snapshot with
+    /// `POST /vms/:uuid/snapshots/:name?action=rollback`.
+    ///
+    /// VMAPI only rolls back stopped VMs; its `409` is returned as
+    /// [`Error::Conflict`] naming the snapshot.
+    pub async fn rollback_snapshot(
+        &self,
+        uuid: InstanceUuid,
+        snapshot: &str,
+    ) -> Result<SnapshotActionResponse> {
+        let path = snapshot_path(uuid, snapshot)?;
+        let mut params = vec![("action".into(), "rollback".to_string())];
+        params.extend(self.owner_params());
+        self.send_json::<(), SnapshotActionResponse>(Method::POST, &path, None, &params)
             .await
+            .map_err(|err| match err {
+                Error::Conflict(message) => Error::Conflict(format!(
+                    "cannot roll back VM {uuid} to snapshot '{snapshot}' (stop the VM first): \
+                     {message}"
+                )),
+                other => other,
+            })
     }
 
     /// Delete a snapshot.
@@ -155,15 +681,123 @@ impl VmapiClient {
         uuid: InstanceUuid,
         snapshot: &str,
     ) -> Result<SnapshotActionResponse> {
-        let path = format!("vms/{uuid}/snapshots/{snapshot}");
-        self.send_json::<(), SnapshotActionResponse>(Method::DELETE, &path, None, &[])
-            .await
+        let path = snapshot_path(uuid, snapshot)?;
+        self.send_json::<(), SnapshotActionResponse>(
+            Method::DELETE,
+            &path,
+            None,
+            &self.owner_params(),
+        )
+        .await
     }
 
     /// Execute a batch action on multiple VMs.
+    ///
+    /// A scoped client rejects batches naming another owner and stamps its
+    /// own owner onto batches that name none. When batch splitting is enabled
+    /// with [`VmapiClientBuilder::with_batch_split`], a batch rejected with
+    /// [`Error::PayloadTooLarge`] is split in half and resent, and the
+    /// per-chunk responses are merged in the original VM order.
     pub async fn batch_action(&self, request: &BatchVMRequest) -> Result<BatchVMResponse> {
-        self.send_json(Method::POST, "vms/actions", Some(request), &[])
-            .await
+        self.ensure_owner(request.owner_uuid)?;
+        let mut request = request.clone();
+        request.owner_uuid = request.owner_uuid.or(self.scoped_owner);
+
+        let mut pending = VecDeque::from([request.vm_uuids.clone()]);
+        let mut merged: Option<BatchVMResponse> = None;
+        while let Some(vm_uuids) = pending.pop_front() {
+            let chunk = BatchVMRequest {
+                vm_uuids,
+                ..request.clone()
+            };
+            let response = match self
+                .send_json(Method::POST, "vms/actions", Some(&chunk), &[])
+                .await
+            {
+                Err(Error::PayloadTooLarge(message)) => match self.batch_split_min {
+                    Some(min_chunk) if chunk.vm_uuids.len() > min_chunk => {
+                        let mut first = chunk.vm_uuids;
+                        let second = first.split_off(first.len() / 2);
+                        debug!(
+                            first = first.len(),
+                            second = second.len(),
+                            "Splitting VMAPI batch after 413"
+                        );
+                        pending.push_front(second);
+                        pending.push_front(first);
+                        continue;
+                    }
+                    _ => return Err(Error::PayloadTooLarge(message)),
+                },
+                other => other?,
+            };
+
+            merged = Some(match merged {
+                None => response,
+                Some(mut total) => {
+                    total.summary.total += response.summary.total;
+                    total.summary.succeeded += response.summary.succeeded;
+                    total.summary.failed += response.summary.failed;
+                    total.results.extend(response.results);
+                    total
+                }
+            });
+        }
+
+        merged.ok_or_else(|| Error::InternalError("VMAPI batch produced no response".into()))
+    }
+
+    /// Run `action` against each VM in `request` with single-VM calls.
+    ///
+    /// Unlike [`batch_action`](Self::batch_action), which hands the whole batch
+    /// to VMAPI, this issues one call per VM with at most `request.concurrency`
+    /// in flight (at least one). A failing VM does not stop the others: its
+    /// error is recorded in its [`VMBatchResult`], and results keep the order
+    /// of `request.vm_uuids`. `Stop` uses the default graceful
+    /// [`StopVmOptions`]. Only owner-scope violations fail the whole call.
+    pub async fn run_batch(
+        &self,
+        action: BatchVMAction,
+        request: BatchVMRequest,
+    ) -> Result<BatchVMResponse> {
+        self.ensure_owner(request.owner_uuid)?;
+        let client = Self {
+            scoped_owner: request.owner_uuid.or(self.scoped_owner),
+            ..self.clone()
+        };
+        let client = &client;
+        let action = &action;
+
+        let results: Vec<VMBatchResult> = stream::iter(request.vm_uuids)
+            .map(|vm_uuid| async move {
+                let outcome = match action {
+                    BatchVMAction::Start => client.start_vm(vm_uuid).await.map(drop),
+                    BatchVMAction::Stop => client
+                        .stop_vm(vm_uuid, &StopVmOptions::default())
+                        .await
+                        .map(drop),
+                    BatchVMAction::Reboot => client.reboot_vm(vm_uuid).await.map(drop),
+                    BatchVMAction::Delete => client.delete_vm(vm_uuid).await.map(drop),
+                };
+                VMBatchResult {
+                    vm_uuid,
+                    success: outcome.is_ok(),
+                    error: outcome.err().map(|err| err.to_string()),
+                }
+            })
+            .buffered(request.concurrency.max(1))
+            .collect()
+            .await;
+
+        let succeeded = results.iter().filter(|result| result.success).count();
+        Ok(BatchVMResponse {
+            summary: BatchSummary {
+                total: results.len(),
+                succeeded,
+                failed: results.len() - succeeded,
+            },
+            results,
+        })
     }
 
     /// List jobs.
@@ -171,7 +805,92 @@ impl VmapiClient {
         self.get_json("jobs", &params.to_pairs()).await
     }
 
-    async fn get_json<T>(&self, path: &str, params: &[(&'static str, String)]) -> Result<T>
+    /// Fetch a single job by UUID.
+    pub async fn get_job(&self, job_uuid: &str) -> Result<VmapiJob> {
+        let path = format!("jobs/{}", encode_path_segment(job_uuid)?);
+        self.get_json(&path, &[]).await
+    }
+
+    /// Poll a job until it finishes.
+    ///
+    /// Returns the job once it has `succeeded` or been `canceled`. A `failed`
+    /// job becomes [`Error::ExternalServiceError`] carrying the chain result
+    /// errors, and a job still running after `options.timeout` becomes a
+    /// deadline [`Error::Timeout`].
+    pub async fn wait_for_job(&self, job_uuid: &str, options: WaitOptions) -> Result<VmapiJob> {
+        poll_until(options, || async move {
+            let job = self.get_job(job_uuid).await?;
+            if job.execution == "failed" {
+                let errors: Vec<&str> = job
+                    .chain_results
+                    .iter()
+                    .flatten()
+                    .map(|result| result.error.as_str())
+                    .filter(|error| !error.is_empty())
+                    .collect();
+                let message = if errors.is_empty() {
+                    format!("job {job_uuid} ({}) failed", job.name)
+                } else {
+                    format!(
+                        "job {job_uuid} ({}) failed: {}",
+                        job.name,
+                        errors.join("; ")
+                    )
+                };
+                return Err(Error::ExternalServiceError {
+                    service: "vmapi".to_string(),
+                    message,
+                });
+            }
+            Ok(job.is_finished().then_some(job))
+        })
+        .await
+    }
+
+    /// Fetch any VMAPI path as raw JSON; see [`ServiceClient::get_raw`].
+    ///
+    /// Parameters for a scoped owner are appended as for typed calls.
+    pub async fn get_raw(&self, path: &str, params: &[QueryPair]) -> Result<serde_json::Value> {
+        let mut params = params.to_vec();
+        params.extend(self.owner_params());
+        self.inner.get_raw(path, &params, map_status_to_error).await
+    }
+
+    /// Send an arbitrary request to VMAPI and return the raw JSON response;
+    /// see [`ServiceClient::send_raw`].
+    pub async fn send_raw(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        self.inner
+            .send_raw(
+                method,
+                path,
+                body,
+                &self.owner_params(),
+                map_status_to_error,
+            )
+            .await
+    }
+
+    fn owner_params(&self) -> Vec<QueryPair> {
+        self.scoped_owner
+            .map(|owner| vec![("owner_uuid".into(), owner.to_string())])
+            .unwrap_or_default()
+    }
+
+    fn ensure_owner(&self, owner: Option<OwnerUuid>) -> Result<()> {
+        match (self.scoped_owner, owner) {
+            (Some(scoped), Some(owner)) if scoped != owner => Err(Error::InvalidRequest(format!(
+                "VMAPI client is scoped to owner {scoped}; refusing request for owner {owner}"
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    async fn get_json<T>(&self, path: &str, params: &[QueryPair]) -> Result<T>
     where
         T: DeserializeOwned,
     {
@@ -184,40 +903,102 @@ impl VmapiClient {
         method: Method,
         path: &str,
         body: Option<&B>,
-        params: &[(&'static str, String)],
+        params: &[QueryPair],
     ) -> Result<R>
     where
         B: Serialize + ?Sized,
         R: DeserializeOwned,
     {
-        let response = self
-            .inner
-            .execute_with_retry(
+        self.send_json_response(method, path, body, params)
+            .await
+            .map(|response| response.body)
+    }
+
+    async fn send_json_response<B, R>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&B>,
+        params: &[QueryPair],
+    ) -> Result<ServiceResponse<R>>
+    where
+        B: Serialize + ?Sized,
+        R: DeserializeOwned,
+    {
+        self.inner
+            .execute_json_response_with_retry(
                 method,
                 path,
                 params,
                 |mut request| {
                     request = request.header("Accept", "application/json");
                     if let Some(payload) = body {
-                        request = request.json(payload);
+                        request = self.inner.request_options().json_body(request, payload);
                     }
                     request
                 },
                 map_status_to_error,
             )
-            .await?;
+            .await
+    }
+}
 
-        response.json::<R>().await.map_err(|err| {
-            Error::SapiParseError(format!(
-                "Failed to parse VMAPI response for `{path}`: {err}"
-            ))
-        })
+#[async_trait]
+impl PreflightCheck for VmapiClient {
+    fn service(&self) -> TritonService {
+        TritonService::Vmapi
+    }
+
+    async fn preflight(&self) -> PreflightResult {
+        self.inner
+            .preflight("vms", &[("limit".into(), "1".to_string())])
+            .await
     }
 }
 
+fn snapshot_path(uuid: InstanceUuid, snapshot: &str) -> Result<String> {
+    Ok(format!(
+        "vms/{}/snapshots/{}",
+        encode_path_segment(uuid)?,
+        encode_path_segment(snapshot)?
+    ))
+}
+
+fn metadata_key_path(
+    uuid: InstanceUuid,
+    namespace: MetadataNamespace,
+    key: &str,
+) -> Result<String> {
+    Ok(format!(
+        "vms/{}/{namespace}/{}",
+        encode_path_segment(uuid)?,
+        encode_path_segment(key)?
+    ))
+}
+
+/// Headers VMAPI uses to report the total size of a listing.
+const RESOURCE_COUNT_HEADERS: [&str; 2] = ["x-joyent-resource-count", "x-resource-count"];
+
+fn resource_count(headers: &HeaderMap) -> Option<&str> {
+    RESOURCE_COUNT_HEADERS
+        .iter()
+        .find_map(|name| headers.get(*name)?.to_str().ok())
+}
+
+fn parse_resource_count(status: StatusCode, value: &str) -> Result<u64> {
+    value.trim().parse().map_err(|_| {
+        Error::http(
+            status,
+            format!("VMAPI returned an invalid resource count: {value}"),
+        )
+    })
+}
+
 fn map_status_to_error(status: StatusCode, text: String) -> Error {
     match status {
         StatusCode::NOT_FOUND => Error::NotFound(text),
+        StatusCode::CONFLICT => Error::Conflict(text),
+        StatusCode::PAYLOAD_TOO_LARGE => Error::PayloadTooLarge(text),
         StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
             Error::InvalidRequest(format!("VMAPI authentication failed: {text}"))
         }
@@ -230,7 +1011,7 @@ fn map_status_to_error(status: StatusCode, text: String) -> Error {
         status if status.is_server_error() => {
             Error::ServiceUnavailable(format!("VMAPI server error {status}: {text}"))
         }
-        _ => Error::HttpError(format!("VMAPI error {status}: {text}")),
+        _ => Error::http(status, format!("VMAPI error {status}: {text}")),
     }
 }
 
@@ -274,9 +1055,12 @@ pub type VmQuery = VMListParams;
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::SortSpec;
     use serde_json::json;
-    use triton_core::uuid::{ImageUuid, OwnerUuid};
-    use wiremock::matchers::{body_json, method, path};
+    use triton_core::types::ServiceEndpoint;
+    use triton_core::uuid::NetworkUuid;
+    use triton_core::TimeoutKind;
+    use wiremock::matchers::{body_json, header, method, path, query_param};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     fn test_client(server: &MockServer) -> VmapiClient {
@@ -308,8 +1092,452 @@ mod tests {
         assert_eq!(vms[0].alias.as_deref(), Some("vm-01"));
     }
 
+    async fn mount_alias_listing(server: &MockServer, owner: OwnerUuid, vms: serde_json::Value) {
+        Mock::given(method("GET"))
+            .and(path("/vms"))
+            .and(query_param("owner_uuid", owner.to_string()))
+            .and(query_param("alias", "web-01"))
+            .and(query_param("state", "active"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(vms))
+            .expect(1)
+            .mount(server)
+            .await;
+    }
+
     #[tokio::test]
-    async fn get_vm_not_found() {
+    async fn get_vm_by_alias_returns_unique_match() {
+        let server = MockServer::start().await;
+        let owner = OwnerUuid::new_v4();
+        let uuid = InstanceUuid::new_v4();
+        mount_alias_listing(
+            &server,
+            owner,
+            json!([
+                { "uuid": uuid, "alias": "web-01", "state": "running" },
+                { "uuid": InstanceUuid::new_v4(), "alias": "web-010", "state": "running" }
+            ]),
+        )
+        .await;
+
+        let client = test_client(&server);
+        let vm = client.get_vm_by_alias(owner, "web-01").await.unwrap();
+        assert_eq!(vm.uuid, uuid);
+    }
+
+    #[tokio::test]
+    async fn get_vm_by_alias_not_found() {
+        let server = MockServer::start().await;
+        let owner = OwnerUuid::new_v4();
+        mount_alias_listing(&server, owner, json!([])).await;
+
+        let client = test_client(&server);
+        let err = client.get_vm_by_alias(owner, "web-01").await.unwrap_err();
+        assert!(matches!(err, Error::NotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn get_vm_by_alias_rejects_duplicates() {
+        let server = MockServer::start().await;
+        let owner = OwnerUuid::new_v4();
+        let first = InstanceUuid::new_v4();
+        let second = InstanceUuid::new_v4();
+        mount_alias_listing(
+            &server,
+            owner,
+            json!([
+                { "uuid": first, "alias": "web-01", "state": "running" },
+                { "uuid": second, "alias": "web-01", "state": "stopped" }
+            ]),
+        )
+        .await;
+
+        let client = test_client(&server);
+        let err = client.get_vm_by_alias(owner, "web-01").await.unwrap_err();
+        let Error::Conflict(message) = err else {
+            panic!("expected conflict, got {err:?}");
+        };
+        assert!(message.contains(&first.to_string()));
+        assert!(message.contains(&second.to_string()));
+    }
+
+    #[tokio::test]
+    async fn list_vms_with_headers_surfaces_metadata() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/vms"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("ETag", "\"vms-v1\"")
+                    .insert_header("x-resource-count", "42")
+                    .set_body_json(json!([
+                        { "uuid": InstanceUuid::new_v4(), "state": "running" }
+                    ])),
+            )
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let response = client
+            .list_vms_with_headers(&VMListParams::default())
+            .await
+            .unwrap();
+        assert_eq!(response.status, StatusCode::OK);
+        assert_eq!(response.header("etag"), Some("\"vms-v1\""));
+        assert_eq!(response.header("x-resource-count"), Some("42"));
+        assert_eq!(response.body.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn count_vms_reads_header_from_head() {
+        let server = MockServer::start().await;
+        let owner = OwnerUuid::new_v4();
+        Mock::given(method("HEAD"))
+            .and(path("/vms"))
+            .and(query_param("owner_uuid", owner.to_string()))
+            .and(query_param("state", "running"))
+            .respond_with(
+                ResponseTemplate::new(200).insert_header("x-joyent-resource-count", "1234"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let params = VMListParams {
+            owner_uuid: Some(owner),
+            state: Some("running".to_string()),
+            ..VMListParams::default()
+        };
+        assert_eq!(client.count_vms(&params).await.unwrap(), 1234);
+    }
+
+    #[tokio::test]
+    async fn count_vms_falls_back_to_get() {
+        let server = MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .and(path("/vms"))
+            .respond_with(ResponseTemplate::new(405))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/vms"))
+            .and(query_param("limit", "1"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("x-joyent-resource-count", "7")
+                    .set_body_json(json!([{ "uuid": InstanceUuid::new_v4() }])),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        assert_eq!(client.count_vms(&VMListParams::default()).await.unwrap(), 7);
+    }
+
+    #[tokio::test]
+    async fn count_vms_requires_header() {
+        let server = MockServer::start().await;
+        Mock::given(path("/vms"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let err = client
+            .count_vms(&VMListParams::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::HttpError {
+                status: Some(StatusCode::OK),
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn list_vms_stream_keeps_sort_order_across_pages() {
+        let server = MockServer::start().await;
+        for (offset, aliases) in [("0", vec!["vm-c", "vm-b"]), ("2", vec!["vm-a"])] {
+            let page: Vec<_> = aliases
+                .into_iter()
+                .map(|alias| json!({ "uuid": InstanceUuid::new_v4(), "alias": alias }))
+                .collect();
+            Mock::given(method("GET"))
+                .and(path("/vms"))
+                .and(query_param("sort", "alias.desc"))
+                .and(query_param("limit", "2"))
+                .and(query_param("offset", offset))
+                .respond_with(ResponseTemplate::new(200).set_body_json(page))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let client = test_client(&server);
+        let params = VMListParams {
+            limit: Some(2),
+            ..Default::default()
+        }
+        .sorted_by(&SortSpec::desc("alias"));
+        let aliases: Vec<String> = client
+            .list_vms_stream(params)
+            .map(|vm| vm.unwrap().alias.unwrap())
+            .collect()
+            .await;
+        assert_eq!(aliases, vec!["vm-c", "vm-b", "vm-a"]);
+    }
+
+    #[tokio::test]
+    async fn list_vms_rejects_limit_above_max() {
+        let server = MockServer::start().await;
+        let client = test_client(&server);
+        let params = VMListParams {
+            limit: Some(VMListParams::MAX_LIMIT + 1),
+            ..Default::default()
+        };
+
+        let err = client.list_vms(&params).await.unwrap_err();
+        assert!(matches!(err, Error::BadRequest(_)));
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn list_vms_stream_splits_oversized_limit() {
+        let server = MockServer::start().await;
+        for (offset, count) in [("0", 1000), ("1000", 500)] {
+            let page: Vec<_> = (0..count)
+                .map(|_| json!({ "uuid": InstanceUuid::new_v4() }))
+                .collect();
+            Mock::given(method("GET"))
+                .and(path("/vms"))
+                .and(query_param("limit", "1000"))
+                .and(query_param("offset", offset))
+                .respond_with(ResponseTemplate::new(200).set_body_json(page))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let client = test_client(&server);
+        let params = VMListParams {
+            limit: Some(5000),
+            ..Default::default()
+        };
+        let vms: Vec<_> = client.list_vms_stream(params).collect().await;
+        assert_eq!(vms.len(), 1500);
+        assert!(vms.iter().all(Result::is_ok));
+    }
+
+    #[tokio::test]
+    async fn list_vms_stream_concatenates_pages_in_order() {
+        let server = MockServer::start().await;
+        let pages = [
+            ("0", vec!["vm-1", "vm-2"]),
+            ("2", vec!["vm-3", "vm-4"]),
+            ("4", vec!["vm-5"]),
+        ];
+        for (offset, aliases) in pages {
+            let page: Vec<_> = aliases
+                .into_iter()
+                .map(|alias| json!({ "uuid": InstanceUuid::new_v4(), "alias": alias }))
+                .collect();
+            Mock::given(method("GET"))
+                .and(path("/vms"))
+                .and(query_param("limit", "2"))
+                .and(query_param("offset", offset))
+                .respond_with(ResponseTemplate::new(200).set_body_json(page))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let client = test_client(&server);
+        let aliases: Vec<String> = client
+            .list_vms_stream(VMListParams {
+                limit: Some(2),
+                ..Default::default()
+            })
+            .map(|vm| vm.unwrap().alias.unwrap())
+            .collect()
+            .await;
+        assert_eq!(aliases, vec!["vm-1", "vm-2", "vm-3", "vm-4", "vm-5"]);
+    }
+
+    #[tokio::test]
+    async fn list_vms_stream_yields_error_and_stops() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/vms"))
+            .and(query_param("offset", "0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "uuid": InstanceUuid::new_v4() },
+                { "uuid": InstanceUuid::new_v4() }
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/vms"))
+            .and(query_param("offset", "2"))
+            .respond_with(ResponseTemplate::new(404).set_body_string("gone"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let items: Vec<_> = client
+            .list_vms_stream(VMListParams {
+                limit: Some(2),
+                ..Default::default()
+            })
+            .collect()
+            .await;
+        assert_eq!(items.len(), 3);
+        assert!(items[..2].iter().all(Result::is_ok));
+        assert!(matches!(items[2], Err(Error::NotFound(_))));
+    }
+
+    fn job_in_state(execution: &str) -> serde_json::Value {
+        json!({
+            "uuid": "job-1",
+            "name": "provision-abc",
+            "execution": execution,
+            "params": {}
+        })
+    }
+
+    fn fast_job_wait() -> WaitOptions {
+        WaitOptions::default()
+            .with_poll_interval(Duration::from_millis(10))
+            .with_timeout(Duration::from_secs(5))
+    }
+
+    #[tokio::test]
+    async fn wait_for_job_polls_until_succeeded() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/jobs/job-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(job_in_state("queued")))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/jobs/job-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(job_in_state("running")))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/jobs/job-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(job_in_state("succeeded")))
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let job = client.wait_for_job("job-1", fast_job_wait()).await.unwrap();
+        assert_eq!(job.execution, "succeeded");
+        assert_eq!(server.received_requests().await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn wait_for_job_surfaces_chain_result_errors() {
+        let server = MockServer::start().await;
+        let mut failed = job_in_state("failed");
+        failed["chain_results"] = json!([
+            {
+                "result": "OK",
+                "error": "",
+                "started_at": "2024-01-01T00:00:00Z",
+                "finished_at": "2024-01-01T00:00:01Z"
+            },
+            {
+                "result": "",
+                "error": "no compute resources available",
+                "started_at": "2024-01-01T00:00:01Z",
+                "finished_at": "2024-01-01T00:00:02Z"
+            }
+        ]);
+        Mock::given(method("GET"))
+            .and(path("/jobs/job-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(failed))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let err = client
+            .wait_for_job("job-1", fast_job_wait())
+            .await
+            .unwrap_err();
+
+        match err {
+            Error::ExternalServiceError { service, message } => {
+                assert_eq!(service, "vmapi");
+                assert!(message.contains("no compute resources available"));
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn wait_for_job_times_out() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/jobs/job-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(job_in_state("running")))
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let options = fast_job_wait().with_timeout(Duration::from_millis(50));
+        let err = client.wait_for_job("job-1", options).await.unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Timeout {
+                kind: TimeoutKind::Deadline,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn from_config_applies_service_timeout() {
+        use triton_core::config::{ServiceEndpointConfig, ServiceEndpoints, ServiceTimeouts};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/vms"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!([]))
+                    .set_delay(Duration::from_secs(2)),
+            )
+            .mount(&server)
+            .await;
+
+        let mut config = TritonClientConfig::default()
+            .with_max_retries(0)
+            .with_service_timeouts(
+                ServiceTimeouts::new()
+                    .with_timeout(TritonService::Vmapi, Duration::from_millis(100)),
+            );
+        config.service_discovery.services =
+            ServiceEndpoints::new().with_vmapi(ServiceEndpointConfig::new(server.uri()).unwrap());
+
+        let client = VmapiClientBuilder::from_config(&config)
+            .unwrap()
+            .build()
+            .unwrap();
+        let err = client.list_vms(&VMListParams::default()).await.unwrap_err();
+        assert!(matches!(err, Error::Timeout { .. }), "{err:?}");
+    }
+
+    #[tokio::test]
+    async fn get_vm_not_found() {
         let server = MockServer::start().await;
         let uuid = InstanceUuid::new_v4();
 
@@ -372,6 +1600,593 @@ mod tests {
         assert_eq!(job.execution, "running");
     }
 
+    #[tokio::test]
+    async fn set_vm_role_tags_sends_tags() {
+        let server = MockServer::start().await;
+        let uuid = InstanceUuid::new_v4();
+
+        Mock::given(method("PUT"))
+            .and(path(format!("/vms/{uuid}/role_tags").as_str()))
+            .and(body_json(json!({ "role_tags": ["devs", "operators"] })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!(["devs", "operators"])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let tags = client
+            .set_vm_role_tags(uuid, vec!["devs".into(), "operators".into()])
+            .await
+            .unwrap();
+        assert_eq!(tags, vec!["devs", "operators"]);
+    }
+
+    #[tokio::test]
+    async fn delete_snapshot_encodes_snapshot_name() {
+        let server = MockServer::start().await;
+        let uuid = InstanceUuid::new_v4();
+
+        Mock::given(method("DELETE"))
+            .and(path(
+                format!("/vms/{uuid}/snapshots/nightly%20backup%2F1").as_str(),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "name": "nightly backup/1",
+                "state": "deleted"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let response = client
+            .delete_snapshot(uuid, "nightly backup/1")
+            .await
+            .unwrap();
+        assert_eq!(response.name, "nightly backup/1");
+    }
+
+    #[tokio::test]
+    async fn rollback_snapshot_posts_rollback_action() {
+        let server = MockServer::start().await;
+        let uuid = InstanceUuid::new_v4();
+        Mock::given(method("POST"))
+            .and(path(format!("/vms/{uuid}/snapshots/pre-upgrade").as_str()))
+            .and(query_param("action", "rollback"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(json!({
+                "name": "pre-upgrade",
+                "state": "rolling_back",
+                "job_uuid": "job-rollback"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let response = client.rollback_snapshot(uuid, "pre-upgrade").await.unwrap();
+        assert_eq!(response.job_uuid.as_deref(), Some("job-rollback"));
+    }
+
+    #[tokio::test]
+    async fn rollback_snapshot_on_running_vm_conflicts() {
+        let server = MockServer::start().await;
+        let uuid = InstanceUuid::new_v4();
+        Mock::given(method("POST"))
+            .and(path(format!("/vms/{uuid}/snapshots/pre-upgrade").as_str()))
+            .respond_with(ResponseTemplate::new(409).set_body_json(json!({
+                "code": "VmNotStopped",
+                "message": "VM must be stopped"
+            })))
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let err = client
+            .rollback_snapshot(uuid, "pre-upgrade")
+            .await
+            .unwrap_err();
+        let Error::Conflict(message) = err else {
+            panic!("expected conflict, got {err:?}");
+        };
+        assert!(message.contains("'pre-upgrade'"));
+        assert!(message.contains("stop the VM first"));
+    }
+
+    #[tokio::test]
+    async fn merge_customer_metadata_posts_to_sub_resource() {
+        let server = MockServer::start().await;
+        let uuid = InstanceUuid::new_v4();
+        Mock::given(method("POST"))
+            .and(path(format!("/vms/{uuid}/customer_metadata").as_str()))
+            .and(body_json(json!({ "app.config.version": "42" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "app.config.version": "42",
+                "user-script": "echo hi"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let merged = client
+            .merge_customer_metadata(
+                uuid,
+                HashMap::from([("app.config.version".to_string(), "42".to_string())]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged["app.config.version"], "42");
+        assert_eq!(merged["user-script"], "echo hi");
+    }
+
+    #[tokio::test]
+    async fn delete_customer_metadata_keys_deletes_each_key() {
+        let server = MockServer::start().await;
+        let uuid = InstanceUuid::new_v4();
+        for key in ["app.config.version", "deploy%2Flock"] {
+            Mock::given(method("DELETE"))
+                .and(path(
+                    format!("/vms/{uuid}/customer_metadata/{key}").as_str(),
+                ))
+                .respond_with(ResponseTemplate::new(204))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+        Mock::given(method("GET"))
+            .and(path(format!("/vms/{uuid}/customer_metadata").as_str()))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!({ "user-script": "echo hi" })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let remaining = client
+            .delete_customer_metadata_keys(
+                uuid,
+                vec!["app.config.version".into(), "deploy/lock".into()],
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            remaining,
+            HashMap::from([("user-script".to_string(), "echo hi".to_string())])
+        );
+    }
+
+    #[tokio::test]
+    async fn start_and_reboot_post_action() {
+        let server = MockServer::start().await;
+        let uuid = InstanceUuid::new_v4();
+        for action in ["start", "reboot"] {
+            Mock::given(method("POST"))
+                .and(path(format!("/vms/{uuid}").as_str()))
+                .and(query_param("action", action))
+                .respond_with(ResponseTemplate::new(202).set_body_json(json!({
+                    "vm_uuid": uuid,
+                    "job_uuid": format!("job-{action}")
+                })))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let client = test_client(&server);
+        let started = client.start_vm(uuid).await.unwrap();
+        assert_eq!(started.vm_uuid, uuid);
+        assert_eq!(started.job_uuid, "job-start");
+        let rebooted = client.reboot_vm(uuid).await.unwrap();
+        assert_eq!(rebooted.job_uuid, "job-reboot");
+    }
+
+    #[tokio::test]
+    async fn stop_vm_sends_force_and_timeout() {
+        let server = MockServer::start().await;
+        let uuid = InstanceUuid::new_v4();
+        Mock::given(method("POST"))
+            .and(path(format!("/vms/{uuid}").as_str()))
+            .and(query_param("action", "stop"))
+            .and(query_param("force", "true"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(json!({
+                "vm_uuid": uuid,
+                "job_uuid": "job-forced"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(format!("/vms/{uuid}").as_str()))
+            .and(query_param("action", "stop"))
+            .and(query_param("timeout", "30"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(json!({
+                "vm_uuid": uuid,
+                "job_uuid": "job-graceful"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let forced = client
+            .stop_vm(uuid, &StopVmOptions::forced())
+            .await
+            .unwrap();
+        assert_eq!(forced.job_uuid, "job-forced");
+        let graceful = client
+            .stop_vm(uuid, &StopVmOptions::default().with_timeout(30))
+            .await
+            .unwrap();
+        assert_eq!(graceful.job_uuid, "job-graceful");
+    }
+
+    #[test]
+    fn stop_options_omit_defaults() {
+        assert!(StopVmOptions::default().to_pairs().is_empty());
+    }
+
+    #[tokio::test]
+    async fn resize_vm_posts_update_with_billing_id() {
+        let server = MockServer::start().await;
+        let uuid = InstanceUuid::new_v4();
+        let package = PackageUuid::new_v4();
+        Mock::given(method("POST"))
+            .and(path(format!("/vms/{uuid}").as_str()))
+            .and(query_param("action", "update"))
+            .and(body_json(json!({ "billing_id": package })))
+            .respond_with(ResponseTemplate::new(202).set_body_json(json!({
+                "vm_uuid": uuid,
+                "job_uuid": "job-resize"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let response = client.resize_vm(uuid, package).await.unwrap();
+        assert_eq!(response.job_uuid, "job-resize");
+    }
+
+    #[tokio::test]
+    async fn reprovision_vm_posts_image_uuid() {
+        let server = MockServer::start().await;
+        let uuid = InstanceUuid::new_v4();
+        let image = ImageUuid::new_v4();
+        Mock::given(method("POST"))
+            .and(path(format!("/vms/{uuid}").as_str()))
+            .and(query_param("action", "reprovision"))
+            .and(body_json(json!({ "image_uuid": image })))
+            .respond_with(ResponseTemplate::new(202).set_body_json(json!({
+                "vm_uuid": uuid,
+                "job_uuid": "job-reprovision"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let response = client.reprovision_vm(uuid, image).await.unwrap();
+        assert_eq!(response.job_uuid, "job-reprovision");
+    }
+
+    #[tokio::test]
+    async fn add_nics_posts_networks() {
+        let server = MockServer::start().await;
+        let uuid = InstanceUuid::new_v4();
+        let network = NetworkUuid::new_v4();
+        Mock::given(method("POST"))
+            .and(path(format!("/vms/{uuid}").as_str()))
+            .and(query_param("action", "add_nics"))
+            .and(body_json(json!({
+                "networks": [{ "uuid": network, "primary": true }]
+            })))
+            .respond_with(ResponseTemplate::new(202).set_body_json(json!({
+                "vm_uuid": uuid,
+                "job_uuid": "job-add-nics"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let response = client
+            .add_nics(
+                uuid,
+                vec![NetworkConfig {
+                    uuid: network,
+                    primary: Some(true),
+                    ip: None,
+                }],
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.job_uuid, "job-add-nics");
+    }
+
+    #[tokio::test]
+    async fn remove_nics_posts_macs() {
+        let server = MockServer::start().await;
+        let uuid = InstanceUuid::new_v4();
+        Mock::given(method("POST"))
+            .and(path(format!("/vms/{uuid}").as_str()))
+            .and(query_param("action", "remove_nics"))
+            .and(body_json(json!({ "macs": ["90:b8:d0:00:00:01"] })))
+            .respond_with(ResponseTemplate::new(202).set_body_json(json!({
+                "vm_uuid": uuid,
+                "job_uuid": "job-remove-nics"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let response = client
+            .remove_nics(uuid, vec!["90:b8:d0:00:00:01".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(response.job_uuid, "job-remove-nics");
+    }
+
+    #[tokio::test]
+    async fn nic_changes_on_running_vm_conflict() {
+        let server = MockServer::start().await;
+        let uuid = InstanceUuid::new_v4();
+        Mock::given(method("POST"))
+            .and(path(format!("/vms/{uuid}").as_str()))
+            .respond_with(ResponseTemplate::new(409).set_body_json(json!({
+                "code": "VmNotStopped",
+                "message": "VM must be stopped"
+            })))
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let err = client
+            .remove_nics(uuid, vec!["90:b8:d0:00:00:01".to_string()])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Conflict(message) if message.contains("VM must be stopped")));
+    }
+
+    #[tokio::test]
+    async fn run_batch_records_per_vm_failures() {
+        let server = MockServer::start().await;
+        let found = [InstanceUuid::new_v4(), InstanceUuid::new_v4()];
+        let missing = InstanceUuid::new_v4();
+        for uuid in found {
+            Mock::given(method("POST"))
+                .and(path(format!("/vms/{uuid}").as_str()))
+                .and(query_param("action", "reboot"))
+                .respond_with(ResponseTemplate::new(202).set_body_json(json!({
+                    "vm_uuid": uuid,
+                    "job_uuid": format!("job-{uuid}")
+                })))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+        Mock::given(method("POST"))
+            .and(path(format!("/vms/{missing}").as_str()))
+            .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+                "code": "ResourceNotFound",
+                "message": "VM not found"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let request = BatchVMRequest {
+            vm_uuids: vec![found[0], missing, found[1]],
+            concurrency: 2,
+            owner_uuid: None,
+        };
+        let response = client
+            .run_batch(BatchVMAction::Reboot, request)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response.summary,
+            BatchSummary {
+                total: 3,
+                succeeded: 2,
+                failed: 1,
+            }
+        );
+        let order: Vec<_> = response.results.iter().map(|r| r.vm_uuid).collect();
+        assert_eq!(order, vec![found[0], missing, found[1]]);
+        assert!(!response.results[1].success);
+        assert!(response.results[1]
+            .error
+            .as_deref()
+            .is_some_and(|error| error.contains("VM not found")));
+    }
+
+    #[tokio::test]
+    async fn get_metadata_reads_single_key() {
+        let server = MockServer::start().await;
+        let uuid = InstanceUuid::new_v4();
+        Mock::given(method("GET"))
+            .and(path(
+                format!("/vms/{uuid}/internal_metadata/deploy%2Flock").as_str(),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!("held")))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let value = client
+            .get_metadata(uuid, MetadataNamespace::Internal, "deploy/lock")
+            .await
+            .unwrap();
+        assert_eq!(value, "held");
+    }
+
+    #[tokio::test]
+    async fn set_metadata_posts_single_key() {
+        let server = MockServer::start().await;
+        let uuid = InstanceUuid::new_v4();
+        Mock::given(method("POST"))
+            .and(path(format!("/vms/{uuid}/internal_metadata").as_str()))
+            .and(body_json(json!({ "deploy.stage": "canary" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "deploy.stage": "canary",
+                "owner": "ops"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let updated = client
+            .set_metadata(uuid, MetadataNamespace::Internal, "deploy.stage", "canary")
+            .await
+            .unwrap();
+        assert_eq!(updated.len(), 2);
+        assert_eq!(updated["deploy.stage"], "canary");
+    }
+
+    #[tokio::test]
+    async fn delete_metadata_deletes_single_key() {
+        let server = MockServer::start().await;
+        let uuid = InstanceUuid::new_v4();
+        Mock::given(method("DELETE"))
+            .and(path(
+                format!("/vms/{uuid}/customer_metadata/user-script").as_str(),
+            ))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        client
+            .delete_metadata(uuid, MetadataNamespace::Customer, "user-script")
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn delete_snapshot_rejects_dot_segments() {
+        let server = MockServer::start().await;
+        let client = test_client(&server);
+
+        let err = client
+            .delete_snapshot(InstanceUuid::new_v4(), "..")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidRequest(_)));
+    }
+
+    #[tokio::test]
+    async fn set_deletion_protection_sends_flag() {
+        let server = MockServer::start().await;
+        let uuid = InstanceUuid::new_v4();
+
+        Mock::given(method("PUT"))
+            .and(path(format!("/vms/{uuid}").as_str()))
+            .and(body_json(json!({ "deletion_protection": true })))
+            .respond_with(ResponseTemplate::new(202).set_body_json(json!({
+                "uuid": "job-1",
+                "name": "update-vm",
+                "execution": "queued",
+                "params": {}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+        let job = client.set_deletion_protection(uuid, true).await.unwrap();
+        assert_eq!(job.uuid, "job-1");
+    }
+
+    #[test]
+    fn update_vm_request_serializes_protection_flags() {
+        let request = UpdateVMRequest {
+            indestructible_zoneroot: Some(true),
+            indestructible_delegated: Some(false),
+            ..UpdateVMRequest::default()
+        };
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            json!({ "indestructible_zoneroot": true, "indestructible_delegated": false })
+        );
+    }
+
+    #[tokio::test]
+    async fn custom_content_type_is_sent_with_json_body() {
+        let server = MockServer::start().await;
+        let uuid = InstanceUuid::new_v4();
+
+        Mock::given(method("PUT"))
+            .and(path(format!("/vms/{uuid}/role_tags").as_str()))
+            .and(header("content-type", "application/json; charset=utf-8"))
+            .and(body_json(json!({ "role_tags": ["devs"] })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!(["devs"])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = VmapiClientBuilder::new(server.uri())
+            .unwrap()
+            .with_request_options(
+                RequestOptions::new().with_content_type("application/json; charset=utf-8"),
+            )
+            .build()
+            .unwrap();
+        let tags = client
+            .set_vm_role_tags(uuid, vec!["devs".into()])
+            .await
+            .unwrap();
+        assert_eq!(tags, vec!["devs"]);
+    }
+
+    #[tokio::test]
+    async fn pinned_endpoint_receives_every_request() {
+        let uuid = InstanceUuid::new_v4();
+        let mut servers = Vec::new();
+        for _ in 0..2 {
+            let server = MockServer::start().await;
+            Mock::given(method("PUT"))
+                .and(path(format!("/vms/{uuid}/role_tags").as_str()))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!(["devs"])))
+                .mount(&server)
+                .await;
+            servers.push(server);
+        }
+        let endpoints = EndpointList::from_endpoints(
+            servers
+                .iter()
+                .enumerate()
+                .map(|(index, server)| {
+                    ServiceEndpoint::new(index.to_string(), "vmapi".into(), *server.address())
+                })
+                .collect(),
+        );
+
+        let client = VmapiClientBuilder::new("http://127.0.0.1:9")
+            .unwrap()
+            .with_endpoints(endpoints, EndpointSelector::default())
+            .pin_endpoint(servers[1].uri())
+            .build()
+            .unwrap();
+        for _ in 0..3 {
+            client
+                .set_vm_role_tags(uuid, vec!["devs".into()])
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(client.pinned_endpoint().as_deref(), Some("1"));
+        assert!(servers[0].received_requests().await.unwrap().is_empty());
+        assert_eq!(servers[1].received_requests().await.unwrap().len(), 3);
+    }
+
     #[tokio::test]
     async fn batch_action_success() {
         let server = MockServer::start().await;
@@ -388,8 +2203,131 @@ mod tests {
         let request = BatchVMRequest {
             vm_uuids: vec![InstanceUuid::new_v4()],
             concurrency: 5,
+            owner_uuid: None,
         };
         let response = client.batch_action(&request).await.unwrap();
         assert_eq!(response.summary.succeeded, 2);
     }
+
+    #[tokio::test]
+    async fn batch_action_splits_on_payload_too_large() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/vms/actions"))
+            .respond_with(|request: &wiremock::Request| {
+                let body: BatchVMRequest = serde_json::from_slice(&request.body).unwrap();
+                if body.vm_uuids.len() > 2 {
+                    return ResponseTemplate::new(413).set_body_json(json!({
+                        "code": "PayloadTooLarge",
+                        "message": "body too large"
+                    }));
+                }
+                let results: Vec<_> = body
+                    .vm_uuids
+                    .iter()
+                    .map(|uuid| json!({ "vm_uuid": uuid, "success": true }))
+                    .collect();
+                ResponseTemplate::new(200).set_body_json(json!({
+                    "summary": { "total": results.len(), "succeeded": results.len(), "failed": 0 },
+                    "results": results
+                }))
+            })
+            .mount(&server)
+            .await;
+
+        let vm_uuids: Vec<_> = (0..5).map(|_| InstanceUuid::new_v4()).collect();
+        let request = BatchVMRequest {
+            vm_uuids: vm_uuids.clone(),
+            concurrency: 5,
+            owner_uuid: None,
+        };
+
+        let err = test_client(&server)
+            .batch_action(&request)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::PayloadTooLarge(_)));
+
+        let client = VmapiClientBuilder::new(server.uri())
+            .unwrap()
+            .with_batch_split(1)
+            .build()
+            .unwrap();
+        let response = client.batch_action(&request).await.unwrap();
+        assert_eq!(response.summary.total, 5);
+        assert_eq!(response.summary.succeeded, 5);
+        let ordered: Vec<_> = response.results.iter().map(|r| r.vm_uuid).collect();
+        assert_eq!(ordered, vm_uuids);
+
+        let chunk_sizes: Vec<usize> = server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .skip(1)
+            .map(|request| {
+                serde_json::from_slice::<BatchVMRequest>(&request.body)
+                    .unwrap()
+                    .vm_uuids
+                    .len()
+            })
+            .collect();
+        assert_eq!(chunk_sizes, vec![5, 2, 3, 1, 2]);
+    }
+
+    #[tokio::test]
+    async fn scoped_owner_is_appended_and_mismatch_is_not_found() {
+        let server = MockServer::start().await;
+        let owner = OwnerUuid::new_v4();
+        let own_vm = InstanceUuid::new_v4();
+        let foreign_vm = InstanceUuid::new_v4();
+
+        Mock::given(method("GET"))
+            .and(path(format!("/vms/{own_vm}").as_str()))
+            .and(query_param("owner_uuid", owner.to_string().as_str()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "uuid": own_vm,
+                "owner_uuid": owner
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/vms/{foreign_vm}").as_str()))
+            .and(query_param("owner_uuid", owner.to_string().as_str()))
+            .respond_with(ResponseTemplate::new(404).set_body_string("vm not found"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = VmapiClientBuilder::new(server.uri())
+            .unwrap()
+            .with_scoped_owner(owner)
+            .build()
+            .unwrap();
+
+        let vm = client.get_vm(own_vm).await.unwrap();
+        assert_eq!(vm.uuid, own_vm);
+
+        let err = client.get_vm(foreign_vm).await.unwrap_err();
+        assert!(matches!(err, Error::NotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn scoped_owner_rejects_cross_owner_batch() {
+        let server = MockServer::start().await;
+        let client = VmapiClientBuilder::new(server.uri())
+            .unwrap()
+            .with_scoped_owner(OwnerUuid::new_v4())
+            .build()
+            .unwrap();
+
+        let request = BatchVMRequest {
+            vm_uuids: vec![InstanceUuid::new_v4()],
+            concurrency: 5,
+            owner_uuid: Some(OwnerUuid::new_v4()),
+        };
+        let err = client.batch_action(&request).await.unwrap_err();
+        assert!(matches!(err, Error::InvalidRequest(_)));
+    }
 }
@@ -10,9 +10,11 @@ pub mod models;
 
 pub use client::{VmQuery, VmapiClient, VmapiClientBuilder};
 pub use models::{
-    BatchSummary, BatchVMRequest, BatchVMResponse, ChainResult, CreateSnapshotRequest,
-    CreateVMRequest, JobListParams, NetworkConfig, Nic, SnapshotActionResponse, UpdateVMRequest,
-    VMListParams, Vm, VmSnapshot, VmapiJob,
+    AddNicsRequest, BatchSummary, BatchVMAction, BatchVMRequest, BatchVMResponse, ChainResult,
+    CreateSnapshotRequest, CreateVMRequest, JobListParams, MetadataNamespace, NetworkConfig, Nic,
+    RemoveNicsRequest, ReprovisionVmRequest, ResizeVmRequest, RoleTagsRequest,
+    SnapshotActionResponse, SortOrder, SortSpec, StopVmOptions, UpdateVMRequest, VMBatchResult,
+    VMListParams, Vm, VmActionResponse, VmSnapshot, VmapiJob,
 };
 
 /// Convenient result alias that reuses the shared Triton error type.
@@ -1,9 +1,11 @@
 //! VMAPI models shared by client and prospective server implementations.
 
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize};
 use std::collections::HashMap;
-use triton_core::query::QueryParams;
+use std::fmt;
+use std::str::FromStr;
+use triton_core::query::{QueryPair, QueryParams};
 use triton_core::uuid::{ImageUuid, InstanceUuid, NetworkUuid, OwnerUuid, PackageUuid, ServerUuid};
 
 /// Parameters supported by the `/vms` list endpoint.
@@ -21,18 +23,29 @@ pub struct VMListParams {
     pub image_uuid: Option<ImageUuid>,
     /// Filter by VM brand (joyent, kvm, bhyve, etc.).
     pub brand: Option<String>,
-    /// Maximum number of results (1-1000).
+    /// Maximum number of results (1-[`VMListParams::MAX_LIMIT`]).
     pub limit: Option<u32>,
     /// Offset for pagination.
     pub offset: Option<u32>,
     /// Comma-separated list of fields to return.
     pub fields: Option<String>,
+    /// Tag filters, sent as `tag.<key>=<value>`.
+    pub tags: Vec<(String, String)>,
+    /// Sort order such as `create_timestamp.desc`; see [`SortSpec`].
+    pub sort: Option<String>,
+    /// Additional query parameters appended verbatim after the typed ones.
+    ///
+    /// Keys already set by a typed field are skipped.
+    pub extra_params: Vec<(String, String)>,
 }
 
 impl VMListParams {
+    /// Largest `limit` VMAPI honours; larger values are silently clamped by the server.
+    pub const MAX_LIMIT: u32 = 1000;
+
     /// Convert the parameters into URL query pairs.
     #[must_use]
-    pub fn to_pairs(&self) -> Vec<(&'static str, String)> {
+    pub fn to_pairs(&self) -> Vec<QueryPair> {
         let mut params = QueryParams::new();
         params.push_opt("owner_uuid", self.owner_uuid.as_ref());
         params.push_opt("state", self.state.as_deref());
@@ -43,9 +56,70 @@ impl VMListParams {
         params.push_opt("limit", self.limit);
         params.push_opt("offset", self.offset);
         params.push_opt("fields", self.fields.as_deref());
+        params.push_opt("sort", self.sort.as_deref());
+        for (key, value) in &self.tags {
+            params.push(format!("tag.{key}"), value);
+        }
+        params.push_extra(&self.extra_params);
 
         params.into_pairs()
     }
+
+    /// Sort results by `spec`.
+    #[must_use]
+    pub fn sorted_by(mut self, spec: &SortSpec) -> Self {
+        self.sort = Some(spec.to_string());
+        self
+    }
+}
+
+/// Direction of a [`SortSpec`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    /// Ascending order.
+    #[default]
+    Asc,
+    /// Descending order.
+    Desc,
+}
+
+/// Typed form of VMAPI's `sort` parameter, rendered as `<field>.<asc|desc>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortSpec {
+    /// Field to sort on, e.g. `create_timestamp`.
+    pub field: String,
+    /// Sort direction.
+    pub order: SortOrder,
+}
+
+impl SortSpec {
+    /// Sort ascending on `field`.
+    #[must_use]
+    pub fn asc(field: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            order: SortOrder::Asc,
+        }
+    }
+
+    /// Sort descending on `field`.
+    #[must_use]
+    pub fn desc(field: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            order: SortOrder::Desc,
+        }
+    }
+}
+
+impl fmt::Display for SortSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let order = match self.order {
+            SortOrder::Asc => "asc",
+            SortOrder::Desc => "desc",
+        };
+        write!(f, "{}.{order}", self.field)
+    }
 }
 
 /// Representation of a VM as returned by VMAPI.
@@ -154,6 +228,9 @@ pub struct Vm {
     /// Tag map.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub tags: Option<HashMap<String, String>>,
+    /// RBAC role tags gating sub-user access to the VM.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role_tags: Option<Vec<String>>,
 
     /// Creation timestamp.
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -245,12 +322,20 @@ pub struct Nic {
     /// NIC model.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub model: Option<String>,
-    /// VLAN identifier (may be int or string).
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub vlan_id: Option<serde_json::Value>,
-    /// MTU (int or string).
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub mtu: Option<serde_json::Value>,
+    /// VLAN identifier (VMAPI may send an int or a numeric string).
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_flexible_number"
+    )]
+    pub vlan_id: Option<u16>,
+    /// MTU (VMAPI may send an int or a numeric string).
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_flexible_number"
+    )]
+    pub mtu: Option<u32>,
     /// Interface name.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub interface: Option<String>,
@@ -324,7 +409,7 @@ pub struct NetworkConfig {
 }
 
 /// Request payload for updating a VM.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct UpdateVMRequest {
     /// Alias override.
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -353,6 +438,50 @@ pub struct UpdateVMRequest {
     /// Firewall enable/disable toggle.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub firewall_enabled: Option<bool>,
+    /// Deletion protection toggle.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deletion_protection: Option<bool>,
+    /// Indestructible zoneroot toggle.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub indestructible_zoneroot: Option<bool>,
+    /// Indestructible delegated dataset toggle.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub indestructible_delegated: Option<bool>,
+}
+
+/// Request payload for replacing the role tags on a VM.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RoleTagsRequest {
+    /// Role tags to apply.
+    pub role_tags: Vec<String>,
+}
+
+/// Request payload for attaching NICs to a VM.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AddNicsRequest {
+    /// Networks to add a NIC on.
+    pub networks: Vec<NetworkConfig>,
+}
+
+/// Request payload for detaching NICs from a VM.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RemoveNicsRequest {
+    /// MAC addresses of the NICs to remove.
+    pub macs: Vec<String>,
+}
+
+/// Request payload for moving a VM to another package.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ResizeVmRequest {
+    /// Package to apply.
+    pub billing_id: PackageUuid,
+}
+
+/// Request payload for reprovisioning a VM from a new image.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ReprovisionVmRequest {
+    /// Image to rebuild the VM from.
+    pub image_uuid: ImageUuid,
 }
 
 /// Representation of a VM snapshot.
@@ -409,6 +538,52 @@ pub struct SnapshotActionResponse {
     pub raw_response: Option<serde_json::Value>,
 }
 
+/// Job descriptor returned by single-VM actions such as `start` and `stop`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct VmActionResponse {
+    /// VM the action was queued for.
+    pub vm_uuid: InstanceUuid,
+    /// Workflow job running the action, for
+    /// [`VmapiClient::wait_for_job`](crate::VmapiClient::wait_for_job).
+    pub job_uuid: String,
+}
+
+/// Options for [`VmapiClient::stop_vm`](crate::VmapiClient::stop_vm).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StopVmOptions {
+    /// Halt the VM immediately instead of asking the guest to shut down.
+    pub force: bool,
+    /// Seconds to wait for a graceful shutdown before the stop fails.
+    pub timeout: Option<u32>,
+}
+
+impl StopVmOptions {
+    /// Options for an immediate, forced stop.
+    #[must_use]
+    pub fn forced() -> Self {
+        Self {
+            force: true,
+            ..Self::default()
+        }
+    }
+
+    /// Set the graceful shutdown timeout in seconds.
+    #[must_use]
+    pub fn with_timeout(mut self, seconds: u32) -> Self {
+        self.timeout = Some(seconds);
+        self
+    }
+
+    /// Convert to URL pairs.
+    #[must_use]
+    pub fn to_pairs(&self) -> Vec<QueryPair> {
+        let mut params = QueryParams::new();
+        params.push_opt("force", self.force.then_some(true));
+        params.push_opt("timeout", self.timeout);
+        params.into_pairs()
+    }
+}
+
 /// Supported batch VM actions.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -431,6 +606,9 @@ pub struct BatchVMRequest {
     /// Maximum concurrent operations.
     #[serde(default = "default_concurrency")]
     pub concurrency: usize,
+    /// Account that owns every VM in the batch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner_uuid: Option<OwnerUuid>,
 }
 
 const fn default_concurrency() -> usize {
@@ -469,6 +647,32 @@ pub struct BatchVMResponse {
     pub results: Vec<VMBatchResult>,
 }
 
+/// VM metadata map addressed by the key-level metadata calls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MetadataNamespace {
+    /// `customer_metadata`, visible to the VM through the metadata service.
+    Customer,
+    /// `internal_metadata`, for operator tooling; not visible to the VM.
+    Internal,
+}
+
+impl MetadataNamespace {
+    /// Path segment of the namespace's VMAPI sub-resource.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Customer => "customer_metadata",
+            Self::Internal => "internal_metadata",
+        }
+    }
+}
+
+impl fmt::Display for MetadataNamespace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// VMAPI job representation.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct VmapiJob {
@@ -494,6 +698,32 @@ pub struct VmapiJob {
     pub chain_results: Option<Vec<ChainResult>>,
 }
 
+impl VmapiJob {
+    /// Returns true once the job has `succeeded`, `failed`, or been `canceled`.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        matches!(self.execution.as_str(), "succeeded" | "failed" | "canceled")
+    }
+
+    /// Parses [`VmapiJob::created_at`], returning `None` if missing or malformed.
+    #[must_use]
+    pub fn created_at_datetime(&self) -> Option<DateTime<Utc>> {
+        parse_timestamp(self.created_at.as_deref())
+    }
+
+    /// Parses [`VmapiJob::exec_after`], returning `None` if missing or malformed.
+    #[must_use]
+    pub fn exec_after_datetime(&self) -> Option<DateTime<Utc>> {
+        parse_timestamp(self.exec_after.as_deref())
+    }
+}
+
+fn parse_timestamp(value: Option<&str>) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value?)
+        .ok()
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+}
+
 /// Result of an individual job step.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ChainResult {
@@ -520,23 +750,53 @@ pub struct JobListParams {
     pub limit: Option<u32>,
     /// Offset.
     pub offset: Option<u32>,
+    /// Additional query parameters appended verbatim after the typed ones.
+    ///
+    /// Keys already set by a typed field are skipped.
+    pub extra_params: Vec<(String, String)>,
 }
 
 impl JobListParams {
     /// Convert to URL pairs.
     #[must_use]
-    pub fn to_pairs(&self) -> Vec<(&'static str, String)> {
+    pub fn to_pairs(&self) -> Vec<QueryPair> {
         let mut params = QueryParams::new();
         params.push_opt("vm_uuid", self.vm_uuid.as_ref());
         params.push_opt("execution", self.execution.as_deref());
         params.push_opt("task", self.task.as_deref());
         params.push_opt("limit", self.limit);
         params.push_opt("offset", self.offset);
+        params.push_extra(&self.extra_params);
 
         params.into_pairs()
     }
 }
 
+/// Deserialize an optional number that may arrive as a JSON number or a numeric string.
+fn deserialize_flexible_number<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr + TryFrom<u64>,
+{
+    match Option::<serde_json::Value>::deserialize(deserializer)? {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(serde_json::Value::Number(number)) => number
+            .as_u64()
+            .and_then(|value| T::try_from(value).ok())
+            .map(Some)
+            .ok_or_else(|| de::Error::custom(format!("number out of range: {number}"))),
+        Some(serde_json::Value::String(text)) if text.trim().is_empty() => Ok(None),
+        Some(serde_json::Value::String(text)) => text
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| de::Error::custom(format!("invalid numeric string: {text:?}"))),
+        Some(other) => Err(de::Error::custom(format!(
+            "expected a number or numeric string, got {other}"
+        ))),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -557,6 +817,54 @@ mod tests {
         assert!(pairs.iter().any(|(k, v)| *k == "limit" && v == "50"));
     }
 
+    #[test]
+    fn vm_list_params_appends_extra_params_after_typed_ones() {
+        let params = VMListParams {
+            state: Some("running".into()),
+            extra_params: vec![
+                ("state".into(), "provisioning".into()),
+                ("internal_metadata.beta".into(), "true".into()),
+            ],
+            ..VMListParams::default()
+        };
+
+        let pairs = params.to_pairs();
+        assert_eq!(
+            pairs,
+            vec![
+                ("state".into(), "running".to_string()),
+                ("internal_metadata.beta".into(), "true".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn vm_list_params_serializes_tag_filters() {
+        let params = VMListParams {
+            tags: vec![("env".into(), "prod".into()), ("role".into(), "db".into())],
+            ..VMListParams::default()
+        };
+
+        let pairs = params.to_pairs();
+        assert_eq!(
+            pairs,
+            vec![
+                ("tag.env".into(), "prod".to_string()),
+                ("tag.role".into(), "db".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn vm_list_params_serializes_sort() {
+        let params = VMListParams::default().sorted_by(&SortSpec::desc("create_timestamp"));
+        assert_eq!(
+            params.to_pairs(),
+            vec![("sort".into(), "create_timestamp.desc".to_string())]
+        );
+        assert_eq!(SortSpec::asc("alias").to_string(), "alias.asc");
+    }
+
     #[test]
     fn vm_deserialize_basic() {
         let uuid = InstanceUuid::new_v4();
@@ -573,6 +881,47 @@ mod tests {
         assert_eq!(vm.state.as_deref(), Some("running"));
     }
 
+    #[test]
+    fn vm_deserialize_role_tags() {
+        let json = json!({
+            "uuid": InstanceUuid::new_v4(),
+            "role_tags": ["devs", "operators"]
+        });
+
+        let vm: Vm = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            vm.role_tags,
+            Some(vec!["devs".to_string(), "operators".to_string()])
+        );
+
+        let untagged: Vm = serde_json::from_value(json!({ "uuid": vm.uuid })).unwrap();
+        assert!(untagged.role_tags.is_none());
+    }
+
+    #[test]
+    fn job_timestamps_parse_on_demand() {
+        let job = |created_at: serde_json::Value| -> VmapiJob {
+            serde_json::from_value(json!({
+                "uuid": "9a9c3e4c-2b39-4c54-8a3b-1a2f1c2b3d4e",
+                "name": "provision-9a9c3e4c",
+                "execution": "running",
+                "params": {},
+                "created_at": created_at
+            }))
+            .unwrap()
+        };
+
+        let parsed = job(json!("2024-05-01T12:34:56.789Z"));
+        assert_eq!(
+            parsed.created_at_datetime().unwrap().to_rfc3339(),
+            "2024-05-01T12:34:56.789+00:00"
+        );
+        assert_eq!(parsed.exec_after_datetime(), None);
+
+        assert_eq!(job(json!(null)).created_at_datetime(), None);
+        assert_eq!(job(json!("2024-13-45")).created_at_datetime(), None);
+    }
+
     #[test]
     fn job_list_params_pairs() {
         let params = JobListParams {
@@ -581,6 +930,7 @@ mod tests {
             task: Some("provision".into()),
             limit: Some(5),
             offset: Some(10),
+            extra_params: Vec::new(),
         };
 
         let pairs = params.to_pairs();
@@ -607,4 +957,37 @@ mod tests {
         assert_eq!(back.summary.total, 2);
         assert_eq!(back.results.len(), 1);
     }
+
+    #[test]
+    fn nic_vlan_id_and_mtu_accept_numbers_and_strings() {
+        let from_numbers: Nic = serde_json::from_value(json!({
+            "mac": "90:b8:d0:00:00:01",
+            "vlan_id": 42,
+            "mtu": 9000
+        }))
+        .unwrap();
+        let from_strings: Nic = serde_json::from_value(json!({
+            "mac": "90:b8:d0:00:00:01",
+            "vlan_id": "42",
+            "mtu": "9000"
+        }))
+        .unwrap();
+
+        for nic in [&from_numbers, &from_strings] {
+            assert_eq!(nic.vlan_id, Some(42));
+            assert_eq!(nic.mtu, Some(9000));
+        }
+
+        let value = serde_json::to_value(&from_strings).unwrap();
+        assert_eq!(value["vlan_id"], json!(42));
+        assert_eq!(value["mtu"], json!(9000));
+    }
+
+    #[test]
+    fn nic_vlan_id_rejects_out_of_range_and_garbage() {
+        let missing: Nic = serde_json::from_value(json!({ "mac": "m", "vlan_id": null })).unwrap();
+        assert_eq!(missing.vlan_id, None);
+        assert!(serde_json::from_value::<Nic>(json!({ "mac": "m", "vlan_id": 70000 })).is_err());
+        assert!(serde_json::from_value::<Nic>(json!({ "mac": "m", "mtu": "jumbo" })).is_err());
+    }
 }
@@ -0,0 +1,77 @@
+//! Record a VMAPI interaction against a mock server and replay it offline.
+//!
+//! Run with `cargo test --package triton-vmapi --features record-replay`.
+
+#![cfg(feature = "record-replay")]
+
+use serde_json::json;
+use triton_core::cassette::{Cassette, REDACTED};
+use triton_core::uuid::InstanceUuid;
+use triton_core::Error;
+use triton_vmapi::{VMListParams, VmapiClientBuilder};
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn list_vms_replays_recorded_interaction() {
+    let cassette_path =
+        std::env::temp_dir().join(format!("vmapi-cassette-{}.json", InstanceUuid::new_v4()));
+    let uuid = InstanceUuid::new_v4();
+    let params = VMListParams {
+        state: Some("running".into()),
+        ..Default::default()
+    };
+
+    let recorded = {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/vms"))
+            .and(query_param("state", "running"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("x-resource-count", "1")
+                    .set_body_json(json!([{
+                        "uuid": uuid,
+                        "alias": "web-1",
+                        "state": "running",
+                        "customer_metadata": { "root_pw": "hunter2" }
+                    }])),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = VmapiClientBuilder::new(server.uri())
+            .unwrap()
+            .with_token("live-secret-token")
+            .with_cassette(Cassette::record(&cassette_path))
+            .build()
+            .unwrap();
+        client.list_vms(&params).await.unwrap()
+    };
+
+    let contents = std::fs::read_to_string(&cassette_path).unwrap();
+    assert!(!contents.contains("hunter2"));
+    assert!(!contents.contains("live-secret-token"));
+
+    // The mock server is gone; every response must come from the cassette.
+    let client = VmapiClientBuilder::new("http://127.0.0.1:9")
+        .unwrap()
+        .with_cassette(Cassette::replay(&cassette_path).unwrap())
+        .build()
+        .unwrap();
+    let response = client.list_vms_with_headers(&params).await.unwrap();
+    std::fs::remove_file(&cassette_path).unwrap();
+
+    assert_eq!(response.header("x-resource-count"), Some("1"));
+    assert_eq!(response.body.len(), recorded.len());
+    assert_eq!(response.body[0].uuid, recorded[0].uuid);
+    assert_eq!(response.body[0].alias.as_deref(), Some("web-1"));
+    assert_eq!(
+        response.body[0].customer_metadata,
+        Some(json!({ "root_pw": REDACTED }))
+    );
+
+    let err = client.list_vms(&params).await.unwrap_err();
+    assert!(matches!(err, Error::InvalidRequest(_)));
+}